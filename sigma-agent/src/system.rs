@@ -12,6 +12,7 @@ pub fn collect_system_info(metrics_port: u16, public_ip: Option<&str>) -> serde_
     let mut info = json!({
         "cpu_cores": cpu_cores().unwrap_or(0),
         "ram_mb": ram_mb().unwrap_or(0),
+        "ram_used_mb": ram_used_mb().unwrap_or(0),
         "disk_gb": disk_total,
         "disk_used_gb": disk_used,
         "uptime_seconds": uptime_seconds().unwrap_or(0),
@@ -169,9 +170,18 @@ fn cpu_cores() -> Option<u32> {
 }
 
 fn ram_mb() -> Option<u64> {
+    meminfo_mb("MemTotal:")
+}
+
+/// Memory in use, i.e. MemTotal minus MemAvailable (page cache counts as free).
+fn ram_used_mb() -> Option<u64> {
+    Some(meminfo_mb("MemTotal:")?.saturating_sub(meminfo_mb("MemAvailable:")?))
+}
+
+fn meminfo_mb(field: &str) -> Option<u64> {
     let content = std::fs::read_to_string("/proc/meminfo").ok()?;
     for line in content.lines() {
-        if line.starts_with("MemTotal:") {
+        if line.starts_with(field) {
             let parts: Vec<&str> = line.split_whitespace().collect();
             let kb: u64 = parts.get(1)?.parse().ok()?;
             return Some(kb / 1024);
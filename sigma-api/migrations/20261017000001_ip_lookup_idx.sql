@@ -0,0 +1,33 @@
+-- Indexed inet lookups for IP / CIDR reverse search (GET /api/lookup)
+
+-- Cast text to INET, returning NULL for malformed values instead of raising.
+-- Marked IMMUTABLE so it can back expression indexes on free-form text columns.
+CREATE OR REPLACE FUNCTION try_inet(value TEXT) RETURNS INET AS $$
+BEGIN
+    RETURN value::inet;
+EXCEPTION WHEN others THEN
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql IMMUTABLE STRICT PARALLEL SAFE;
+
+CREATE INDEX idx_vps_ip_history_inet
+    ON vps_ip_history USING GIST (try_inet(ip) inet_ops);
+
+CREATE INDEX idx_dns_records_content_inet
+    ON dns_records USING GIST (try_inet(content) inet_ops)
+    WHERE record_type IN ('A', 'AAAA');
+
+CREATE INDEX idx_ip_checks_ip
+    ON ip_checks USING GIST (ip inet_ops);
+
+-- Backfill 'added' rows for IPs that predate the history trigger, so the
+-- history table alone answers "who holds / held this address".
+INSERT INTO vps_ip_history (vps_id, action, ip, label, source, created_at)
+SELECT v.id, 'added', e->>'ip', COALESCE(e->>'label', ''), v.source, v.created_at
+FROM vps v
+CROSS JOIN LATERAL jsonb_array_elements(v.ip_addresses) AS e
+WHERE COALESCE(e->>'ip', '') <> ''
+  AND NOT EXISTS (
+      SELECT 1 FROM vps_ip_history h
+      WHERE h.vps_id = v.id AND h.ip = e->>'ip'
+  );
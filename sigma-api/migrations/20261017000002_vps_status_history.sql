@@ -0,0 +1,56 @@
+-- VPS lifecycle status history (tracked automatically via trigger)
+--
+-- API handlers set the per-transaction settings app.change_source
+-- ('manual', 'agent', 'cloud-sync', 'import') and app.actor before writing.
+-- If app.change_source is not set, falls back to vps.source.
+
+CREATE TABLE vps_status_history (
+    id          UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    vps_id      UUID NOT NULL REFERENCES vps(id) ON DELETE CASCADE,
+    from_status TEXT,
+    to_status   TEXT NOT NULL,
+    source      TEXT NOT NULL DEFAULT '',
+    actor       TEXT NOT NULL DEFAULT '',
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_vps_status_history_vps ON vps_status_history(vps_id, created_at DESC);
+CREATE INDEX idx_vps_status_history_to ON vps_status_history(to_status);
+
+CREATE OR REPLACE FUNCTION track_vps_status_changes() RETURNS trigger AS $$
+DECLARE
+    change_source TEXT;
+    change_actor  TEXT;
+BEGIN
+    IF TG_OP = 'UPDATE' AND OLD.status IS NOT DISTINCT FROM NEW.status THEN
+        RETURN NEW;
+    END IF;
+
+    change_source := COALESCE(
+        NULLIF(current_setting('app.change_source', true), ''),
+        NEW.source,
+        ''
+    );
+    change_actor := COALESCE(current_setting('app.actor', true), '');
+
+    INSERT INTO vps_status_history (vps_id, from_status, to_status, source, actor)
+    VALUES (
+        NEW.id,
+        CASE WHEN TG_OP = 'UPDATE' THEN OLD.status END,
+        NEW.status,
+        change_source,
+        change_actor
+    );
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_vps_status_changes
+    AFTER INSERT OR UPDATE OF status ON vps
+    FOR EACH ROW
+    EXECUTE FUNCTION track_vps_status_changes();
+
+-- Seed the current status of existing VPS so durations have a starting point.
+INSERT INTO vps_status_history (vps_id, from_status, to_status, source, actor, created_at)
+SELECT id, NULL, status, source, '', created_at FROM vps;
@@ -0,0 +1,21 @@
+-- Renewal ledger: each row records one paid renewal and the expire_date it produced
+
+ALTER TABLE vps ADD COLUMN auto_renew BOOLEAN NOT NULL DEFAULT false;
+
+CREATE TABLE vps_renewals (
+    id                   UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    vps_id               UUID NOT NULL REFERENCES vps(id) ON DELETE CASCADE,
+    period_months        INT NOT NULL CHECK (period_months > 0),
+    amount               NUMERIC(12,2) NOT NULL,
+    currency             TEXT NOT NULL DEFAULT 'USD',
+    paid_at              TIMESTAMPTZ NOT NULL DEFAULT now(),
+    invoice_ref          TEXT NOT NULL DEFAULT '',
+    previous_expire_date DATE,
+    new_expire_date      DATE NOT NULL,
+    actor                TEXT NOT NULL DEFAULT '',
+    notes                TEXT NOT NULL DEFAULT '',
+    created_at           TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_vps_renewals_vps ON vps_renewals(vps_id, paid_at DESC);
+CREATE INDEX idx_vps_renewals_paid_at ON vps_renewals(paid_at);
@@ -0,0 +1,21 @@
+-- Admin-defined custom fields for VPS, stored in vps.extra->'custom'
+CREATE TABLE custom_fields (
+    id           UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name         TEXT NOT NULL UNIQUE CHECK (name ~ '^[a-z][a-z0-9_]*$'),
+    label        TEXT NOT NULL DEFAULT '',
+    field_type   TEXT NOT NULL DEFAULT 'string'
+                 CHECK (field_type IN ('string', 'number', 'integer', 'boolean', 'date', 'url', 'enum')),
+    enum_values  TEXT[] NOT NULL DEFAULT '{}',
+    required     BOOLEAN NOT NULL DEFAULT false,
+    -- VPS purposes the field applies to; empty = all purposes
+    purposes     TEXT[] NOT NULL DEFAULT '{}',
+    -- Expose as Prometheus target label / Ansible hostvar
+    expose_label BOOLEAN NOT NULL DEFAULT false,
+    sort_order   INT NOT NULL DEFAULT 0,
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at   TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TRIGGER trg_custom_fields_updated
+    BEFORE UPDATE ON custom_fields
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at();
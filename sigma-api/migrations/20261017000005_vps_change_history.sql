@@ -0,0 +1,62 @@
+-- Field-level VPS change history (tracked automatically via trigger)
+--
+-- Generalizes vps_ip_history / vps_status_history: one row per changed column
+-- with the old and new value as JSONB. Like the other history triggers it reads
+-- app.change_source and app.actor, falling back to vps.source.
+
+CREATE TABLE vps_change_history (
+    id         UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    vps_id     UUID NOT NULL REFERENCES vps(id) ON DELETE CASCADE,
+    field      TEXT NOT NULL,
+    old_value  JSONB,
+    new_value  JSONB,
+    source     TEXT NOT NULL DEFAULT '',
+    actor      TEXT NOT NULL DEFAULT '',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_vps_change_history_vps ON vps_change_history(vps_id, created_at DESC);
+CREATE INDEX idx_vps_change_history_field ON vps_change_history(field);
+
+CREATE OR REPLACE FUNCTION track_vps_field_changes() RETURNS trigger AS $$
+DECLARE
+    old_row       JSONB;
+    new_row       JSONB;
+    col           TEXT;
+    change_source TEXT;
+    change_actor  TEXT;
+BEGIN
+    change_source := COALESCE(
+        NULLIF(current_setting('app.change_source', true), ''),
+        NEW.source,
+        ''
+    );
+    change_actor := COALESCE(current_setting('app.actor', true), '');
+
+    old_row := to_jsonb(OLD);
+    new_row := to_jsonb(NEW);
+
+    -- Keep in sync with the UPDATE OF column list below
+    FOREACH col IN ARRAY ARRAY['cost_monthly', 'expire_date', 'provider_id', 'purpose', 'tags', 'status']
+    LOOP
+        IF (old_row -> col) IS DISTINCT FROM (new_row -> col) THEN
+            INSERT INTO vps_change_history (vps_id, field, old_value, new_value, source, actor)
+            VALUES (
+                NEW.id,
+                col,
+                NULLIF(old_row -> col, 'null'::jsonb),
+                NULLIF(new_row -> col, 'null'::jsonb),
+                change_source,
+                change_actor
+            );
+        END IF;
+    END LOOP;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_vps_field_changes
+    AFTER UPDATE OF cost_monthly, expire_date, provider_id, purpose, tags, status ON vps
+    FOR EACH ROW
+    EXECUTE FUNCTION track_vps_field_changes();
@@ -0,0 +1,35 @@
+-- Agent heartbeat time series
+--
+-- Every heartbeat appends a raw sample (resolution 0). A background worker rolls
+-- raw samples older than heartbeat_raw_retention_hours into hourly rows
+-- (resolution 3600) and drops everything older than heartbeat_retention_days.
+--
+-- covered_secs is the time since the previous raw sample when that gap is short
+-- enough to count as "agent up"; SUM(covered_secs) over a window is the agent's
+-- uptime in that window, and survives downsampling.
+
+CREATE TABLE vps_heartbeats (
+    id             UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    vps_id         UUID NOT NULL REFERENCES vps(id) ON DELETE CASCADE,
+    ts             TIMESTAMPTZ NOT NULL DEFAULT now(),
+    resolution     INTEGER NOT NULL DEFAULT 0,
+    samples        INTEGER NOT NULL DEFAULT 1,
+    covered_secs   INTEGER NOT NULL DEFAULT 0,
+    load_1         DOUBLE PRECISION,
+    load_5         DOUBLE PRECISION,
+    load_15        DOUBLE PRECISION,
+    ram_mb         INTEGER,
+    ram_used_mb    INTEGER,
+    disk_gb        INTEGER,
+    disk_used_gb   INTEGER,
+    uptime_seconds BIGINT
+);
+
+CREATE INDEX idx_vps_heartbeats_vps_ts ON vps_heartbeats(vps_id, ts);
+CREATE INDEX idx_vps_heartbeats_rollup ON vps_heartbeats(resolution, ts);
+
+INSERT INTO system_settings (key, value) VALUES
+    ('agent_interval_secs', '60'),
+    ('heartbeat_raw_retention_hours', '48'),
+    ('heartbeat_retention_days', '90')
+ON CONFLICT (key) DO NOTHING;
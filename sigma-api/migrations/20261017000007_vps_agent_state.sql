@@ -0,0 +1,13 @@
+-- Agent liveness, maintained by the agent-state worker from vps_heartbeats.
+-- '' until an agent has reported, then online / stale / offline.
+
+ALTER TABLE vps ADD COLUMN agent_state TEXT NOT NULL DEFAULT '';
+ALTER TABLE vps ADD COLUMN agent_state_changed_at TIMESTAMPTZ;
+
+CREATE INDEX idx_vps_agent_state ON vps(agent_state);
+
+-- Multiples of agent_interval_secs without a heartbeat before an agent is stale / offline
+INSERT INTO system_settings (key, value) VALUES
+    ('agent_stale_after_intervals', '3'),
+    ('agent_offline_after_intervals', '10')
+ON CONFLICT (key) DO NOTHING;
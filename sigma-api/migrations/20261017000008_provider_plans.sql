@@ -0,0 +1,29 @@
+-- Provider plan catalogue: the offerings a provider sells, used to pre-fill
+-- specs and cost when creating a VPS and to compare prices across providers
+
+CREATE TABLE provider_plans (
+    id             UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    provider_id    UUID NOT NULL REFERENCES providers(id) ON DELETE CASCADE,
+    name           TEXT NOT NULL,
+    cpu_cores      SMALLINT,
+    ram_mb         INT,
+    disk_gb        INT,
+    bandwidth_tb   NUMERIC(10,2),
+    price          NUMERIC(12,2) NOT NULL CHECK (price >= 0),
+    currency       TEXT NOT NULL DEFAULT 'USD',
+    -- Months covered by `price` (1 = monthly, 12 = yearly)
+    billing_cycle_months INT NOT NULL DEFAULT 1 CHECK (billing_cycle_months > 0),
+    locations      TEXT[] NOT NULL DEFAULT '{}',
+    stock_url      TEXT NOT NULL DEFAULT '',
+    notes          TEXT NOT NULL DEFAULT '',
+    created_at     TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at     TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (provider_id, name)
+);
+
+CREATE TRIGGER trg_provider_plans_updated
+    BEFORE UPDATE ON provider_plans
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at();
+
+ALTER TABLE vps ADD COLUMN plan_id UUID REFERENCES provider_plans(id) ON DELETE SET NULL;
+CREATE INDEX idx_vps_plan ON vps(plan_id);
@@ -0,0 +1,23 @@
+-- Resolution timestamp for tickets (used for provider scorecards).
+-- Set when a ticket enters resolved / closed, cleared when it is reopened.
+
+ALTER TABLE tickets ADD COLUMN resolved_at TIMESTAMPTZ;
+
+UPDATE tickets SET resolved_at = updated_at WHERE status IN ('resolved', 'closed');
+
+CREATE OR REPLACE FUNCTION set_ticket_resolved_at() RETURNS trigger AS $$
+BEGIN
+    IF NEW.status IN ('resolved', 'closed') THEN
+        IF TG_OP = 'INSERT' OR OLD.status NOT IN ('resolved', 'closed') THEN
+            NEW.resolved_at := now();
+        END IF;
+    ELSE
+        NEW.resolved_at := NULL;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_tickets_resolved_at
+    BEFORE INSERT OR UPDATE OF status ON tickets
+    FOR EACH ROW EXECUTE FUNCTION set_ticket_resolved_at();
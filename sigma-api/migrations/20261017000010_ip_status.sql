@@ -0,0 +1,33 @@
+-- China-blocking status per VPS IP, classified by the ip-status worker from
+-- recent ip_checks: Chinese probe sources (cn-*) against overseas control sources.
+--
+-- Each row is one state period; the open period (ended_at IS NULL) is the
+-- current status. vps.ip_status holds the most severe current status of the
+-- VPS's IPs: blocked_cn, down, partial, ok ('' until classified).
+
+CREATE TABLE ip_status (
+    id               UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    vps_id           UUID NOT NULL REFERENCES vps(id) ON DELETE CASCADE,
+    ip               TEXT NOT NULL,
+    status           TEXT NOT NULL CHECK (status IN ('ok', 'blocked_cn', 'partial', 'down')),
+    previous_status  TEXT NOT NULL DEFAULT '',
+    -- Carriers (ip_labels names) whose probe sources all failed
+    blocked_carriers TEXT[] NOT NULL DEFAULT '{}',
+    -- Success ratio per probe source over the window at the time of the transition
+    sources          JSONB NOT NULL DEFAULT '{}',
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT now(),
+    ended_at         TIMESTAMPTZ
+);
+
+CREATE UNIQUE INDEX idx_ip_status_current ON ip_status(vps_id, ip) WHERE ended_at IS NULL;
+CREATE INDEX idx_ip_status_vps ON ip_status(vps_id, created_at DESC);
+CREATE INDEX idx_ip_checks_vps_time ON ip_checks(vps_id, checked_at DESC);
+
+ALTER TABLE vps ADD COLUMN ip_status TEXT NOT NULL DEFAULT '';
+CREATE INDEX idx_vps_ip_status ON vps(ip_status);
+
+INSERT INTO system_settings (key, value) VALUES
+    ('ip_status_interval_secs', '300'),
+    ('ip_status_window_minutes', '30'),
+    ('ip_status_min_success_pct', '50')
+ON CONFLICT (key) DO NOTHING;
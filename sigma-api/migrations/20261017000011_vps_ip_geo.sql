@@ -0,0 +1,10 @@
+-- GeoIP / ASN enrichment of VPS IPs, maintained by the GeoIP worker from the
+-- local mmdb files in GEOIP_DB_PATH. One entry per IP in ip_addresses:
+-- {"ip", "asn", "as_org", "country", "city"}.
+--
+-- geo_mismatch is set when a public IP geolocates outside the VPS's country.
+
+ALTER TABLE vps ADD COLUMN ip_geo JSONB NOT NULL DEFAULT '[]';
+ALTER TABLE vps ADD COLUMN geo_mismatch BOOLEAN NOT NULL DEFAULT false;
+
+CREATE INDEX idx_vps_geo_mismatch ON vps(geo_mismatch) WHERE geo_mismatch;
@@ -0,0 +1,30 @@
+-- Spare IP pool: addresses owned but not bound to a VPS, reserved for a
+-- purpose, or floating (anycast / movable IPs that move between hosts).
+-- Assign / release moves the address into or out of vps.ip_addresses, so the
+-- change lands in vps_ip_history through the existing trigger.
+
+CREATE TABLE ip_pool (
+    id           UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    ip           TEXT NOT NULL UNIQUE,
+    provider_id  UUID REFERENCES providers(id) ON DELETE SET NULL,
+    status       TEXT NOT NULL DEFAULT 'available'
+                 CHECK (status IN ('available', 'reserved', 'assigned')),
+    -- Floating IPs may move between VPS and are not reported as duplicates
+    floating     BOOLEAN NOT NULL DEFAULT false,
+    vps_id       UUID REFERENCES vps(id) ON DELETE SET NULL,
+    label        TEXT NOT NULL DEFAULT '',
+    purpose      TEXT NOT NULL DEFAULT '',
+    cost_monthly NUMERIC(10,2),
+    currency     TEXT NOT NULL DEFAULT 'USD',
+    notes        TEXT NOT NULL DEFAULT '',
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at   TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_ip_pool_provider ON ip_pool(provider_id);
+CREATE INDEX idx_ip_pool_vps ON ip_pool(vps_id);
+CREATE INDEX idx_ip_pool_status ON ip_pool(status);
+
+CREATE TRIGGER trg_ip_pool_updated
+    BEFORE UPDATE ON ip_pool
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at();
@@ -0,0 +1,37 @@
+-- IP rotation of cloud-sourced VPS: allocate a new Elastic IP / EIP, bind it,
+-- update ip_addresses, optionally repoint DNS, release the old address.
+-- `step` is the next step to run, so a failed rotation resumes where it stopped.
+
+CREATE TABLE ip_rotations (
+    id               UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    vps_id           UUID NOT NULL REFERENCES vps(id) ON DELETE CASCADE,
+    cloud_account_id UUID REFERENCES cloud_accounts(id) ON DELETE SET NULL,
+    provider_type    TEXT NOT NULL,
+    region           TEXT NOT NULL,
+    instance_id      TEXT NOT NULL,
+    old_ip           TEXT NOT NULL,
+    new_ip           TEXT,
+    -- Provider handle of the new address (EC2 / Alibaba allocation ID)
+    allocation_id    TEXT,
+    repoint_dns      BOOLEAN NOT NULL DEFAULT false,
+    release_old      BOOLEAN NOT NULL DEFAULT true,
+    step             TEXT NOT NULL DEFAULT 'allocate'
+                     CHECK (step IN ('allocate', 'associate', 'update_vps', 'update_dns', 'release_old', 'done')),
+    status           TEXT NOT NULL DEFAULT 'running'
+                     CHECK (status IN ('running', 'failed', 'completed')),
+    error            TEXT NOT NULL DEFAULT '',
+    dns_records_updated INT NOT NULL DEFAULT 0,
+    old_released     BOOLEAN NOT NULL DEFAULT false,
+    actor            TEXT NOT NULL DEFAULT '',
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at       TIMESTAMPTZ NOT NULL DEFAULT now(),
+    completed_at     TIMESTAMPTZ
+);
+
+CREATE INDEX idx_ip_rotations_vps ON ip_rotations(vps_id, created_at DESC);
+-- At most one unfinished rotation per VPS
+CREATE UNIQUE INDEX idx_ip_rotations_open ON ip_rotations(vps_id) WHERE status != 'completed';
+
+CREATE TRIGGER trg_ip_rotations_updated
+    BEFORE UPDATE ON ip_rotations
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at();
@@ -0,0 +1,31 @@
+-- Blocklist / reputation listings of fleet IPs, maintained by the reputation
+-- worker from the DNSBL zones in DNSBL_ZONES and, with ABUSEIPDB_API_KEY set,
+-- the AbuseIPDB API.
+--
+-- Each row is one listing period of an IP on one list; the open period
+-- (delisted_at IS NULL) is a current listing. vps.blocklists holds the lists
+-- any of the VPS's IPs is currently on.
+
+CREATE TABLE ip_reputation (
+    id           UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    vps_id       UUID NOT NULL REFERENCES vps(id) ON DELETE CASCADE,
+    ip           TEXT NOT NULL,
+    -- DNSBL zone (e.g. zen.spamhaus.org) or 'abuseipdb'
+    list         TEXT NOT NULL,
+    -- DNSBL return codes or API score, as of the last check
+    detail       TEXT NOT NULL DEFAULT '',
+    listed_at    TIMESTAMPTZ NOT NULL DEFAULT now(),
+    last_seen_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    delisted_at  TIMESTAMPTZ
+);
+
+CREATE UNIQUE INDEX idx_ip_reputation_current ON ip_reputation(vps_id, ip, list) WHERE delisted_at IS NULL;
+CREATE INDEX idx_ip_reputation_vps ON ip_reputation(vps_id, listed_at DESC);
+
+ALTER TABLE vps ADD COLUMN blocklists TEXT[] NOT NULL DEFAULT '{}';
+CREATE INDEX idx_vps_blocklists ON vps USING GIN (blocklists);
+
+INSERT INTO system_settings (key, value) VALUES
+    ('ip_reputation_interval_secs', '21600'),
+    ('ip_reputation_abuse_min_score', '50')
+ON CONFLICT (key) DO NOTHING;
@@ -0,0 +1,18 @@
+-- Background sync of cloud accounts. The worker syncs every enabled account
+-- whose interval (sync_interval_secs, else the cloud_sync_interval_secs
+-- setting) has passed since its last attempt, at most cloud_sync_concurrency
+-- at a time. Manual and background syncs both record their outcome here.
+
+ALTER TABLE cloud_accounts
+    ADD COLUMN sync_enabled         BOOLEAN NOT NULL DEFAULT true,
+    ADD COLUMN sync_interval_secs   INT CHECK (sync_interval_secs >= 60),
+    ADD COLUMN last_sync_attempt_at TIMESTAMPTZ,
+    -- NULL when the last attempt succeeded
+    ADD COLUMN last_sync_error      TEXT,
+    -- CloudSyncResult of the last successful sync
+    ADD COLUMN last_sync_result     JSONB;
+
+INSERT INTO system_settings (key, value) VALUES
+    ('cloud_sync_interval_secs', '3600'),
+    ('cloud_sync_concurrency', '4')
+ON CONFLICT (key) DO NOTHING;
@@ -0,0 +1,204 @@
+//! Agent liveness.
+//!
+//! A background worker derives `vps.agent_state` from the age of the latest
+//! heartbeat: `online` within `agent_stale_after_intervals` agent intervals,
+//! `stale` up to `agent_offline_after_intervals`, `offline` beyond that. Each
+//! transition of an active VPS is sent to the configured notification channels,
+//! including recoveries back to `online`.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::db::{self, Db};
+use crate::notifications::{send_telegram, send_webhook};
+
+pub const AGENT_ONLINE: &str = "online";
+pub const AGENT_STALE: &str = "stale";
+pub const AGENT_OFFLINE: &str = "offline";
+
+const DEFAULT_AGENT_INTERVAL_SECS: u64 = 60;
+const DEFAULT_STALE_AFTER_INTERVALS: u64 = 3;
+const DEFAULT_OFFLINE_AFTER_INTERVALS: u64 = 10;
+
+/// Background worker that re-evaluates agent states once per agent interval.
+pub async fn run(db: Db, http_client: reqwest::Client, cfg: Config) {
+    tracing::info!("Agent state worker started");
+
+    loop {
+        if let Err(e) = check_and_notify(&db, &http_client, &cfg).await {
+            tracing::error!("Agent state check error: {e}");
+        }
+        let interval =
+            db::setting_u64(&db, "agent_interval_secs", DEFAULT_AGENT_INTERVAL_SECS).await;
+        tokio::time::sleep(std::time::Duration::from_secs(interval.max(10))).await;
+    }
+}
+
+/// Notification event for a state change, if it is worth one. The first state
+/// recorded for a VPS (from `''`) is never announced.
+pub fn transition_event(previous: &str, current: &str) -> Option<&'static str> {
+    match (previous, current) {
+        ("", _) => None,
+        (p, c) if p == c => None,
+        (_, AGENT_STALE) => Some("agent_stale"),
+        (_, AGENT_OFFLINE) => Some("agent_offline"),
+        (AGENT_STALE | AGENT_OFFLINE, AGENT_ONLINE) => Some("agent_recovered"),
+        _ => None,
+    }
+}
+
+async fn check_and_notify(
+    db: &Db,
+    http_client: &reqwest::Client,
+    cfg: &Config,
+) -> anyhow::Result<()> {
+    let interval = db::setting_u64(db, "agent_interval_secs", DEFAULT_AGENT_INTERVAL_SECS).await;
+    let stale_after =
+        db::setting_u64(db, "agent_stale_after_intervals", DEFAULT_STALE_AFTER_INTERVALS).await;
+    let offline_after =
+        db::setting_u64(db, "agent_offline_after_intervals", DEFAULT_OFFLINE_AFTER_INTERVALS)
+            .await;
+    let stale_secs = (interval * stale_after) as f64;
+    let offline_secs = (interval * offline_after.max(stale_after)) as f64;
+
+    let changed = sqlx::query_as::<_, AgentTransition>(
+        r#"WITH computed AS (
+               SELECT v.id, v.agent_state AS previous_state, hb.last_heartbeat,
+                      CASE
+                          WHEN hb.last_heartbeat >= now() - make_interval(secs => $1) THEN 'online'
+                          WHEN hb.last_heartbeat >= now() - make_interval(secs => $2) THEN 'stale'
+                          ELSE 'offline'
+                      END AS agent_state
+               FROM vps v
+               CROSS JOIN LATERAL (
+                   SELECT MAX(ts) AS last_heartbeat FROM vps_heartbeats h WHERE h.vps_id = v.id
+               ) hb
+               WHERE v.status != 'deleted' AND hb.last_heartbeat IS NOT NULL
+           )
+           UPDATE vps v
+           SET agent_state = c.agent_state, agent_state_changed_at = now()
+           FROM computed c
+           WHERE v.id = c.id AND v.agent_state != c.agent_state
+           RETURNING v.id, v.hostname, v.alias, v.country, v.status,
+                     COALESCE((SELECT name FROM providers p WHERE p.id = v.provider_id), '') AS provider_name,
+                     c.previous_state, c.agent_state, c.last_heartbeat"#,
+    )
+    .bind(stale_secs)
+    .bind(offline_secs)
+    .fetch_all(db)
+    .await?;
+
+    for row in &changed {
+        tracing::info!(
+            "Agent state of {} changed: {} -> {}",
+            row.hostname,
+            if row.previous_state.is_empty() { "(none)" } else { &row.previous_state },
+            row.agent_state,
+        );
+
+        if !matches!(row.status.as_str(), "active" | "provisioning") {
+            continue;
+        }
+        let Some(event) = transition_event(&row.previous_state, &row.agent_state) else {
+            continue;
+        };
+
+        let payload = AgentStatePayload {
+            event: event.into(),
+            vps_id: row.id,
+            hostname: row.hostname.clone(),
+            alias: row.alias.clone(),
+            provider: row.provider_name.clone(),
+            country: row.country.clone(),
+            previous_state: row.previous_state.clone(),
+            agent_state: row.agent_state.clone(),
+            last_heartbeat: row.last_heartbeat,
+        };
+
+        if let (Some(token), Some(chat_id)) = (&cfg.telegram_bot_token, &cfg.telegram_chat_id) {
+            let message = format_telegram_message(&payload);
+            if let Err(e) = send_telegram(http_client, token, chat_id, &message).await {
+                tracing::error!("Telegram send error for {}: {e}", row.hostname);
+            }
+        }
+
+        if let Some(url) = &cfg.webhook_url {
+            if let Err(e) = send_webhook(http_client, url, &payload).await {
+                tracing::error!("Webhook send error for {}: {e}", row.hostname);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn format_telegram_message(p: &AgentStatePayload) -> String {
+    let title = match p.event.as_str() {
+        "agent_recovered" => "\u{2705} <b>Agent Recovered</b>",
+        "agent_offline" => "\u{1f534} <b>Agent Offline</b>",
+        _ => "\u{26a0}\u{fe0f} <b>Agent Stale</b>",
+    };
+    let host_display = if p.alias.is_empty() {
+        p.hostname.clone()
+    } else {
+        format!("{} ({})", p.hostname, p.alias)
+    };
+    let minutes_ago = (Utc::now() - p.last_heartbeat).num_minutes();
+
+    format!(
+        "{title}\n\
+         Host: <code>{host_display}</code>\n\
+         Provider: {provider} / {country}\n\
+         State: {previous} \u{2192} <b>{current}</b>\n\
+         Last heartbeat: {last} ({minutes_ago} min ago)",
+        provider = p.provider,
+        country = p.country,
+        previous = p.previous_state,
+        current = p.agent_state,
+        last = p.last_heartbeat.format("%Y-%m-%d %H:%M UTC"),
+    )
+}
+
+#[derive(Debug, Serialize)]
+struct AgentStatePayload {
+    event: String,
+    vps_id: Uuid,
+    hostname: String,
+    alias: String,
+    provider: String,
+    country: String,
+    previous_state: String,
+    agent_state: String,
+    last_heartbeat: DateTime<Utc>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct AgentTransition {
+    id: Uuid,
+    hostname: String,
+    alias: String,
+    country: String,
+    status: String,
+    provider_name: String,
+    previous_state: String,
+    agent_state: String,
+    last_heartbeat: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transition_event() {
+        assert_eq!(transition_event("", AGENT_OFFLINE), None);
+        assert_eq!(transition_event(AGENT_ONLINE, AGENT_ONLINE), None);
+        assert_eq!(transition_event(AGENT_ONLINE, AGENT_STALE), Some("agent_stale"));
+        assert_eq!(transition_event(AGENT_STALE, AGENT_OFFLINE), Some("agent_offline"));
+        assert_eq!(transition_event(AGENT_ONLINE, AGENT_OFFLINE), Some("agent_offline"));
+        assert_eq!(transition_event(AGENT_OFFLINE, AGENT_ONLINE), Some("agent_recovered"));
+        assert_eq!(transition_event(AGENT_STALE, AGENT_ONLINE), Some("agent_recovered"));
+    }
+}
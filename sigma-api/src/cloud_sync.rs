@@ -0,0 +1,91 @@
+use futures::StreamExt;
+
+use crate::db;
+use crate::models::CloudAccount;
+use crate::routes::AppState;
+
+/// How often the worker looks for accounts that are due.
+const TICK_SECS: u64 = 60;
+
+/// Background worker that syncs cloud accounts on a schedule.
+/// Each account is due once its interval (`sync_interval_secs`, else the
+/// `cloud_sync_interval_secs` setting) has passed since its last attempt;
+/// at most `cloud_sync_concurrency` accounts sync at a time. Both settings
+/// are read every tick, so changes from the Settings page apply at runtime.
+pub async fn run(state: AppState) {
+    tracing::info!("Cloud background sync started");
+
+    loop {
+        // Sleep first so the server can settle after boot
+        tokio::time::sleep(std::time::Duration::from_secs(TICK_SECS)).await;
+
+        let interval_secs = db::setting_u64(&state.db, "cloud_sync_interval_secs", 3600).await;
+        if interval_secs == 0 {
+            tracing::debug!("Cloud sync: disabled via settings");
+            continue;
+        }
+        let concurrency = db::setting_u64(&state.db, "cloud_sync_concurrency", 4).await.max(1) as usize;
+
+        if let Err(e) = sync_due_accounts(&state, interval_secs, concurrency).await {
+            tracing::error!("Cloud background sync error: {e}");
+        }
+    }
+}
+
+async fn sync_due_accounts(
+    state: &AppState,
+    interval_secs: u64,
+    concurrency: usize,
+) -> anyhow::Result<()> {
+    // Claim due accounts by stamping the attempt time, so a slow sync is not
+    // picked up again by the next tick (or another replica) while it runs.
+    let accounts: Vec<CloudAccount> = sqlx::query_as(
+        r#"UPDATE cloud_accounts SET last_sync_attempt_at = now()
+           WHERE id IN (
+               SELECT id FROM cloud_accounts
+               WHERE sync_enabled
+                 AND (last_sync_attempt_at IS NULL
+                      OR last_sync_attempt_at <= now()
+                         - make_interval(secs => COALESCE(sync_interval_secs, $1::INT)))
+               FOR UPDATE SKIP LOCKED
+           )
+           RETURNING *"#,
+    )
+    .bind(interval_secs.min(i32::MAX as u64) as i32)
+    .fetch_all(&state.db)
+    .await?;
+
+    if accounts.is_empty() {
+        return Ok(());
+    }
+
+    tracing::info!("Cloud sync: syncing {} due account(s)", accounts.len());
+
+    futures::stream::iter(accounts)
+        .for_each_concurrent(concurrency, |account| async move {
+            match crate::routes::cloud::run_sync(state, &account).await {
+                Ok(result) => {
+                    tracing::info!(
+                        "Cloud sync: {} ({}) — {} found, {} created, {} updated, {} retired, {} merged",
+                        account.name,
+                        account.provider_type,
+                        result.instances_found,
+                        result.created,
+                        result.updated,
+                        result.retired,
+                        result.merged,
+                    );
+                }
+                Err(e) => {
+                    tracing::error!(
+                        "Cloud sync: failed for {} ({}): {e}",
+                        account.name,
+                        account.provider_type,
+                    );
+                }
+            }
+        })
+        .await;
+
+    Ok(())
+}
@@ -1,3 +1,4 @@
+#[derive(Clone)]
 pub struct Config {
     pub database_url: String,
     pub listen_host: String,
@@ -15,6 +16,10 @@ pub struct Config {
     pub jwt_secret: String,
     pub jwt_expiry_hours: u64,
     pub dns_sync_interval_secs: u64,
+    pub geoip_db_paths: Vec<String>,
+    pub dnsbl_zones: Vec<String>,
+    pub dnsbl_resolver: Option<String>,
+    pub abuseipdb_api_key: Option<String>,
 }
 
 impl Config {
@@ -70,6 +75,24 @@ impl Config {
                 .ok()
                 .and_then(|p| p.parse().ok())
                 .unwrap_or(3600),
+            geoip_db_paths: std::env::var("GEOIP_DB_PATH")
+                .unwrap_or_default()
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect(),
+            dnsbl_zones: std::env::var("DNSBL_ZONES")
+                .unwrap_or_default()
+                .split(',')
+                .map(|s| s.trim().trim_matches('.').to_lowercase())
+                .filter(|s| !s.is_empty())
+                .collect(),
+            dnsbl_resolver: std::env::var("DNSBL_RESOLVER")
+                .ok()
+                .filter(|s| !s.is_empty()),
+            abuseipdb_api_key: std::env::var("ABUSEIPDB_API_KEY")
+                .ok()
+                .filter(|s| !s.is_empty()),
         }
     }
 }
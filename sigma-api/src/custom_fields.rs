@@ -0,0 +1,218 @@
+//! Validation of typed custom fields stored in `vps.extra.custom`.
+//!
+//! Definitions live in the `custom_fields` table and are managed through
+//! `/api/custom-fields`. A definition applies to every VPS when its `purposes`
+//! list is empty, otherwise only to VPS with one of the listed purposes.
+
+use sqlx::PgConnection;
+
+use crate::errors::AppError;
+use crate::models::CustomField;
+
+pub const FIELD_TYPES: &[&str] = &["string", "number", "integer", "boolean", "date", "url", "enum"];
+
+/// Load all custom field definitions.
+pub async fn load_definitions(conn: &mut PgConnection) -> Result<Vec<CustomField>, AppError> {
+    let rows = sqlx::query_as::<_, CustomField>("SELECT * FROM custom_fields ORDER BY sort_order, name")
+        .fetch_all(&mut *conn)
+        .await?;
+    Ok(rows)
+}
+
+/// Check a definition's own shape (type and enum values) before storing it.
+pub fn validate_definition(field_type: &str, enum_values: &[String]) -> Result<(), AppError> {
+    if !FIELD_TYPES.contains(&field_type) {
+        return Err(AppError::BadRequest(format!(
+            "Invalid field_type '{}' (expected one of: {})",
+            field_type,
+            FIELD_TYPES.join(", ")
+        )));
+    }
+    if field_type == "enum" && enum_values.is_empty() {
+        return Err(AppError::BadRequest("enum fields need at least one enum value".into()));
+    }
+    Ok(())
+}
+
+/// The `extra.custom` object of a VPS, if any.
+pub fn custom_values(extra: &serde_json::Value) -> Option<&serde_json::Map<String, serde_json::Value>> {
+    extra.get("custom").and_then(|v| v.as_object())
+}
+
+fn applies_to(def: &CustomField, purpose: &str) -> bool {
+    def.purposes.is_empty() || def.purposes.iter().any(|p| p == purpose)
+}
+
+/// Validate `extra.custom` against the definitions for the given purpose.
+/// Unknown keys, keys scoped to another purpose, wrong types and missing
+/// required fields are rejected with `BadRequest`.
+pub fn validate_custom(
+    defs: &[CustomField],
+    purpose: &str,
+    extra: &serde_json::Value,
+) -> Result<(), AppError> {
+    let empty = serde_json::Map::new();
+    let values = match extra.get("custom") {
+        None | Some(serde_json::Value::Null) => &empty,
+        Some(serde_json::Value::Object(m)) => m,
+        Some(_) => return Err(AppError::BadRequest("extra.custom must be an object".into())),
+    };
+
+    for (key, value) in values {
+        let def = defs
+            .iter()
+            .find(|d| &d.name == key)
+            .ok_or_else(|| AppError::BadRequest(format!("Unknown custom field '{}'", key)))?;
+        if !applies_to(def, purpose) {
+            return Err(AppError::BadRequest(format!(
+                "Custom field '{}' does not apply to purpose '{}'",
+                key, purpose
+            )));
+        }
+        if !value.is_null() {
+            check_type(def, value)?;
+        }
+    }
+
+    for def in defs.iter().filter(|d| d.required && applies_to(d, purpose)) {
+        let present = values.get(&def.name).is_some_and(|v| match v {
+            serde_json::Value::Null => false,
+            serde_json::Value::String(s) => !s.trim().is_empty(),
+            _ => true,
+        });
+        if !present {
+            return Err(AppError::BadRequest(format!(
+                "Custom field '{}' is required for purpose '{}'",
+                def.name, purpose
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+fn check_type(def: &CustomField, value: &serde_json::Value) -> Result<(), AppError> {
+    let ok = match def.field_type.as_str() {
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "boolean" => value.is_boolean(),
+        "date" => value
+            .as_str()
+            .is_some_and(|s| s.parse::<chrono::NaiveDate>().is_ok()),
+        "url" => value
+            .as_str()
+            .is_some_and(|s| s.starts_with("http://") || s.starts_with("https://")),
+        "enum" => value
+            .as_str()
+            .is_some_and(|s| def.enum_values.iter().any(|e| e == s)),
+        _ => true,
+    };
+    if ok {
+        return Ok(());
+    }
+    let expected = if def.field_type == "enum" {
+        format!("one of: {}", def.enum_values.join(", "))
+    } else {
+        format!("a {}", def.field_type)
+    };
+    Err(AppError::BadRequest(format!(
+        "Custom field '{}' must be {}, got {}",
+        def.name, expected, value
+    )))
+}
+
+/// Render a custom value as a plain string (for CSV columns and labels).
+pub fn value_to_string(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// `(name, value)` pairs of fields marked `expose_label` that are set on a VPS.
+pub fn exposed_labels(defs: &[CustomField], extra: &serde_json::Value) -> Vec<(String, String)> {
+    let Some(values) = custom_values(extra) else {
+        return Vec::new();
+    };
+    defs.iter()
+        .filter(|d| d.expose_label)
+        .filter_map(|d| {
+            let v = value_to_string(values.get(&d.name)?);
+            (!v.is_empty()).then(|| (d.name.clone(), v))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn def(name: &str, field_type: &str, required: bool, purposes: &[&str]) -> CustomField {
+        CustomField {
+            id: uuid::Uuid::nil(),
+            name: name.into(),
+            label: String::new(),
+            field_type: field_type.into(),
+            enum_values: vec!["a".into(), "b".into()],
+            required,
+            purposes: purposes.iter().map(|s| s.to_string()).collect(),
+            expose_label: true,
+            sort_order: 0,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_valid_values() {
+        let defs = vec![
+            def("panel_url", "url", false, &[]),
+            def("contract_id", "integer", false, &[]),
+            def("tier", "enum", false, &[]),
+            def("renew_on", "date", false, &[]),
+        ];
+        let extra = json!({"custom": {
+            "panel_url": "https://panel.example.com",
+            "contract_id": 42,
+            "tier": "a",
+            "renew_on": "2026-11-01",
+        }});
+        assert!(validate_custom(&defs, "vpn-exit", &extra).is_ok());
+    }
+
+    #[test]
+    fn test_type_errors() {
+        let defs = vec![def("contract_id", "integer", false, &[]), def("tier", "enum", false, &[])];
+        assert!(validate_custom(&defs, "", &json!({"custom": {"contract_id": "42"}})).is_err());
+        assert!(validate_custom(&defs, "", &json!({"custom": {"tier": "c"}})).is_err());
+        assert!(validate_custom(&defs, "", &json!({"custom": "nope"})).is_err());
+    }
+
+    #[test]
+    fn test_unknown_and_scoped_fields() {
+        let defs = vec![def("sni_domain", "string", false, &["vpn-exit"])];
+        assert!(validate_custom(&defs, "", &json!({"custom": {"other": "x"}})).is_err());
+        assert!(validate_custom(&defs, "monitor", &json!({"custom": {"sni_domain": "x"}})).is_err());
+        assert!(validate_custom(&defs, "vpn-exit", &json!({"custom": {"sni_domain": "x"}})).is_ok());
+    }
+
+    #[test]
+    fn test_required_only_for_scoped_purpose() {
+        let defs = vec![def("sni_domain", "string", true, &["vpn-exit"])];
+        assert!(validate_custom(&defs, "vpn-exit", &json!({})).is_err());
+        assert!(validate_custom(&defs, "vpn-exit", &json!({"custom": {"sni_domain": " "}})).is_err());
+        assert!(validate_custom(&defs, "monitor", &json!({})).is_ok());
+    }
+
+    #[test]
+    fn test_exposed_labels() {
+        let mut hidden = def("contract_id", "integer", false, &[]);
+        hidden.expose_label = false;
+        let defs = vec![def("tier", "enum", false, &[]), hidden];
+        let extra = json!({"custom": {"tier": "b", "contract_id": 7}});
+        assert_eq!(exposed_labels(&defs, &extra), vec![("tier".to_string(), "b".to_string())]);
+    }
+}
@@ -1 +1,15 @@
 pub type Db = sqlx::PgPool;
+
+/// Read a numeric `system_settings` value, falling back to `default` when unset or invalid.
+pub async fn setting_u64(db: &Db, key: &str, default: u64) -> u64 {
+    let result: Result<Option<(String,)>, _> =
+        sqlx::query_as("SELECT value FROM system_settings WHERE key = $1")
+            .bind(key)
+            .fetch_optional(db)
+            .await;
+
+    match result {
+        Ok(Some((val,))) => val.parse::<u64>().unwrap_or(default),
+        _ => default,
+    }
+}
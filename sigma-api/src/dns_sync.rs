@@ -1,3 +1,4 @@
+use crate::db;
 use crate::models::DnsAccount;
 use crate::routes::AppState;
 
@@ -32,16 +33,7 @@ pub async fn run(state: AppState, default_interval_secs: u64) {
 
 /// Query the DNS sync interval from system_settings, falling back to the startup default.
 async fn get_interval_secs(state: &AppState, default: u64) -> u64 {
-    let result: Result<Option<(String,)>, _> = sqlx::query_as(
-        "SELECT value FROM system_settings WHERE key = 'dns_sync_interval_secs'",
-    )
-    .fetch_optional(&state.db)
-    .await;
-
-    match result {
-        Ok(Some((val,))) => val.parse::<u64>().unwrap_or(default),
-        _ => default,
-    }
+    db::setting_u64(&state.db, "dns_sync_interval_secs", default).await
 }
 
 async fn sync_all_accounts(state: &AppState) -> anyhow::Result<()> {
@@ -32,6 +32,9 @@ pub enum AppError {
 
     #[error("Bad gateway: {0}")]
     BadGateway(String),
+
+    #[error("Precondition failed: {0}")]
+    PreconditionFailed(String),
 }
 
 impl IntoResponse for AppError {
@@ -47,6 +50,7 @@ impl IntoResponse for AppError {
             }
             AppError::Internal(_) => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
             AppError::BadGateway(_) => (StatusCode::BAD_GATEWAY, self.to_string()),
+            AppError::PreconditionFailed(_) => (StatusCode::PRECONDITION_FAILED, self.to_string()),
         };
 
         (status, Json(json!({ "error": msg }))).into_response()
@@ -89,6 +93,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_precondition_failed_is_412() {
+        assert_eq!(
+            status_of(AppError::PreconditionFailed("stale".into())),
+            StatusCode::PRECONDITION_FAILED
+        );
+    }
+
     #[test]
     fn test_internal_is_500() {
         assert_eq!(
@@ -0,0 +1,82 @@
+//! Optimistic concurrency for mutable resources.
+//!
+//! GET responses for VPS, providers, envoy routes and tickets carry an `ETag`
+//! derived from the row's `updated_at`. PUT and DELETE honour `If-Match`: the
+//! handler locks the row, compares its current tag and fails with 412 if the
+//! row changed since the client read it. Requests without `If-Match` behave
+//! as before (last write wins).
+
+use axum::http::{header, HeaderMap, HeaderName};
+use axum::Json;
+use chrono::{DateTime, Utc};
+
+use crate::errors::AppError;
+
+/// A JSON body with its `ETag` header.
+pub type Tagged<T> = ([(HeaderName, String); 1], Json<T>);
+
+/// Strong entity tag for a row version.
+pub fn etag(updated_at: DateTime<Utc>) -> String {
+    format!("\"{}\"", updated_at.timestamp_micros())
+}
+
+/// Attach the `ETag` header for `updated_at` to a JSON body.
+pub fn tagged<T>(updated_at: DateTime<Utc>, body: T) -> Tagged<T> {
+    ([(header::ETAG, etag(updated_at))], Json(body))
+}
+
+/// Check the request's `If-Match` header against the row's current version.
+/// A missing header always passes; `*` matches any existing row.
+pub fn check_if_match(headers: &HeaderMap, updated_at: DateTime<Utc>) -> Result<(), AppError> {
+    let Some(value) = headers.get(header::IF_MATCH) else {
+        return Ok(());
+    };
+    let value = value
+        .to_str()
+        .map_err(|_| AppError::BadRequest("Invalid If-Match header".into()))?;
+    let current = etag(updated_at);
+    let matched = value
+        .split(',')
+        .map(str::trim)
+        .any(|t| t == "*" || t.trim_start_matches("W/") == current);
+    if matched {
+        Ok(())
+    } else {
+        Err(AppError::PreconditionFailed(format!(
+            "resource was modified since it was read (current ETag {})",
+            current
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn headers(if_match: &str) -> HeaderMap {
+        let mut h = HeaderMap::new();
+        h.insert(header::IF_MATCH, HeaderValue::from_str(if_match).unwrap());
+        h
+    }
+
+    #[test]
+    fn test_missing_header_passes() {
+        assert!(check_if_match(&HeaderMap::new(), Utc::now()).is_ok());
+    }
+
+    #[test]
+    fn test_matching_and_stale_tags() {
+        let ts = Utc::now();
+        assert!(check_if_match(&headers(&etag(ts)), ts).is_ok());
+        assert!(check_if_match(&headers(&format!("W/{}", etag(ts))), ts).is_ok());
+        assert!(check_if_match(&headers(&format!("\"1\", {}", etag(ts))), ts).is_ok());
+        assert!(check_if_match(&headers("*"), ts).is_ok());
+
+        let later = ts + chrono::Duration::microseconds(1);
+        assert!(matches!(
+            check_if_match(&headers(&etag(ts)), later),
+            Err(AppError::PreconditionFailed(_))
+        ));
+    }
+}
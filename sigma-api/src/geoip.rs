@@ -0,0 +1,502 @@
+//! Local GeoIP / ASN enrichment.
+//!
+//! `GEOIP_DB_PATH` lists one or more MaxMind DB (`.mmdb`) files, e.g. a
+//! GeoLite2-City and a GeoLite2-ASN database, or a single IPinfo file. A
+//! background worker looks up every VPS IP and stores ASN, AS organization and
+//! geo country / city in `vps.ip_geo`. VPS whose IP set changed are refreshed on
+//! the next pass, all VPS when a database file changes on disk. `geo_mismatch`
+//! flags VPS whose `country` differs from the geolocation of one of their
+//! public IPs (internal and anycast IPs are ignored).
+
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use uuid::Uuid;
+
+use crate::db::Db;
+use crate::models::{IpEntry, IpGeo};
+
+const REFRESH_EVERY_SECS: u64 = 60;
+
+const METADATA_MARKER: &[u8] = b"\xAB\xCD\xEFMaxMind.com";
+const DATA_SECTION_SEPARATOR: usize = 16;
+
+/// A MaxMind DB file loaded into memory.
+pub struct MmdbReader {
+    buf: Vec<u8>,
+    node_count: usize,
+    record_size: usize,
+    ip_version: u64,
+    data_start: usize,
+    ipv4_start: usize,
+}
+
+impl MmdbReader {
+    pub fn from_bytes(buf: Vec<u8>) -> anyhow::Result<Self> {
+        let search_from = buf.len().saturating_sub(128 * 1024);
+        let marker = buf[search_from..]
+            .windows(METADATA_MARKER.len())
+            .rposition(|w| w == METADATA_MARKER)
+            .map(|p| search_from + p)
+            .ok_or_else(|| anyhow::anyhow!("not a MaxMind DB file: metadata marker missing"))?;
+        let meta_start = marker + METADATA_MARKER.len();
+        let (meta, _) = Decoder { buf: &buf, base: meta_start }.decode(meta_start)?;
+
+        let field = |key: &str| {
+            meta.get(key)
+                .and_then(|v| v.as_u64())
+                .ok_or_else(|| anyhow::anyhow!("MaxMind DB metadata lacks {key}"))
+        };
+        let node_count = field("node_count")? as usize;
+        let record_size = field("record_size")? as usize;
+        let ip_version = field("ip_version")?;
+        if !matches!(record_size, 24 | 28 | 32) {
+            anyhow::bail!("unsupported MaxMind DB record size {record_size}");
+        }
+
+        let tree_size = record_size * 2 / 8 * node_count;
+        let mut reader = Self {
+            buf,
+            node_count,
+            record_size,
+            ip_version,
+            data_start: tree_size + DATA_SECTION_SEPARATOR,
+            ipv4_start: 0,
+        };
+        if tree_size > marker {
+            anyhow::bail!("MaxMind DB search tree exceeds file size");
+        }
+
+        // IPv4 addresses live under ::/96 in IPv6 databases
+        if ip_version == 6 {
+            let mut node = 0;
+            for _ in 0..96 {
+                if node >= node_count {
+                    break;
+                }
+                node = reader.record(node, 0);
+            }
+            reader.ipv4_start = node;
+        }
+        Ok(reader)
+    }
+
+    pub fn open(path: &str) -> anyhow::Result<Self> {
+        let buf = std::fs::read(path)
+            .map_err(|e| anyhow::anyhow!("cannot read GeoIP database {path}: {e}"))?;
+        Self::from_bytes(buf)
+    }
+
+    /// Decoded data record for `ip`, or None when the database has no entry.
+    pub fn lookup(&self, ip: IpAddr) -> anyhow::Result<Option<serde_json::Value>> {
+        let (bits, start): (u128, u32) = match ip {
+            IpAddr::V4(v4) => (u32::from(v4) as u128, 32),
+            IpAddr::V6(_) if self.ip_version == 4 => return Ok(None),
+            IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+                Some(v4) => (u32::from(v4) as u128, 32),
+                None => (u128::from(v6), 128),
+            },
+        };
+
+        let mut node = if start == 32 { self.ipv4_start } else { 0 };
+        for i in (0..start).rev() {
+            if node >= self.node_count {
+                break;
+            }
+            node = self.record(node, ((bits >> i) & 1) as usize);
+        }
+
+        if node <= self.node_count {
+            return Ok(None);
+        }
+        let offset = node - self.node_count - DATA_SECTION_SEPARATOR;
+        let decoder = Decoder { buf: &self.buf, base: self.data_start };
+        let (value, _) = decoder.decode(self.data_start + offset)?;
+        Ok(Some(value))
+    }
+
+    fn record(&self, node: usize, bit: usize) -> usize {
+        let b = &self.buf;
+        match self.record_size {
+            24 => {
+                let o = node * 6 + bit * 3;
+                be(&b[o..o + 3])
+            }
+            28 => {
+                let o = node * 7;
+                if bit == 0 {
+                    ((b[o + 3] as usize & 0xF0) << 20) | be(&b[o..o + 3])
+                } else {
+                    ((b[o + 3] as usize & 0x0F) << 24) | be(&b[o + 4..o + 7])
+                }
+            }
+            _ => {
+                let o = node * 8 + bit * 4;
+                be(&b[o..o + 4])
+            }
+        }
+    }
+}
+
+fn be(bytes: &[u8]) -> usize {
+    bytes.iter().fold(0, |acc, b| (acc << 8) | *b as usize)
+}
+
+/// Decoder for the MaxMind DB data section format.
+struct Decoder<'a> {
+    buf: &'a [u8],
+    /// Start of the section pointers are relative to
+    base: usize,
+}
+
+impl Decoder<'_> {
+    fn byte(&self, at: usize) -> anyhow::Result<u8> {
+        self.buf
+            .get(at)
+            .copied()
+            .ok_or_else(|| anyhow::anyhow!("MaxMind DB data truncated at {at}"))
+    }
+
+    fn slice(&self, at: usize, len: usize) -> anyhow::Result<&[u8]> {
+        self.buf
+            .get(at..at + len)
+            .ok_or_else(|| anyhow::anyhow!("MaxMind DB data truncated at {at}"))
+    }
+
+    /// Decode the value at `at`, returning it and the offset after it.
+    fn decode(&self, at: usize) -> anyhow::Result<(serde_json::Value, usize)> {
+        let ctrl = self.byte(at)?;
+        let mut pos = at + 1;
+        let mut kind = ctrl >> 5;
+
+        if kind == 1 {
+            let ss = (ctrl >> 3) & 0x3;
+            let vvv = (ctrl & 0x7) as usize;
+            let len = ss as usize + 1;
+            let raw = be(self.slice(pos, len)?);
+            let target = match ss {
+                0 => (vvv << 8) | raw,
+                1 => ((vvv << 16) | raw) + 2048,
+                2 => ((vvv << 24) | raw) + 526_336,
+                _ => raw,
+            };
+            let (value, _) = self.decode(self.base + target)?;
+            return Ok((value, pos + len));
+        }
+
+        if kind == 0 {
+            kind = 7 + self.byte(pos)?;
+            pos += 1;
+        }
+
+        let mut size = (ctrl & 0x1f) as usize;
+        match size {
+            29 => {
+                size = 29 + self.byte(pos)? as usize;
+                pos += 1;
+            }
+            30 => {
+                size = 285 + be(self.slice(pos, 2)?);
+                pos += 2;
+            }
+            31 => {
+                size = 65_821 + be(self.slice(pos, 3)?);
+                pos += 3;
+            }
+            _ => {}
+        }
+
+        use serde_json::Value;
+        Ok(match kind {
+            2 => {
+                let s = String::from_utf8_lossy(self.slice(pos, size)?).into_owned();
+                (Value::String(s), pos + size)
+            }
+            3 => {
+                let v = f64::from_be_bytes(self.slice(pos, 8)?.try_into()?);
+                (serde_json::json!(v), pos + 8)
+            }
+            4 => (Value::Null, pos + size),
+            5 | 6 | 9 | 10 => {
+                let bytes = self.slice(pos, size)?;
+                let v = bytes.iter().fold(0u128, |acc, b| (acc << 8) | *b as u128);
+                let v = u64::try_from(v).map_or_else(|_| Value::String(v.to_string()), Value::from);
+                (v, pos + size)
+            }
+            7 => {
+                let mut map = serde_json::Map::new();
+                for _ in 0..size {
+                    let (key, next) = self.decode(pos)?;
+                    let (value, next) = self.decode(next)?;
+                    pos = next;
+                    if let Value::String(key) = key {
+                        map.insert(key, value);
+                    }
+                }
+                (Value::Object(map), pos)
+            }
+            8 => {
+                let bytes = self.slice(pos, size)?;
+                let v = bytes.iter().fold(0u32, |acc, b| (acc << 8) | *b as u32) as i32;
+                (Value::from(v), pos + size)
+            }
+            11 => {
+                let mut items = Vec::with_capacity(size);
+                for _ in 0..size {
+                    let (value, next) = self.decode(pos)?;
+                    pos = next;
+                    items.push(value);
+                }
+                (Value::Array(items), pos)
+            }
+            14 => (Value::Bool(size != 0), pos),
+            15 => {
+                let v = f32::from_be_bytes(self.slice(pos, 4)?.try_into()?);
+                (serde_json::json!(v), pos + 4)
+            }
+            _ => anyhow::bail!("unsupported MaxMind DB data type {kind}"),
+        })
+    }
+}
+
+/// Pull ASN and geo fields out of a record, accepting both MaxMind
+/// (GeoLite2-City / -Country / -ASN) and IPinfo field layouts.
+pub fn geo_from_record(ip: &str, record: &serde_json::Value) -> IpGeo {
+    let text = |v: Option<&serde_json::Value>| {
+        v.and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+    };
+
+    let asn = record
+        .get("autonomous_system_number")
+        .and_then(|v| v.as_i64())
+        .or_else(|| match record.get("asn") {
+            Some(serde_json::Value::String(s)) => s.trim_start_matches("AS").parse().ok(),
+            Some(v) => v.as_i64(),
+            None => None,
+        });
+    let as_org = text(record.get("autonomous_system_organization"))
+        .or_else(|| text(record.get("as_name")));
+    let country = match record.get("country") {
+        Some(serde_json::Value::Object(c)) => text(c.get("iso_code")),
+        other => text(other),
+    }
+    .map(|c| c.to_uppercase());
+    let city = match record.get("city") {
+        Some(serde_json::Value::Object(c)) => text(c.get("names").and_then(|n| n.get("en"))),
+        other => text(other),
+    };
+
+    IpGeo { ip: ip.to_string(), asn, as_org, country, city }
+}
+
+/// Lookups across every configured database; the first one with a field wins.
+pub struct GeoIp {
+    readers: Vec<MmdbReader>,
+}
+
+impl GeoIp {
+    pub fn open(paths: &[String]) -> anyhow::Result<Self> {
+        let readers = paths
+            .iter()
+            .map(|p| MmdbReader::open(p))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(Self { readers })
+    }
+
+    pub fn lookup(&self, ip: &str) -> IpGeo {
+        let mut geo = IpGeo { ip: ip.to_string(), ..Default::default() };
+        let Ok(addr) = ip.parse::<IpAddr>() else {
+            return geo;
+        };
+        for reader in &self.readers {
+            let record = match reader.lookup(addr) {
+                Ok(Some(record)) => record,
+                Ok(None) => continue,
+                Err(e) => {
+                    tracing::warn!("GeoIP lookup of {ip} failed: {e}");
+                    continue;
+                }
+            };
+            let found = geo_from_record(ip, &record);
+            geo.asn = geo.asn.or(found.asn);
+            geo.as_org = geo.as_org.or(found.as_org);
+            geo.country = geo.country.or(found.country);
+            geo.city = geo.city.or(found.city);
+        }
+        geo
+    }
+}
+
+/// Background worker: enrich changed VPS every minute, all VPS when a database changes.
+pub async fn run(db: Db, paths: Vec<String>) {
+    tracing::info!("GeoIP enrichment worker started ({})", paths.join(", "));
+
+    let mut loaded: Option<(GeoIp, Vec<Option<SystemTime>>)> = None;
+    loop {
+        let mtimes: Vec<Option<SystemTime>> = paths
+            .iter()
+            .map(|p| std::fs::metadata(PathBuf::from(p)).and_then(|m| m.modified()).ok())
+            .collect();
+
+        let reload = loaded.as_ref().is_none_or(|(_, seen)| *seen != mtimes);
+        if reload {
+            match GeoIp::open(&paths) {
+                Ok(geo) => loaded = Some((geo, mtimes)),
+                Err(e) => tracing::error!("GeoIP database load error: {e}"),
+            }
+        }
+
+        if let Some((geo, _)) = &loaded {
+            if let Err(e) = refresh(&db, geo, reload).await {
+                tracing::error!("GeoIP enrichment error: {e}");
+            }
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(REFRESH_EVERY_SECS)).await;
+    }
+}
+
+async fn refresh(db: &Db, geo: &GeoIp, all: bool) -> Result<(), sqlx::Error> {
+    // VPS whose IP set no longer matches the enriched set
+    let rows = sqlx::query_as::<_, (Uuid, sqlx::types::Json<Vec<IpEntry>>)>(
+        r#"SELECT id, ip_addresses FROM vps
+           WHERE status != 'deleted' AND (
+               $1 OR
+               (SELECT COALESCE(array_agg(e->>'ip' ORDER BY e->>'ip'), '{}')
+                FROM jsonb_array_elements(ip_addresses) e)
+               IS DISTINCT FROM
+               (SELECT COALESCE(array_agg(g->>'ip' ORDER BY g->>'ip'), '{}')
+                FROM jsonb_array_elements(ip_geo) g)
+           )"#,
+    )
+    .bind(all)
+    .fetch_all(db)
+    .await?;
+
+    for (id, entries) in &rows {
+        let enriched: Vec<IpGeo> = entries.0.iter().map(|e| geo.lookup(e.ip.trim())).collect();
+        sqlx::query("UPDATE vps SET ip_geo = $2 WHERE id = $1")
+            .bind(id)
+            .bind(sqlx::types::Json(&enriched))
+            .execute(db)
+            .await?;
+    }
+
+    // Also picks up hand edits of `country`
+    let flagged = sqlx::query(
+        r#"UPDATE vps v SET geo_mismatch = m.mismatch
+           FROM (
+               SELECT v2.id, (v2.country != '' AND EXISTS (
+                   SELECT 1
+                   FROM jsonb_array_elements(v2.ip_geo) g
+                   JOIN jsonb_array_elements(v2.ip_addresses) e ON e->>'ip' = g->>'ip'
+                   WHERE COALESCE(e->>'label', '') NOT IN ('internal', 'anycast')
+                     AND COALESCE(g->>'country', '') != ''
+                     AND upper(g->>'country') != upper(v2.country)
+               )) AS mismatch
+               FROM vps v2
+           ) m
+           WHERE v.id = m.id AND v.geo_mismatch != m.mismatch"#,
+    )
+    .execute(db)
+    .await?
+    .rows_affected();
+
+    if !rows.is_empty() || flagged > 0 {
+        tracing::info!(
+            "GeoIP: {} VPS enriched, {} mismatch flags changed",
+            rows.len(),
+            flagged
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enc_head(kind: u8, size: usize) -> Vec<u8> {
+        assert!(size < 29);
+        if kind <= 7 {
+            vec![(kind << 5) | size as u8]
+        } else {
+            vec![size as u8, kind - 7]
+        }
+    }
+
+    fn enc_str(s: &str) -> Vec<u8> {
+        let mut out = enc_head(2, s.len());
+        out.extend_from_slice(s.as_bytes());
+        out
+    }
+
+    fn enc_uint(kind: u8, v: u32) -> Vec<u8> {
+        let bytes: Vec<u8> = v.to_be_bytes().into_iter().skip_while(|b| *b == 0).collect();
+        let mut out = enc_head(kind, bytes.len());
+        out.extend(bytes);
+        out
+    }
+
+    fn enc_map(entries: Vec<(&str, Vec<u8>)>) -> Vec<u8> {
+        let mut out = enc_head(7, entries.len());
+        for (k, v) in entries {
+            out.extend(enc_str(k));
+            out.extend(v);
+        }
+        out
+    }
+
+    /// IPv4 database with one node: 0.0.0.0/1 has data, 128.0.0.0/1 is empty.
+    fn test_db() -> Vec<u8> {
+        let node_count = 1u32;
+        let mut buf = Vec::new();
+        let data_record = node_count + DATA_SECTION_SEPARATOR as u32;
+        buf.extend(&data_record.to_be_bytes()[1..]);
+        buf.extend(&node_count.to_be_bytes()[1..]);
+        buf.extend([0u8; DATA_SECTION_SEPARATOR]);
+        buf.extend(enc_map(vec![
+            ("country", enc_map(vec![("iso_code", enc_str("jp"))])),
+            ("city", enc_map(vec![("names", enc_map(vec![("en", enc_str("Tokyo"))]))])),
+            ("autonomous_system_number", enc_uint(6, 2516)),
+            ("autonomous_system_organization", enc_str("KDDI")),
+        ]));
+        buf.extend(METADATA_MARKER);
+        buf.extend(enc_map(vec![
+            ("node_count", enc_uint(6, node_count)),
+            ("record_size", enc_uint(5, 24)),
+            ("ip_version", enc_uint(5, 4)),
+        ]));
+        buf
+    }
+
+    #[test]
+    fn test_mmdb_lookup() {
+        let reader = MmdbReader::from_bytes(test_db()).unwrap();
+        let record = reader.lookup("1.2.3.4".parse().unwrap()).unwrap().unwrap();
+        let geo = geo_from_record("1.2.3.4", &record);
+        assert_eq!(geo.asn, Some(2516));
+        assert_eq!(geo.as_org.as_deref(), Some("KDDI"));
+        assert_eq!(geo.country.as_deref(), Some("JP"));
+        assert_eq!(geo.city.as_deref(), Some("Tokyo"));
+
+        assert!(reader.lookup("200.1.1.1".parse().unwrap()).unwrap().is_none());
+        assert!(reader.lookup("2001:db8::1".parse().unwrap()).unwrap().is_none());
+        assert!(MmdbReader::from_bytes(b"not a database".to_vec()).is_err());
+    }
+
+    #[test]
+    fn test_geo_from_ipinfo_record() {
+        let record = serde_json::json!({
+            "country": "HK",
+            "asn": "AS4809",
+            "as_name": "China Telecom Next Generation Carrier Network",
+        });
+        let geo = geo_from_record("203.0.113.9", &record);
+        assert_eq!(geo.asn, Some(4809));
+        assert_eq!(geo.country.as_deref(), Some("HK"));
+        assert_eq!(geo.city, None);
+    }
+}
@@ -0,0 +1,260 @@
+//! Agent heartbeat time series.
+//!
+//! Each heartbeat stores a raw sample in `vps_heartbeats`. The background worker
+//! rolls raw samples older than `heartbeat_raw_retention_hours` into hourly rows
+//! and deletes rows older than `heartbeat_retention_days` (0 keeps them forever).
+//! Agent uptime is derived from `covered_secs`, the gap to the previous sample
+//! when it is short enough to count as continuous.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::db::{self, Db};
+use crate::models::{ProviderAgentUptime, VpsAgentUptime};
+
+pub const RESOLUTION_RAW: i32 = 0;
+pub const RESOLUTION_HOURLY: i32 = 3600;
+
+/// Gaps of up to this many agent intervals still count as the agent being up.
+const MAX_GAP_INTERVALS: u64 = 3;
+
+const DEFAULT_AGENT_INTERVAL_SECS: u64 = 60;
+const DEFAULT_RAW_RETENTION_HOURS: u64 = 48;
+const DEFAULT_RETENTION_DAYS: u64 = 90;
+
+const ROLLUP_EVERY_SECS: u64 = 3600;
+
+/// Longest gap between two heartbeats that still counts as uptime.
+pub fn max_gap_secs(agent_interval_secs: u64) -> u64 {
+    agent_interval_secs.max(1) * MAX_GAP_INTERVALS
+}
+
+/// Store a raw sample taken from a heartbeat's `system_info`.
+pub async fn record(
+    db: &Db,
+    vps_id: Uuid,
+    system_info: &serde_json::Value,
+) -> Result<(), sqlx::Error> {
+    let interval = db::setting_u64(db, "agent_interval_secs", DEFAULT_AGENT_INTERVAL_SECS).await;
+    let load = |i: usize| {
+        system_info
+            .get("load_avg")
+            .and_then(|l| l.get(i))
+            .and_then(|v| v.as_f64())
+    };
+    let int = |key: &str| {
+        system_info
+            .get(key)
+            .and_then(|v| v.as_i64())
+            .and_then(|v| i32::try_from(v).ok())
+    };
+
+    sqlx::query(
+        r#"INSERT INTO vps_heartbeats
+               (vps_id, covered_secs, load_1, load_5, load_15,
+                ram_mb, ram_used_mb, disk_gb, disk_used_gb, uptime_seconds)
+           SELECT $1,
+                  COALESCE((
+                      SELECT CASE WHEN gap <= $2 THEN gap ELSE 0 END
+                      FROM (
+                          SELECT EXTRACT(EPOCH FROM now() - MAX(ts))::INTEGER AS gap
+                          FROM vps_heartbeats WHERE vps_id = $1 AND resolution = 0
+                      ) g
+                  ), 0),
+                  $3, $4, $5, $6, $7, $8, $9, $10"#,
+    )
+    .bind(vps_id)
+    .bind(max_gap_secs(interval) as i32)
+    .bind(load(0))
+    .bind(load(1))
+    .bind(load(2))
+    .bind(int("ram_mb"))
+    .bind(int("ram_used_mb"))
+    .bind(int("disk_gb"))
+    .bind(int("disk_used_gb"))
+    .bind(system_info.get("uptime_seconds").and_then(|v| v.as_i64()))
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Background worker: downsample and expire heartbeats once an hour.
+pub async fn run(db: Db) {
+    tracing::info!("Heartbeat rollup worker started");
+
+    loop {
+        if let Err(e) = rollup(&db).await {
+            tracing::error!("Heartbeat rollup error: {e}");
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(ROLLUP_EVERY_SECS)).await;
+    }
+}
+
+async fn rollup(db: &Db) -> Result<(), sqlx::Error> {
+    let raw_hours = db::setting_u64(
+        db,
+        "heartbeat_raw_retention_hours",
+        DEFAULT_RAW_RETENTION_HOURS,
+    )
+    .await;
+    let keep_days = db::setting_u64(db, "heartbeat_retention_days", DEFAULT_RETENTION_DAYS).await;
+
+    let mut tx = db.begin().await?;
+
+    // The cutoff is hour-aligned, so every hourly bucket is rolled up exactly once
+    let rolled = sqlx::query(
+        r#"WITH moved AS (
+               DELETE FROM vps_heartbeats
+               WHERE resolution = $1
+                 AND ts < date_trunc('hour', now() - make_interval(hours => $3))
+               RETURNING *
+           )
+           INSERT INTO vps_heartbeats
+               (vps_id, ts, resolution, samples, covered_secs, load_1, load_5, load_15,
+                ram_mb, ram_used_mb, disk_gb, disk_used_gb, uptime_seconds)
+           SELECT vps_id, date_trunc('hour', ts), $2, SUM(samples), SUM(covered_secs),
+                  AVG(load_1), AVG(load_5), AVG(load_15),
+                  MAX(ram_mb), AVG(ram_used_mb)::INTEGER, MAX(disk_gb), MAX(disk_used_gb),
+                  MAX(uptime_seconds)
+           FROM moved
+           GROUP BY vps_id, date_trunc('hour', ts)"#,
+    )
+    .bind(RESOLUTION_RAW)
+    .bind(RESOLUTION_HOURLY)
+    .bind(raw_hours.min(i32::MAX as u64) as i32)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    let expired = if keep_days > 0 {
+        sqlx::query("DELETE FROM vps_heartbeats WHERE ts < now() - make_interval(days => $1)")
+            .bind(keep_days.min(i32::MAX as u64) as i32)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected()
+    } else {
+        0
+    };
+
+    tx.commit().await?;
+
+    if rolled > 0 || expired > 0 {
+        tracing::info!(
+            "Heartbeat rollup: {} hourly rows written, {} rows expired",
+            rolled,
+            expired
+        );
+    }
+    Ok(())
+}
+
+/// Agent uptime per VPS since `since`, optionally for a single VPS.
+/// VPS without heartbeats in the window are left out.
+pub async fn vps_uptime(
+    db: &Db,
+    since: DateTime<Utc>,
+    vps_id: Option<Uuid>,
+) -> Result<Vec<VpsAgentUptime>, sqlx::Error> {
+    sqlx::query_as::<_, VpsAgentUptime>(
+        r#"SELECT w.vps_id, v.hostname, v.provider_id, p.name AS provider_name,
+                  w.covered_secs, w.window_secs,
+                  LEAST(100.0, 100.0 * w.covered_secs / w.window_secs)::FLOAT8 AS uptime_pct
+           FROM (
+               SELECT vps_id,
+                      SUM(covered_secs)::FLOAT8 AS covered_secs,
+                      GREATEST(EXTRACT(EPOCH FROM now() - GREATEST($1, MIN(ts))), 1)::FLOAT8 AS window_secs
+               FROM vps_heartbeats
+               WHERE ts >= $1 AND ($2::UUID IS NULL OR vps_id = $2)
+               GROUP BY vps_id
+           ) w
+           JOIN vps v ON v.id = w.vps_id
+           LEFT JOIN providers p ON p.id = v.provider_id
+           WHERE v.status != 'deleted'
+           ORDER BY uptime_pct, v.hostname"#,
+    )
+    .bind(since)
+    .bind(vps_id)
+    .fetch_all(db)
+    .await
+}
+
+/// Roll per-VPS uptime up to providers, weighting each VPS by its window.
+pub fn provider_uptime(vps: &[VpsAgentUptime]) -> Vec<ProviderAgentUptime> {
+    // provider_id -> (name, vps count, covered secs, window secs)
+    let mut totals: BTreeMap<Option<Uuid>, (String, usize, f64, f64)> = BTreeMap::new();
+    for v in vps {
+        let entry = totals.entry(v.provider_id).or_insert_with(|| {
+            let name = v
+                .provider_name
+                .clone()
+                .unwrap_or_else(|| "(unassigned)".to_string());
+            (name, 0, 0.0, 0.0)
+        });
+        entry.1 += 1;
+        entry.2 += v.covered_secs;
+        entry.3 += v.window_secs;
+    }
+
+    let mut rows: Vec<ProviderAgentUptime> = totals
+        .into_iter()
+        .map(
+            |(provider_id, (provider_name, vps_count, covered, window))| ProviderAgentUptime {
+                provider_id,
+                provider_name,
+                vps_count,
+                uptime_pct: if window > 0.0 {
+                    (100.0 * covered / window).min(100.0)
+                } else {
+                    0.0
+                },
+            },
+        )
+        .collect();
+    rows.sort_by(|a, b| a.provider_name.cmp(&b.provider_name));
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uptime(
+        provider: Option<Uuid>,
+        name: Option<&str>,
+        covered: f64,
+        window: f64,
+    ) -> VpsAgentUptime {
+        VpsAgentUptime {
+            vps_id: Uuid::new_v4(),
+            hostname: "h".into(),
+            provider_id: provider,
+            provider_name: name.map(String::from),
+            covered_secs: covered,
+            window_secs: window,
+            uptime_pct: 100.0 * covered / window,
+        }
+    }
+
+    #[test]
+    fn test_max_gap_secs() {
+        assert_eq!(max_gap_secs(60), 180);
+        assert_eq!(max_gap_secs(0), 3);
+    }
+
+    #[test]
+    fn test_provider_uptime_weighted_by_window() {
+        let p = Uuid::new_v4();
+        let rows = provider_uptime(&[
+            uptime(Some(p), Some("Acme"), 900.0, 1000.0),
+            uptime(Some(p), Some("Acme"), 3000.0, 3000.0),
+            uptime(None, None, 50.0, 100.0),
+        ]);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].provider_name, "(unassigned)");
+        assert_eq!(rows[0].uptime_pct, 50.0);
+        assert_eq!(rows[1].vps_count, 2);
+        assert_eq!(rows[1].uptime_pct, 97.5);
+    }
+}
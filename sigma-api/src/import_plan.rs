@@ -0,0 +1,162 @@
+//! Import modes and per-row plans shared by the VPS and provider importers.
+//!
+//! `insert` keeps the original behaviour: every valid row is inserted in its own
+//! transaction. `upsert` matches rows against existing ones on a natural key
+//! (VPS hostname or alias, provider name), plans create / update / skip per row
+//! and applies the whole plan in one transaction. `dry_run` returns the upsert
+//! plan without writing anything.
+
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::errors::AppError;
+use crate::models::{ImportPlanRow, ImportResult};
+
+pub const ACTION_CREATE: &str = "create";
+pub const ACTION_UPDATE: &str = "update";
+pub const ACTION_SKIP: &str = "skip";
+pub const ACTION_ERROR: &str = "error";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportMode {
+    DryRun,
+    Insert,
+    Upsert,
+}
+
+impl ImportMode {
+    pub fn parse(input: &str) -> Result<ImportMode, AppError> {
+        match input {
+            "dry_run" => Ok(ImportMode::DryRun),
+            "insert" => Ok(ImportMode::Insert),
+            "upsert" => Ok(ImportMode::Upsert),
+            other => Err(AppError::BadRequest(format!(
+                "Invalid mode '{}' (allowed: dry_run, insert, upsert)",
+                other
+            ))),
+        }
+    }
+
+    /// Whether rows are matched against existing ones by natural key.
+    pub fn matches_existing(self) -> bool {
+        self != ImportMode::Insert
+    }
+}
+
+impl ImportPlanRow {
+    pub fn create(row: usize, key: String) -> ImportPlanRow {
+        ImportPlanRow::new(row, ACTION_CREATE, key, None, serde_json::Map::new(), None)
+    }
+
+    pub fn update(
+        row: usize,
+        key: String,
+        id: Uuid,
+        changes: serde_json::Map<String, serde_json::Value>,
+    ) -> ImportPlanRow {
+        ImportPlanRow::new(row, ACTION_UPDATE, key, Some(id), changes, None)
+    }
+
+    pub fn skip(row: usize, key: String, id: Uuid) -> ImportPlanRow {
+        ImportPlanRow::new(row, ACTION_SKIP, key, Some(id), serde_json::Map::new(), None)
+    }
+
+    pub fn error(row: usize, key: String, error: String) -> ImportPlanRow {
+        ImportPlanRow::new(row, ACTION_ERROR, key, None, serde_json::Map::new(), Some(error))
+    }
+
+    fn new(
+        row: usize,
+        action: &str,
+        key: String,
+        id: Option<Uuid>,
+        changes: serde_json::Map<String, serde_json::Value>,
+        error: Option<String>,
+    ) -> ImportPlanRow {
+        ImportPlanRow {
+            row,
+            action: action.to_string(),
+            key,
+            id,
+            changes: serde_json::Value::Object(changes),
+            error,
+        }
+    }
+}
+
+/// Record `field` in `changes` as `{"from": old, "to": new}` when `new` is given and differs.
+pub fn diff_field<T: Serialize + PartialEq>(
+    changes: &mut serde_json::Map<String, serde_json::Value>,
+    field: &str,
+    old: &T,
+    new: Option<&T>,
+) {
+    if let Some(new) = new {
+        if new != old {
+            changes.insert(field.to_string(), serde_json::json!({"from": old, "to": new}));
+        }
+    }
+}
+
+/// Message for a plan row: the bare text of a `BadRequest`, the full error otherwise.
+pub fn error_message(e: AppError) -> String {
+    match e {
+        AppError::BadRequest(msg) => msg,
+        other => other.to_string(),
+    }
+}
+
+/// Summarize a finished plan. `errors` repeats the error rows as `Row N: message`.
+pub fn summarize(mode: ImportMode, plan: Vec<ImportPlanRow>) -> ImportResult {
+    let count = |action: &str| plan.iter().filter(|p| p.action == action).count();
+    let errors = plan
+        .iter()
+        .filter(|p| p.action == ACTION_ERROR)
+        .map(|p| format!("Row {}: {}", p.row, p.error.as_deref().unwrap_or_default()))
+        .collect();
+    ImportResult {
+        dry_run: mode == ImportMode::DryRun,
+        imported: count(ACTION_CREATE),
+        updated: count(ACTION_UPDATE),
+        skipped: count(ACTION_SKIP),
+        errors,
+        plan,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mode() {
+        assert_eq!(ImportMode::parse("dry_run").unwrap(), ImportMode::DryRun);
+        assert_eq!(ImportMode::parse("upsert").unwrap(), ImportMode::Upsert);
+        assert!(!ImportMode::parse("insert").unwrap().matches_existing());
+        assert!(ImportMode::parse("merge").is_err());
+    }
+
+    #[test]
+    fn test_diff_field() {
+        let mut changes = serde_json::Map::new();
+        diff_field(&mut changes, "country", &"JP".to_string(), Some(&"HK".to_string()));
+        diff_field(&mut changes, "city", &"Tokyo".to_string(), Some(&"Tokyo".to_string()));
+        diff_field(&mut changes, "notes", &"x".to_string(), None);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes["country"], serde_json::json!({"from": "JP", "to": "HK"}));
+    }
+
+    #[test]
+    fn test_summarize() {
+        let plan = vec![
+            ImportPlanRow::create(1, "a".into()),
+            ImportPlanRow::update(2, "b".into(), Uuid::nil(), serde_json::Map::new()),
+            ImportPlanRow::skip(3, "c".into(), Uuid::nil()),
+            ImportPlanRow::error(4, "".into(), "hostname is required".into()),
+        ];
+        let result = summarize(ImportMode::DryRun, plan);
+        assert!(result.dry_run);
+        assert_eq!((result.imported, result.updated, result.skipped), (1, 1, 1));
+        assert_eq!(result.errors, vec!["Row 4: hostname is required".to_string()]);
+    }
+}
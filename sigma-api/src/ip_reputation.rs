@@ -0,0 +1,618 @@
+//! Blocklist / IP reputation checks.
+//!
+//! Every `ip_reputation_interval_secs` a background worker looks up each public
+//! IP of a live VPS (labels other than `internal`) in the DNSBL zones from
+//! `DNSBL_ZONES`, and with `ABUSEIPDB_API_KEY` set in AbuseIPDB, where a score of
+//! at least `ip_reputation_abuse_min_score` counts as listed. DNSBL queries go
+//! to `DNSBL_RESOLVER` (`host[:port]`), by default the first nameserver of
+//! `/etc/resolv.conf`. Public resolvers are refused by several lists, so point
+//! it at a local recursive resolver.
+//!
+//! Listings are kept in `ip_reputation`; a lookup that fails leaves the IP's
+//! listing on that list as it was. New listings of active VPS go to the
+//! configured notification channels.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::Duration;
+
+use futures::StreamExt;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::db::{self, Db};
+use crate::notifications::{send_telegram, send_webhook};
+
+/// List name used for AbuseIPDB results.
+pub const LIST_ABUSEIPDB: &str = "abuseipdb";
+
+const DEFAULT_INTERVAL_SECS: u64 = 21600;
+const DEFAULT_ABUSE_MIN_SCORE: u64 = 50;
+
+const ABUSEIPDB_URL: &str = "https://api.abuseipdb.com/api/v2/check";
+const DNS_TIMEOUT: Duration = Duration::from_secs(3);
+const DNS_ATTEMPTS: usize = 2;
+/// Lookups in flight at once
+const CONCURRENCY: usize = 16;
+
+/// Resolver from `DNSBL_RESOLVER`, else the first nameserver in `/etc/resolv.conf`.
+pub fn resolver_addr(configured: Option<&str>) -> Option<SocketAddr> {
+    if let Some(s) = configured {
+        return parse_resolver(s);
+    }
+    std::fs::read_to_string("/etc/resolv.conf")
+        .ok()?
+        .lines()
+        .filter_map(|l| l.trim().strip_prefix("nameserver"))
+        .find_map(|s| parse_resolver(s.trim()))
+}
+
+/// `1.2.3.4`, `1.2.3.4:5353`, `::1` or `[::1]:5353`; port 53 when omitted.
+pub fn parse_resolver(s: &str) -> Option<SocketAddr> {
+    s.parse::<SocketAddr>()
+        .ok()
+        .or_else(|| s.parse::<IpAddr>().ok().map(|ip| SocketAddr::new(ip, 53)))
+}
+
+/// Whether an address is worth checking: blocklists only carry public addresses.
+pub fn is_checkable(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+            !(v4.is_private()
+                || v4.is_loopback()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+                || v4.is_multicast()
+                || (a == 100 && (64..128).contains(&b)))
+        }
+        IpAddr::V6(v6) => {
+            let first = v6.segments()[0];
+            !(v6.is_loopback()
+                || v6.is_unspecified()
+                || v6.is_multicast()
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80)
+        }
+    }
+}
+
+/// DNSBL query name: reversed octets (IPv4) or nibbles (IPv6) under the zone.
+pub fn dnsbl_name(ip: IpAddr, zone: &str) -> String {
+    let reversed = match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, c, d] = v4.octets();
+            format!("{d}.{c}.{b}.{a}")
+        }
+        IpAddr::V6(v6) => v6
+            .octets()
+            .iter()
+            .rev()
+            .flat_map(|byte| [byte & 0x0f, byte >> 4])
+            .map(|n| format!("{n:x}"))
+            .collect::<Vec<_>>()
+            .join("."),
+    };
+    format!("{reversed}.{zone}")
+}
+
+/// Look `ip` up in a DNSBL zone. Returns the return codes when listed, None when not.
+pub async fn dnsbl_lookup(
+    resolver: SocketAddr,
+    ip: IpAddr,
+    zone: &str,
+) -> anyhow::Result<Option<String>> {
+    let answers = resolve_a(resolver, &dnsbl_name(ip, zone)).await?;
+    if answers.is_empty() {
+        return Ok(None);
+    }
+    // Spamhaus and others answer 127.255.255.x when they refuse the query
+    if let Some(refused) = answers.iter().find(|a| a.octets()[..3] == [127, 255, 255]) {
+        anyhow::bail!("{zone} refused the query ({refused}); use a local recursive resolver");
+    }
+    if !answers.iter().all(|a| a.octets()[0] == 127) {
+        anyhow::bail!("{zone} answered outside 127.0.0.0/8; the resolver rewrites NXDOMAIN");
+    }
+    Ok(Some(
+        answers.iter().map(|a| a.to_string()).collect::<Vec<_>>().join(", "),
+    ))
+}
+
+/// AbuseIPDB confidence score. Returns a detail string when at or above `min_score`.
+pub async fn abuseipdb_lookup(
+    client: &reqwest::Client,
+    api_key: &str,
+    ip: IpAddr,
+    min_score: u64,
+) -> anyhow::Result<Option<String>> {
+    let resp = client
+        .get(ABUSEIPDB_URL)
+        .query(&[("ipAddress", ip.to_string()), ("maxAgeInDays", "90".to_string())])
+        .header("Key", api_key)
+        .header("Accept", "application/json")
+        .timeout(Duration::from_secs(15))
+        .send()
+        .await?;
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp.text().await.unwrap_or_default();
+        anyhow::bail!("AbuseIPDB {status}: {body}");
+    }
+
+    let body: serde_json::Value = resp.json().await?;
+    let score = body["data"]["abuseConfidenceScore"]
+        .as_u64()
+        .ok_or_else(|| anyhow::anyhow!("AbuseIPDB response lacks abuseConfidenceScore"))?;
+    if score < min_score {
+        return Ok(None);
+    }
+    let reports = body["data"]["totalReports"].as_u64().unwrap_or(0);
+    Ok(Some(format!("score {score}, {reports} reports")))
+}
+
+// ─── Minimal DNS client ─────────────────────────────────
+
+const TYPE_A: u16 = 1;
+const CLASS_IN: u16 = 1;
+const RCODE_NXDOMAIN: u16 = 3;
+
+/// A records of `name`; empty for NXDOMAIN or no data.
+pub async fn resolve_a(resolver: SocketAddr, name: &str) -> anyhow::Result<Vec<Ipv4Addr>> {
+    let bind: SocketAddr = if resolver.is_ipv4() {
+        (Ipv4Addr::UNSPECIFIED, 0).into()
+    } else {
+        (std::net::Ipv6Addr::UNSPECIFIED, 0).into()
+    };
+    let socket = tokio::net::UdpSocket::bind(bind).await?;
+    socket.connect(resolver).await?;
+
+    let mut last_err = anyhow::anyhow!("no attempt made");
+    for _ in 0..DNS_ATTEMPTS {
+        let id = rand_id();
+        socket.send(&build_query(id, name)?).await?;
+        let mut buf = [0u8; 1500];
+        // Skip stray datagrams (e.g. late answers to an earlier attempt)
+        let received = tokio::time::timeout(DNS_TIMEOUT, async {
+            loop {
+                let n = socket.recv(&mut buf).await?;
+                if n >= 2 && u16::from_be_bytes([buf[0], buf[1]]) == id {
+                    return Ok::<usize, std::io::Error>(n);
+                }
+            }
+        })
+        .await;
+        match received {
+            Ok(Ok(n)) => return parse_a_response(&buf[..n]),
+            Ok(Err(e)) => last_err = e.into(),
+            Err(_) => last_err = anyhow::anyhow!("DNS query for {name} to {resolver} timed out"),
+        }
+    }
+    Err(last_err)
+}
+
+fn rand_id() -> u16 {
+    let bytes = Uuid::new_v4().into_bytes();
+    u16::from_be_bytes([bytes[0], bytes[1]])
+}
+
+fn build_query(id: u16, name: &str) -> anyhow::Result<Vec<u8>> {
+    let mut q = Vec::with_capacity(name.len() + 18);
+    q.extend_from_slice(&id.to_be_bytes());
+    q.extend_from_slice(&0x0100u16.to_be_bytes()); // recursion desired
+    q.extend_from_slice(&[0, 1, 0, 0, 0, 0, 0, 0]); // one question
+    for label in name.trim_end_matches('.').split('.') {
+        if label.is_empty() || label.len() > 63 {
+            anyhow::bail!("invalid DNS name {name}");
+        }
+        q.push(label.len() as u8);
+        q.extend_from_slice(label.as_bytes());
+    }
+    q.push(0);
+    q.extend_from_slice(&TYPE_A.to_be_bytes());
+    q.extend_from_slice(&CLASS_IN.to_be_bytes());
+    Ok(q)
+}
+
+fn parse_a_response(buf: &[u8]) -> anyhow::Result<Vec<Ipv4Addr>> {
+    let u16_at = |pos: usize| -> anyhow::Result<u16> {
+        buf.get(pos..pos + 2)
+            .map(|b| u16::from_be_bytes([b[0], b[1]]))
+            .ok_or_else(|| anyhow::anyhow!("truncated DNS response"))
+    };
+    let flags = u16_at(2)?;
+    if flags & 0x8000 == 0 {
+        anyhow::bail!("DNS reply is not a response");
+    }
+    match flags & 0x000f {
+        0 => {}
+        RCODE_NXDOMAIN => return Ok(Vec::new()),
+        rcode => anyhow::bail!("DNS error rcode {rcode}"),
+    }
+    let questions = u16_at(4)?;
+    let answers = u16_at(6)?;
+
+    let mut pos = 12;
+    for _ in 0..questions {
+        pos = skip_name(buf, pos)? + 4;
+    }
+    let mut out = Vec::new();
+    for _ in 0..answers {
+        pos = skip_name(buf, pos)?;
+        let rtype = u16_at(pos)?;
+        let rdlen = u16_at(pos + 8)? as usize;
+        let rdata = buf
+            .get(pos + 10..pos + 10 + rdlen)
+            .ok_or_else(|| anyhow::anyhow!("truncated DNS response"))?;
+        if rtype == TYPE_A && rdlen == 4 {
+            out.push(Ipv4Addr::new(rdata[0], rdata[1], rdata[2], rdata[3]));
+        }
+        pos += 10 + rdlen;
+    }
+    Ok(out)
+}
+
+/// Position after the (possibly compressed) name starting at `pos`.
+fn skip_name(buf: &[u8], mut pos: usize) -> anyhow::Result<usize> {
+    loop {
+        let len = *buf
+            .get(pos)
+            .ok_or_else(|| anyhow::anyhow!("truncated DNS response"))? as usize;
+        match len {
+            0 => return Ok(pos + 1),
+            l if l & 0xc0 == 0xc0 => return Ok(pos + 2),
+            l => pos += 1 + l,
+        }
+    }
+}
+
+// ─── Worker ─────────────────────────────────────────────
+
+/// Background worker that re-checks every fleet IP every `ip_reputation_interval_secs`.
+pub async fn run(db: Db, http_client: reqwest::Client, cfg: Config) {
+    let resolver = resolver_addr(cfg.dnsbl_resolver.as_deref());
+    match (resolver, cfg.dnsbl_zones.is_empty()) {
+        (_, true) => {}
+        (Some(r), false) => tracing::info!("DNSBL resolver: {r}"),
+        (None, false) => tracing::error!(
+            "DNSBL checks disabled: no usable DNSBL_RESOLVER or /etc/resolv.conf nameserver"
+        ),
+    }
+    tracing::info!(
+        "IP reputation worker started (zones={:?}, abuseipdb={})",
+        cfg.dnsbl_zones,
+        cfg.abuseipdb_api_key.is_some(),
+    );
+
+    loop {
+        if let Err(e) = check_and_notify(&db, &http_client, &cfg, resolver).await {
+            tracing::error!("IP reputation check error: {e}");
+        }
+        let interval =
+            db::setting_u64(&db, "ip_reputation_interval_secs", DEFAULT_INTERVAL_SECS).await;
+        tokio::time::sleep(Duration::from_secs(interval.max(300))).await;
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct Target {
+    vps_id: Uuid,
+    hostname: String,
+    alias: String,
+    country: String,
+    status: String,
+    provider_name: String,
+    ip: String,
+}
+
+async fn check_and_notify(
+    db: &Db,
+    http_client: &reqwest::Client,
+    cfg: &Config,
+    resolver: Option<SocketAddr>,
+) -> anyhow::Result<()> {
+    let min_score =
+        db::setting_u64(db, "ip_reputation_abuse_min_score", DEFAULT_ABUSE_MIN_SCORE).await;
+
+    let targets: Vec<Target> = sqlx::query_as::<_, Target>(
+        r#"SELECT v.id AS vps_id, v.hostname, v.alias, v.country, v.status,
+                  COALESCE((SELECT name FROM providers p WHERE p.id = v.provider_id), '') AS provider_name,
+                  btrim(e.ip) AS ip
+           FROM vps v
+           CROSS JOIN LATERAL jsonb_to_recordset(v.ip_addresses) AS e(ip TEXT, label TEXT)
+           WHERE v.status != 'deleted' AND COALESCE(e.label, '') != 'internal'"#,
+    )
+    .fetch_all(db)
+    .await?
+    .into_iter()
+    .filter(|t| t.ip.parse::<IpAddr>().is_ok_and(is_checkable))
+    .collect();
+
+    let mut lists: Vec<String> = Vec::new();
+    if resolver.is_some() {
+        lists.extend(cfg.dnsbl_zones.iter().cloned());
+    }
+    if cfg.abuseipdb_api_key.is_some() {
+        lists.push(LIST_ABUSEIPDB.to_string());
+    }
+
+    let ips: BTreeSet<IpAddr> = targets.iter().filter_map(|t| t.ip.parse().ok()).collect();
+    let lookups = ips
+        .iter()
+        .flat_map(|ip| lists.iter().map(move |list| (*ip, list.clone())))
+        .map(|(ip, list)| async move {
+            let result = if list == LIST_ABUSEIPDB {
+                let key = cfg.abuseipdb_api_key.as_deref().unwrap_or_default();
+                abuseipdb_lookup(http_client, key, ip, min_score).await
+            } else {
+                match resolver {
+                    Some(r) => dnsbl_lookup(r, ip, &list).await,
+                    None => Err(anyhow::anyhow!("no DNSBL resolver")),
+                }
+            };
+            (ip, list, result)
+        });
+
+    // (ip, list) -> Some(detail) when listed, None when clean; failed lookups are absent
+    let mut results: HashMap<(String, String), Option<String>> = HashMap::new();
+    let mut failed = 0;
+    let mut stream = futures::stream::iter(lookups).buffer_unordered(CONCURRENCY);
+    while let Some((ip, list, result)) = stream.next().await {
+        match result {
+            Ok(listed) => {
+                results.insert((ip.to_string(), list), listed);
+            }
+            Err(e) => {
+                tracing::debug!("Reputation lookup of {ip} on {list} failed: {e}");
+                failed += 1;
+            }
+        }
+    }
+
+    let open: HashSet<(Uuid, String, String)> = sqlx::query_as::<_, (Uuid, String, String)>(
+        "SELECT vps_id, ip, list FROM ip_reputation WHERE delisted_at IS NULL",
+    )
+    .fetch_all(db)
+    .await?
+    .into_iter()
+    .collect();
+
+    let mut tx = db.begin().await?;
+    let mut new_listings: Vec<(&Target, &str, &str)> = Vec::new();
+
+    for target in &targets {
+        let ip = target.ip.parse::<IpAddr>()?.to_string();
+        for list in &lists {
+            let Some(result) = results.get(&(ip.clone(), list.clone())) else {
+                continue;
+            };
+            let key = (target.vps_id, target.ip.clone(), list.clone());
+            match (result, open.contains(&key)) {
+                (Some(detail), true) => {
+                    sqlx::query(
+                        r#"UPDATE ip_reputation SET detail = $4, last_seen_at = now()
+                           WHERE vps_id = $1 AND ip = $2 AND list = $3 AND delisted_at IS NULL"#,
+                    )
+                    .bind(target.vps_id)
+                    .bind(&target.ip)
+                    .bind(list)
+                    .bind(detail)
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                (Some(detail), false) => {
+                    sqlx::query(
+                        "INSERT INTO ip_reputation (vps_id, ip, list, detail) VALUES ($1, $2, $3, $4)",
+                    )
+                    .bind(target.vps_id)
+                    .bind(&target.ip)
+                    .bind(list)
+                    .bind(detail)
+                    .execute(&mut *tx)
+                    .await?;
+                    tracing::info!("IP {} of {} listed on {list}: {detail}", target.ip, target.hostname);
+                    new_listings.push((target, list, detail));
+                }
+                (None, true) => {
+                    sqlx::query(
+                        r#"UPDATE ip_reputation SET delisted_at = now()
+                           WHERE vps_id = $1 AND ip = $2 AND list = $3 AND delisted_at IS NULL"#,
+                    )
+                    .bind(target.vps_id)
+                    .bind(&target.ip)
+                    .bind(list)
+                    .execute(&mut *tx)
+                    .await?;
+                    tracing::info!("IP {} of {} delisted from {list}", target.ip, target.hostname);
+                }
+                (None, false) => {}
+            }
+        }
+    }
+
+    // Listings of IPs no longer on a live VPS, or on lists no longer configured, end
+    sqlx::query(
+        r#"UPDATE ip_reputation r SET delisted_at = now()
+           WHERE r.delisted_at IS NULL AND (
+               r.list != ALL($1) OR NOT EXISTS (
+                   SELECT 1 FROM vps v, jsonb_array_elements(v.ip_addresses) e
+                   WHERE v.id = r.vps_id AND v.status != 'deleted'
+                     AND btrim(e->>'ip') = r.ip AND COALESCE(e->>'label', '') != 'internal'
+               )
+           )"#,
+    )
+    .bind(&lists)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"UPDATE vps v SET blocklists = w.blocklists
+           FROM (
+               SELECT v2.id, COALESCE((
+                   SELECT array_agg(DISTINCT r.list ORDER BY r.list) FROM ip_reputation r
+                   WHERE r.vps_id = v2.id AND r.delisted_at IS NULL
+               ), '{}') AS blocklists
+               FROM vps v2
+           ) w
+           WHERE v.id = w.id AND v.blocklists != w.blocklists"#,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    tracing::info!(
+        "IP reputation: {} IPs checked on {} lists, {} new listings, {} lookups failed",
+        ips.len(),
+        lists.len(),
+        new_listings.len(),
+        failed,
+    );
+
+    for (target, list, detail) in new_listings {
+        if !matches!(target.status.as_str(), "active" | "provisioning") {
+            continue;
+        }
+        let payload = BlocklistPayload {
+            event: "ip_blocklisted".into(),
+            vps_id: target.vps_id,
+            hostname: target.hostname.clone(),
+            alias: target.alias.clone(),
+            provider: target.provider_name.clone(),
+            country: target.country.clone(),
+            ip: target.ip.clone(),
+            list: list.to_string(),
+            detail: detail.to_string(),
+        };
+
+        if let (Some(token), Some(chat_id)) = (&cfg.telegram_bot_token, &cfg.telegram_chat_id) {
+            let message = format_telegram_message(&payload);
+            if let Err(e) = send_telegram(http_client, token, chat_id, &message).await {
+                tracing::error!("Telegram send error for {}: {e}", target.hostname);
+            }
+        }
+
+        if let Some(url) = &cfg.webhook_url {
+            if let Err(e) = send_webhook(http_client, url, &payload).await {
+                tracing::error!("Webhook send error for {}: {e}", target.hostname);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn format_telegram_message(p: &BlocklistPayload) -> String {
+    let host_display = if p.alias.is_empty() {
+        p.hostname.clone()
+    } else {
+        format!("{} ({})", p.hostname, p.alias)
+    };
+
+    format!(
+        "\u{1f6ab} <b>IP Blocklisted</b>\n\
+         Host: <code>{host_display}</code>\n\
+         Provider: {provider} / {country}\n\
+         IP: <code>{ip}</code>\n\
+         List: <b>{list}</b> ({detail})",
+        provider = p.provider,
+        country = p.country,
+        ip = p.ip,
+        list = p.list,
+        detail = p.detail,
+    )
+}
+
+#[derive(Debug, Serialize)]
+struct BlocklistPayload {
+    event: String,
+    vps_id: Uuid,
+    hostname: String,
+    alias: String,
+    provider: String,
+    country: String,
+    ip: String,
+    list: String,
+    detail: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Answers `answer` for the DNSBL test address 127.0.0.2, NXDOMAIN for anything else.
+    async fn stub_resolver(answer: Ipv4Addr) -> SocketAddr {
+        let socket = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = socket.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 512];
+            loop {
+                let Ok((n, peer)) = socket.recv_from(&mut buf).await else {
+                    return;
+                };
+                let query = &buf[..n];
+                let name_end = skip_name(query, 12).unwrap();
+                let listed = query[12..name_end].starts_with(b"\x012\x010\x010\x03127");
+
+                let mut resp = query[..2].to_vec();
+                resp.extend_from_slice(if listed { &[0x81, 0x80] } else { &[0x81, 0x83] });
+                resp.extend_from_slice(&[0, 1, 0, listed as u8, 0, 0, 0, 0]);
+                resp.extend_from_slice(&query[12..name_end + 4]);
+                if listed {
+                    resp.extend_from_slice(&[0xc0, 0x0c, 0, 1, 0, 1, 0, 0, 0, 60, 0, 4]);
+                    resp.extend_from_slice(&answer.octets());
+                }
+                socket.send_to(&resp, peer).await.unwrap();
+            }
+        });
+        addr
+    }
+
+    #[test]
+    fn test_dnsbl_name() {
+        assert_eq!(
+            dnsbl_name("203.0.113.7".parse().unwrap(), "zen.spamhaus.org"),
+            "7.113.0.203.zen.spamhaus.org"
+        );
+        let v6 = dnsbl_name("2001:db8::1".parse().unwrap(), "bl.example");
+        assert!(v6.starts_with("1.0.0.0.0.0.0.0"));
+        assert!(v6.ends_with("8.b.d.0.1.0.0.2.bl.example"));
+        assert_eq!(v6.split('.').count(), 32 + 2);
+    }
+
+    #[test]
+    fn test_is_checkable() {
+        assert!(is_checkable("8.8.8.8".parse().unwrap()));
+        assert!(is_checkable("2606:4700::1111".parse().unwrap()));
+        assert!(!is_checkable("10.1.2.3".parse().unwrap()));
+        assert!(!is_checkable("100.64.0.1".parse().unwrap()));
+        assert!(!is_checkable("127.0.0.1".parse().unwrap()));
+        assert!(!is_checkable("fd00::1".parse().unwrap()));
+        assert!(!is_checkable("fe80::1".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_parse_resolver() {
+        assert_eq!(parse_resolver("9.9.9.9"), Some("9.9.9.9:53".parse().unwrap()));
+        assert_eq!(parse_resolver("127.0.0.1:5353"), Some("127.0.0.1:5353".parse().unwrap()));
+        assert_eq!(parse_resolver("[::1]:5353"), Some("[::1]:5353".parse().unwrap()));
+        assert_eq!(parse_resolver("resolver.local"), None);
+    }
+
+    #[tokio::test]
+    async fn test_dnsbl_lookup_against_stub() {
+        let resolver = stub_resolver(Ipv4Addr::new(127, 0, 0, 2)).await;
+        let listed = dnsbl_lookup(resolver, "127.0.0.2".parse().unwrap(), "bl.test").await.unwrap();
+        assert_eq!(listed.as_deref(), Some("127.0.0.2"));
+        let clean = dnsbl_lookup(resolver, "198.51.100.1".parse().unwrap(), "bl.test").await.unwrap();
+        assert_eq!(clean, None);
+    }
+
+    #[tokio::test]
+    async fn test_dnsbl_lookup_refused() {
+        let resolver = stub_resolver(Ipv4Addr::new(127, 255, 255, 254)).await;
+        let err = dnsbl_lookup(resolver, "127.0.0.2".parse().unwrap(), "bl.test").await;
+        assert!(err.unwrap_err().to_string().contains("refused"));
+    }
+}
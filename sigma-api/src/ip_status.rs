@@ -0,0 +1,308 @@
+//! China-blocking detection.
+//!
+//! A background worker classifies every IP of a live VPS from its `ip_checks`
+//! within `ip_status_window_minutes`, comparing Chinese probe sources (`cn-*`)
+//! against overseas control sources (everything else). A source counts as
+//! reaching the IP when at least `ip_status_min_success_pct` of its checks
+//! succeeded:
+//!
+//! - `ok`: every Chinese source reaches the IP
+//! - `blocked_cn`: no Chinese source does while a control source does, or the
+//!   carrier in the IP's label (e.g. `china-telecom`) is cut off
+//! - `partial`: some Chinese sources fail, typically one carrier
+//! - `down`: nothing reaches the IP
+//!
+//! Probe sources name their carrier with a `-`-separated token matching an
+//! ip_labels short code or name (`cn-sh-ct`, `cn-bj-unicom`). IPs without both
+//! Chinese and control results in the window keep their last status.
+
+use std::collections::{BTreeMap, HashMap};
+
+use uuid::Uuid;
+
+use crate::db::{self, Db};
+
+pub const IP_OK: &str = "ok";
+pub const IP_BLOCKED_CN: &str = "blocked_cn";
+pub const IP_PARTIAL: &str = "partial";
+pub const IP_DOWN: &str = "down";
+
+/// Most severe first; `vps.ip_status` is the first of these any IP has.
+pub const SEVERITY: &[&str] = &[IP_BLOCKED_CN, IP_DOWN, IP_PARTIAL, IP_OK];
+
+/// Probe sources inside China start with this prefix.
+pub const CN_SOURCE_PREFIX: &str = "cn-";
+
+const DEFAULT_INTERVAL_SECS: u64 = 300;
+const DEFAULT_WINDOW_MINUTES: u64 = 30;
+const DEFAULT_MIN_SUCCESS_PCT: u64 = 50;
+
+/// A Chinese carrier, from the `china-*` IP labels.
+#[derive(Debug, sqlx::FromRow)]
+pub struct Carrier {
+    pub name: String,
+    pub short: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Classification {
+    pub status: &'static str,
+    pub blocked_carriers: Vec<String>,
+}
+
+pub fn is_cn_source(source: &str) -> bool {
+    source.starts_with(CN_SOURCE_PREFIX)
+}
+
+/// Carrier measured by a probe source, if its name carries one.
+pub fn source_carrier<'a>(source: &str, carriers: &'a [Carrier]) -> Option<&'a str> {
+    let tokens: Vec<&str> = source.split('-').skip(1).collect();
+    carriers
+        .iter()
+        .find(|c| {
+            let suffix = c.name.strip_prefix("china-").unwrap_or(&c.name);
+            tokens.iter().any(|t| {
+                (!c.short.is_empty() && t.eq_ignore_ascii_case(&c.short))
+                    || t.eq_ignore_ascii_case(suffix)
+            })
+        })
+        .map(|c| c.name.as_str())
+}
+
+/// Classify one IP from per-source success ratios (0.0..=1.0). Returns None
+/// when the window lacks either Chinese or control results.
+pub fn classify(
+    rates: &[(String, f64)],
+    ip_label: &str,
+    carriers: &[Carrier],
+    min_success: f64,
+) -> Option<Classification> {
+    let reaches = |ratio: f64| ratio >= min_success;
+    let (cn, control): (Vec<_>, Vec<_>) = rates.iter().partition(|(s, _)| is_cn_source(s));
+    if cn.is_empty() || control.is_empty() {
+        return None;
+    }
+
+    let control_up = control.iter().any(|(_, r)| reaches(*r));
+    let cn_up = cn.iter().filter(|(_, r)| reaches(*r)).count();
+
+    // carrier -> reached by any of its sources
+    let mut by_carrier: BTreeMap<&str, bool> = BTreeMap::new();
+    for (source, ratio) in &cn {
+        if let Some(carrier) = source_carrier(source, carriers) {
+            *by_carrier.entry(carrier).or_insert(false) |= reaches(*ratio);
+        }
+    }
+    let mut blocked_carriers: Vec<String> = by_carrier
+        .into_iter()
+        .filter(|(_, up)| !up)
+        .map(|(c, _)| c.to_string())
+        .collect();
+
+    let status = if cn_up == cn.len() {
+        IP_OK
+    } else if cn_up == 0 && !control_up {
+        blocked_carriers.clear();
+        IP_DOWN
+    } else if cn_up == 0 || blocked_carriers.iter().any(|c| c == ip_label) {
+        IP_BLOCKED_CN
+    } else {
+        IP_PARTIAL
+    };
+
+    Some(Classification { status, blocked_carriers })
+}
+
+/// Background worker that re-classifies IPs every `ip_status_interval_secs`.
+pub async fn run(db: Db) {
+    tracing::info!("IP status worker started");
+
+    loop {
+        if let Err(e) = evaluate(&db).await {
+            tracing::error!("IP status evaluation error: {e}");
+        }
+        let interval = db::setting_u64(&db, "ip_status_interval_secs", DEFAULT_INTERVAL_SECS).await;
+        tokio::time::sleep(std::time::Duration::from_secs(interval.max(30))).await;
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct SourceRate {
+    vps_id: Uuid,
+    ip: String,
+    label: String,
+    source: String,
+    ok_ratio: f64,
+}
+
+async fn evaluate(db: &Db) -> Result<(), sqlx::Error> {
+    let window = db::setting_u64(db, "ip_status_window_minutes", DEFAULT_WINDOW_MINUTES).await;
+    let min_success =
+        db::setting_u64(db, "ip_status_min_success_pct", DEFAULT_MIN_SUCCESS_PCT).await as f64
+            / 100.0;
+
+    let carriers = sqlx::query_as::<_, Carrier>(
+        "SELECT name, short FROM ip_labels WHERE name LIKE 'china-%' ORDER BY sort_order",
+    )
+    .fetch_all(db)
+    .await?;
+
+    let rates = sqlx::query_as::<_, SourceRate>(
+        r#"SELECT v.id AS vps_id, e.ip, COALESCE(e.label, '') AS label, c.source,
+                  AVG(CASE WHEN c.success THEN 1.0 ELSE 0.0 END)::FLOAT8 AS ok_ratio
+           FROM vps v
+           CROSS JOIN LATERAL jsonb_to_recordset(v.ip_addresses) AS e(ip TEXT, label TEXT)
+           JOIN ip_checks c
+             ON c.vps_id = v.id AND host(c.ip) = e.ip
+            AND c.checked_at >= now() - make_interval(mins => $1)
+           WHERE v.status != 'deleted'
+           GROUP BY v.id, e.ip, e.label, c.source"#,
+    )
+    .bind(window.min(i32::MAX as u64) as i32)
+    .fetch_all(db)
+    .await?;
+
+    // (vps, ip) -> (label, [(source, ratio)])
+    let mut by_ip: BTreeMap<(Uuid, String), (String, Vec<(String, f64)>)> = BTreeMap::new();
+    for r in rates {
+        by_ip
+            .entry((r.vps_id, r.ip))
+            .or_insert_with(|| (r.label, Vec::new()))
+            .1
+            .push((r.source, r.ok_ratio));
+    }
+
+    let current: HashMap<(Uuid, String), String> = sqlx::query_as::<_, (Uuid, String, String)>(
+        "SELECT vps_id, ip, status FROM ip_status WHERE ended_at IS NULL",
+    )
+    .fetch_all(db)
+    .await?
+    .into_iter()
+    .map(|(vps_id, ip, status)| ((vps_id, ip), status))
+    .collect();
+
+    let mut tx = db.begin().await?;
+    let mut transitions = 0;
+
+    for ((vps_id, ip), (label, sources)) in &by_ip {
+        let Some(c) = classify(sources, label, &carriers, min_success) else {
+            continue;
+        };
+        let previous = current.get(&(*vps_id, ip.clone()));
+        if previous.map(String::as_str) == Some(c.status) {
+            continue;
+        }
+
+        sqlx::query("UPDATE ip_status SET ended_at = now() WHERE vps_id = $1 AND ip = $2 AND ended_at IS NULL")
+            .bind(vps_id)
+            .bind(ip)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(
+            r#"INSERT INTO ip_status (vps_id, ip, status, previous_status, blocked_carriers, sources)
+               VALUES ($1, $2, $3, $4, $5, $6)"#,
+        )
+        .bind(vps_id)
+        .bind(ip)
+        .bind(c.status)
+        .bind(previous.cloned().unwrap_or_default())
+        .bind(&c.blocked_carriers)
+        .bind(serde_json::json!(sources.iter().cloned().collect::<BTreeMap<_, _>>()))
+        .execute(&mut *tx)
+        .await?;
+
+        if let Some(previous) = previous {
+            tracing::info!("IP {ip} status changed: {previous} -> {}", c.status);
+        }
+        transitions += 1;
+    }
+
+    // IPs removed from their VPS (or VPS deleted) no longer have a status
+    sqlx::query(
+        r#"UPDATE ip_status s SET ended_at = now()
+           WHERE s.ended_at IS NULL AND NOT EXISTS (
+               SELECT 1 FROM vps v, jsonb_array_elements(v.ip_addresses) e
+               WHERE v.id = s.vps_id AND v.status != 'deleted' AND e->>'ip' = s.ip
+           )"#,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"UPDATE vps v SET ip_status = w.ip_status
+           FROM (
+               SELECT v2.id, COALESCE((
+                   SELECT s.status FROM ip_status s
+                   WHERE s.vps_id = v2.id AND s.ended_at IS NULL
+                   ORDER BY array_position($1::TEXT[], s.status)
+                   LIMIT 1
+               ), '') AS ip_status
+               FROM vps v2
+           ) w
+           WHERE v.id = w.id AND v.ip_status != w.ip_status"#,
+    )
+    .bind(SEVERITY)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    if transitions > 0 {
+        tracing::info!("IP status: {} transitions recorded", transitions);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn carriers() -> Vec<Carrier> {
+        [("china-telecom", "CT"), ("china-unicom", "CU"), ("china-mobile", "CM")]
+            .into_iter()
+            .map(|(name, short)| Carrier { name: name.into(), short: short.into() })
+            .collect()
+    }
+
+    fn rates(r: &[(&str, f64)]) -> Vec<(String, f64)> {
+        r.iter().map(|(s, v)| (s.to_string(), *v)).collect()
+    }
+
+    fn status(r: &[(&str, f64)], label: &str) -> Option<&'static str> {
+        classify(&rates(r), label, &carriers(), 0.5).map(|c| c.status)
+    }
+
+    #[test]
+    fn test_source_carrier() {
+        let c = carriers();
+        assert_eq!(source_carrier("cn-sh-ct", &c), Some("china-telecom"));
+        assert_eq!(source_carrier("cn-bj-unicom", &c), Some("china-unicom"));
+        assert_eq!(source_carrier("cn-beijing", &c), None);
+    }
+
+    #[test]
+    fn test_classify() {
+        // Needs both sides
+        assert_eq!(status(&[("cn-sh-ct", 1.0)], ""), None);
+        assert_eq!(status(&[("us-west", 1.0)], ""), None);
+
+        assert_eq!(status(&[("cn-sh-ct", 1.0), ("cn-bj-cu", 0.9), ("us-west", 1.0)], ""), Some(IP_OK));
+        assert_eq!(status(&[("cn-sh-ct", 0.0), ("cn-bj-cu", 0.1), ("us-west", 1.0)], ""), Some(IP_BLOCKED_CN));
+        assert_eq!(status(&[("cn-sh-ct", 0.0), ("cn-bj-cu", 0.0), ("us-west", 0.0)], ""), Some(IP_DOWN));
+        assert_eq!(status(&[("cn-sh-ct", 0.0), ("cn-bj-cu", 1.0), ("us-west", 1.0)], ""), Some(IP_PARTIAL));
+        // The carrier the IP is meant for is cut off
+        assert_eq!(
+            status(&[("cn-sh-ct", 0.0), ("cn-bj-cu", 1.0), ("us-west", 1.0)], "china-telecom"),
+            Some(IP_BLOCKED_CN)
+        );
+
+        let c = classify(
+            &rates(&[("cn-sh-ct", 0.0), ("cn-gz-ct", 0.2), ("cn-bj-cu", 1.0), ("de-fra", 1.0)]),
+            "",
+            &carriers(),
+            0.5,
+        )
+        .unwrap();
+        assert_eq!(c.blocked_carriers, vec!["china-telecom".to_string()]);
+    }
+}
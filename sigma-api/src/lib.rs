@@ -1,9 +1,22 @@
+pub mod agent_state;
 pub mod auth;
+pub mod cloud_sync;
 pub mod config;
+pub mod custom_fields;
 pub mod db;
 pub mod dns_sync;
 pub mod errors;
+pub mod etag;
+pub mod geoip;
+pub mod heartbeats;
+pub mod import_plan;
+pub mod ip_reputation;
+pub mod ip_status;
 pub mod models;
 pub mod notifications;
 pub mod openapi;
+pub mod pagination;
 pub mod routes;
+pub mod scorecard;
+pub mod vps_filter;
+pub mod vps_lifecycle;
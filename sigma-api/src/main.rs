@@ -1,6 +1,12 @@
+use sigma_api::agent_state;
 use sigma_api::auth;
+use sigma_api::cloud_sync;
 use sigma_api::config;
 use sigma_api::dns_sync;
+use sigma_api::geoip;
+use sigma_api::heartbeats;
+use sigma_api::ip_reputation;
+use sigma_api::ip_status;
 use sigma_api::notifications;
 use sigma_api::openapi;
 use sigma_api::routes;
@@ -79,6 +85,25 @@ async fn main() -> anyhow::Result<()> {
 
     // Capture before cfg is moved into notification worker
     let dns_sync_interval_secs = cfg.dns_sync_interval_secs;
+    let geoip_db_paths = cfg.geoip_db_paths.clone();
+
+    // Spawn blocklist / reputation worker if any list is configured
+    if !cfg.dnsbl_zones.is_empty() || cfg.abuseipdb_api_key.is_some() {
+        tokio::spawn(ip_reputation::run(
+            app_state.db.clone(),
+            http_client.clone(),
+            cfg.clone(),
+        ));
+    } else {
+        tracing::info!("IP reputation checks disabled (no DNSBL_ZONES or ABUSEIPDB_API_KEY set)");
+    }
+
+    // Spawn agent state worker (notifies on transitions if a channel is configured)
+    tokio::spawn(agent_state::run(
+        app_state.db.clone(),
+        http_client.clone(),
+        cfg.clone(),
+    ));
 
     // Spawn notification worker if any channel is configured
     if cfg.telegram_bot_token.is_some() || cfg.webhook_url.is_some() {
@@ -96,13 +121,31 @@ async fn main() -> anyhow::Result<()> {
         tracing::info!("DNS background sync disabled (DNS_SYNC_INTERVAL_SECS=0)");
     }
 
+    // Spawn cloud account background sync worker (interval and concurrency from settings)
+    tokio::spawn(cloud_sync::run(app_state.clone()));
+
+    // Spawn heartbeat rollup / retention worker
+    tokio::spawn(heartbeats::run(app_state.db.clone()));
+
+    // Spawn China-blocking classifier over recent IP checks
+    tokio::spawn(ip_status::run(app_state.db.clone()));
+
+    // Spawn GeoIP / ASN enrichment worker
+    if !geoip_db_paths.is_empty() {
+        tokio::spawn(geoip::run(app_state.db.clone(), geoip_db_paths));
+    } else {
+        tracing::info!("GeoIP enrichment disabled (no GEOIP_DB_PATH set)");
+    }
+
     // Public routes (no auth required)
     let public_routes = routes::auth_routes::router();
 
     // Protected API routes (auth required)
     let api_routes = Router::new()
         .merge(routes::providers::router())
+        .merge(routes::provider_plans::router())
         .merge(routes::vps::router())
+        .merge(routes::renewals::router())
         .merge(routes::ip_checks::router())
         .merge(routes::prometheus::router())
         .merge(routes::stats::router())
@@ -119,7 +162,10 @@ async fn main() -> anyhow::Result<()> {
         .merge(routes::cloud::router())
         .merge(routes::vps_duplicates::router())
         .merge(routes::vps_purposes::router())
+        .merge(routes::custom_fields::router())
         .merge(routes::ip_labels::router())
+        .merge(routes::ip_pool::router())
+        .merge(routes::lookup::router())
         .merge(routes::api_keys::router())
         .merge(routes::settings::router())
         .layer(axum::middleware::from_fn_with_state(
@@ -13,6 +13,17 @@ pub struct IpEntry {
     pub label: String,
 }
 
+/// GeoIP / ASN data for one IP, from the local mmdb files.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct IpGeo {
+    pub ip: String,
+    pub asn: Option<i64>,
+    pub as_org: Option<String>,
+    /// ISO country code
+    pub country: Option<String>,
+    pub city: Option<String>,
+}
+
 // ─── Provider ────────────────────────────────────────────
 
 #[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
@@ -86,6 +97,8 @@ pub struct Vps {
     pub status: String,
     pub purchase_date: Option<NaiveDate>,
     pub expire_date: Option<NaiveDate>,
+    /// Renews on its own; expiry notifications are suppressed
+    pub auto_renew: bool,
 
     pub purpose: String,
     pub vpn_protocol: String,
@@ -100,6 +113,22 @@ pub struct Vps {
 
     pub source: String,
     pub cloud_account_id: Option<Uuid>,
+    /// Provider plan the VPS was created from
+    pub plan_id: Option<Uuid>,
+
+    /// Agent liveness: online, stale or offline; empty until an agent has reported
+    pub agent_state: String,
+    pub agent_state_changed_at: Option<DateTime<Utc>>,
+    /// Most severe China-blocking status of its IPs: blocked_cn, down, partial or ok;
+    /// empty until classified
+    pub ip_status: String,
+    /// GeoIP / ASN data per IP
+    #[schema(value_type = Vec<IpGeo>)]
+    pub ip_geo: sqlx::types::Json<Vec<IpGeo>>,
+    /// A public IP geolocates outside `country`
+    pub geo_mismatch: bool,
+    /// Blocklists (DNSBL zones, `abuseipdb`) any of its IPs is currently listed on
+    pub blocklists: Vec<String>,
 
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
@@ -112,6 +141,9 @@ pub struct CreateVps {
     pub alias: String,
     #[serde(default)]
     pub provider_id: Option<Uuid>,
+    /// Provider plan: fills in provider, specs, cost and currency left unset
+    #[serde(default)]
+    pub plan_id: Option<Uuid>,
 
     #[serde(default)]
     pub ip_addresses: Vec<IpEntry>,
@@ -138,6 +170,8 @@ pub struct CreateVps {
     pub status: String,
     pub purchase_date: Option<NaiveDate>,
     pub expire_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub auto_renew: bool,
 
     #[serde(default)]
     pub purpose: String,
@@ -158,7 +192,7 @@ pub struct CreateVps {
     pub notes: String,
 }
 
-#[derive(Debug, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
 pub struct UpdateVps {
     pub hostname: Option<String>,
     pub alias: Option<String>,
@@ -177,6 +211,7 @@ pub struct UpdateVps {
     pub status: Option<String>,
     pub purchase_date: Option<Option<NaiveDate>>,
     pub expire_date: Option<Option<NaiveDate>>,
+    pub auto_renew: Option<bool>,
     pub purpose: Option<String>,
     pub vpn_protocol: Option<String>,
     pub tags: Option<Vec<String>>,
@@ -196,6 +231,17 @@ pub struct VpsListQuery {
     pub tag: Option<String>,
     pub expiring_within_days: Option<i32>,
     pub source: Option<String>,
+    /// Agent liveness: online, stale or offline
+    pub agent_state: Option<String>,
+    /// China-blocking status: ok, blocked_cn, partial or down
+    pub ip_status: Option<String>,
+    /// Filter expression, e.g. `country:(JP|HK) AND tag:cn2 AND NOT purpose:relay AND expire<30d`.
+    /// Combined with the other filters using AND.
+    pub q: Option<String>,
+    /// Sort key, `-` prefix for descending (e.g. `-created_at`)
+    pub sort: Option<String>,
+    /// Keyset pagination: pass empty for the first page, then `next_cursor`
+    pub cursor: Option<String>,
     #[serde(default = "default_page")]
     pub page: i64,
     #[serde(default = "default_per_page")]
@@ -220,6 +266,23 @@ pub struct PaginatedResponse<T: Serialize> {
     pub per_page: i64,
 }
 
+/// Keyset-mode page: no total, `next_cursor` is None on the last page.
+#[derive(Debug, Serialize)]
+pub struct CursorResponse<T: Serialize> {
+    pub data: Vec<T>,
+    pub per_page: i64,
+    pub sort: String,
+    pub next_cursor: Option<String>,
+}
+
+/// Response of list endpoints that support both page and cursor mode.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum ListResponse<T: Serialize> {
+    Page(PaginatedResponse<T>),
+    Cursor(CursorResponse<T>),
+}
+
 // Concrete paginated response types for OpenAPI schema generation
 #[derive(Serialize, ToSchema)]
 pub struct PaginatedProviderResponse {
@@ -266,6 +329,9 @@ pub struct DashboardStats {
     pub by_country: Vec<CountStat>,
     pub by_provider: Vec<CountStat>,
     pub by_status: Vec<CountStat>,
+    /// VPS with an agent, by online / stale / offline
+    pub by_agent_state: Vec<CountStat>,
+    pub by_ip_status: Vec<CountStat>,
     pub expiring_soon: Vec<Vps>,
 }
 
@@ -283,12 +349,44 @@ pub struct ImportRequest {
     pub format: String,
     /// Raw CSV or JSON data as a string
     pub data: String,
+    /// "insert" (default), "upsert" or "dry_run" (upsert plan without writing)
+    #[serde(default = "default_import_mode")]
+    pub mode: String,
+    /// Natural key for upsert / dry_run: `hostname` (default) or `alias` for VPS, `name` for providers
+    pub key: Option<String>,
 }
 
+fn default_import_mode() -> String { "insert".into() }
+
 #[derive(Debug, Serialize, ToSchema)]
 pub struct ImportResult {
+    /// True if nothing was written; the counts describe the plan
+    pub dry_run: bool,
+    /// Rows created
     pub imported: usize,
+    /// Existing rows updated (upsert)
+    pub updated: usize,
+    /// Existing rows already up to date (upsert)
+    pub skipped: usize,
     pub errors: Vec<String>,
+    pub plan: Vec<ImportPlanRow>,
+}
+
+/// What an import does with one input row.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ImportPlanRow {
+    /// 1-based row number in the input
+    pub row: usize,
+    /// create, update, skip or error
+    pub action: String,
+    /// Natural key value of the row
+    pub key: String,
+    /// Matched existing row (update / skip)
+    pub id: Option<Uuid>,
+    /// Field → {"from", "to"} for updates
+    #[schema(value_type = Object)]
+    pub changes: serde_json::Value,
+    pub error: Option<String>,
 }
 
 #[derive(Debug, serde::Deserialize, serde::Serialize)]
@@ -365,6 +463,21 @@ pub struct ExportQuery {
 
 fn default_json_format() -> String { "json".into() }
 
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct VpsExportQuery {
+    /// Format: "csv" or "json" (default: "json")
+    #[serde(default = "default_json_format")]
+    pub format: String,
+    /// Filter expression (same syntax as `q` on `GET /api/vps`)
+    pub q: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct VpsFilterQuery {
+    /// Filter expression (same syntax as `q` on `GET /api/vps`)
+    pub q: Option<String>,
+}
+
 // ─── IP Checks ───────────────────────────────────────────
 
 #[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
@@ -400,6 +513,10 @@ pub struct IpCheckListQuery {
     pub source: Option<String>,
     pub check_type: Option<String>,
     pub success: Option<bool>,
+    /// Sort key, `-` prefix for descending (e.g. `-created_at`)
+    pub sort: Option<String>,
+    /// Keyset pagination: pass empty for the first page, then `next_cursor`
+    pub cursor: Option<String>,
     #[serde(default = "default_page")]
     pub page: i64,
     #[serde(default = "default_per_page")]
@@ -423,6 +540,61 @@ pub struct IpCheckSummaryQuery {
     pub vps_id: Option<Uuid>,
 }
 
+/// One period of an IP's China-blocking status; `ended_at` is None for the current one.
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct IpStatus {
+    pub id: Uuid,
+    pub vps_id: Uuid,
+    pub ip: String,
+    /// ok, blocked_cn, partial or down
+    pub status: String,
+    pub previous_status: String,
+    /// Carriers whose probe sources all failed
+    pub blocked_carriers: Vec<String>,
+    /// Success ratio per probe source when the status was recorded
+    #[schema(value_type = Object)]
+    pub sources: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct VpsIpStatus {
+    pub vps_id: Uuid,
+    /// Most severe current status across the VPS's IPs
+    pub ip_status: String,
+    /// Current status per IP
+    pub ips: Vec<IpStatus>,
+    /// Most recent transitions first
+    pub transitions: Vec<IpStatus>,
+}
+
+/// One listing period of an IP on a blocklist; `delisted_at` is None while listed.
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct IpReputation {
+    pub id: Uuid,
+    pub vps_id: Uuid,
+    pub ip: String,
+    /// DNSBL zone or `abuseipdb`
+    pub list: String,
+    /// DNSBL return codes or AbuseIPDB score at the last check
+    pub detail: String,
+    pub listed_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub delisted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct VpsIpReputation {
+    pub vps_id: Uuid,
+    /// Lists any of the VPS's IPs is currently on
+    pub blocklists: Vec<String>,
+    /// Current listings
+    pub listings: Vec<IpReputation>,
+    /// Listing history, most recent first
+    pub history: Vec<IpReputation>,
+}
+
 #[derive(Debug, Deserialize, IntoParams)]
 pub struct PurgeQuery {
     pub older_than_days: i32,
@@ -533,6 +705,49 @@ pub struct CostSummaryResponse {
     pub converted_total: Option<ConvertedTotal>,
 }
 
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct CostCompareQuery {
+    pub provider_id: Option<Uuid>,
+    /// Only plans offered in this location
+    pub location: Option<String>,
+    /// Convert all prices to this currency; needed to compare across currencies
+    pub convert_to: Option<String>,
+    /// monthly (default), per_core, per_gb_ram, per_gb_disk or per_tb_bandwidth
+    pub sort: Option<String>,
+}
+
+/// Unit prices of one provider plan, per month in `currency`.
+/// A unit price is None when the plan does not list that spec.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PlanCostComparison {
+    pub plan_id: Uuid,
+    pub plan_name: String,
+    pub provider_id: Uuid,
+    pub provider_name: String,
+    pub cpu_cores: Option<i16>,
+    pub ram_mb: Option<i32>,
+    pub disk_gb: Option<i32>,
+    #[schema(value_type = Option<String>)]
+    pub bandwidth_tb: Option<Decimal>,
+    pub locations: Vec<String>,
+    pub currency: String,
+    #[schema(value_type = String)]
+    pub monthly: Decimal,
+    #[schema(value_type = Option<String>)]
+    pub per_core: Option<Decimal>,
+    #[schema(value_type = Option<String>)]
+    pub per_gb_ram: Option<Decimal>,
+    #[schema(value_type = Option<String>)]
+    pub per_gb_disk: Option<Decimal>,
+    #[schema(value_type = Option<String>)]
+    pub per_tb_bandwidth: Option<Decimal>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CostCompareResponse {
+    pub plans: Vec<PlanCostComparison>,
+}
+
 #[derive(Debug, Deserialize, IntoParams)]
 pub struct CostMonthlyQuery {
     /// Number of months to look back (default 12)
@@ -705,6 +920,8 @@ pub struct Ticket {
     pub provider_id: Option<Uuid>,
     pub created_by: Uuid,
     pub assigned_to: Option<Uuid>,
+    /// When the ticket last entered resolved / closed
+    pub resolved_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -755,6 +972,10 @@ pub struct TicketListQuery {
     pub priority: Option<String>,
     pub assigned_to: Option<Uuid>,
     pub vps_id: Option<Uuid>,
+    /// Sort key, `-` prefix for descending (e.g. `-created_at`)
+    pub sort: Option<String>,
+    /// Keyset pagination: pass empty for the first page, then `next_cursor`
+    pub cursor: Option<String>,
     #[serde(default = "default_page")]
     pub page: i64,
     #[serde(default = "default_per_page")]
@@ -966,6 +1187,142 @@ fn default_cluster_type() -> String { "logical_dns".into() }
 fn default_connect_timeout() -> i32 { 5 }
 fn default_proxy_protocol() -> i32 { 1 }
 
+// ─── Provider Plans ──────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct ProviderPlan {
+    pub id: Uuid,
+    pub provider_id: Uuid,
+    pub name: String,
+    pub cpu_cores: Option<i16>,
+    pub ram_mb: Option<i32>,
+    pub disk_gb: Option<i32>,
+    #[schema(value_type = Option<String>)]
+    pub bandwidth_tb: Option<Decimal>,
+    #[schema(value_type = String)]
+    pub price: Decimal,
+    pub currency: String,
+    /// Months covered by `price` (1 = monthly, 12 = yearly)
+    pub billing_cycle_months: i32,
+    pub locations: Vec<String>,
+    pub stock_url: String,
+    pub notes: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateProviderPlan {
+    pub provider_id: Uuid,
+    pub name: String,
+    pub cpu_cores: Option<i16>,
+    pub ram_mb: Option<i32>,
+    pub disk_gb: Option<i32>,
+    pub bandwidth_tb: Option<f64>,
+    pub price: f64,
+    #[serde(default = "default_currency")]
+    pub currency: String,
+    #[serde(default = "default_billing_cycle_months")]
+    pub billing_cycle_months: i32,
+    #[serde(default)]
+    pub locations: Vec<String>,
+    #[serde(default)]
+    pub stock_url: String,
+    #[serde(default)]
+    pub notes: String,
+}
+
+fn default_billing_cycle_months() -> i32 { 1 }
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateProviderPlan {
+    pub name: Option<String>,
+    pub cpu_cores: Option<Option<i16>>,
+    pub ram_mb: Option<Option<i32>>,
+    pub disk_gb: Option<Option<i32>>,
+    pub bandwidth_tb: Option<Option<f64>>,
+    pub price: Option<f64>,
+    pub currency: Option<String>,
+    pub billing_cycle_months: Option<i32>,
+    pub locations: Option<Vec<String>>,
+    pub stock_url: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ProviderPlanListQuery {
+    pub provider_id: Option<Uuid>,
+    /// Only plans offered in this location
+    pub location: Option<String>,
+    #[serde(default = "default_page")]
+    pub page: i64,
+    #[serde(default = "default_per_page")]
+    pub per_page: i64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PaginatedProviderPlanResponse {
+    pub data: Vec<ProviderPlan>,
+    pub total: i64,
+    pub page: i64,
+    pub per_page: i64,
+}
+
+// ─── Provider Scorecards ─────────────────────────────────
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ScorecardQuery {
+    /// Window in days, counted back from now (default 365)
+    pub days: Option<i32>,
+    /// Currency for cost figures, converted with stored exchange rates (default USD)
+    pub currency: Option<String>,
+    /// Ranking key, best first: agent_uptime_pct (default), ip_block_rate,
+    /// median_lifespan_days, tickets_per_vps, median_resolution_hours or cost_per_active_month
+    pub sort: Option<String>,
+}
+
+/// Provider metrics over a time window, computed from fleet history.
+/// Metrics without underlying data in the window are None.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProviderScorecard {
+    pub provider_id: Uuid,
+    pub provider_name: String,
+    /// Manual rating, for comparison
+    pub rating: Option<i16>,
+    pub since: DateTime<Utc>,
+    /// VPS alive at any point in the window
+    pub vps_count: i64,
+    pub vps_active: i64,
+    pub retired_count: i64,
+    /// Median days from purchase (or creation) to retirement, for VPS retired in the window
+    pub median_lifespan_days: Option<f64>,
+    pub ips_checked: i64,
+    /// IPs that failed at least half of their checks in the window
+    pub ips_blocked: i64,
+    pub ip_block_rate: Option<f64>,
+    /// IP addresses removed from VPS in the window
+    pub ip_changes: i64,
+    pub tickets_opened: i64,
+    pub tickets_resolved: i64,
+    pub tickets_per_vps: Option<f64>,
+    pub median_resolution_hours: Option<f64>,
+    pub agent_uptime_pct: Option<f64>,
+    /// VPS-months of service within the window
+    pub active_months: f64,
+    pub currency: String,
+    #[schema(value_type = Option<String>)]
+    pub cost_per_active_month: Option<Decimal>,
+    /// Currencies left out of the cost figure for lack of an exchange rate
+    pub unconverted_currencies: Vec<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProviderRanking {
+    pub since: DateTime<Utc>,
+    pub sort: String,
+    pub providers: Vec<ProviderScorecard>,
+}
+
 // ─── VPS Purposes ────────────────────────────────────────
 
 #[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
@@ -1015,6 +1372,75 @@ pub struct PaginatedVpsPurposeResponse {
     pub per_page: i64,
 }
 
+// ─── Custom Fields ───────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct CustomField {
+    pub id: Uuid,
+    /// Key under `extra.custom`
+    pub name: String,
+    pub label: String,
+    /// string, number, integer, boolean, date, url or enum
+    pub field_type: String,
+    pub enum_values: Vec<String>,
+    pub required: bool,
+    /// VPS purposes the field applies to; empty = all purposes
+    pub purposes: Vec<String>,
+    /// Expose as Prometheus target label and Ansible hostvar
+    pub expose_label: bool,
+    pub sort_order: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateCustomField {
+    pub name: String,
+    #[serde(default)]
+    pub label: String,
+    #[serde(default = "default_field_type")]
+    pub field_type: String,
+    #[serde(default)]
+    pub enum_values: Vec<String>,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub purposes: Vec<String>,
+    #[serde(default)]
+    pub expose_label: bool,
+    #[serde(default)]
+    pub sort_order: i32,
+}
+
+fn default_field_type() -> String { "string".into() }
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateCustomField {
+    pub label: Option<String>,
+    pub field_type: Option<String>,
+    pub enum_values: Option<Vec<String>>,
+    pub required: Option<bool>,
+    pub purposes: Option<Vec<String>>,
+    pub expose_label: Option<bool>,
+    pub sort_order: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct CustomFieldListQuery {
+    #[serde(default = "default_page")]
+    pub page: i64,
+    #[serde(default = "default_per_page")]
+    pub per_page: i64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PaginatedCustomFieldResponse {
+    pub data: Vec<CustomField>,
+    pub total: i64,
+    pub page: i64,
+    pub per_page: i64,
+}
+
 // ─── IP Labels ───────────────────────────────────────────
 
 #[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
@@ -1066,6 +1492,100 @@ pub struct PaginatedIpLabelResponse {
     pub per_page: i64,
 }
 
+// ─── IP Pool ─────────────────────────────────────────────
+
+/// An owned IP address outside (or moving between) VPS: spare, reserved or floating.
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct PoolIp {
+    pub id: Uuid,
+    pub ip: String,
+    pub provider_id: Option<Uuid>,
+    /// available, reserved or assigned
+    pub status: String,
+    /// Anycast / movable IP; not reported as a duplicate
+    pub floating: bool,
+    /// VPS the address is assigned to
+    pub vps_id: Option<Uuid>,
+    /// IP label applied to the VPS entry on assign
+    pub label: String,
+    pub purpose: String,
+    #[schema(value_type = Option<String>)]
+    pub cost_monthly: Option<Decimal>,
+    pub currency: String,
+    pub notes: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreatePoolIp {
+    pub ip: String,
+    pub provider_id: Option<Uuid>,
+    /// available (default) or reserved
+    #[serde(default = "default_pool_status")]
+    pub status: String,
+    #[serde(default)]
+    pub floating: bool,
+    #[serde(default)]
+    pub label: String,
+    #[serde(default)]
+    pub purpose: String,
+    pub cost_monthly: Option<f64>,
+    #[serde(default = "default_currency")]
+    pub currency: String,
+    #[serde(default)]
+    pub notes: String,
+}
+
+fn default_pool_status() -> String { "available".into() }
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdatePoolIp {
+    pub provider_id: Option<Option<Uuid>>,
+    /// available or reserved; use assign / release to bind an address
+    pub status: Option<String>,
+    pub floating: Option<bool>,
+    pub label: Option<String>,
+    pub purpose: Option<String>,
+    pub cost_monthly: Option<Option<f64>>,
+    pub currency: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct PoolIpListQuery {
+    pub provider_id: Option<Uuid>,
+    pub status: Option<String>,
+    pub floating: Option<bool>,
+    pub vps_id: Option<Uuid>,
+    #[serde(default = "default_page")]
+    pub page: i64,
+    #[serde(default = "default_per_page")]
+    pub per_page: i64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PaginatedPoolIpResponse {
+    pub data: Vec<PoolIp>,
+    pub total: i64,
+    pub page: i64,
+    pub per_page: i64,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AssignPoolIp {
+    pub vps_id: Uuid,
+    /// Label for the VPS entry; defaults to the pool entry's label
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ReleasePoolIp {
+    /// Keep the address reserved instead of returning it to available
+    #[serde(default)]
+    pub reserve: bool,
+}
+
 // ─── Defaults ────────────────────────────────────────────
 
 fn default_ssh_port() -> i32 { 22 }
@@ -1164,6 +1684,10 @@ pub struct DnsRecordListQuery {
     pub record_type: Option<String>,
     pub has_vps: Option<bool>,
     pub vps_id: Option<Uuid>,
+    /// Sort key, `-` prefix for descending (e.g. `-created_at`)
+    pub sort: Option<String>,
+    /// Keyset pagination: pass empty for the first page, then `next_cursor`
+    pub cursor: Option<String>,
     #[serde(default = "default_page")]
     pub page: i64,
     #[serde(default = "default_per_page")]
@@ -1241,6 +1765,262 @@ pub struct PaginatedVpsIpHistoryResponse {
     pub per_page: i64,
 }
 
+// ─── VPS Renewals ────────────────────────────────────────
+
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct VpsRenewal {
+    pub id: Uuid,
+    pub vps_id: Uuid,
+    pub period_months: i32,
+    #[schema(value_type = String)]
+    pub amount: Decimal,
+    pub currency: String,
+    pub paid_at: DateTime<Utc>,
+    pub invoice_ref: String,
+    pub previous_expire_date: Option<NaiveDate>,
+    pub new_expire_date: NaiveDate,
+    pub actor: String,
+    pub notes: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateVpsRenewal {
+    /// Billing period in months (1 = monthly, 12 = yearly)
+    pub period_months: i32,
+    pub amount: f64,
+    /// Defaults to the VPS currency
+    pub currency: Option<String>,
+    /// Defaults to now
+    pub paid_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub invoice_ref: String,
+    #[serde(default)]
+    pub notes: String,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct VpsRenewalQuery {
+    #[serde(default = "default_page")]
+    pub page: i64,
+    #[serde(default = "default_per_page")]
+    pub per_page: i64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PaginatedVpsRenewalResponse {
+    pub data: Vec<VpsRenewal>,
+    pub total: i64,
+    pub page: i64,
+    pub per_page: i64,
+}
+
+// ─── VPS Status History ──────────────────────────────────
+
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct VpsStatusHistory {
+    pub id: Uuid,
+    pub vps_id: Uuid,
+    /// None for the initial status
+    pub from_status: Option<String>,
+    pub to_status: String,
+    /// manual, agent, cloud-sync or import
+    pub source: String,
+    pub actor: String,
+    pub created_at: DateTime<Utc>,
+    /// Seconds spent in `to_status` until the next change (or until now)
+    pub duration_secs: i64,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct VpsStatusHistoryQuery {
+    pub to_status: Option<String>,
+    pub source: Option<String>,
+    #[serde(default = "default_page")]
+    pub page: i64,
+    #[serde(default = "default_per_page")]
+    pub per_page: i64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PaginatedVpsStatusHistoryResponse {
+    pub data: Vec<VpsStatusHistory>,
+    pub total: i64,
+    pub page: i64,
+    pub per_page: i64,
+}
+
+// ─── VPS Timeline ────────────────────────────────────────
+
+/// One entry of the unified VPS timeline.
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct VpsHistoryEvent {
+    /// field, ip, ticket or audit
+    pub kind: String,
+    /// ID of the underlying history, ticket or audit row
+    pub ref_id: Uuid,
+    /// Changed column for `field` events, `ip_addresses` for `ip` events
+    pub field: Option<String>,
+    #[schema(value_type = Option<Object>)]
+    pub old_value: Option<serde_json::Value>,
+    #[schema(value_type = Option<Object>)]
+    pub new_value: Option<serde_json::Value>,
+    /// manual, agent, cloud-sync or import (field and ip events)
+    pub source: String,
+    pub actor: String,
+    #[schema(value_type = Object)]
+    pub details: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct VpsHistoryQuery {
+    /// Only this kind: field, ip, ticket or audit
+    pub kind: Option<String>,
+    /// Only `field` events for this column, e.g. `cost_monthly`
+    pub field: Option<String>,
+    #[serde(default = "default_page")]
+    pub page: i64,
+    #[serde(default = "default_per_page")]
+    pub per_page: i64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PaginatedVpsHistoryResponse {
+    pub data: Vec<VpsHistoryEvent>,
+    pub total: i64,
+    pub page: i64,
+    pub per_page: i64,
+}
+
+// ─── Heartbeats ──────────────────────────────────────────
+
+/// One heartbeat sample. `resolution` is 0 for a raw sample and 3600 for an hourly
+/// rollup, whose metrics are averages (load, RAM used) or maxima (sizes) over `samples`.
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct VpsHeartbeat {
+    pub ts: DateTime<Utc>,
+    pub resolution: i32,
+    pub samples: i32,
+    /// Seconds of agent uptime this row accounts for
+    pub covered_secs: i32,
+    pub load_1: Option<f64>,
+    pub load_5: Option<f64>,
+    pub load_15: Option<f64>,
+    pub ram_mb: Option<i32>,
+    pub ram_used_mb: Option<i32>,
+    pub disk_gb: Option<i32>,
+    pub disk_used_gb: Option<i32>,
+    pub uptime_seconds: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct HeartbeatQuery {
+    /// Start of the window (RFC 3339), defaults to 24 hours ago
+    pub since: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct VpsHeartbeatSeries {
+    pub vps_id: Uuid,
+    pub since: DateTime<Utc>,
+    /// Agent uptime over the window in percent; None without heartbeats in the window
+    pub uptime_pct: Option<f64>,
+    pub data: Vec<VpsHeartbeat>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct VpsAgentUptime {
+    pub vps_id: Uuid,
+    pub hostname: String,
+    pub provider_id: Option<Uuid>,
+    pub provider_name: Option<String>,
+    pub covered_secs: f64,
+    /// Seconds from the later of `since` and the first heartbeat until now
+    pub window_secs: f64,
+    pub uptime_pct: f64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProviderAgentUptime {
+    pub provider_id: Option<Uuid>,
+    pub provider_name: String,
+    pub vps_count: usize,
+    pub uptime_pct: f64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AgentUptimeReport {
+    pub since: DateTime<Utc>,
+    pub vps: Vec<VpsAgentUptime>,
+    pub providers: Vec<ProviderAgentUptime>,
+}
+
+// ─── IP Lookup ───────────────────────────────────────────
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct IpLookupQuery {
+    /// IP address or CIDR, e.g. `103.21.4.5` or `103.21.0.0/16`
+    pub ip: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct IpLookupResponse {
+    pub query: String,
+    pub vps: Vec<IpLookupVps>,
+    /// All A/AAAA records whose content falls within the query
+    pub dns_records: Vec<IpLookupDnsRecord>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct IpLookupVps {
+    pub vps_id: Uuid,
+    pub hostname: String,
+    pub alias: String,
+    pub status: String,
+    pub country: String,
+    pub provider_id: Option<Uuid>,
+    /// Whether the VPS still holds at least one matching address
+    pub current: bool,
+    pub holdings: Vec<IpHolding>,
+    pub dns_names: Vec<String>,
+    pub reachability: Vec<IpReachability>,
+}
+
+/// One period during which a VPS held a matching address.
+/// `from` is None when the add predates IP history; `until` is None while still held.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct IpHolding {
+    pub ip: String,
+    pub label: String,
+    pub from: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub current: bool,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct IpLookupDnsRecord {
+    pub id: Uuid,
+    pub zone_name: String,
+    pub record_type: String,
+    pub name: String,
+    pub content: String,
+    pub vps_id: Option<Uuid>,
+}
+
+/// Latest check result per (ip, source, check_type).
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct IpReachability {
+    #[serde(skip)]
+    pub vps_id: Uuid,
+    pub ip: String,
+    pub source: String,
+    pub check_type: String,
+    pub success: bool,
+    pub latency_ms: Option<i32>,
+    pub checked_at: DateTime<Utc>,
+}
+
 // ─── DNS Record History ────────────────────────────────
 
 #[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
@@ -1298,6 +2078,12 @@ pub struct CloudAccount {
     pub last_synced_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub sync_enabled: bool,
+    pub sync_interval_secs: Option<i32>,
+    pub last_sync_attempt_at: Option<DateTime<Utc>>,
+    pub last_sync_error: Option<String>,
+    #[schema(value_type = Option<CloudSyncResult>)]
+    pub last_sync_result: Option<sqlx::types::Json<CloudSyncResult>>,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
@@ -1310,6 +2096,15 @@ pub struct CloudAccountResponse {
     pub last_synced_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Whether the background worker syncs this account
+    pub sync_enabled: bool,
+    /// Per-account background sync interval; null uses `cloud_sync_interval_secs`
+    pub sync_interval_secs: Option<i32>,
+    pub last_sync_attempt_at: Option<DateTime<Utc>>,
+    /// Error of the last sync attempt, null when it succeeded
+    pub last_sync_error: Option<String>,
+    /// Result of the last successful sync
+    pub last_sync_result: Option<CloudSyncResult>,
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
@@ -1317,15 +2112,21 @@ pub struct CreateCloudAccount {
     pub name: String,
     pub provider_type: String,
     pub config: serde_json::Value,
+    #[serde(default = "default_true")]
+    pub sync_enabled: bool,
+    pub sync_interval_secs: Option<i32>,
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateCloudAccount {
     pub name: Option<String>,
     pub config: Option<serde_json::Value>,
+    pub sync_enabled: Option<bool>,
+    /// Seconds between background syncs; 0 falls back to `cloud_sync_interval_secs`
+    pub sync_interval_secs: Option<i32>,
 }
 
-#[derive(Debug, Serialize, ToSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct CloudSyncResult {
     pub instances_found: i64,
     pub created: i64,
@@ -1350,6 +2151,64 @@ pub struct PaginatedCloudAccountResponse {
     pub per_page: i64,
 }
 
+// ─── Cloud IP Rotation ───────────────────────────────────
+
+/// One IP rotation of a cloud-sourced VPS. `step` is the next step to run:
+/// allocate → associate → update_vps → update_dns → release_old → done.
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct IpRotation {
+    pub id: Uuid,
+    pub vps_id: Uuid,
+    pub cloud_account_id: Option<Uuid>,
+    pub provider_type: String,
+    pub region: String,
+    pub instance_id: String,
+    pub old_ip: String,
+    pub new_ip: Option<String>,
+    /// Provider handle of the new address (EC2 / Alibaba allocation ID)
+    pub allocation_id: Option<String>,
+    pub repoint_dns: bool,
+    pub release_old: bool,
+    pub step: String,
+    /// running, failed or completed
+    pub status: String,
+    /// Error of the last failed step
+    pub error: String,
+    pub dns_records_updated: i32,
+    /// The old address was an Elastic IP / EIP and has been released
+    pub old_released: bool,
+    pub actor: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RotateIpRequest {
+    /// Address to replace; defaults to the first public IP of the VPS
+    pub ip: Option<String>,
+    /// Repoint A / AAAA records linked to the VPS from the old to the new address
+    #[serde(default)]
+    pub repoint_dns: bool,
+    /// Release the old Elastic IP / EIP once the new one is bound (default true)
+    #[serde(default = "default_true")]
+    pub release_old: bool,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PanelActionRequest {
+    /// reboot, boot or shutdown
+    pub action: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PanelActionResult {
+    pub vps_id: Uuid,
+    pub action: String,
+    /// Message returned by the panel
+    pub message: String,
+}
+
 // ─── VPS Duplicate Detection ─────────────────────────────
 
 #[derive(Debug, Serialize, ToSchema)]
@@ -1377,6 +2236,56 @@ pub struct MergeVpsResponse {
     pub deleted_id: Uuid,
 }
 
+// ─── VPS Bulk Operations ─────────────────────────────────
+
+/// Bulk operation request. Targets are selected by exactly one of `ids` or `filter`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct VpsBulkRequest {
+    pub ids: Option<Vec<Uuid>>,
+    /// Filter expression (same syntax as `q` on `GET /api/vps`).
+    /// Deleted VPS are excluded unless the expression selects on status.
+    pub filter: Option<String>,
+    pub operation: VpsBulkOperation,
+    /// Run every row and report results, then roll back
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum VpsBulkOperation {
+    /// Set the given fields on every target (per-host fields such as hostname are rejected)
+    SetFields { fields: UpdateVps },
+    AddTags { tags: Vec<String> },
+    RemoveTags { tags: Vec<String> },
+    Retire,
+    Restore,
+    SetProvider { provider_id: Uuid },
+    SetMonitoring { enabled: bool },
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct VpsBulkResponse {
+    pub dry_run: bool,
+    /// True when the changes were committed (not a dry run and no row failed)
+    pub applied: bool,
+    pub matched: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub results: Vec<VpsBulkRowResult>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct VpsBulkRowResult {
+    pub id: Uuid,
+    pub hostname: Option<String>,
+    pub success: bool,
+    pub error: Option<String>,
+    /// Changed fields as `{"field": {"from": .., "to": ..}}`
+    #[schema(value_type = Object)]
+    pub changes: serde_json::Value,
+}
+
 // ─── API Keys ─────────────────────────────────────────────
 
 #[derive(Debug, sqlx::FromRow, Serialize)]
@@ -8,6 +8,7 @@ use crate::db::Db;
 use crate::models::IpEntry;
 
 /// Background worker that checks for expiring VPS and sends notifications.
+/// VPS flagged `auto_renew` are skipped.
 pub async fn run(
     db: Db,
     redis: redis::aio::ConnectionManager,
@@ -51,6 +52,7 @@ async fn check_and_notify(
              AND v.expire_date <= CURRENT_DATE + $1 * INTERVAL '1 day'
              AND v.expire_date >= CURRENT_DATE
              AND v.status IN ('active', 'provisioning')
+             AND NOT v.auto_renew
            ORDER BY v.expire_date ASC"#,
     )
     .bind(max_days)
@@ -136,7 +138,7 @@ async fn check_and_notify(
     Ok(())
 }
 
-async fn send_telegram(
+pub(crate) async fn send_telegram(
     client: &reqwest::Client,
     token: &str,
     chat_id: &str,
@@ -162,10 +164,10 @@ async fn send_telegram(
     Ok(())
 }
 
-async fn send_webhook(
+pub(crate) async fn send_webhook<T: Serialize>(
     client: &reqwest::Client,
     url: &str,
-    payload: &NotificationPayload,
+    payload: &T,
 ) -> anyhow::Result<()> {
     let resp = client.post(url).json(payload).send().await?;
 
@@ -5,16 +5,19 @@ use crate::errors::ErrorResponse;
 use crate::models::{
     AgentHeartbeat, AgentRegister, BatchCreateEnvoyRoutes, ChangePasswordRequest,
     CloudAccountResponse, CloudSyncResult, CreateCloudAccount, UpdateCloudAccount,
+    IpRotation, RotateIpRequest, PanelActionRequest, PanelActionResult,
     PaginatedCloudAccountResponse, SystemSetting,
     DuplicateGroup, DuplicateDetectionResponse, MergeVpsRequest, MergeVpsResponse,
+    VpsBulkRequest, VpsBulkOperation, VpsBulkResponse, VpsBulkRowResult,
     ConvertedTotal, CostMonthlyResponse, CostSummaryResponse, CountStat,
     CreateDnsAccount, CreateEnvoyNode, CreateEnvoyRoute, CreateExchangeRate,
     CreateIpCheck, CreateProvider, CreateTicket, CreateTicketComment, CreateUser, CreateVps,
     CreateVpsPurpose,
+    CustomField, CreateCustomField, UpdateCustomField, PaginatedCustomFieldResponse,
     CurrencyBreakdown, DashboardStats, DnsAccountResponse, DnsRecord, DnsSyncResult, DnsZone,
     EnvoyNode, EnvoyRoute, ExchangeRate, ImportRequest, StaticRouteEntry, SyncStaticRoutes,
     SyncStaticRoutesResponse,
-    ImportResult, IpCheck, IpCheckSummary, IpEntry, LoginRequest, LoginResponse, MonthlyCostEntry,
+    ImportResult, IpCheck, IpCheckSummary, IpEntry, IpGeo, LoginRequest, LoginResponse, MonthlyCostEntry,
     PaginatedDnsAccountResponse, PaginatedDnsRecordResponse,
     PaginatedDnsZoneResponse, PaginatedEnvoyNodeResponse, PaginatedEnvoyRouteResponse,
     PaginatedExchangeRateResponse, PaginatedIpCheckResponse, PaginatedProviderResponse,
@@ -26,9 +29,19 @@ use crate::models::{
     UpdateEnvoyRoute, UpdateExchangeRate, UpdateProvider, UpdateTicket, UpdateUser, UpdateVps,
     UpdateVpsPurpose, UserResponse, Vps, VpsPurpose,
     VpsIpHistory, PaginatedVpsIpHistoryResponse,
+    VpsStatusHistory, PaginatedVpsStatusHistoryResponse,
+    VpsHistoryEvent, PaginatedVpsHistoryResponse,
+    VpsHeartbeat, VpsHeartbeatSeries, VpsAgentUptime, ProviderAgentUptime, AgentUptimeReport,
+    ProviderPlan, CreateProviderPlan, UpdateProviderPlan, PaginatedProviderPlanResponse,
+    CostCompareResponse, PlanCostComparison,
+    ProviderScorecard, ProviderRanking,
+    IpStatus, VpsIpStatus, IpReputation, VpsIpReputation,
+    VpsRenewal, CreateVpsRenewal, PaginatedVpsRenewalResponse,
     DnsRecordHistory, PaginatedDnsRecordHistoryResponse,
     IpLabel, CreateIpLabel, UpdateIpLabel, PaginatedIpLabelResponse,
+    PoolIp, CreatePoolIp, UpdatePoolIp, PaginatedPoolIpResponse, AssignPoolIp, ReleasePoolIp,
     ApiKeyResponse, ApiKeyCreatedResponse, CreateApiKey, PaginatedApiKeyResponse,
+    IpLookupResponse, IpLookupVps, IpHolding, IpLookupDnsRecord, IpReachability,
 };
 
 #[derive(OpenApi)]
@@ -62,6 +75,8 @@ use crate::models::{
         crate::routes::providers::delete,
         crate::routes::providers::export,
         crate::routes::providers::import,
+        crate::routes::providers::scorecard,
+        crate::routes::providers::ranking,
         // VPS
         crate::routes::vps::list,
         crate::routes::vps::get_one,
@@ -70,15 +85,33 @@ use crate::models::{
         crate::routes::vps::delete,
         crate::routes::vps::retire,
         crate::routes::vps::restore,
+        crate::routes::vps::bulk,
         crate::routes::vps::export,
         crate::routes::vps::import,
         crate::routes::vps::ip_history,
+        crate::routes::vps::status_history,
+        crate::routes::vps::history,
+        crate::routes::vps::heartbeats,
+        crate::routes::vps::ip_status,
+        crate::routes::vps::ip_reputation,
+        crate::routes::renewals::list,
+        crate::routes::renewals::create,
         // IP Labels
         crate::routes::ip_labels::list,
         crate::routes::ip_labels::get_one,
         crate::routes::ip_labels::create,
         crate::routes::ip_labels::update,
         crate::routes::ip_labels::delete,
+        // IP Pool
+        crate::routes::ip_pool::list,
+        crate::routes::ip_pool::get_one,
+        crate::routes::ip_pool::create,
+        crate::routes::ip_pool::update,
+        crate::routes::ip_pool::delete,
+        crate::routes::ip_pool::assign,
+        crate::routes::ip_pool::release,
+        // IP Lookup
+        crate::routes::lookup::lookup,
         // IP Checks
         crate::routes::ip_checks::list,
         crate::routes::ip_checks::get_one,
@@ -88,6 +121,7 @@ use crate::models::{
         crate::routes::ip_checks::purge,
         // Stats
         crate::routes::stats::dashboard,
+        crate::routes::stats::agent_uptime,
         // Prometheus
         crate::routes::prometheus::targets,
         // Agent
@@ -104,6 +138,8 @@ use crate::models::{
         // Costs
         crate::routes::costs::summary,
         crate::routes::costs::monthly,
+        crate::routes::costs::spend,
+        crate::routes::costs::compare,
         // Tickets
         crate::routes::tickets::list,
         crate::routes::tickets::get_one,
@@ -143,6 +179,9 @@ use crate::models::{
         crate::routes::cloud::update_account,
         crate::routes::cloud::delete_account,
         crate::routes::cloud::sync_account,
+        crate::routes::cloud::rotate::rotate_ip,
+        crate::routes::cloud::rotate::list_rotations,
+        crate::routes::cloud::panel::panel_action,
         // VPS Duplicates
         crate::routes::vps_duplicates::detect_duplicates,
         crate::routes::vps_duplicates::merge_vps,
@@ -155,6 +194,17 @@ use crate::models::{
         crate::routes::vps_purposes::create,
         crate::routes::vps_purposes::update,
         crate::routes::vps_purposes::delete,
+        crate::routes::provider_plans::list,
+        crate::routes::provider_plans::get_one,
+        crate::routes::provider_plans::create,
+        crate::routes::provider_plans::update,
+        crate::routes::provider_plans::delete,
+        // Custom Fields
+        crate::routes::custom_fields::list,
+        crate::routes::custom_fields::get_one,
+        crate::routes::custom_fields::create,
+        crate::routes::custom_fields::update,
+        crate::routes::custom_fields::delete,
         // API Keys
         crate::routes::api_keys::list,
         crate::routes::api_keys::get_one,
@@ -163,7 +213,7 @@ use crate::models::{
     ),
     components(schemas(
         ErrorResponse,
-        IpEntry,
+        IpEntry, IpGeo,
         Provider, CreateProvider, UpdateProvider,
         Vps, CreateVps, UpdateVps,
         PaginatedProviderResponse, PaginatedVpsResponse, PaginatedIpCheckResponse,
@@ -190,13 +240,26 @@ use crate::models::{
         PaginatedDnsAccountResponse, PaginatedDnsZoneResponse,
         PaginatedDnsRecordResponse, DnsRecordHistory, PaginatedDnsRecordHistoryResponse,
         VpsPurpose, CreateVpsPurpose, UpdateVpsPurpose, PaginatedVpsPurposeResponse,
+        CustomField, CreateCustomField, UpdateCustomField, PaginatedCustomFieldResponse,
         CloudAccountResponse, CreateCloudAccount, UpdateCloudAccount, CloudSyncResult,
+        IpRotation, RotateIpRequest, PanelActionRequest, PanelActionResult,
         PaginatedCloudAccountResponse,
         DuplicateGroup, DuplicateDetectionResponse, MergeVpsRequest, MergeVpsResponse,
+        VpsBulkRequest, VpsBulkOperation, VpsBulkResponse, VpsBulkRowResult,
         VpsIpHistory, PaginatedVpsIpHistoryResponse,
+        VpsStatusHistory, PaginatedVpsStatusHistoryResponse,
+        VpsHistoryEvent, PaginatedVpsHistoryResponse,
+        VpsHeartbeat, VpsHeartbeatSeries, VpsAgentUptime, ProviderAgentUptime, AgentUptimeReport,
+        ProviderPlan, CreateProviderPlan, UpdateProviderPlan, PaginatedProviderPlanResponse,
+        CostCompareResponse, PlanCostComparison,
+        ProviderScorecard, ProviderRanking,
+        IpStatus, VpsIpStatus, IpReputation, VpsIpReputation,
+        VpsRenewal, CreateVpsRenewal, PaginatedVpsRenewalResponse,
         IpLabel, CreateIpLabel, UpdateIpLabel, PaginatedIpLabelResponse,
+        PoolIp, CreatePoolIp, UpdatePoolIp, PaginatedPoolIpResponse, AssignPoolIp, ReleasePoolIp,
         SystemSetting,
         ApiKeyResponse, ApiKeyCreatedResponse, CreateApiKey, PaginatedApiKeyResponse,
+        IpLookupResponse, IpLookupVps, IpHolding, IpLookupDnsRecord, IpReachability,
     )),
     tags(
         (name = "Auth", description = "Authentication and session management"),
@@ -215,7 +278,11 @@ use crate::models::{
         (name = "DNS", description = "Multi-provider DNS domain management (read-only sync)"),
         (name = "Cloud Accounts", description = "Cloud provider account management (AWS, Alibaba) with VPS auto-sync"),
         (name = "VPS Purposes", description = "VPS purpose type management"),
+        (name = "Provider Plans", description = "Provider plan catalogue"),
+        (name = "Custom Fields", description = "Typed custom field definitions for VPS (admin only)"),
         (name = "IP Labels", description = "IP address label management"),
+        (name = "IP Pool", description = "Spare, reserved and floating IP addresses"),
+        (name = "Lookup", description = "Reverse lookup of IPs across VPS, IP history, DNS and probes"),
         (name = "Settings", description = "System-wide runtime settings"),
         (name = "API Keys", description = "API key management (admin only)"),
     )
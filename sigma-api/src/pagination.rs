@@ -0,0 +1,321 @@
+//! Sort keys and keyset (cursor) pagination for list endpoints.
+//!
+//! Every list endpoint that adopts this accepts `sort=<column>` or
+//! `sort=-<column>` from a per-endpoint whitelist, plus an optional `cursor`.
+//! Without `cursor` the endpoint stays in page mode (`page` / `per_page`,
+//! OFFSET). With `cursor` present — empty for the first page — it switches to
+//! keyset mode: rows are selected with `(sort_expr, id) > (last_value, last_id)`,
+//! so pages stay stable and fast while rows are being inserted, and the
+//! response carries `next_cursor` instead of `total` / `page`.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::{PgArguments, PgRow, Postgres};
+use sqlx::query::QueryAs;
+use sqlx::{FromRow, Row};
+use uuid::Uuid;
+
+use crate::errors::AppError;
+use crate::models::{CursorResponse, ListResponse, PaginatedResponse};
+
+/// A column that may be used as a sort key.
+pub struct SortColumn {
+    /// Name accepted in `sort=`
+    pub name: &'static str,
+    /// SQL expression; must be NOT NULL (wrap nullable columns in COALESCE)
+    pub expr: &'static str,
+    /// SQL type used to cast the cursor value back, e.g. `timestamptz`
+    pub sql_type: &'static str,
+}
+
+/// A parsed `sort=` parameter.
+pub struct Sort {
+    column: &'static SortColumn,
+    desc: bool,
+}
+
+impl Sort {
+    /// Parse `name` or `-name` against the whitelist. Returns None for a blank input.
+    pub fn parse(input: Option<&str>, columns: &'static [SortColumn]) -> Result<Option<Sort>, AppError> {
+        let Some(input) = input.map(str::trim).filter(|s| !s.is_empty()) else {
+            return Ok(None);
+        };
+        let (name, desc) = match input.strip_prefix('-') {
+            Some(rest) => (rest, true),
+            None => (input, false),
+        };
+        let column = columns.iter().find(|c| c.name == name).ok_or_else(|| {
+            AppError::BadRequest(format!(
+                "Invalid sort '{}' (allowed: {})",
+                name,
+                columns.iter().map(|c| c.name).collect::<Vec<_>>().join(", ")
+            ))
+        })?;
+        Ok(Some(Sort { column, desc }))
+    }
+
+    /// The canonical `sort=` value, e.g. `-created_at`.
+    pub fn name(&self) -> String {
+        if self.desc {
+            format!("-{}", self.column.name)
+        } else {
+            self.column.name.to_string()
+        }
+    }
+
+    /// ORDER BY clause body with `id_col` as tie-breaker.
+    pub fn order_by(&self, id_col: &str) -> String {
+        let dir = if self.desc { "DESC" } else { "ASC" };
+        format!("{} {dir}, {id_col} {dir}", self.column.expr)
+    }
+}
+
+/// Pagination and ordering for one list request, in page or keyset mode.
+pub struct Listing {
+    key: Sort,
+    explicit_sort: bool,
+    keyset: bool,
+    after: Option<(String, Uuid)>,
+    page: i64,
+    per_page: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CursorToken {
+    s: String,
+    v: String,
+    id: Uuid,
+}
+
+impl Listing {
+    /// Build from the query parameters. `per_page` is clamped to 1..=100.
+    /// `default_sort` is the key used in keyset mode when `sort` is not given.
+    pub fn from_query(
+        page: i64,
+        per_page: i64,
+        cursor: Option<&str>,
+        sort: Option<&str>,
+        columns: &'static [SortColumn],
+        default_sort: &str,
+    ) -> Result<Listing, AppError> {
+        let explicit = Sort::parse(sort, columns)?;
+        let explicit_sort = explicit.is_some();
+        let key = match explicit {
+            Some(s) => s,
+            None => Sort::parse(Some(default_sort), columns)?
+                .ok_or_else(|| AppError::Internal("empty default sort".into()))?,
+        };
+        let mut listing = Listing {
+            key,
+            explicit_sort,
+            keyset: cursor.is_some(),
+            after: None,
+            page: page.max(1),
+            per_page: per_page.clamp(1, 100),
+        };
+
+        let cursor = cursor.map(str::trim).unwrap_or_default();
+        if !cursor.is_empty() {
+            let token: CursorToken = URL_SAFE_NO_PAD
+                .decode(cursor)
+                .ok()
+                .and_then(|b| serde_json::from_slice(&b).ok())
+                .ok_or_else(|| AppError::BadRequest("Invalid cursor".into()))?;
+            if token.s != listing.key.name() {
+                return Err(AppError::BadRequest(format!(
+                    "Cursor was issued for sort '{}', not '{}'",
+                    token.s,
+                    listing.key.name()
+                )));
+            }
+            listing.after = Some((token.v, token.id));
+        }
+        Ok(listing)
+    }
+
+    /// Keyset mode: skip the COUNT query.
+    pub fn is_keyset(&self) -> bool {
+        self.keyset
+    }
+
+    /// Extra select-list items that [`Keyed`] reads back.
+    pub fn select(&self, id_col: &str) -> String {
+        format!(", ({})::text AS _sort_key, {id_col} AS _sort_id", self.key.column.expr)
+    }
+
+    /// SQL appended directly after a `WHERE ...` clause: the keyset condition,
+    /// ORDER BY and LIMIT/OFFSET. `legacy_order` is the endpoint's historical
+    /// ORDER BY, kept in page mode when no `sort` is given. Placeholders start at
+    /// `first_param`; bind them with [`Listing::bind`].
+    pub fn tail(&self, id_col: &str, legacy_order: &str, first_param: u32) -> String {
+        let mut sql = String::new();
+        let mut idx = first_param;
+        if self.after.is_some() {
+            let op = if self.key.desc { "<" } else { ">" };
+            sql.push_str(&format!(
+                " AND ({}, {id_col}) {op} (${idx}::{}, ${}::uuid)",
+                self.key.column.expr,
+                self.key.column.sql_type,
+                idx + 1
+            ));
+            idx += 2;
+        }
+        if self.keyset || self.explicit_sort {
+            sql.push_str(&format!(" ORDER BY {}", self.key.order_by(id_col)));
+        } else {
+            sql.push_str(&format!(" ORDER BY {legacy_order}"));
+        }
+        if self.keyset {
+            sql.push_str(&format!(" LIMIT ${idx}"));
+        } else {
+            sql.push_str(&format!(" LIMIT ${idx} OFFSET ${}", idx + 1));
+        }
+        sql
+    }
+
+    /// Bind the placeholders emitted by [`Listing::tail`], in order.
+    pub fn bind<'q, O>(
+        &self,
+        mut query: QueryAs<'q, Postgres, O, PgArguments>,
+    ) -> QueryAs<'q, Postgres, O, PgArguments> {
+        if let Some((value, id)) = &self.after {
+            query = query.bind(value.clone()).bind(*id);
+        }
+        if self.keyset {
+            // One extra row tells whether there is a next page
+            query.bind(self.per_page + 1)
+        } else {
+            query.bind(self.per_page).bind((self.page - 1) * self.per_page)
+        }
+    }
+
+    /// Build the response from the fetched rows. `total` is ignored in keyset mode.
+    pub fn finish<T: Serialize>(&self, mut rows: Vec<Keyed<T>>, total: i64) -> ListResponse<T> {
+        if !self.keyset {
+            return ListResponse::Page(PaginatedResponse {
+                data: rows.into_iter().map(|k| k.row).collect(),
+                total,
+                page: self.page,
+                per_page: self.per_page,
+            });
+        }
+
+        let has_more = rows.len() as i64 > self.per_page;
+        rows.truncate(self.per_page as usize);
+        let next_cursor = if has_more {
+            rows.last().map(|last| {
+                let token = CursorToken {
+                    s: self.key.name(),
+                    v: last.sort_key.clone(),
+                    id: last.sort_id,
+                };
+                URL_SAFE_NO_PAD.encode(serde_json::to_vec(&token).unwrap_or_default())
+            })
+        } else {
+            None
+        };
+        ListResponse::Cursor(CursorResponse {
+            data: rows.into_iter().map(|k| k.row).collect(),
+            per_page: self.per_page,
+            sort: self.key.name(),
+            next_cursor,
+        })
+    }
+}
+
+/// A row plus the sort key columns added by [`Listing::select`].
+pub struct Keyed<T> {
+    pub row: T,
+    sort_key: String,
+    sort_id: Uuid,
+}
+
+impl<'r, T: FromRow<'r, PgRow>> FromRow<'r, PgRow> for Keyed<T> {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Keyed {
+            row: T::from_row(row)?,
+            sort_key: row.try_get("_sort_key")?,
+            sort_id: row.try_get("_sort_id")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static COLUMNS: &[SortColumn] = &[
+        SortColumn { name: "created_at", expr: "created_at", sql_type: "timestamptz" },
+        SortColumn { name: "hostname", expr: "hostname", sql_type: "text" },
+    ];
+
+    fn keyed(row: i32, key: &str, id: Uuid) -> Keyed<i32> {
+        Keyed { row, sort_key: key.into(), sort_id: id }
+    }
+
+    fn next_cursor(resp: ListResponse<i32>) -> Option<String> {
+        match resp {
+            ListResponse::Cursor(c) => c.next_cursor,
+            ListResponse::Page(_) => panic!("expected cursor response"),
+        }
+    }
+
+    #[test]
+    fn test_parse_sort() {
+        let s = Sort::parse(Some("-created_at"), COLUMNS).unwrap().unwrap();
+        assert_eq!(s.name(), "-created_at");
+        assert_eq!(s.order_by("id"), "created_at DESC, id DESC");
+        assert!(Sort::parse(Some(""), COLUMNS).unwrap().is_none());
+        assert!(Sort::parse(Some("password"), COLUMNS).is_err());
+    }
+
+    #[test]
+    fn test_page_mode() {
+        let l = Listing::from_query(3, 500, None, None, COLUMNS, "hostname").unwrap();
+        assert!(!l.is_keyset());
+        assert_eq!(l.tail("id", "hostname", 4), " ORDER BY hostname LIMIT $4 OFFSET $5");
+
+        let l = Listing::from_query(1, 25, None, Some("-created_at"), COLUMNS, "hostname").unwrap();
+        assert_eq!(
+            l.tail("v.id", "hostname", 1),
+            " ORDER BY created_at DESC, v.id DESC LIMIT $1 OFFSET $2"
+        );
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let l = Listing::from_query(1, 2, Some(""), Some("-created_at"), COLUMNS, "hostname").unwrap();
+        assert!(l.is_keyset());
+        assert_eq!(l.tail("id", "hostname", 3), " ORDER BY created_at DESC, id DESC LIMIT $3");
+
+        let id = Uuid::new_v4();
+        let rows = vec![keyed(1, "b", Uuid::nil()), keyed(2, "a", id), keyed(3, "0", Uuid::nil())];
+        let cursor = next_cursor(l.finish(rows, 0));
+        assert!(cursor.is_some());
+
+        let next =
+            Listing::from_query(1, 2, cursor.as_deref(), Some("-created_at"), COLUMNS, "hostname")
+                .unwrap();
+        assert_eq!(next.after, Some(("a".to_string(), id)));
+        assert_eq!(
+            next.tail("id", "hostname", 3),
+            " AND (created_at, id) < ($3::timestamptz, $4::uuid) ORDER BY created_at DESC, id DESC LIMIT $5"
+        );
+    }
+
+    #[test]
+    fn test_cursor_sort_mismatch_and_garbage() {
+        let l = Listing::from_query(1, 1, Some(""), None, COLUMNS, "hostname").unwrap();
+        let rows = vec![keyed(1, "a", Uuid::nil()), keyed(2, "b", Uuid::nil())];
+        let cursor = next_cursor(l.finish(rows, 0));
+        assert!(Listing::from_query(1, 1, cursor.as_deref(), Some("created_at"), COLUMNS, "hostname").is_err());
+        assert!(Listing::from_query(1, 1, Some("not-a-cursor"), None, COLUMNS, "hostname").is_err());
+    }
+
+    #[test]
+    fn test_last_page_has_no_cursor() {
+        let l = Listing::from_query(1, 5, Some(""), None, COLUMNS, "hostname").unwrap();
+        assert!(next_cursor(l.finish(vec![keyed(1, "a", Uuid::nil())], 0)).is_none());
+    }
+}
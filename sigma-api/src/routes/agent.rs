@@ -4,6 +4,7 @@ use axum::{extract::State, routing::post, Extension, Json, Router};
 
 use crate::auth::{require_role, CurrentUser};
 use crate::errors::{AppError, ErrorResponse};
+use crate::heartbeats;
 use crate::models::{AgentHeartbeat, AgentRegister, IpEntry, Vps};
 use crate::routes::AppState;
 
@@ -196,14 +197,11 @@ pub async fn heartbeat(
         .await?
         .ok_or(AppError::NotFound)?;
 
-    let mut extra = existing.extra.clone();
-    if let serde_json::Value::Object(ref mut map) = extra {
-        map.insert("system_info".to_string(), input.system_info.clone());
-        map.insert(
-            "last_heartbeat".to_string(),
-            serde_json::Value::String(chrono::Utc::now().to_rfc3339()),
-        );
-    }
+    // Merged into `extra` in SQL so concurrent operator edits to other keys are kept
+    let extra = serde_json::json!({
+        "system_info": input.system_info,
+        "last_heartbeat": chrono::Utc::now().to_rfc3339(),
+    });
 
     let row = if !input.ip_addresses.is_empty() {
         validate_ips(&input.ip_addresses)?;
@@ -219,7 +217,7 @@ pub async fn heartbeat(
             .await?;
 
         let row = sqlx::query_as::<_, Vps>(
-            "UPDATE vps SET hostname = $2, ip_addresses = $3, extra = $4 WHERE id = $1 RETURNING *",
+            "UPDATE vps SET hostname = $2, ip_addresses = $3, extra = COALESCE(extra, '{}'::jsonb) || $4 WHERE id = $1 RETURNING *",
         )
         .bind(existing.id)
         .bind(&input.hostname)
@@ -232,7 +230,7 @@ pub async fn heartbeat(
         row
     } else {
         sqlx::query_as::<_, Vps>(
-            "UPDATE vps SET hostname = $2, extra = $3 WHERE id = $1 RETURNING *",
+            "UPDATE vps SET hostname = $2, extra = COALESCE(extra, '{}'::jsonb) || $3 WHERE id = $1 RETURNING *",
         )
         .bind(existing.id)
         .bind(&input.hostname)
@@ -241,6 +239,10 @@ pub async fn heartbeat(
         .await?
     };
 
+    if let Err(e) = heartbeats::record(&state.db, row.id, &input.system_info).await {
+        tracing::warn!("Failed to store heartbeat sample for {}: {e}", row.hostname);
+    }
+
     Ok(Json(row))
 }
 
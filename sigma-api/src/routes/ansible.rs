@@ -1,10 +1,16 @@
-use axum::{extract::State, routing::get, Json, Router};
+use axum::{
+    extract::{Query, State},
+    routing::get,
+    Json, Router,
+};
 use serde::Serialize;
 use std::collections::HashMap;
 
-use crate::errors::AppError;
-use crate::models::IpEntry;
+use crate::custom_fields::{exposed_labels, load_definitions};
+use crate::errors::{AppError, ErrorResponse};
+use crate::models::{IpEntry, VpsFilterQuery};
 use crate::routes::AppState;
+use crate::vps_filter::{bind_filter, VpsFilter};
 
 pub fn router() -> Router<AppState> {
     Router::new().route("/api/ansible/inventory", get(inventory))
@@ -12,29 +18,51 @@ pub fn router() -> Router<AppState> {
 
 /// Ansible dynamic inventory JSON output.
 /// Groups hosts by: status, country, purpose, provider, and each tag.
-/// Only includes VPS with status in (active, provisioning).
+/// Only includes VPS with status in (active, provisioning), unless the `q`
+/// filter expression selects on status itself. Custom fields marked
+/// `expose_label` are added as `sigma_custom_<name>` hostvars.
 #[utoipa::path(
     get, path = "/api/ansible/inventory",
     tag = "Ansible",
+    params(VpsFilterQuery),
     responses(
         (status = 200, description = "Ansible dynamic inventory JSON"),
+        (status = 400, body = ErrorResponse),
     )
 )]
-pub async fn inventory(State(state): State<AppState>) -> Result<Json<AnsibleInventory>, AppError> {
-    let rows = sqlx::query_as::<_, VpsInventoryRow>(
+pub async fn inventory(
+    State(state): State<AppState>,
+    Query(q): Query<VpsFilterQuery>,
+) -> Result<Json<AnsibleInventory>, AppError> {
+    let filter = VpsFilter::parse_opt(q.q.as_deref())?;
+    let mut where_clause = String::from(" WHERE 1=1");
+    if !filter.as_ref().is_some_and(|f| f.references("status")) {
+        where_clause.push_str(" AND v.status IN ('active', 'provisioning')");
+    }
+    let mut bindings = Vec::new();
+    if let Some(ref f) = filter {
+        let (sql, b) = f.to_sql("v", 1)?;
+        where_clause.push_str(&format!(" AND {}", sql));
+        bindings = b;
+    }
+
+    let sql = format!(
         r#"SELECT
             v.hostname, v.alias, v.ip_addresses, v.ssh_port,
             v.country, v.city, v.dc_name, v.status, v.purpose, v.vpn_protocol,
             v.tags, v.cpu_cores, v.ram_mb, v.disk_gb,
-            v.monitoring_enabled, v.node_exporter_port,
+            v.monitoring_enabled, v.node_exporter_port, v.extra, v.ip_geo, v.geo_mismatch,
             COALESCE(p.name, '') as provider_name
            FROM vps v
-           LEFT JOIN providers p ON p.id = v.provider_id
-           WHERE v.status IN ('active', 'provisioning')
+           LEFT JOIN providers p ON p.id = v.provider_id{}
            ORDER BY v.hostname"#,
-    )
-    .fetch_all(&state.db)
-    .await?;
+        where_clause
+    );
+    let rows = bind_filter(sqlx::query_as::<_, VpsInventoryRow>(&sql), &bindings)
+        .fetch_all(&state.db)
+        .await?;
+    let mut conn = state.db.acquire().await?;
+    let defs = load_definitions(&mut conn).await?;
 
     let mut all_hosts: Vec<String> = Vec::new();
     let mut groups: HashMap<String, Vec<String>> = HashMap::new();
@@ -113,14 +141,25 @@ pub async fn inventory(State(state): State<AppState>) -> Result<Json<AnsibleInve
             vars.insert("sigma_tags".into(), row.tags.clone().into());
         }
 
-        // All IP addresses with labels
+        // All IP addresses with labels and GeoIP data
         let ips: Vec<serde_json::Value> = row
             .ip_addresses
             .0
             .iter()
-            .map(|e| serde_json::json!({"ip": e.ip, "label": e.label}))
+            .map(|e| {
+                let geo = row.ip_geo.0.iter().find(|g| g.ip == e.ip);
+                serde_json::json!({
+                    "ip": e.ip,
+                    "label": e.label,
+                    "asn": geo.and_then(|g| g.asn),
+                    "as_org": geo.and_then(|g| g.as_org.clone()),
+                    "geo_country": geo.and_then(|g| g.country.clone()),
+                    "geo_city": geo.and_then(|g| g.city.clone()),
+                })
+            })
             .collect();
         vars.insert("sigma_ip_addresses".into(), ips.into());
+        vars.insert("sigma_geo_mismatch".into(), row.geo_mismatch.into());
 
         if let Some(cores) = row.cpu_cores {
             vars.insert("sigma_cpu_cores".into(), cores.into());
@@ -133,6 +172,9 @@ pub async fn inventory(State(state): State<AppState>) -> Result<Json<AnsibleInve
         }
         vars.insert("sigma_monitoring_enabled".into(), row.monitoring_enabled.into());
         vars.insert("sigma_node_exporter_port".into(), row.node_exporter_port.into());
+        for (name, value) in exposed_labels(&defs, &row.extra) {
+            vars.insert(format!("sigma_custom_{}", name), value.into());
+        }
 
         hostvars.insert(hostname.clone(), serde_json::Value::Object(vars));
     }
@@ -201,5 +243,6 @@ struct VpsInventoryRow {
     disk_gb: Option<i32>,
     monitoring_enabled: bool,
     node_exporter_port: i32,
+    extra: serde_json::Value,
     provider_name: String,
 }
@@ -10,7 +10,8 @@ use uuid::Uuid;
 use crate::auth::{require_role, CurrentUser};
 use crate::db::Db;
 use crate::errors::AppError;
-use crate::models::PaginatedResponse;
+use crate::models::ListResponse;
+use crate::pagination::{Keyed, Listing, SortColumn};
 use crate::routes::AppState;
 
 #[derive(Debug, Serialize, sqlx::FromRow)]
@@ -33,6 +34,8 @@ pub struct AuditLogQuery {
     pub action: Option<String>,
     pub since: Option<String>,
     pub until: Option<String>,
+    pub sort: Option<String>,
+    pub cursor: Option<String>,
     #[serde(default = "default_page")]
     pub page: i64,
     #[serde(default = "default_per_page")]
@@ -42,6 +45,12 @@ pub struct AuditLogQuery {
 fn default_page() -> i64 { 1 }
 fn default_per_page() -> i64 { 50 }
 
+static AUDIT_LOG_SORT_COLUMNS: &[SortColumn] = &[
+    SortColumn { name: "created_at", expr: "created_at", sql_type: "timestamptz" },
+    SortColumn { name: "action", expr: "action", sql_type: "text" },
+    SortColumn { name: "resource", expr: "resource", sql_type: "text" },
+];
+
 pub fn router() -> Router<AppState> {
     Router::new().route("/api/audit-logs", get(list))
 }
@@ -77,12 +86,17 @@ async fn list(
     State(state): State<AppState>,
     Extension(user): Extension<CurrentUser>,
     Query(q): Query<AuditLogQuery>,
-) -> Result<Json<PaginatedResponse<AuditLog>>, AppError> {
+) -> Result<Json<ListResponse<AuditLog>>, AppError> {
     require_role(&user, &["admin"])?;
 
-    let per_page = q.per_page.clamp(1, 100);
-    let page = q.page.max(1);
-    let offset = (page - 1) * per_page;
+    let listing = Listing::from_query(
+        q.page,
+        q.per_page,
+        q.cursor.as_deref(),
+        q.sort.as_deref(),
+        AUDIT_LOG_SORT_COLUMNS,
+        "-created_at",
+    )?;
 
     let mut where_clause = String::from(" WHERE 1=1");
     let mut param_idx = 0u32;
@@ -112,42 +126,37 @@ async fn list(
         where_clause.push_str(&format!(" AND created_at <= ${param_idx}::timestamptz"));
     }
 
-    // Count query
-    let count_sql = format!("SELECT COUNT(*) FROM audit_logs{where_clause}");
-    let mut count_query = sqlx::query_as::<_, (i64,)>(&count_sql);
-    if let Some(ref v) = q.resource { count_query = count_query.bind(v); }
-    if let Some(ref v) = q.resource_id { count_query = count_query.bind(v); }
-    if let Some(ref v) = q.user_id { count_query = count_query.bind(v); }
-    if let Some(ref v) = q.action { count_query = count_query.bind(v); }
-    if let Some(ref v) = q.since { count_query = count_query.bind(v); }
-    if let Some(ref v) = q.until { count_query = count_query.bind(v); }
-
-    let total = count_query.fetch_one(&state.db).await?.0;
+    // Count query (page mode only)
+    let mut total = 0;
+    if !listing.is_keyset() {
+        let count_sql = format!("SELECT COUNT(*) FROM audit_logs{where_clause}");
+        let mut count_query = sqlx::query_as::<_, (i64,)>(&count_sql);
+        if let Some(ref v) = q.resource { count_query = count_query.bind(v); }
+        if let Some(ref v) = q.resource_id { count_query = count_query.bind(v); }
+        if let Some(ref v) = q.user_id { count_query = count_query.bind(v); }
+        if let Some(ref v) = q.action { count_query = count_query.bind(v); }
+        if let Some(ref v) = q.since { count_query = count_query.bind(v); }
+        if let Some(ref v) = q.until { count_query = count_query.bind(v); }
+
+        total = count_query.fetch_one(&state.db).await?.0;
+    }
 
     // Data query
-    param_idx += 1;
-    let limit_param = param_idx;
-    param_idx += 1;
-    let offset_param = param_idx;
-
     let data_sql = format!(
-        "SELECT * FROM audit_logs{where_clause} ORDER BY created_at DESC LIMIT ${limit_param} OFFSET ${offset_param}"
+        "SELECT *{} FROM audit_logs{where_clause}{}",
+        listing.select("id"),
+        listing.tail("id", "created_at DESC", param_idx + 1)
     );
-    let mut query = sqlx::query_as::<_, AuditLog>(&data_sql);
+    let mut query = sqlx::query_as::<_, Keyed<AuditLog>>(&data_sql);
     if let Some(ref v) = q.resource { query = query.bind(v); }
     if let Some(ref v) = q.resource_id { query = query.bind(v); }
     if let Some(ref v) = q.user_id { query = query.bind(v); }
     if let Some(ref v) = q.action { query = query.bind(v); }
     if let Some(ref v) = q.since { query = query.bind(v); }
     if let Some(ref v) = q.until { query = query.bind(v); }
-    query = query.bind(per_page).bind(offset);
+    query = listing.bind(query);
 
     let rows = query.fetch_all(&state.db).await?;
 
-    Ok(Json(PaginatedResponse {
-        data: rows,
-        total,
-        page,
-        per_page,
-    }))
+    Ok(Json(listing.finish(rows, total)))
 }
@@ -278,6 +278,171 @@ pub async fn sync(state: &AppState, account: &CloudAccount) -> Result<CloudSyncR
     })
 }
 
+// ─── IP rotation ──────────────────────────────────────────
+
+/// Polls (3 s apart) for an unbound EIP to become available again
+const EIP_WAIT_ATTEMPTS: usize = 10;
+
+/// Allocate a new EIP in `region`. Returns (allocation ID, address).
+/// Bandwidth comes from `eip_bandwidth` in the account config (Mbps, default 5).
+pub async fn allocate_ip(account: &CloudAccount, region: &str) -> Result<(String, String), AppError> {
+    let (access_key_id, access_key_secret) = credentials(account)?;
+    let bandwidth = account
+        .config
+        .get("eip_bandwidth")
+        .and_then(|v| v.as_i64())
+        .unwrap_or(5)
+        .to_string();
+    let client = reqwest::Client::new();
+    let resp = call_vpc_api(
+        &client,
+        access_key_id,
+        access_key_secret,
+        region,
+        "AllocateEipAddress",
+        &[("Bandwidth", &bandwidth), ("InternetChargeType", "PayByTraffic")],
+    )
+    .await?;
+
+    match (resp["AllocationId"].as_str(), resp["EipAddress"].as_str()) {
+        (Some(allocation_id), Some(ip)) => Ok((allocation_id.to_string(), ip.to_string())),
+        _ => Err(AppError::BadGateway("AllocateEipAddress returned no address".into())),
+    }
+}
+
+/// Bind an EIP to the instance. An ECS instance holds a single EIP, so a current
+/// EIP `old_ip` is unbound first and waited on until it is available again.
+pub async fn associate_ip(
+    account: &CloudAccount,
+    region: &str,
+    instance_id: &str,
+    allocation_id: &str,
+    old_ip: &str,
+) -> Result<(), AppError> {
+    let (access_key_id, access_key_secret) = credentials(account)?;
+    let client = reqwest::Client::new();
+
+    if let Some(old) = describe_eip(&client, account, region, old_ip).await? {
+        let old_allocation = old["AllocationId"].as_str().unwrap_or_default();
+        if old["InstanceId"].as_str() == Some(instance_id) {
+            call_vpc_api(
+                &client,
+                access_key_id,
+                access_key_secret,
+                region,
+                "UnassociateEipAddress",
+                &[("AllocationId", old_allocation), ("InstanceId", instance_id)],
+            )
+            .await?;
+        }
+        let mut available = false;
+        for _ in 0..EIP_WAIT_ATTEMPTS {
+            let status = describe_eip(&client, account, region, old_ip)
+                .await?
+                .and_then(|e| e["Status"].as_str().map(String::from));
+            if status.as_deref() != Some("Unassociating") {
+                available = true;
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+        }
+        if !available {
+            return Err(AppError::BadGateway(format!("EIP {old_ip} is still being unbound")));
+        }
+    }
+
+    call_vpc_api(
+        &client,
+        access_key_id,
+        access_key_secret,
+        region,
+        "AssociateEipAddress",
+        &[("AllocationId", allocation_id), ("InstanceId", instance_id)],
+    )
+    .await?;
+    Ok(())
+}
+
+/// Release `ip` if it is an EIP of this account. Returns false for a fixed
+/// public IP, which cannot be released separately from the instance.
+pub async fn release_ip(
+    account: &CloudAccount,
+    region: &str,
+    instance_id: &str,
+    ip: &str,
+) -> Result<bool, AppError> {
+    let (access_key_id, access_key_secret) = credentials(account)?;
+    let client = reqwest::Client::new();
+
+    let Some(eip) = describe_eip(&client, account, region, ip).await? else {
+        return Ok(false);
+    };
+    let bound_to = eip["InstanceId"].as_str().unwrap_or_default();
+    if !bound_to.is_empty() && bound_to != instance_id {
+        return Err(AppError::BadRequest(format!(
+            "EIP {ip} is now bound to {bound_to}; not releasing it"
+        )));
+    }
+    let allocation_id = eip["AllocationId"].as_str().unwrap_or_default();
+    if !bound_to.is_empty() {
+        call_vpc_api(
+            &client,
+            access_key_id,
+            access_key_secret,
+            region,
+            "UnassociateEipAddress",
+            &[("AllocationId", allocation_id), ("InstanceId", instance_id)],
+        )
+        .await?;
+    }
+    call_vpc_api(
+        &client,
+        access_key_id,
+        access_key_secret,
+        region,
+        "ReleaseEipAddress",
+        &[("AllocationId", allocation_id)],
+    )
+    .await?;
+    Ok(true)
+}
+
+async fn describe_eip(
+    client: &reqwest::Client,
+    account: &CloudAccount,
+    region: &str,
+    ip: &str,
+) -> Result<Option<serde_json::Value>, AppError> {
+    let (access_key_id, access_key_secret) = credentials(account)?;
+    let resp = call_vpc_api(
+        client,
+        access_key_id,
+        access_key_secret,
+        region,
+        "DescribeEipAddresses",
+        &[("EipAddress", ip)],
+    )
+    .await?;
+    Ok(resp["EipAddresses"]["EipAddress"]
+        .as_array()
+        .and_then(|a| a.first())
+        .cloned())
+}
+
+fn credentials(account: &CloudAccount) -> Result<(&str, &str), AppError> {
+    let access_key_id = account
+        .config
+        .get("access_key_id")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| AppError::Internal("Missing access_key_id in config".into()))?;
+    let access_key_secret = account
+        .config
+        .get("access_key_secret")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| AppError::Internal("Missing access_key_secret in config".into()))?;
+    Ok((access_key_id, access_key_secret))
+}
+
 // ─── Alibaba Cloud API signing ──────────────────────────
 
 async fn call_ecs_api(
@@ -287,13 +452,59 @@ async fn call_ecs_api(
     region: &str,
     action: &str,
     extra_params: &[(&str, &str)],
+) -> Result<serde_json::Value, AppError> {
+    call_api(
+        client,
+        "ecs.aliyuncs.com",
+        "2014-05-26",
+        access_key_id,
+        access_key_secret,
+        region,
+        action,
+        extra_params,
+    )
+    .await
+}
+
+/// EIPs are managed through the VPC API.
+async fn call_vpc_api(
+    client: &reqwest::Client,
+    access_key_id: &str,
+    access_key_secret: &str,
+    region: &str,
+    action: &str,
+    extra_params: &[(&str, &str)],
+) -> Result<serde_json::Value, AppError> {
+    call_api(
+        client,
+        "vpc.aliyuncs.com",
+        "2016-04-28",
+        access_key_id,
+        access_key_secret,
+        region,
+        action,
+        extra_params,
+    )
+    .await
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn call_api(
+    client: &reqwest::Client,
+    host: &str,
+    version: &str,
+    access_key_id: &str,
+    access_key_secret: &str,
+    region: &str,
+    action: &str,
+    extra_params: &[(&str, &str)],
 ) -> Result<serde_json::Value, AppError> {
     let timestamp = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
     let nonce = Uuid::new_v4().to_string();
 
     let mut params: Vec<(&str, String)> = vec![
         ("Format", "JSON".to_string()),
-        ("Version", "2014-05-26".to_string()),
+        ("Version", version.to_string()),
         ("AccessKeyId", access_key_id.to_string()),
         ("SignatureMethod", "HMAC-SHA1".to_string()),
         ("Timestamp", timestamp),
@@ -337,7 +548,8 @@ async fn call_ecs_api(
     let signature = base64_encode(&mac.finalize().into_bytes());
 
     let url = format!(
-        "https://ecs.aliyuncs.com/?{}&Signature={}",
+        "https://{}/?{}&Signature={}",
+        host,
         canonical_qs,
         percent_encode(&signature)
     );
@@ -270,6 +270,110 @@ pub async fn sync(state: &AppState, account: &CloudAccount) -> Result<CloudSyncR
     })
 }
 
+// ─── IP rotation ──────────────────────────────────────────
+
+/// Allocate a new Elastic IP in `region`. Returns (allocation ID, public IP).
+pub async fn allocate_ip(account: &CloudAccount, region: &str) -> Result<(String, String), AppError> {
+    let client = ec2_client(account, region).await?;
+    let resp = client
+        .allocate_address()
+        .domain(aws_sdk_ec2::types::DomainType::Vpc)
+        .send()
+        .await
+        .map_err(|e| AppError::BadGateway(format!("EC2 AllocateAddress error in {region}: {e}")))?;
+
+    match (resp.allocation_id(), resp.public_ip()) {
+        (Some(allocation_id), Some(ip)) => Ok((allocation_id.to_string(), ip.to_string())),
+        _ => Err(AppError::BadGateway("EC2 AllocateAddress returned no address".into())),
+    }
+}
+
+/// Bind an Elastic IP to the instance. This replaces the current public IP of its
+/// primary interface; a previous Elastic IP is disassociated by EC2.
+pub async fn associate_ip(
+    account: &CloudAccount,
+    region: &str,
+    instance_id: &str,
+    allocation_id: &str,
+) -> Result<(), AppError> {
+    let client = ec2_client(account, region).await?;
+    client
+        .associate_address()
+        .allocation_id(allocation_id)
+        .instance_id(instance_id)
+        .allow_reassociation(true)
+        .send()
+        .await
+        .map_err(|e| AppError::BadGateway(format!("EC2 AssociateAddress error: {e}")))?;
+    Ok(())
+}
+
+/// Release `ip` if it is an Elastic IP of this account. Returns false for an
+/// auto-assigned public IP, which is gone once the new address is bound.
+pub async fn release_ip(
+    account: &CloudAccount,
+    region: &str,
+    instance_id: &str,
+    ip: &str,
+) -> Result<bool, AppError> {
+    let client = ec2_client(account, region).await?;
+    let resp = client
+        .describe_addresses()
+        .filters(
+            aws_sdk_ec2::types::Filter::builder()
+                .name("public-ip")
+                .values(ip)
+                .build(),
+        )
+        .send()
+        .await
+        .map_err(|e| AppError::BadGateway(format!("EC2 DescribeAddresses error: {e}")))?;
+
+    let Some(address) = resp.addresses().first() else {
+        return Ok(false);
+    };
+    let allocation_id = address
+        .allocation_id()
+        .ok_or_else(|| AppError::BadGateway(format!("Elastic IP {ip} has no allocation ID")))?;
+
+    if let Some(association_id) = address.association_id() {
+        if address.instance_id() != Some(instance_id) {
+            return Err(AppError::BadRequest(format!(
+                "Elastic IP {ip} is now bound to another resource; not releasing it"
+            )));
+        }
+        client
+            .disassociate_address()
+            .association_id(association_id)
+            .send()
+            .await
+            .map_err(|e| AppError::BadGateway(format!("EC2 DisassociateAddress error: {e}")))?;
+    }
+
+    client
+        .release_address()
+        .allocation_id(allocation_id)
+        .send()
+        .await
+        .map_err(|e| AppError::BadGateway(format!("EC2 ReleaseAddress error: {e}")))?;
+    Ok(true)
+}
+
+async fn ec2_client(account: &CloudAccount, region: &str) -> Result<aws_sdk_ec2::Client, AppError> {
+    let access_key_id = account
+        .config
+        .get("access_key_id")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| AppError::Internal("Missing access_key_id in config".into()))?;
+    let secret_access_key = account
+        .config
+        .get("secret_access_key")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| AppError::Internal("Missing secret_access_key in config".into()))?;
+    let sdk_config = build_sdk_config(access_key_id, secret_access_key, region).await;
+    Ok(aws_sdk_ec2::Client::new(&sdk_config))
+}
+
 // ─── Helpers ──────────────────────────────────────────────
 
 fn parse_regions(config: &serde_json::Value) -> Vec<String> {
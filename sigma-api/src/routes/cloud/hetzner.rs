@@ -0,0 +1,672 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::errors::AppError;
+use crate::models::{CloudAccount, CloudSyncResult, IpEntry};
+use crate::routes::AppState;
+
+const DEFAULT_API_URL: &str = "https://api.hetzner.cloud/v1";
+const PER_PAGE: &str = "50";
+
+/// Validate Hetzner Cloud credentials by listing one server.
+/// Hetzner has no account endpoint; any read call checks the project token.
+pub async fn validate(
+    http_client: &reqwest::Client,
+    config: &serde_json::Value,
+) -> Result<(), AppError> {
+    let api_token = config
+        .get("api_token")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| AppError::BadRequest("Missing api_token in config".into()))?;
+
+    let resp = http_client
+        .get(format!("{}/servers", api_url(config)))
+        .bearer_auth(api_token)
+        .query(&[("per_page", "1")])
+        .timeout(std::time::Duration::from_secs(15))
+        .send()
+        .await
+        .map_err(|e| AppError::BadRequest(format!("Hetzner API request failed: {e}")))?;
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp.text().await.unwrap_or_default();
+        return Err(AppError::BadRequest(format!(
+            "Hetzner auth failed ({status}): {body}"
+        )));
+    }
+
+    Ok(())
+}
+
+/// Mask sensitive fields in Hetzner config.
+pub fn mask_config(config: &serde_json::Value) -> serde_json::Value {
+    let api_token = config
+        .get("api_token")
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+
+    let mut masked = serde_json::json!({
+        "api_token": mask_key(api_token),
+    });
+    if let Some(url) = config.get("api_url").and_then(|v| v.as_str()) {
+        masked["api_url"] = url.into();
+    }
+    masked
+}
+
+/// Full sync: fetch all servers of the Hetzner Cloud project.
+/// Floating IPs are added to the IP pool as floating addresses.
+pub async fn sync(state: &AppState, account: &CloudAccount) -> Result<CloudSyncResult, AppError> {
+    let api_token = account
+        .config
+        .get("api_token")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| AppError::Internal("Missing api_token in config".into()))?;
+
+    let provider_id = ensure_provider(state, "Hetzner").await?;
+
+    let inventory = fetch_inventory(&state.http_client, &api_url(&account.config), api_token).await?;
+
+    let mut created: i64 = 0;
+    let mut updated: i64 = 0;
+    let merged: i64 = 0;
+    let mut seen_instance_ids: Vec<String> = Vec::new();
+    let mut vps_ids: HashMap<String, Uuid> = HashMap::new();
+
+    for server in &inventory.servers {
+        seen_instance_ids.push(server.instance_id.clone());
+
+        let ip_json = serde_json::to_value(&server.ips).unwrap_or_default();
+
+        // Upsert
+        let existing_vps: Option<(Uuid, String, String)> = sqlx::query_as(
+            "SELECT id, source, hostname FROM vps WHERE extra->>'cloud_instance_id' = $1",
+        )
+        .bind(&server.instance_id)
+        .fetch_optional(&state.db)
+        .await?;
+
+        if let Some((vps_id, _, _)) = existing_vps {
+            sqlx::query(
+                r#"UPDATE vps SET
+                    hostname = $2,
+                    ip_addresses = $3,
+                    status = $4,
+                    country = $5,
+                    city = $6,
+                    provider_id = $7,
+                    cloud_account_id = $8,
+                    cpu_cores = COALESCE($9, cpu_cores),
+                    ram_mb = COALESCE($10, ram_mb),
+                    disk_gb = COALESCE($11, disk_gb),
+                    cost_monthly = COALESCE($12, cost_monthly),
+                    currency = $13,
+                    source = 'cloud-sync',
+                    extra = extra || $14::jsonb
+                WHERE id = $1"#,
+            )
+            .bind(vps_id)
+            .bind(&server.hostname)
+            .bind(&ip_json)
+            .bind(server.status)
+            .bind(&server.country)
+            .bind(&server.city)
+            .bind(provider_id)
+            .bind(account.id)
+            .bind(server.cpu_cores)
+            .bind(server.ram_mb)
+            .bind(server.disk_gb)
+            .bind(server.cost_monthly)
+            .bind(&inventory.currency)
+            .bind(&server.extra)
+            .execute(&state.db)
+            .await?;
+            vps_ids.insert(server.instance_id.clone(), vps_id);
+            updated += 1;
+        } else {
+            let (vps_id,): (Uuid,) = sqlx::query_as(
+                r#"INSERT INTO vps (
+                    hostname, alias, provider_id,
+                    ip_addresses, ssh_port,
+                    country, city, dc_name,
+                    cpu_cores, ram_mb, disk_gb, bandwidth_tb,
+                    cost_monthly, currency,
+                    status, purchase_date, expire_date,
+                    purpose, vpn_protocol, tags,
+                    monitoring_enabled, node_exporter_port,
+                    extra, notes,
+                    source, cloud_account_id
+                ) VALUES (
+                    $1, '', $2,
+                    $3, 22,
+                    $4, $5, $6,
+                    $7, $8, $9, NULL,
+                    $10, $11,
+                    $12, NULL, NULL,
+                    '', '', '{}',
+                    true, 9100,
+                    $13, '',
+                    'cloud-sync', $14
+                ) RETURNING id"#,
+            )
+            .bind(&server.hostname)
+            .bind(provider_id)
+            .bind(&ip_json)
+            .bind(&server.country)
+            .bind(&server.city)
+            .bind(&server.datacenter)
+            .bind(server.cpu_cores)
+            .bind(server.ram_mb)
+            .bind(server.disk_gb)
+            .bind(server.cost_monthly)
+            .bind(&inventory.currency)
+            .bind(server.status)
+            .bind(&server.extra)
+            .bind(account.id)
+            .fetch_one(&state.db)
+            .await?;
+            vps_ids.insert(server.instance_id.clone(), vps_id);
+            created += 1;
+        }
+    }
+
+    // Floating IPs: keep the pool entry pointing at the server holding the address
+    for fip in &inventory.floating_ips {
+        let vps_id = fip.server_id.as_ref().and_then(|id| vps_ids.get(id)).copied();
+        sqlx::query(
+            r#"INSERT INTO ip_pool (ip, provider_id, status, floating, vps_id, label, notes)
+               VALUES ($1, $2, CASE WHEN $3::uuid IS NULL THEN 'available' ELSE 'assigned' END,
+                       true, $3, 'overseas', $4)
+               ON CONFLICT (ip) DO UPDATE SET
+                   provider_id = EXCLUDED.provider_id,
+                   floating = true,
+                   vps_id = EXCLUDED.vps_id,
+                   status = CASE
+                       WHEN EXCLUDED.vps_id IS NOT NULL THEN 'assigned'
+                       WHEN ip_pool.status = 'reserved' THEN 'reserved'
+                       ELSE 'available'
+                   END"#,
+        )
+        .bind(&fip.ip)
+        .bind(provider_id)
+        .bind(vps_id)
+        .bind(format!("Hetzner floating IP {}", fip.id))
+        .execute(&state.db)
+        .await?;
+    }
+
+    // Retire stale VPS: those linked to this account but not seen in sync
+    let retired = if !seen_instance_ids.is_empty() {
+        let result = sqlx::query(
+            r#"UPDATE vps SET status = 'retired', monitoring_enabled = false
+               WHERE cloud_account_id = $1
+                 AND source = 'cloud-sync'
+                 AND extra->>'cloud_instance_id' IS NOT NULL
+                 AND extra->>'cloud_instance_id' != ALL($2)
+                 AND status NOT IN ('retired', 'deleted')"#,
+        )
+        .bind(account.id)
+        .bind(&seen_instance_ids)
+        .execute(&state.db)
+        .await?;
+        result.rows_affected() as i64
+    } else {
+        0
+    };
+
+    Ok(CloudSyncResult {
+        instances_found: inventory.servers.len() as i64,
+        created,
+        updated,
+        retired,
+        merged,
+    })
+}
+
+// ─── API ──────────────────────────────────────────────────
+
+/// Servers and floating IPs of a project, mapped to VPS fields.
+#[derive(Debug)]
+struct Inventory {
+    servers: Vec<Server>,
+    floating_ips: Vec<FloatingIp>,
+    /// Currency of the pricing API (EUR)
+    currency: String,
+}
+
+#[derive(Debug)]
+struct Server {
+    instance_id: String,
+    hostname: String,
+    ips: Vec<IpEntry>,
+    status: &'static str,
+    cpu_cores: Option<i16>,
+    ram_mb: Option<i32>,
+    disk_gb: Option<i32>,
+    country: String,
+    city: String,
+    datacenter: String,
+    cost_monthly: Option<Decimal>,
+    extra: serde_json::Value,
+}
+
+#[derive(Debug)]
+struct FloatingIp {
+    id: String,
+    ip: String,
+    server_id: Option<String>,
+}
+
+async fn fetch_inventory(
+    client: &reqwest::Client,
+    base: &str,
+    api_token: &str,
+) -> Result<Inventory, AppError> {
+    let pricing = get_json(client, base, api_token, "pricing", &[]).await?;
+    let currency = pricing["pricing"]["currency"].as_str().unwrap_or("EUR").to_string();
+    // (server type, location) → net monthly price
+    let mut prices: HashMap<(String, String), Decimal> = HashMap::new();
+    for server_type in pricing["pricing"]["server_types"].as_array().into_iter().flatten() {
+        let name = server_type["name"].as_str().unwrap_or("");
+        for price in server_type["prices"].as_array().into_iter().flatten() {
+            let location = price["location"].as_str().unwrap_or("");
+            if let Some(net) = parse_price(&price["price_monthly"]["net"]) {
+                prices.insert((name.to_string(), location.to_string()), net);
+            }
+        }
+    }
+
+    let floating_ips: Vec<FloatingIp> = get_paginated(client, base, api_token, "floating_ips", "floating_ips")
+        .await?
+        .iter()
+        .filter_map(|f| {
+            Some(FloatingIp {
+                id: f["id"].as_u64()?.to_string(),
+                ip: host_ip(f["ip"].as_str()?),
+                server_id: f["server"].as_u64().map(|id| id.to_string()),
+            })
+        })
+        .collect();
+
+    let servers = get_paginated(client, base, api_token, "servers", "servers")
+        .await?
+        .iter()
+        .filter_map(|s| map_server(s, &floating_ips, &prices))
+        .collect();
+
+    Ok(Inventory {
+        servers,
+        floating_ips,
+        currency,
+    })
+}
+
+fn map_server(
+    server: &serde_json::Value,
+    floating_ips: &[FloatingIp],
+    prices: &HashMap<(String, String), Decimal>,
+) -> Option<Server> {
+    let instance_id = server["id"].as_u64().filter(|id| *id != 0)?.to_string();
+    let hostname = server["name"].as_str().unwrap_or(&instance_id).to_string();
+
+    // IPs: primary IPv4 / IPv6, floating IPs routed to the server, private networks
+    let mut ips: Vec<IpEntry> = Vec::new();
+    let mut push = |ip: Option<String>, label: &str| {
+        if let Some(ip) = ip.filter(|ip| !ip.is_empty()) {
+            if !ips.iter().any(|e| e.ip == ip) {
+                ips.push(IpEntry {
+                    ip,
+                    label: label.to_string(),
+                });
+            }
+        }
+    };
+    let public_net = &server["public_net"];
+    push(public_net["ipv4"]["ip"].as_str().map(String::from), "overseas");
+    push(public_net["ipv6"]["ip"].as_str().map(host_ip), "overseas");
+    for fip in floating_ips.iter().filter(|f| f.server_id.as_deref() == Some(instance_id.as_str())) {
+        push(Some(fip.ip.clone()), "overseas");
+    }
+    for net in server["private_net"].as_array().into_iter().flatten() {
+        push(net["ip"].as_str().map(String::from), "internal");
+    }
+
+    // Status mapping
+    let status = match server["status"].as_str().unwrap_or("") {
+        "running" => "active",
+        "initializing" | "starting" => "provisioning",
+        "off" | "stopping" => "suspended",
+        "deleting" => "retiring",
+        _ => "active",
+    };
+
+    let server_type = &server["server_type"];
+    let type_name = server_type["name"].as_str().unwrap_or("");
+    let cpu_cores = server_type["cores"].as_i64().map(|v| v as i16);
+    // Memory is in GB, possibly fractional
+    let ram_mb = server_type["memory"].as_f64().map(|gb| (gb * 1024.0).round() as i32);
+    let disk_gb = server_type["disk"].as_i64().map(|v| v as i32);
+
+    let datacenter = server["datacenter"]["name"].as_str().unwrap_or("").to_string();
+    let location = &server["datacenter"]["location"];
+    let location_name = location["name"].as_str().unwrap_or("");
+    let (fallback_country, fallback_city) = location_to_country_city(location_name);
+    let country = location["country"].as_str().unwrap_or(fallback_country).to_string();
+    let city = location["city"].as_str().unwrap_or(fallback_city).to_string();
+
+    let cost_monthly = prices
+        .get(&(type_name.to_string(), location_name.to_string()))
+        .copied()
+        .or_else(|| {
+            // Older API responses carry prices on the server type only
+            server_type["prices"]
+                .as_array()?
+                .iter()
+                .find(|p| p["location"].as_str() == Some(location_name))
+                .and_then(|p| parse_price(&p["price_monthly"]["net"]))
+        });
+
+    let extra = serde_json::json!({
+        "cloud_instance_id": instance_id,
+        "cloud_provider": "hetzner",
+        "cloud_region": location_name,
+        "server_type": type_name,
+    });
+
+    Some(Server {
+        instance_id,
+        hostname,
+        ips,
+        status,
+        cpu_cores,
+        ram_mb,
+        disk_gb,
+        country,
+        city,
+        datacenter,
+        cost_monthly,
+        extra,
+    })
+}
+
+/// GET a Hetzner list endpoint, following `meta.pagination.next_page`, and
+/// collect the array under `key`.
+async fn get_paginated(
+    client: &reqwest::Client,
+    base: &str,
+    api_token: &str,
+    path: &str,
+    key: &str,
+) -> Result<Vec<serde_json::Value>, AppError> {
+    let mut items = Vec::new();
+    let mut page = 1u64;
+    loop {
+        let query = [("page", page.to_string()), ("per_page", PER_PAGE.to_string())];
+        let body = get_json(client, base, api_token, path, &query).await?;
+        items.extend(body[key].as_array().cloned().unwrap_or_default());
+
+        match body["meta"]["pagination"]["next_page"].as_u64() {
+            Some(next) if next > page => page = next,
+            _ => break,
+        }
+    }
+    Ok(items)
+}
+
+async fn get_json(
+    client: &reqwest::Client,
+    base: &str,
+    api_token: &str,
+    path: &str,
+    query: &[(&str, String)],
+) -> Result<serde_json::Value, AppError> {
+    let resp = client
+        .get(format!("{base}/{path}"))
+        .bearer_auth(api_token)
+        .query(query)
+        .timeout(std::time::Duration::from_secs(30))
+        .send()
+        .await
+        .map_err(|e| AppError::Internal(format!("Hetzner API error: {e}")))?;
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp.text().await.unwrap_or_default();
+        return Err(AppError::Internal(format!(
+            "Hetzner GET /{path} failed ({status}): {body}"
+        )));
+    }
+
+    resp.json()
+        .await
+        .map_err(|e| AppError::Internal(format!("Hetzner response parse error: {e}")))
+}
+
+// ─── Helpers ──────────────────────────────────────────────
+
+/// API base URL; `api_url` in the config overrides it (e.g. for a proxy).
+fn api_url(config: &serde_json::Value) -> String {
+    config
+        .get("api_url")
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .unwrap_or(DEFAULT_API_URL)
+        .trim_end_matches('/')
+        .to_string()
+}
+
+/// Hetzner reports IPv6 as the routed /64 (`2a01:4f8:1:2::/64`); the
+/// server answers on `::1` of it.
+fn host_ip(ip: &str) -> String {
+    match ip.split_once('/') {
+        Some((net, _)) if net.ends_with("::") => format!("{net}1"),
+        Some((net, _)) => net.to_string(),
+        None => ip.to_string(),
+    }
+}
+
+/// Prices are decimal strings with ten fractional digits.
+fn parse_price(v: &serde_json::Value) -> Option<Decimal> {
+    let price = match v {
+        serde_json::Value::String(s) => Decimal::from_str(s).ok()?,
+        v => Decimal::from_f64_retain(v.as_f64()?)?,
+    };
+    Some(price.round_dp(2))
+}
+
+fn mask_key(key: &str) -> String {
+    if key.len() <= 8 {
+        "****".into()
+    } else {
+        format!("{}...{}", &key[..4], &key[key.len() - 4..])
+    }
+}
+
+/// Ensure a Provider record for Hetzner exists, return its ID.
+async fn ensure_provider(state: &AppState, name: &str) -> Result<Uuid, AppError> {
+    let existing: Option<(Uuid,)> =
+        sqlx::query_as("SELECT id FROM providers WHERE LOWER(name) = LOWER($1) LIMIT 1")
+            .bind(name)
+            .fetch_optional(&state.db)
+            .await?;
+
+    if let Some((id,)) = existing {
+        Ok(id)
+    } else {
+        let (id,): (Uuid,) = sqlx::query_as(
+            "INSERT INTO providers (name, country, website, panel_url, api_supported, notes) VALUES ($1, 'DE', 'https://www.hetzner.com', 'https://console.hetzner.cloud', true, 'Auto-created by cloud sync') RETURNING id",
+        )
+        .bind(name)
+        .fetch_one(&state.db)
+        .await?;
+        Ok(id)
+    }
+}
+
+/// Map Hetzner location name to (ISO country code, city), for responses
+/// without the location's `country` / `city`.
+fn location_to_country_city(location: &str) -> (&'static str, &'static str) {
+    match location {
+        "fsn1" => ("DE", "Falkenstein"),
+        "nbg1" => ("DE", "Nuremberg"),
+        "hel1" => ("FI", "Helsinki"),
+        "ash" => ("US", "Ashburn, VA"),
+        "hil" => ("US", "Hillsboro, OR"),
+        "sin" => ("SG", "Singapore"),
+        _ => ("", ""),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use axum::{
+        extract::Query,
+        http::{HeaderMap, StatusCode},
+        routing::get,
+        Json, Router,
+    };
+
+    const TOKEN: &str = "test-token";
+
+    fn authorized(headers: &HeaderMap) -> bool {
+        headers.get("authorization").and_then(|v| v.to_str().ok()) == Some("Bearer test-token")
+    }
+
+    /// Serve a canned Hetzner API on a local port; returns its base URL.
+    async fn mock_api() -> String {
+        let app = Router::new()
+            .route(
+                "/servers",
+                get(|headers: HeaderMap, Query(q): Query<HashMap<String, String>>| async move {
+                    if !authorized(&headers) {
+                        return Err(StatusCode::UNAUTHORIZED);
+                    }
+                    // Two pages of one server each
+                    let body = match q.get("page").map(String::as_str) {
+                        Some("2") => serde_json::json!({
+                            "servers": [{
+                                "id": 43,
+                                "name": "hel-db",
+                                "status": "off",
+                                "public_net": {"ipv4": {"ip": "198.51.100.20"}, "ipv6": null},
+                                "private_net": [{"ip": "10.0.0.3"}],
+                                "server_type": {"name": "cx22", "cores": 2, "memory": 4.0, "disk": 40},
+                                "datacenter": {"name": "hel1-dc2", "location": {"name": "hel1"}},
+                            }],
+                            "meta": {"pagination": {"page": 2, "next_page": null}},
+                        }),
+                        _ => serde_json::json!({
+                            "servers": [{
+                                "id": 42,
+                                "name": "fsn-edge",
+                                "status": "running",
+                                "public_net": {
+                                    "ipv4": {"ip": "203.0.113.10"},
+                                    "ipv6": {"ip": "2001:db8:1::/64"},
+                                },
+                                "private_net": [],
+                                "server_type": {"name": "cpx31", "cores": 4, "memory": 8.0, "disk": 160},
+                                "datacenter": {
+                                    "name": "fsn1-dc14",
+                                    "location": {"name": "fsn1", "country": "DE", "city": "Falkenstein"},
+                                },
+                            }],
+                            "meta": {"pagination": {"page": 1, "next_page": 2}},
+                        }),
+                    };
+                    Ok(Json(body))
+                }),
+            )
+            .route(
+                "/floating_ips",
+                get(|| async {
+                    Json(serde_json::json!({
+                        "floating_ips": [
+                            {"id": 7, "ip": "192.0.2.5", "server": 42},
+                            {"id": 8, "ip": "192.0.2.6", "server": null},
+                        ],
+                        "meta": {"pagination": {"page": 1, "next_page": null}},
+                    }))
+                }),
+            )
+            .route(
+                "/pricing",
+                get(|| async {
+                    Json(serde_json::json!({
+                        "pricing": {
+                            "currency": "EUR",
+                            "server_types": [
+                                {"name": "cpx31", "prices": [
+                                    {"location": "fsn1", "price_monthly": {"net": "13.1000000000", "gross": "15.5890000000"}},
+                                ]},
+                                {"name": "cx22", "prices": [
+                                    {"location": "hel1", "price_monthly": {"net": "3.7900000000", "gross": "4.5101000000"}},
+                                ]},
+                            ],
+                        },
+                    }))
+                }),
+            );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn test_validate_against_mock() {
+        let base = mock_api().await;
+        let client = reqwest::Client::new();
+
+        let ok = serde_json::json!({"api_token": TOKEN, "api_url": base});
+        assert!(validate(&client, &ok).await.is_ok());
+
+        let bad = serde_json::json!({"api_token": "wrong", "api_url": base});
+        assert!(matches!(validate(&client, &bad).await, Err(AppError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_inventory_against_mock() {
+        let base = mock_api().await;
+        let inventory = fetch_inventory(&reqwest::Client::new(), &base, TOKEN).await.unwrap();
+
+        assert_eq!(inventory.currency, "EUR");
+        assert_eq!(inventory.servers.len(), 2);
+        assert_eq!(inventory.floating_ips.len(), 2);
+
+        let edge = &inventory.servers[0];
+        assert_eq!(edge.instance_id, "42");
+        assert_eq!(edge.status, "active");
+        let ips: Vec<(&str, &str)> = edge.ips.iter().map(|e| (e.ip.as_str(), e.label.as_str())).collect();
+        assert_eq!(
+            ips,
+            [
+                ("203.0.113.10", "overseas"),
+                ("2001:db8:1::1", "overseas"),
+                ("192.0.2.5", "overseas"),
+            ]
+        );
+        assert_eq!((edge.cpu_cores, edge.ram_mb, edge.disk_gb), (Some(4), Some(8192), Some(160)));
+        assert_eq!((edge.country.as_str(), edge.city.as_str()), ("DE", "Falkenstein"));
+        assert_eq!(edge.cost_monthly, Some(Decimal::new(1310, 2)));
+
+        let db = &inventory.servers[1];
+        assert_eq!(db.status, "suspended");
+        assert_eq!((db.country.as_str(), db.city.as_str()), ("FI", "Helsinki"));
+        assert_eq!(db.ips.last().map(|e| e.label.as_str()), Some("internal"));
+        assert_eq!(db.cost_monthly, Some(Decimal::new(379, 2)));
+    }
+
+    #[test]
+    fn test_host_ip() {
+        assert_eq!(host_ip("2a01:4f8:c17:b8f::/64"), "2a01:4f8:c17:b8f::1");
+        assert_eq!(host_ip("192.0.2.5"), "192.0.2.5");
+    }
+}
@@ -1,8 +1,14 @@
 pub mod alibaba;
 pub mod aws;
 pub mod digitalocean;
+pub mod hetzner;
 pub mod linode;
+pub mod panel;
+pub mod proxmox;
+pub mod rotate;
+pub mod tencent;
 pub mod volcengine;
+pub mod vultr;
 
 use std::collections::HashMap;
 
@@ -23,7 +29,7 @@ use crate::models::{
 use crate::routes::audit_logs::log_audit;
 use crate::routes::AppState;
 
-const VALID_PROVIDER_TYPES: &[&str] = &["aws", "alibaba", "digitalocean", "linode", "volcengine"];
+const VALID_PROVIDER_TYPES: &[&str] = &["aws", "alibaba", "digitalocean", "hetzner", "linode", "proxmox", "solusvm", "tencent", "virtualizor", "volcengine", "vultr"];
 
 /// Union-merge two IP lists: existing labels preserved for overlapping IPs,
 /// all IPs from both sides kept.
@@ -75,6 +81,8 @@ pub fn router() -> Router<AppState> {
             "/api/cloud-accounts/{id}/sync",
             axum::routing::post(sync_account),
         )
+        .merge(rotate::router())
+        .merge(panel::router())
 }
 
 // ─── Provider dispatch helpers ───────────────────────────
@@ -88,8 +96,13 @@ async fn validate_credentials(
         "aws" => aws::validate(config).await,
         "alibaba" => alibaba::validate(config).await,
         "digitalocean" => digitalocean::validate(&state.http_client, config).await,
+        "hetzner" => hetzner::validate(&state.http_client, config).await,
         "linode" => linode::validate(&state.http_client, config).await,
+        "proxmox" => proxmox::validate(&state.http_client, config).await,
+        "solusvm" | "virtualizor" => panel::validate(&state.http_client, provider_type, config).await,
+        "tencent" => tencent::validate(config).await,
         "volcengine" => volcengine::validate(config).await,
+        "vultr" => vultr::validate(&state.http_client, config).await,
         _ => Err(AppError::BadRequest(format!(
             "Unknown provider type: {provider_type}"
         ))),
@@ -101,8 +114,13 @@ fn mask_config(provider_type: &str, config: &serde_json::Value) -> serde_json::V
         "aws" => aws::mask_config(config),
         "alibaba" => alibaba::mask_config(config),
         "digitalocean" => digitalocean::mask_config(config),
+        "hetzner" => hetzner::mask_config(config),
         "linode" => linode::mask_config(config),
+        "proxmox" => proxmox::mask_config(config),
+        "solusvm" | "virtualizor" => panel::mask_config(config),
+        "tencent" => tencent::mask_config(config),
         "volcengine" => volcengine::mask_config(config),
+        "vultr" => vultr::mask_config(config),
         _ => serde_json::json!({}),
     }
 }
@@ -115,8 +133,13 @@ async fn sync_provider(
         "aws" => aws::sync(state, account).await,
         "alibaba" => alibaba::sync(state, account).await,
         "digitalocean" => digitalocean::sync(state, account).await,
+        "hetzner" => hetzner::sync(state, account).await,
         "linode" => linode::sync(state, account).await,
+        "proxmox" => proxmox::sync(state, account).await,
+        "solusvm" | "virtualizor" => panel::sync(state, account).await,
+        "tencent" => tencent::sync(state, account).await,
         "volcengine" => volcengine::sync(state, account).await,
+        "vultr" => vultr::sync(state, account).await,
         _ => Err(AppError::BadRequest(format!(
             "Unknown provider type: {}",
             account.provider_type
@@ -124,6 +147,54 @@ async fn sync_provider(
     }
 }
 
+/// Sync an account and record the outcome on it: `last_sync_attempt_at`, and
+/// `last_synced_at` + `last_sync_result` on success or `last_sync_error` on
+/// failure. Shared by the sync endpoint and the background worker.
+pub async fn run_sync(
+    state: &AppState,
+    account: &CloudAccount,
+) -> Result<CloudSyncResult, AppError> {
+    let outcome = sync_provider(state, account).await;
+
+    match &outcome {
+        Ok(result) => {
+            sqlx::query(
+                r#"UPDATE cloud_accounts
+                   SET last_synced_at = now(), last_sync_attempt_at = now(),
+                       last_sync_error = NULL, last_sync_result = $2
+                   WHERE id = $1"#,
+            )
+            .bind(account.id)
+            .bind(sqlx::types::Json(result))
+            .execute(&state.db)
+            .await?;
+        }
+        Err(e) => {
+            sqlx::query(
+                "UPDATE cloud_accounts SET last_sync_attempt_at = now(), last_sync_error = $2 WHERE id = $1",
+            )
+            .bind(account.id)
+            .bind(e.to_string())
+            .execute(&state.db)
+            .await?;
+        }
+    }
+
+    outcome
+}
+
+/// Minimum per-account background sync interval.
+const MIN_SYNC_INTERVAL_SECS: i32 = 60;
+
+fn validate_sync_interval(secs: Option<i32>) -> Result<(), AppError> {
+    match secs {
+        Some(s) if s < MIN_SYNC_INTERVAL_SECS => Err(AppError::BadRequest(format!(
+            "sync_interval_secs must be at least {MIN_SYNC_INTERVAL_SECS}"
+        ))),
+        _ => Ok(()),
+    }
+}
+
 /// Build a CloudAccountResponse from a CloudAccount by querying VPS count.
 async fn build_account_response(
     state: &AppState,
@@ -144,6 +215,11 @@ async fn build_account_response(
         last_synced_at: acc.last_synced_at,
         created_at: acc.created_at,
         updated_at: acc.updated_at,
+        sync_enabled: acc.sync_enabled,
+        sync_interval_secs: acc.sync_interval_secs,
+        last_sync_attempt_at: acc.last_sync_attempt_at,
+        last_sync_error: acc.last_sync_error.clone(),
+        last_sync_result: acc.last_sync_result.as_ref().map(|r| r.0.clone()),
     })
 }
 
@@ -239,14 +315,18 @@ pub async fn create_account(
         )));
     }
 
+    validate_sync_interval(input.sync_interval_secs)?;
     validate_credentials(&state, &input.provider_type, &input.config).await?;
 
     let acc = sqlx::query_as::<_, CloudAccount>(
-        "INSERT INTO cloud_accounts (name, provider_type, config) VALUES ($1, $2, $3) RETURNING *",
+        r#"INSERT INTO cloud_accounts (name, provider_type, config, sync_enabled, sync_interval_secs)
+           VALUES ($1, $2, $3, $4, $5) RETURNING *"#,
     )
     .bind(&input.name)
     .bind(&input.provider_type)
     .bind(&input.config)
+    .bind(input.sync_enabled)
+    .bind(input.sync_interval_secs)
     .fetch_one(&state.db)
     .await?;
 
@@ -269,6 +349,11 @@ pub async fn create_account(
         last_synced_at: None,
         created_at: acc.created_at,
         updated_at: acc.updated_at,
+        sync_enabled: acc.sync_enabled,
+        sync_interval_secs: acc.sync_interval_secs,
+        last_sync_attempt_at: None,
+        last_sync_error: None,
+        last_sync_result: None,
     }))
 }
 
@@ -301,6 +386,14 @@ pub async fn update_account(
 
     let new_name = input.name.unwrap_or(existing.name.clone());
     let new_config = input.config.unwrap_or(existing.config.clone());
+    let new_sync_enabled = input.sync_enabled.unwrap_or(existing.sync_enabled);
+    // 0 clears the override
+    let new_sync_interval = match input.sync_interval_secs {
+        Some(0) => None,
+        Some(secs) => Some(secs),
+        None => existing.sync_interval_secs,
+    };
+    validate_sync_interval(new_sync_interval)?;
 
     // Re-validate if config changed
     if new_config != existing.config {
@@ -308,11 +401,15 @@ pub async fn update_account(
     }
 
     let acc = sqlx::query_as::<_, CloudAccount>(
-        "UPDATE cloud_accounts SET name = $2, config = $3, updated_at = now() WHERE id = $1 RETURNING *",
+        r#"UPDATE cloud_accounts
+           SET name = $2, config = $3, sync_enabled = $4, sync_interval_secs = $5, updated_at = now()
+           WHERE id = $1 RETURNING *"#,
     )
     .bind(id)
     .bind(&new_name)
     .bind(&new_config)
+    .bind(new_sync_enabled)
+    .bind(new_sync_interval)
     .fetch_one(&state.db)
     .await?;
 
@@ -322,7 +419,11 @@ pub async fn update_account(
         "update",
         "cloud_account",
         Some(&id.to_string()),
-        serde_json::json!({"name": acc.name}),
+        serde_json::json!({
+            "name": acc.name,
+            "sync_enabled": acc.sync_enabled,
+            "sync_interval_secs": acc.sync_interval_secs,
+        }),
     )
     .await;
 
@@ -393,13 +494,7 @@ pub async fn sync_account(
     .await?
     .ok_or(AppError::NotFound)?;
 
-    let result = sync_provider(&state, &acc).await?;
-
-    // Update last_synced_at
-    sqlx::query("UPDATE cloud_accounts SET last_synced_at = now() WHERE id = $1")
-        .bind(id)
-        .execute(&state.db)
-        .await?;
+    let result = run_sync(&state, &acc).await?;
 
     log_audit(
         &state.db,
@@ -0,0 +1,629 @@
+//! SolusVM / Virtualizor client-panel accounts.
+//!
+//! Small hosts expose only the end-user panel API. A panel account holds the
+//! panel URL and one or more client credentials (`key` + `hash`; for
+//! Virtualizor the API key and API pass). A SolusVM credential covers one VPS,
+//! a Virtualizor credential every VPS of that panel user.
+//!
+//! Sync does not create VPS: each panel VPS is matched to an existing `vps`
+//! row by IP, which then gets the panel's hostname, status, IPs and bandwidth
+//! and is linked to the account so reboot / boot / shutdown can go through the
+//! panel. Which credential serves a VPS is kept as its index in
+//! `extra.panel_credential`; re-sync after reordering credentials.
+
+use axum::{
+    extract::{Path, State},
+    routing::post,
+    Extension, Json, Router,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::auth::{require_role, CurrentUser};
+use crate::errors::{AppError, ErrorResponse};
+use crate::models::{CloudAccount, CloudSyncResult, IpEntry, PanelActionRequest, PanelActionResult, Vps};
+use crate::routes::audit_logs::log_audit;
+use crate::routes::cloud::merge_ip_labels_union;
+use crate::routes::AppState;
+use crate::vps_lifecycle::{set_change_context, SOURCE_PANEL_SYNC};
+
+/// Provider types handled by this module.
+pub const PANEL_PROVIDERS: &[&str] = &["solusvm", "virtualizor"];
+
+/// Power actions accepted by `POST /api/vps/{id}/panel-action`.
+const ACTIONS: &[&str] = &["reboot", "boot", "shutdown"];
+
+const BYTES_PER_TB: i64 = 1_000_000_000_000;
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/api/vps/{id}/panel-action", post(panel_action))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Panel {
+    SolusVm,
+    Virtualizor,
+}
+
+impl Panel {
+    fn from_provider_type(provider_type: &str) -> Result<Self, AppError> {
+        match provider_type {
+            "solusvm" => Ok(Panel::SolusVm),
+            "virtualizor" => Ok(Panel::Virtualizor),
+            other => Err(AppError::BadRequest(format!("{other} is not a panel provider"))),
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Panel::SolusVm => "SolusVM",
+            Panel::Virtualizor => "Virtualizor",
+        }
+    }
+}
+
+/// Client credential from the account config.
+#[derive(Debug, Clone)]
+struct Credential {
+    key: String,
+    hash: String,
+}
+
+/// A VPS as reported by the panel.
+#[derive(Debug)]
+struct PanelVps {
+    /// Virtualizor VPS id; SolusVM credentials address a single VPS
+    panel_vps_id: Option<String>,
+    hostname: String,
+    /// online, offline or suspended
+    state: String,
+    ips: Vec<String>,
+    bandwidth_total_bytes: Option<i64>,
+    bandwidth_used_bytes: Option<i64>,
+}
+
+/// Validate a panel account by querying every credential once.
+pub async fn validate(
+    http_client: &reqwest::Client,
+    provider_type: &str,
+    config: &serde_json::Value,
+) -> Result<(), AppError> {
+    let panel = Panel::from_provider_type(provider_type)?;
+    let panel_url = panel_url(config)
+        .ok_or_else(|| AppError::BadRequest("Missing panel_url in config".into()))?;
+    let credentials = credentials(config);
+    if credentials.is_empty() {
+        return Err(AppError::BadRequest(
+            "config needs at least one credential in credentials: [{key, hash}]".into(),
+        ));
+    }
+
+    for (idx, cred) in credentials.iter().enumerate() {
+        fetch(http_client, panel, &panel_url, cred)
+            .await
+            .map_err(|e| AppError::BadRequest(format!("credential #{idx}: {e}")))?;
+    }
+    Ok(())
+}
+
+/// Mask sensitive fields in panel config.
+pub fn mask_config(config: &serde_json::Value) -> serde_json::Value {
+    let credentials: Vec<serde_json::Value> = credentials(config)
+        .iter()
+        .map(|c| serde_json::json!({ "key": mask_key(&c.key), "hash": "****" }))
+        .collect();
+
+    serde_json::json!({
+        "panel_url": panel_url(config).unwrap_or_default(),
+        "credentials": credentials,
+    })
+}
+
+/// Pull every panel VPS and update the `vps` row holding one of its IPs.
+/// `created` stays 0; `instances_found - updated` panel VPS matched no row.
+pub async fn sync(state: &AppState, account: &CloudAccount) -> Result<CloudSyncResult, AppError> {
+    let panel = Panel::from_provider_type(&account.provider_type)?;
+    let panel_url = panel_url(&account.config)
+        .ok_or_else(|| AppError::Internal("Missing panel_url in config".into()))?;
+
+    let mut instances_found: i64 = 0;
+    let mut updated: i64 = 0;
+
+    for (idx, cred) in credentials(&account.config).iter().enumerate() {
+        let servers = fetch(&state.http_client, panel, &panel_url, cred)
+            .await
+            .map_err(AppError::Internal)?;
+
+        for server in servers {
+            instances_found += 1;
+
+            let matched = sqlx::query_as::<_, Vps>(
+                r#"SELECT * FROM vps v
+                   WHERE v.status NOT IN ('retired', 'deleted')
+                     AND EXISTS (
+                         SELECT 1 FROM jsonb_array_elements(v.ip_addresses) e
+                         WHERE btrim(e->>'ip') = ANY($1)
+                     )
+                   ORDER BY v.created_at"#,
+            )
+            .bind(&server.ips)
+            .fetch_all(&state.db)
+            .await?;
+
+            let vps = match matched.as_slice() {
+                [vps] => vps,
+                [] => {
+                    tracing::info!(
+                        "{} VPS {} ({}) matches no VPS by IP",
+                        panel.label(),
+                        server.hostname,
+                        server.ips.join(", ")
+                    );
+                    continue;
+                }
+                _ => {
+                    tracing::warn!(
+                        "{} VPS {} matches {} VPS by IP; skipped",
+                        panel.label(),
+                        server.hostname,
+                        matched.len()
+                    );
+                    continue;
+                }
+            };
+
+            let new_ips: Vec<IpEntry> = server
+                .ips
+                .iter()
+                .map(|ip| IpEntry {
+                    ip: ip.clone(),
+                    label: "overseas".to_string(),
+                })
+                .collect();
+            let ips = merge_ip_labels_union(&new_ips, &vps.ip_addresses.0);
+
+            let status = match server.state.as_str() {
+                "online" => "active",
+                "offline" | "suspended" => "suspended",
+                _ => vps.status.as_str(),
+            };
+            let bandwidth_tb = server
+                .bandwidth_total_bytes
+                .map(|b| (Decimal::from(b) / Decimal::from(BYTES_PER_TB)).round_dp(2));
+
+            let extra = serde_json::json!({
+                "cloud_provider": account.provider_type,
+                "panel_credential": idx,
+                "panel_vps_id": server.panel_vps_id,
+                "panel_state": server.state,
+                "bandwidth_used_bytes": server.bandwidth_used_bytes,
+                "bandwidth_total_bytes": server.bandwidth_total_bytes,
+            });
+
+            let mut tx = state.db.begin().await?;
+            set_change_context(&mut tx, SOURCE_PANEL_SYNC, &account.name).await?;
+            sqlx::query(
+                r#"UPDATE vps SET
+                    hostname = COALESCE(NULLIF($2, ''), hostname),
+                    ip_addresses = $3,
+                    status = $4,
+                    bandwidth_tb = COALESCE($5, bandwidth_tb),
+                    cloud_account_id = $6,
+                    extra = extra || $7::jsonb
+                WHERE id = $1"#,
+            )
+            .bind(vps.id)
+            .bind(&server.hostname)
+            .bind(sqlx::types::Json(&ips))
+            .bind(status)
+            .bind(bandwidth_tb)
+            .bind(account.id)
+            .bind(&extra)
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await?;
+            updated += 1;
+        }
+    }
+
+    Ok(CloudSyncResult {
+        instances_found,
+        created: 0,
+        updated,
+        retired: 0,
+        merged: 0,
+    })
+}
+
+// ─── Power actions ────────────────────────────────────────
+
+#[utoipa::path(
+    post, path = "/api/vps/{id}/panel-action",
+    tag = "VPS",
+    params(("id" = Uuid, Path, description = "VPS ID")),
+    request_body = PanelActionRequest,
+    responses(
+        (status = 200, body = PanelActionResult),
+        (status = 400, body = ErrorResponse, description = "Unknown action or VPS not linked to a panel account"),
+        (status = 404, body = ErrorResponse),
+        (status = 502, body = ErrorResponse, description = "The panel rejected the action"),
+    )
+)]
+pub async fn panel_action(
+    State(state): State<AppState>,
+    Extension(user): Extension<CurrentUser>,
+    Path(id): Path<Uuid>,
+    Json(input): Json<PanelActionRequest>,
+) -> Result<Json<PanelActionResult>, AppError> {
+    require_role(&user, &["admin", "operator"])?;
+
+    if !ACTIONS.contains(&input.action.as_str()) {
+        return Err(AppError::BadRequest(format!(
+            "Invalid action: {}. Must be one of: {}",
+            input.action,
+            ACTIONS.join(", ")
+        )));
+    }
+
+    let vps = sqlx::query_as::<_, Vps>("SELECT * FROM vps WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    let account_id = vps
+        .cloud_account_id
+        .ok_or_else(|| AppError::BadRequest("VPS is not linked to a panel account".into()))?;
+    let account = sqlx::query_as::<_, CloudAccount>("SELECT * FROM cloud_accounts WHERE id = $1")
+        .bind(account_id)
+        .fetch_one(&state.db)
+        .await?;
+    if !PANEL_PROVIDERS.contains(&account.provider_type.as_str()) {
+        return Err(AppError::BadRequest(format!(
+            "VPS belongs to a {} account; power actions need a panel account ({})",
+            account.provider_type,
+            PANEL_PROVIDERS.join(", ")
+        )));
+    }
+    let panel = Panel::from_provider_type(&account.provider_type)?;
+    let panel_url = panel_url(&account.config)
+        .ok_or_else(|| AppError::Internal("Missing panel_url in config".into()))?;
+
+    let cred = vps
+        .extra
+        .get("panel_credential")
+        .and_then(|v| v.as_u64())
+        .and_then(|idx| credentials(&account.config).into_iter().nth(idx as usize))
+        .ok_or_else(|| AppError::BadRequest("VPS has no panel credential; sync the panel account first".into()))?;
+    let panel_vps_id = vps.extra.get("panel_vps_id").and_then(|v| v.as_str());
+
+    let message = run_action(&state.http_client, panel, &panel_url, &cred, panel_vps_id, &input.action)
+        .await
+        .map_err(AppError::BadGateway)?;
+
+    log_audit(
+        &state.db,
+        &user,
+        &format!("panel_{}", input.action),
+        "vps",
+        Some(&id.to_string()),
+        serde_json::json!({
+            "hostname": vps.hostname,
+            "cloud_account_id": account.id,
+            "message": message,
+        }),
+    )
+    .await;
+
+    Ok(Json(PanelActionResult {
+        vps_id: id,
+        action: input.action,
+        message,
+    }))
+}
+
+// ─── Panel clients ────────────────────────────────────────
+
+async fn fetch(
+    client: &reqwest::Client,
+    panel: Panel,
+    panel_url: &str,
+    cred: &Credential,
+) -> Result<Vec<PanelVps>, String> {
+    match panel {
+        Panel::SolusVm => {
+            let body = solusvm_call(
+                client,
+                panel_url,
+                cred,
+                &[
+                    ("action", "info"),
+                    ("status", "true"),
+                    ("ipaddr", "true"),
+                    ("bw", "true"),
+                ],
+            )
+            .await?;
+            Ok(vec![parse_solusvm_info(&body)])
+        }
+        Panel::Virtualizor => {
+            let body = virtualizor_call(client, panel_url, cred, &[("act", "listvs")]).await?;
+            Ok(parse_virtualizor_list(&body))
+        }
+    }
+}
+
+async fn run_action(
+    client: &reqwest::Client,
+    panel: Panel,
+    panel_url: &str,
+    cred: &Credential,
+    panel_vps_id: Option<&str>,
+    action: &str,
+) -> Result<String, String> {
+    match panel {
+        Panel::SolusVm => {
+            let body = solusvm_call(client, panel_url, cred, &[("action", action)]).await?;
+            Ok(xml_tag(&body, "statusmsg").unwrap_or(action).to_string())
+        }
+        Panel::Virtualizor => {
+            let svs = panel_vps_id.ok_or("VPS has no panel_vps_id; sync the panel account first")?;
+            let act = match action {
+                "boot" => "start",
+                "shutdown" => "stop",
+                _ => "restart",
+            };
+            let body =
+                virtualizor_call(client, panel_url, cred, &[("act", act), ("svs", svs), ("do", "1")])
+                    .await?;
+            Ok(body["done_msg"].as_str().unwrap_or(act).to_string())
+        }
+    }
+}
+
+/// SolusVM client API: `POST /api/client/command.php`, answered with bare XML elements.
+async fn solusvm_call(
+    client: &reqwest::Client,
+    panel_url: &str,
+    cred: &Credential,
+    params: &[(&str, &str)],
+) -> Result<String, String> {
+    let mut form: Vec<(&str, &str)> = vec![("key", &cred.key), ("hash", &cred.hash)];
+    form.extend_from_slice(params);
+
+    let resp = client
+        .post(format!("{panel_url}/api/client/command.php"))
+        .form(&form)
+        .timeout(std::time::Duration::from_secs(30))
+        .send()
+        .await
+        .map_err(|e| format!("SolusVM request failed: {e}"))?;
+    let status = resp.status();
+    let body = resp.text().await.unwrap_or_default();
+    if !status.is_success() {
+        return Err(format!("SolusVM HTTP error ({status}): {body}"));
+    }
+
+    if xml_tag(&body, "status") != Some("success") {
+        let msg = xml_tag(&body, "statusmsg").unwrap_or(body.trim());
+        return Err(format!("SolusVM error: {msg}"));
+    }
+    Ok(body)
+}
+
+/// Virtualizor end-user API: `GET /index.php?api=json&apikey=&apipass=&act=`.
+async fn virtualizor_call(
+    client: &reqwest::Client,
+    panel_url: &str,
+    cred: &Credential,
+    params: &[(&str, &str)],
+) -> Result<serde_json::Value, String> {
+    let mut query: Vec<(&str, &str)> =
+        vec![("api", "json"), ("apikey", &cred.key), ("apipass", &cred.hash)];
+    query.extend_from_slice(params);
+
+    let resp = client
+        .get(format!("{panel_url}/index.php"))
+        .query(&query)
+        .timeout(std::time::Duration::from_secs(30))
+        .send()
+        .await
+        .map_err(|e| format!("Virtualizor request failed: {e}"))?;
+    let status = resp.status();
+    if !status.is_success() {
+        let body = resp.text().await.unwrap_or_default();
+        return Err(format!("Virtualizor HTTP error ({status}): {body}"));
+    }
+    let body: serde_json::Value = resp
+        .json()
+        .await
+        .map_err(|e| format!("Virtualizor response parse error: {e}"))?;
+
+    // Errors come back as HTTP 200 with an `error` map or list
+    let errors: Vec<String> = match &body["error"] {
+        serde_json::Value::Object(m) => m.values().filter_map(|v| v.as_str().map(String::from)).collect(),
+        serde_json::Value::Array(a) => a.iter().filter_map(|v| v.as_str().map(String::from)).collect(),
+        _ => Vec::new(),
+    };
+    if !errors.is_empty() {
+        return Err(format!("Virtualizor error: {}", errors.join("; ")));
+    }
+    Ok(body)
+}
+
+/// Parse a SolusVM `info` reply. `bw` is `total,used,free,percent` in bytes.
+fn parse_solusvm_info(body: &str) -> PanelVps {
+    let mut ips: Vec<String> = Vec::new();
+    let main_ip = xml_tag(body, "ipaddress").into_iter();
+    let all_ips = xml_tag(body, "ipaddr").unwrap_or("").split(',');
+    for ip in main_ip.chain(all_ips).map(str::trim) {
+        if !ip.is_empty() && !ips.iter().any(|e| e == ip) {
+            ips.push(ip.to_string());
+        }
+    }
+
+    let bw: Vec<i64> = xml_tag(body, "bw")
+        .unwrap_or("")
+        .split(',')
+        .filter_map(|s| s.trim().parse().ok())
+        .collect();
+
+    let state = match xml_tag(body, "vmstat").or_else(|| xml_tag(body, "statusmsg")) {
+        Some("online") => "online",
+        Some("offline") => "offline",
+        Some("disabled") | Some("suspended") => "suspended",
+        _ => "",
+    };
+
+    PanelVps {
+        panel_vps_id: None,
+        hostname: xml_tag(body, "hostname").unwrap_or("").to_string(),
+        state: state.to_string(),
+        ips,
+        bandwidth_total_bytes: bw.first().copied(),
+        bandwidth_used_bytes: bw.get(1).copied(),
+    }
+}
+
+/// Parse a Virtualizor `listvs` reply: `vs` maps VPS id to details.
+/// Bandwidth is reported in GB.
+fn parse_virtualizor_list(body: &serde_json::Value) -> Vec<PanelVps> {
+    const BYTES_PER_GB: f64 = 1_000_000_000.0;
+
+    let Some(list) = body["vs"].as_object() else {
+        return Vec::new();
+    };
+    list.iter()
+        .map(|(id, vs)| {
+            let ips: Vec<&str> = match &vs["ips"] {
+                serde_json::Value::Object(m) => m.values().filter_map(|v| v.as_str()).collect(),
+                serde_json::Value::Array(a) => a.iter().filter_map(|v| v.as_str()).collect(),
+                _ => Vec::new(),
+            };
+            let ips = ips
+                .into_iter()
+                .map(|ip| ip.trim().to_string())
+                .filter(|ip| !ip.is_empty())
+                .collect();
+
+            let state = if number(&vs["suspended"]).is_some_and(|s| s > 0.0) {
+                "suspended"
+            } else {
+                match number(&vs["status"]) {
+                    Some(s) if s > 0.0 => "online",
+                    Some(_) => "offline",
+                    None => "",
+                }
+            };
+
+            let gb_to_bytes = |v: &serde_json::Value| number(v).map(|gb| (gb * BYTES_PER_GB) as i64);
+
+            PanelVps {
+                panel_vps_id: Some(vs["vpsid"].as_str().unwrap_or(id).to_string()),
+                hostname: vs["hostname"].as_str().unwrap_or("").to_string(),
+                state: state.to_string(),
+                ips,
+                // 0 means unmetered
+                bandwidth_total_bytes: gb_to_bytes(&vs["bandwidth"]).filter(|b| *b > 0),
+                bandwidth_used_bytes: gb_to_bytes(&vs["used_bandwidth"]),
+            }
+        })
+        .collect()
+}
+
+/// Text of the first `<tag>…</tag>` element.
+fn xml_tag<'a>(body: &'a str, tag: &str) -> Option<&'a str> {
+    let open = format!("<{tag}>");
+    let start = body.find(&open)? + open.len();
+    let end = body[start..].find(&format!("</{tag}>"))? + start;
+    Some(body[start..end].trim())
+}
+
+/// Virtualizor sends numbers as JSON numbers or strings.
+fn number(v: &serde_json::Value) -> Option<f64> {
+    v.as_f64().or_else(|| v.as_str()?.trim().parse().ok())
+}
+
+// ─── Helpers ──────────────────────────────────────────────
+
+fn panel_url(config: &serde_json::Value) -> Option<String> {
+    config
+        .get("panel_url")
+        .and_then(|v| v.as_str())
+        .map(|s| s.trim().trim_end_matches('/').to_string())
+        .filter(|s| !s.is_empty())
+}
+
+fn credentials(config: &serde_json::Value) -> Vec<Credential> {
+    config
+        .get("credentials")
+        .and_then(|v| v.as_array())
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|c| {
+                    Some(Credential {
+                        key: c.get("key")?.as_str()?.to_string(),
+                        hash: c.get("hash")?.as_str()?.to_string(),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn mask_key(key: &str) -> String {
+    if key.len() <= 8 {
+        "****".into()
+    } else {
+        format!("{}...{}", &key[..4], &key[key.len() - 4..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_solusvm_info() {
+        let body = "<status>success</status><statusmsg></statusmsg><vmstat>online</vmstat>\
+                    <hostname>la-edge-3</hostname><ipaddress>203.0.113.30</ipaddress>\
+                    <ipaddr>203.0.113.30,203.0.113.31</ipaddr>\
+                    <bw>1099511627776,274877906944,824633720832,25</bw>";
+        let vps = parse_solusvm_info(body);
+        assert_eq!(vps.hostname, "la-edge-3");
+        assert_eq!(vps.state, "online");
+        assert_eq!(vps.ips, ["203.0.113.30", "203.0.113.31"]);
+        assert_eq!(vps.bandwidth_total_bytes, Some(1_099_511_627_776));
+        assert_eq!(vps.bandwidth_used_bytes, Some(274_877_906_944));
+    }
+
+    #[test]
+    fn test_parse_virtualizor_list() {
+        let body = serde_json::json!({
+            "vs": {
+                "812": {
+                    "vpsid": "812",
+                    "hostname": "fra-relay",
+                    "status": "1",
+                    "suspended": "0",
+                    "ips": {"41": "198.51.100.12", "42": "2001:db8::12"},
+                    "bandwidth": "2000",
+                    "used_bandwidth": 150.5,
+                }
+            }
+        });
+        let list = parse_virtualizor_list(&body);
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].panel_vps_id.as_deref(), Some("812"));
+        assert_eq!(list[0].state, "online");
+        assert_eq!(list[0].ips.len(), 2);
+        assert_eq!(list[0].bandwidth_total_bytes, Some(2_000_000_000_000));
+        assert_eq!(list[0].bandwidth_used_bytes, Some(150_500_000_000));
+    }
+
+    #[test]
+    fn test_xml_tag() {
+        assert_eq!(xml_tag("<status>error</status><statusmsg> Invalid key </statusmsg>", "statusmsg"), Some("Invalid key"));
+        assert_eq!(xml_tag("<status>error</status>", "hostname"), None);
+    }
+}
@@ -0,0 +1,534 @@
+//! Proxmox VE cluster as a cloud account.
+//!
+//! Config: `api_url` (`https://pve.example.com:8006`), `token_id`
+//! (`user@realm!name`), `token_secret`, and optionally `verify_tls: false` for
+//! self-signed certificates plus `country` / `city` for where the cluster runs.
+//! Guests are listed from `/cluster/resources`; IPs come from the QEMU guest
+//! agent or the LXC interface list, so VMs without a running agent sync
+//! without addresses.
+
+use std::net::IpAddr;
+
+use futures::StreamExt;
+use uuid::Uuid;
+
+use crate::errors::AppError;
+use crate::ip_reputation::is_checkable;
+use crate::models::{CloudAccount, CloudSyncResult, IpEntry};
+use crate::routes::AppState;
+
+/// Guest interface lookups in flight at once
+const CONCURRENCY: usize = 8;
+
+/// Validate Proxmox credentials by calling GET /api2/json/version.
+pub async fn validate(
+    http_client: &reqwest::Client,
+    config: &serde_json::Value,
+) -> Result<(), AppError> {
+    let api = Api::from_config(http_client, config).map_err(AppError::BadRequest)?;
+    api.get("version").await.map_err(AppError::BadRequest)?;
+    Ok(())
+}
+
+/// Mask sensitive fields in Proxmox config.
+pub fn mask_config(config: &serde_json::Value) -> serde_json::Value {
+    let str_field = |key: &str| config.get(key).and_then(|v| v.as_str()).unwrap_or("");
+
+    serde_json::json!({
+        "api_url": str_field("api_url"),
+        "token_id": str_field("token_id"),
+        "token_secret": "****",
+        "verify_tls": verify_tls(config),
+        "country": str_field("country"),
+        "city": str_field("city"),
+    })
+}
+
+/// Full sync: fetch all QEMU VMs and LXC containers of the cluster.
+pub async fn sync(state: &AppState, account: &CloudAccount) -> Result<CloudSyncResult, AppError> {
+    let api = Api::from_config(&state.http_client, &account.config).map_err(AppError::Internal)?;
+    let guests = fetch_guests(&api).await.map_err(AppError::Internal)?;
+
+    let country = account.config.get("country").and_then(|v| v.as_str()).unwrap_or("");
+    let city = account.config.get("city").and_then(|v| v.as_str()).unwrap_or("");
+
+    let provider_id = ensure_provider(state, "Proxmox").await?;
+
+    let mut created: i64 = 0;
+    let mut updated: i64 = 0;
+    let merged: i64 = 0;
+    let mut seen_instance_ids: Vec<String> = Vec::new();
+
+    for guest in &guests {
+        // vmids are unique per cluster only
+        let instance_id = format!("{}/{}", account.id, guest.resource_id);
+        seen_instance_ids.push(instance_id.clone());
+
+        let ip_json = serde_json::to_value(&guest.ips).unwrap_or_default();
+
+        let extra = serde_json::json!({
+            "cloud_instance_id": instance_id,
+            "cloud_provider": "proxmox",
+            "cloud_region": guest.node,
+            "guest_type": guest.guest_type,
+            "vmid": guest.vmid,
+        });
+
+        // Upsert
+        let existing_vps: Option<(Uuid, String, String)> = sqlx::query_as(
+            "SELECT id, source, hostname FROM vps WHERE extra->>'cloud_instance_id' = $1",
+        )
+        .bind(&instance_id)
+        .fetch_optional(&state.db)
+        .await?;
+
+        if let Some((vps_id, _, _)) = existing_vps {
+            sqlx::query(
+                r#"UPDATE vps SET
+                    hostname = $2,
+                    ip_addresses = $3,
+                    status = $4,
+                    country = COALESCE(NULLIF($5, ''), country),
+                    city = COALESCE(NULLIF($6, ''), city),
+                    dc_name = $7,
+                    provider_id = $8,
+                    cloud_account_id = $9,
+                    cpu_cores = COALESCE($10, cpu_cores),
+                    ram_mb = COALESCE($11, ram_mb),
+                    disk_gb = COALESCE($12, disk_gb),
+                    source = 'cloud-sync',
+                    extra = extra || $13::jsonb
+                WHERE id = $1"#,
+            )
+            .bind(vps_id)
+            .bind(&guest.name)
+            .bind(&ip_json)
+            .bind(guest.status)
+            .bind(country)
+            .bind(city)
+            .bind(&guest.node)
+            .bind(provider_id)
+            .bind(account.id)
+            .bind(guest.cpu_cores)
+            .bind(guest.ram_mb)
+            .bind(guest.disk_gb)
+            .bind(&extra)
+            .execute(&state.db)
+            .await?;
+            updated += 1;
+        } else {
+            sqlx::query(
+                r#"INSERT INTO vps (
+                    hostname, alias, provider_id,
+                    ip_addresses, ssh_port,
+                    country, city, dc_name,
+                    cpu_cores, ram_mb, disk_gb, bandwidth_tb,
+                    cost_monthly, currency,
+                    status, purchase_date, expire_date,
+                    purpose, vpn_protocol, tags,
+                    monitoring_enabled, node_exporter_port,
+                    extra, notes,
+                    source, cloud_account_id
+                ) VALUES (
+                    $1, '', $2,
+                    $3, 22,
+                    $4, $5, $6,
+                    $7, $8, $9, NULL,
+                    NULL, 'USD',
+                    $10, NULL, NULL,
+                    '', '', '{}',
+                    true, 9100,
+                    $11, '',
+                    'cloud-sync', $12
+                )"#,
+            )
+            .bind(&guest.name)
+            .bind(provider_id)
+            .bind(&ip_json)
+            .bind(country)
+            .bind(city)
+            .bind(&guest.node)
+            .bind(guest.cpu_cores)
+            .bind(guest.ram_mb)
+            .bind(guest.disk_gb)
+            .bind(guest.status)
+            .bind(&extra)
+            .bind(account.id)
+            .execute(&state.db)
+            .await?;
+            created += 1;
+        }
+    }
+
+    // Retire stale VPS: those linked to this account but not seen in sync
+    let retired = if !seen_instance_ids.is_empty() {
+        let result = sqlx::query(
+            r#"UPDATE vps SET status = 'retired', monitoring_enabled = false
+               WHERE cloud_account_id = $1
+                 AND source = 'cloud-sync'
+                 AND extra->>'cloud_instance_id' IS NOT NULL
+                 AND extra->>'cloud_instance_id' != ALL($2)
+                 AND status NOT IN ('retired', 'deleted')"#,
+        )
+        .bind(account.id)
+        .bind(&seen_instance_ids)
+        .execute(&state.db)
+        .await?;
+        result.rows_affected() as i64
+    } else {
+        0
+    };
+
+    Ok(CloudSyncResult {
+        instances_found: guests.len() as i64,
+        created,
+        updated,
+        retired,
+        merged,
+    })
+}
+
+// ─── PVE API ──────────────────────────────────────────────
+
+/// A QEMU VM or LXC container mapped to VPS fields.
+#[derive(Debug)]
+struct Guest {
+    /// `qemu/100` or `lxc/101`
+    resource_id: String,
+    guest_type: String,
+    vmid: u64,
+    node: String,
+    name: String,
+    status: &'static str,
+    cpu_cores: Option<i16>,
+    ram_mb: Option<i32>,
+    disk_gb: Option<i32>,
+    ips: Vec<IpEntry>,
+}
+
+struct Api {
+    client: reqwest::Client,
+    base: String,
+    authorization: String,
+}
+
+impl Api {
+    fn from_config(http_client: &reqwest::Client, config: &serde_json::Value) -> Result<Self, String> {
+        let field = |key: &str| {
+            config
+                .get(key)
+                .and_then(|v| v.as_str())
+                .filter(|s| !s.is_empty())
+                .ok_or_else(|| format!("Missing {key} in config"))
+        };
+        let api_url = field("api_url")?;
+        let token_id = field("token_id")?;
+        let token_secret = field("token_secret")?;
+
+        let client = if verify_tls(config) {
+            http_client.clone()
+        } else {
+            reqwest::Client::builder()
+                .danger_accept_invalid_certs(true)
+                .build()
+                .map_err(|e| format!("HTTP client error: {e}"))?
+        };
+
+        Ok(Api {
+            client,
+            base: format!("{}/api2/json", api_url.trim_end_matches('/')),
+            authorization: format!("PVEAPIToken={token_id}={token_secret}"),
+        })
+    }
+
+    /// GET a PVE endpoint and return its `data`.
+    async fn get(&self, path: &str) -> Result<serde_json::Value, String> {
+        let resp = self
+            .client
+            .get(format!("{}/{path}", self.base))
+            .header("Authorization", &self.authorization)
+            .timeout(std::time::Duration::from_secs(30))
+            .send()
+            .await
+            .map_err(|e| format!("Proxmox API request failed: {e}"))?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            return Err(format!("Proxmox GET /{path} failed ({status}): {body}"));
+        }
+
+        let mut body: serde_json::Value = resp
+            .json()
+            .await
+            .map_err(|e| format!("Proxmox response parse error: {e}"))?;
+        Ok(body["data"].take())
+    }
+}
+
+async fn fetch_guests(api: &Api) -> Result<Vec<Guest>, String> {
+    let resources = api.get("cluster/resources?type=vm").await?;
+
+    let mut guests: Vec<Guest> = resources
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(map_resource)
+        .collect();
+
+    // Interface lookups fail for stopped guests and VMs without the agent; those keep no IPs
+    let lookups = guests.iter().enumerate().map(|(idx, g)| async move {
+        if g.status != "active" {
+            return (idx, Vec::new());
+        }
+        let path = match g.guest_type.as_str() {
+            "qemu" => format!("nodes/{}/qemu/{}/agent/network-get-interfaces", g.node, g.vmid),
+            _ => format!("nodes/{}/lxc/{}/interfaces", g.node, g.vmid),
+        };
+        match api.get(&path).await {
+            Ok(data) => (idx, guest_ips(&g.guest_type, &data)),
+            Err(e) => {
+                tracing::debug!("Proxmox interfaces of {} unavailable: {e}", g.resource_id);
+                (idx, Vec::new())
+            }
+        }
+    });
+    let found: Vec<(usize, Vec<IpEntry>)> = futures::stream::iter(lookups)
+        .buffer_unordered(CONCURRENCY)
+        .collect()
+        .await;
+    for (idx, ips) in found {
+        guests[idx].ips = ips;
+    }
+
+    Ok(guests)
+}
+
+/// Map a `/cluster/resources` entry; templates and non-guest resources are skipped.
+fn map_resource(res: &serde_json::Value) -> Option<Guest> {
+    let guest_type = res["type"].as_str().filter(|t| matches!(*t, "qemu" | "lxc"))?;
+    if res["template"].as_u64() == Some(1) {
+        return None;
+    }
+    let vmid = res["vmid"].as_u64()?;
+    let resource_id = res["id"]
+        .as_str()
+        .map(String::from)
+        .unwrap_or_else(|| format!("{guest_type}/{vmid}"));
+
+    let status = match res["status"].as_str().unwrap_or("") {
+        "running" => "active",
+        "stopped" | "paused" | "suspended" => "suspended",
+        _ => "active",
+    };
+
+    const MIB: u64 = 1024 * 1024;
+    const GIB: u64 = 1024 * MIB;
+    let bytes = |key: &str| res[key].as_u64().filter(|b| *b > 0);
+
+    Some(Guest {
+        resource_id,
+        guest_type: guest_type.to_string(),
+        vmid,
+        node: res["node"].as_str().unwrap_or("").to_string(),
+        name: res["name"]
+            .as_str()
+            .map(String::from)
+            .unwrap_or_else(|| format!("{guest_type}-{vmid}")),
+        status,
+        cpu_cores: res["maxcpu"].as_f64().map(|v| v.ceil() as i16),
+        ram_mb: bytes("maxmem").map(|b| (b / MIB) as i32),
+        disk_gb: bytes("maxdisk").map(|b| (b / GIB) as i32),
+        ips: Vec::new(),
+    })
+}
+
+/// IPs from the guest agent (`qemu`) or `/interfaces` (`lxc`) reply, without
+/// loopback and link-local addresses. Public addresses are labelled `overseas`,
+/// private ones `internal`.
+fn guest_ips(guest_type: &str, data: &serde_json::Value) -> Vec<IpEntry> {
+    let candidates: Vec<&str> = if guest_type == "qemu" {
+        data["result"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .flat_map(|iface| iface["ip-addresses"].as_array().into_iter().flatten())
+            .filter_map(|a| a["ip-address"].as_str())
+            .collect()
+    } else {
+        data.as_array()
+            .into_iter()
+            .flatten()
+            .flat_map(|iface| [&iface["inet"], &iface["inet6"]])
+            .filter_map(|v| v.as_str())
+            .map(|cidr| cidr.split('/').next().unwrap_or(cidr))
+            .collect()
+    };
+
+    let mut ips: Vec<IpEntry> = Vec::new();
+    for candidate in candidates {
+        let Ok(ip) = candidate.trim().parse::<IpAddr>() else {
+            continue;
+        };
+        let link_local = match ip {
+            IpAddr::V4(v4) => v4.is_link_local(),
+            IpAddr::V6(v6) => (v6.segments()[0] & 0xffc0) == 0xfe80,
+        };
+        if ip.is_loopback() || ip.is_unspecified() || link_local {
+            continue;
+        }
+        let ip_str = ip.to_string();
+        if !ips.iter().any(|e| e.ip == ip_str) {
+            ips.push(IpEntry {
+                ip: ip_str,
+                label: if is_checkable(ip) { "overseas" } else { "internal" }.to_string(),
+            });
+        }
+    }
+    ips
+}
+
+// ─── Helpers ──────────────────────────────────────────────
+
+/// TLS verification is on unless the config sets `verify_tls: false`.
+fn verify_tls(config: &serde_json::Value) -> bool {
+    config.get("verify_tls").and_then(|v| v.as_bool()).unwrap_or(true)
+}
+
+/// Ensure a Provider record for Proxmox exists, return its ID.
+async fn ensure_provider(state: &AppState, name: &str) -> Result<Uuid, AppError> {
+    let existing: Option<(Uuid,)> =
+        sqlx::query_as("SELECT id FROM providers WHERE LOWER(name) = LOWER($1) LIMIT 1")
+            .bind(name)
+            .fetch_optional(&state.db)
+            .await?;
+
+    if let Some((id,)) = existing {
+        Ok(id)
+    } else {
+        let (id,): (Uuid,) = sqlx::query_as(
+            "INSERT INTO providers (name, country, website, panel_url, api_supported, notes) VALUES ($1, '', 'https://www.proxmox.com', '', true, 'Auto-created by cloud sync (self-hosted)') RETURNING id",
+        )
+        .bind(name)
+        .fetch_one(&state.db)
+        .await?;
+        Ok(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use axum::{
+        extract::Path,
+        http::{HeaderMap, StatusCode},
+        routing::get,
+        Json, Router,
+    };
+
+    const AUTH: &str = "PVEAPIToken=sigma@pve!sync=00000000-0000-0000-0000-000000000001";
+
+    fn authorized(headers: &HeaderMap) -> Result<(), StatusCode> {
+        match headers.get("authorization").and_then(|v| v.to_str().ok()) {
+            Some(AUTH) => Ok(()),
+            _ => Err(StatusCode::UNAUTHORIZED),
+        }
+    }
+
+    /// Serve a canned PVE JSON API on a local port; returns the account config.
+    async fn mock_pve() -> serde_json::Value {
+        let app = Router::new()
+            .route(
+                "/api2/json/version",
+                get(|headers: HeaderMap| async move {
+                    authorized(&headers)?;
+                    Ok::<_, StatusCode>(Json(serde_json::json!({"data": {"version": "8.2.4"}})))
+                }),
+            )
+            .route(
+                "/api2/json/cluster/resources",
+                get(|headers: HeaderMap| async move {
+                    authorized(&headers)?;
+                    Ok::<_, StatusCode>(Json(serde_json::json!({"data": [
+                        {"id": "qemu/100", "type": "qemu", "vmid": 100, "name": "edge-vm", "node": "pve1",
+                         "status": "running", "maxcpu": 4, "maxmem": 8589934592u64, "maxdisk": 68719476736u64, "template": 0},
+                        {"id": "lxc/101", "type": "lxc", "vmid": 101, "name": "dns-ct", "node": "pve2",
+                         "status": "running", "maxcpu": 1, "maxmem": 536870912, "maxdisk": 8589934592u64},
+                        {"id": "qemu/102", "type": "qemu", "vmid": 102, "name": "old-vm", "node": "pve1",
+                         "status": "stopped", "maxcpu": 2, "maxmem": 2147483648u64, "maxdisk": 0},
+                        {"id": "qemu/9000", "type": "qemu", "vmid": 9000, "name": "tmpl", "node": "pve1",
+                         "status": "stopped", "template": 1},
+                        {"id": "storage/pve1/local", "type": "storage", "node": "pve1"},
+                    ]})))
+                }),
+            )
+            .route(
+                "/api2/json/nodes/{node}/qemu/{vmid}/agent/network-get-interfaces",
+                get(|Path((node, vmid)): Path<(String, u64)>| async move {
+                    if (node.as_str(), vmid) != ("pve1", 100) {
+                        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                    }
+                    Ok(Json(serde_json::json!({"data": {"result": [
+                        {"name": "lo", "ip-addresses": [{"ip-address": "127.0.0.1", "ip-address-type": "ipv4"}]},
+                        {"name": "eth0", "ip-addresses": [
+                            {"ip-address": "203.0.113.40", "ip-address-type": "ipv4", "prefix": 24},
+                            {"ip-address": "fe80::1", "ip-address-type": "ipv6", "prefix": 64},
+                        ]},
+                    ]}})))
+                }),
+            )
+            .route(
+                "/api2/json/nodes/{node}/lxc/{vmid}/interfaces",
+                get(|| async {
+                    Json(serde_json::json!({"data": [
+                        {"name": "lo", "inet": "127.0.0.1/8"},
+                        {"name": "eth0", "inet": "10.10.0.5/24", "inet6": "2001:db8::5/64"},
+                    ]}))
+                }),
+            );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        serde_json::json!({
+            "api_url": format!("http://{addr}"),
+            "token_id": "sigma@pve!sync",
+            "token_secret": "00000000-0000-0000-0000-000000000001",
+        })
+    }
+
+    #[tokio::test]
+    async fn test_validate_against_mock() {
+        let mut config = mock_pve().await;
+        let client = reqwest::Client::new();
+        assert!(validate(&client, &config).await.is_ok());
+
+        config["token_secret"] = "wrong".into();
+        assert!(matches!(validate(&client, &config).await, Err(AppError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_guests_against_mock() {
+        let config = mock_pve().await;
+        let api = Api::from_config(&reqwest::Client::new(), &config).unwrap();
+        let guests = fetch_guests(&api).await.unwrap();
+
+        let ids: Vec<&str> = guests.iter().map(|g| g.resource_id.as_str()).collect();
+        assert_eq!(ids, ["qemu/100", "lxc/101", "qemu/102"]);
+
+        let vm = &guests[0];
+        assert_eq!((vm.cpu_cores, vm.ram_mb, vm.disk_gb), (Some(4), Some(8192), Some(64)));
+        assert_eq!(vm.ips, [IpEntry { ip: "203.0.113.40".into(), label: "overseas".into() }]);
+
+        let ct = &guests[1];
+        assert_eq!(ct.node, "pve2");
+        let ct_ips: Vec<(&str, &str)> = ct.ips.iter().map(|e| (e.ip.as_str(), e.label.as_str())).collect();
+        assert_eq!(ct_ips, [("10.10.0.5", "internal"), ("2001:db8::5", "overseas")]);
+
+        let stopped = &guests[2];
+        assert_eq!(stopped.status, "suspended");
+        assert_eq!(stopped.disk_gb, None);
+        assert!(stopped.ips.is_empty());
+    }
+}
@@ -0,0 +1,443 @@
+//! IP rotation of cloud-sourced VPS.
+//!
+//! A rotation runs allocate → associate → update_vps → update_dns → release_old.
+//! Progress is stored in `ip_rotations` after every step and each step is audited,
+//! so calling the endpoint again after a failure resumes at the failed step
+//! instead of allocating another address.
+
+use axum::{
+    extract::{Path, State},
+    routing::{get, post},
+    Extension, Json, Router,
+};
+use uuid::Uuid;
+
+use crate::auth::{require_role, CurrentUser};
+use crate::errors::{AppError, ErrorResponse};
+use crate::models::{CloudAccount, IpEntry, IpRotation, RotateIpRequest, Vps};
+use crate::routes::audit_logs::log_audit;
+use crate::routes::cloud::{alibaba, aws};
+use crate::routes::dns::repoint_record;
+use crate::routes::AppState;
+use crate::vps_lifecycle::{set_change_context, SOURCE_CLOUD_ROTATE};
+
+/// Providers with an IP rotation implementation.
+const ROTATE_PROVIDERS: &[&str] = &["aws", "alibaba"];
+
+/// A rotation left `running` longer than this is assumed to have died and may be resumed.
+const STALE_RUNNING_MINUTES: i32 = 15;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/api/vps/{id}/rotate-ip", post(rotate_ip))
+        .route("/api/vps/{id}/ip-rotations", get(list_rotations))
+}
+
+#[utoipa::path(
+    post, path = "/api/vps/{id}/rotate-ip",
+    tag = "VPS",
+    params(("id" = Uuid, Path, description = "VPS ID")),
+    request_body = RotateIpRequest,
+    responses(
+        (status = 200, body = IpRotation, description = "Rotation completed"),
+        (status = 400, body = ErrorResponse, description = "VPS is not cloud-sourced or its provider cannot rotate"),
+        (status = 404, body = ErrorResponse),
+        (status = 502, body = ErrorResponse, description = "A step failed; call again to resume"),
+    )
+)]
+pub async fn rotate_ip(
+    State(state): State<AppState>,
+    Extension(user): Extension<CurrentUser>,
+    Path(id): Path<Uuid>,
+    Json(input): Json<RotateIpRequest>,
+) -> Result<Json<IpRotation>, AppError> {
+    require_role(&user, &["admin", "operator"])?;
+
+    let vps = sqlx::query_as::<_, Vps>("SELECT * FROM vps WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    let rotation = match resume(&state, id).await? {
+        Some(r) => r,
+        None => start(&state, &user, &vps, &input).await?,
+    };
+
+    let account_id = rotation
+        .cloud_account_id
+        .ok_or_else(|| AppError::BadRequest("cloud account of this rotation was deleted".into()))?;
+    let account = sqlx::query_as::<_, CloudAccount>("SELECT * FROM cloud_accounts WHERE id = $1")
+        .bind(account_id)
+        .fetch_one(&state.db)
+        .await?;
+
+    run(&state, &user, &account, rotation).await.map(Json)
+}
+
+#[utoipa::path(
+    get, path = "/api/vps/{id}/ip-rotations",
+    tag = "VPS",
+    params(("id" = Uuid, Path, description = "VPS ID")),
+    responses(
+        (status = 200, body = Vec<IpRotation>),
+    )
+)]
+pub async fn list_rotations(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<IpRotation>>, AppError> {
+    let rows = sqlx::query_as::<_, IpRotation>(
+        "SELECT * FROM ip_rotations WHERE vps_id = $1 ORDER BY created_at DESC LIMIT 100",
+    )
+    .bind(id)
+    .fetch_all(&state.db)
+    .await?;
+    Ok(Json(rows))
+}
+
+/// Claim the unfinished rotation of this VPS, if any.
+async fn resume(state: &AppState, vps_id: Uuid) -> Result<Option<IpRotation>, AppError> {
+    let open = sqlx::query_as::<_, IpRotation>(
+        "SELECT * FROM ip_rotations WHERE vps_id = $1 AND status != 'completed'",
+    )
+    .bind(vps_id)
+    .fetch_optional(&state.db)
+    .await?;
+    let Some(open) = open else {
+        return Ok(None);
+    };
+
+    let claimed = sqlx::query_as::<_, IpRotation>(
+        r#"UPDATE ip_rotations SET status = 'running', error = ''
+           WHERE id = $1 AND (
+               status = 'failed'
+               OR updated_at < now() - make_interval(mins => $2)
+           )
+           RETURNING *"#,
+    )
+    .bind(open.id)
+    .bind(STALE_RUNNING_MINUTES)
+    .fetch_optional(&state.db)
+    .await?;
+
+    claimed.map(Some).ok_or_else(|| {
+        AppError::BadRequest(format!(
+            "IP rotation {} of this VPS is already running (step {})",
+            open.id, open.step
+        ))
+    })
+}
+
+async fn start(
+    state: &AppState,
+    user: &CurrentUser,
+    vps: &Vps,
+    input: &RotateIpRequest,
+) -> Result<IpRotation, AppError> {
+    let account_id = vps
+        .cloud_account_id
+        .ok_or_else(|| AppError::BadRequest("IP rotation needs a cloud-sourced VPS (cloud_account_id set)".into()))?;
+    let provider_type = vps.extra.get("cloud_provider").and_then(|v| v.as_str()).unwrap_or_default();
+    if !ROTATE_PROVIDERS.contains(&provider_type) {
+        return Err(AppError::BadRequest(format!(
+            "IP rotation is not supported for cloud provider '{}' (supported: {})",
+            provider_type,
+            ROTATE_PROVIDERS.join(", ")
+        )));
+    }
+    let instance_id = vps.extra.get("cloud_instance_id").and_then(|v| v.as_str()).unwrap_or_default();
+    let region = vps.extra.get("cloud_region").and_then(|v| v.as_str()).unwrap_or_default();
+    if instance_id.is_empty() || region.is_empty() {
+        return Err(AppError::BadRequest("VPS lacks cloud_instance_id / cloud_region; sync the cloud account first".into()));
+    }
+
+    let old_ip = match input.ip {
+        Some(ref ip) => {
+            let ip = ip.trim();
+            if !vps.ip_addresses.0.iter().any(|e| e.ip.trim() == ip) {
+                return Err(AppError::BadRequest(format!("{} is not an address of this VPS", ip)));
+            }
+            ip.to_string()
+        }
+        None => vps
+            .ip_addresses
+            .0
+            .iter()
+            .find(|e| e.label != "internal")
+            .map(|e| e.ip.trim().to_string())
+            .ok_or_else(|| AppError::BadRequest("VPS has no public IP to rotate".into()))?,
+    };
+
+    let rotation = sqlx::query_as::<_, IpRotation>(
+        r#"INSERT INTO ip_rotations (
+               vps_id, cloud_account_id, provider_type, region, instance_id,
+               old_ip, repoint_dns, release_old, actor
+           ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+           ON CONFLICT (vps_id) WHERE status != 'completed' DO NOTHING
+           RETURNING *"#,
+    )
+    .bind(vps.id)
+    .bind(account_id)
+    .bind(provider_type)
+    .bind(region)
+    .bind(instance_id)
+    .bind(&old_ip)
+    .bind(input.repoint_dns)
+    .bind(input.release_old)
+    .bind(&user.email)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::BadRequest("an IP rotation of this VPS was started concurrently".into()))?;
+
+    log_audit(
+        &state.db,
+        user,
+        "rotate_ip_start",
+        "vps",
+        Some(&vps.id.to_string()),
+        serde_json::json!({
+            "rotation_id": rotation.id,
+            "hostname": vps.hostname,
+            "old_ip": rotation.old_ip,
+            "repoint_dns": rotation.repoint_dns,
+            "release_old": rotation.release_old,
+        }),
+    )
+    .await;
+
+    Ok(rotation)
+}
+
+/// Run the remaining steps. On failure the rotation is marked failed with the
+/// error and the step it stopped at.
+async fn run(
+    state: &AppState,
+    user: &CurrentUser,
+    account: &CloudAccount,
+    mut rotation: IpRotation,
+) -> Result<IpRotation, AppError> {
+    while rotation.step != "done" {
+        let step = rotation.step.clone();
+        match run_step(state, account, &mut rotation).await {
+            Ok(details) => {
+                rotation.step = next_step(&step).to_string();
+                rotation = save(state, &rotation).await?;
+                log_audit(
+                    &state.db,
+                    user,
+                    "rotate_ip_step",
+                    "vps",
+                    Some(&rotation.vps_id.to_string()),
+                    serde_json::json!({"rotation_id": rotation.id, "step": step, "result": details}),
+                )
+                .await;
+            }
+            Err(e) => {
+                let message = e.to_string();
+                rotation.status = "failed".into();
+                rotation.error = message.clone();
+                save(state, &rotation).await?;
+                log_audit(
+                    &state.db,
+                    user,
+                    "rotate_ip_failed",
+                    "vps",
+                    Some(&rotation.vps_id.to_string()),
+                    serde_json::json!({"rotation_id": rotation.id, "step": step, "error": message}),
+                )
+                .await;
+                return Err(AppError::BadGateway(format!(
+                    "IP rotation {} failed at step {}: {}; call rotate-ip again to resume",
+                    rotation.id, step, message
+                )));
+            }
+        }
+    }
+
+    let completed = sqlx::query_as::<_, IpRotation>(
+        "UPDATE ip_rotations SET status = 'completed', completed_at = now() WHERE id = $1 RETURNING *",
+    )
+    .bind(rotation.id)
+    .fetch_one(&state.db)
+    .await?;
+
+    log_audit(
+        &state.db,
+        user,
+        "rotate_ip",
+        "vps",
+        Some(&completed.vps_id.to_string()),
+        serde_json::json!({
+            "rotation_id": completed.id,
+            "old_ip": completed.old_ip,
+            "new_ip": completed.new_ip,
+            "dns_records_updated": completed.dns_records_updated,
+            "old_released": completed.old_released,
+        }),
+    )
+    .await;
+
+    Ok(completed)
+}
+
+fn next_step(step: &str) -> &'static str {
+    match step {
+        "allocate" => "associate",
+        "associate" => "update_vps",
+        "update_vps" => "update_dns",
+        "update_dns" => "release_old",
+        _ => "done",
+    }
+}
+
+/// Run one step, updating `rotation` in place. Returns details for the audit log.
+async fn run_step(
+    state: &AppState,
+    account: &CloudAccount,
+    rotation: &mut IpRotation,
+) -> Result<serde_json::Value, AppError> {
+    let region = rotation.region.as_str();
+    let instance_id = rotation.instance_id.as_str();
+
+    match rotation.step.as_str() {
+        "allocate" => {
+            let (allocation_id, new_ip) = match rotation.provider_type.as_str() {
+                "aws" => aws::allocate_ip(account, region).await?,
+                _ => alibaba::allocate_ip(account, region).await?,
+            };
+            rotation.allocation_id = Some(allocation_id.clone());
+            rotation.new_ip = Some(new_ip.clone());
+            Ok(serde_json::json!({"allocation_id": allocation_id, "new_ip": new_ip}))
+        }
+        "associate" => {
+            let allocation_id = rotation.allocation_id.as_deref().unwrap_or_default();
+            match rotation.provider_type.as_str() {
+                "aws" => aws::associate_ip(account, region, instance_id, allocation_id).await?,
+                _ => {
+                    alibaba::associate_ip(account, region, instance_id, allocation_id, &rotation.old_ip)
+                        .await?
+                }
+            }
+            Ok(serde_json::json!({"instance_id": instance_id, "allocation_id": allocation_id}))
+        }
+        "update_vps" => {
+            let new_ip = rotation.new_ip.clone().unwrap_or_default();
+            let mut tx = state.db.begin().await?;
+            let vps = sqlx::query_as::<_, Vps>("SELECT * FROM vps WHERE id = $1 FOR UPDATE")
+                .bind(rotation.vps_id)
+                .fetch_one(&mut *tx)
+                .await?;
+            let ips = replace_ip(&vps.ip_addresses.0, &rotation.old_ip, &new_ip);
+            set_change_context(&mut tx, SOURCE_CLOUD_ROTATE, &rotation.actor).await?;
+            sqlx::query("UPDATE vps SET ip_addresses = $2 WHERE id = $1")
+                .bind(rotation.vps_id)
+                .bind(sqlx::types::Json(&ips))
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+            Ok(serde_json::json!({"old_ip": rotation.old_ip, "new_ip": new_ip}))
+        }
+        "update_dns" => {
+            if !rotation.repoint_dns {
+                return Ok(serde_json::json!({"skipped": true}));
+            }
+            let new_ip = rotation.new_ip.clone().unwrap_or_default();
+            // Records already repointed by an earlier attempt no longer match old_ip
+            let records: Vec<(Uuid, String)> = sqlx::query_as(
+                r#"SELECT id, name FROM dns_records
+                   WHERE vps_id = $1 AND record_type IN ('A', 'AAAA') AND content = $2"#,
+            )
+            .bind(rotation.vps_id)
+            .bind(&rotation.old_ip)
+            .fetch_all(&state.db)
+            .await?;
+            let mut names = Vec::with_capacity(records.len());
+            for (record_id, name) in records {
+                repoint_record(state, record_id, &rotation.old_ip, &new_ip).await?;
+                rotation.dns_records_updated += 1;
+                names.push(name);
+            }
+            Ok(serde_json::json!({"records": names}))
+        }
+        "release_old" => {
+            if !rotation.release_old {
+                return Ok(serde_json::json!({"skipped": true}));
+            }
+            let released = match rotation.provider_type.as_str() {
+                "aws" => aws::release_ip(account, region, instance_id, &rotation.old_ip).await?,
+                _ => alibaba::release_ip(account, region, instance_id, &rotation.old_ip).await?,
+            };
+            rotation.old_released = released;
+            Ok(serde_json::json!({"ip": rotation.old_ip, "released": released}))
+        }
+        other => Err(AppError::Internal(format!("unknown rotation step '{other}'"))),
+    }
+}
+
+async fn save(state: &AppState, rotation: &IpRotation) -> Result<IpRotation, AppError> {
+    let row = sqlx::query_as::<_, IpRotation>(
+        r#"UPDATE ip_rotations SET
+               new_ip = $2, allocation_id = $3, step = $4, status = $5, error = $6,
+               dns_records_updated = $7, old_released = $8
+           WHERE id = $1
+           RETURNING *"#,
+    )
+    .bind(rotation.id)
+    .bind(&rotation.new_ip)
+    .bind(&rotation.allocation_id)
+    .bind(&rotation.step)
+    .bind(&rotation.status)
+    .bind(&rotation.error)
+    .bind(rotation.dns_records_updated)
+    .bind(rotation.old_released)
+    .fetch_one(&state.db)
+    .await?;
+    Ok(row)
+}
+
+/// Swap `old_ip` for `new_ip` in place, keeping its label. Idempotent on resume.
+fn replace_ip(entries: &[IpEntry], old_ip: &str, new_ip: &str) -> Vec<IpEntry> {
+    let mut out: Vec<IpEntry> = Vec::with_capacity(entries.len());
+    for e in entries {
+        let ip = e.ip.trim();
+        if ip != old_ip && ip != new_ip {
+            out.push(e.clone());
+        } else if !out.iter().any(|o| o.ip == new_ip) {
+            out.push(IpEntry { ip: new_ip.to_string(), label: e.label.clone() });
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(ip: &str, label: &str) -> IpEntry {
+        IpEntry { ip: ip.into(), label: label.into() }
+    }
+
+    #[test]
+    fn test_replace_ip_keeps_label() {
+        let ips = vec![entry("203.0.113.1", "china-telecom"), entry("10.0.0.5", "internal")];
+        let out = replace_ip(&ips, "203.0.113.1", "198.51.100.9");
+        assert_eq!(out, vec![entry("198.51.100.9", "china-telecom"), entry("10.0.0.5", "internal")]);
+        // Resuming after the VPS was already updated changes nothing
+        assert_eq!(replace_ip(&out, "203.0.113.1", "198.51.100.9").len(), 2);
+    }
+
+    #[test]
+    fn test_step_order() {
+        let mut step = "allocate";
+        let mut seen = vec![step];
+        while step != "done" {
+            step = next_step(step);
+            seen.push(step);
+        }
+        assert_eq!(
+            seen,
+            ["allocate", "associate", "update_vps", "update_dns", "release_old", "done"]
+        );
+    }
+}
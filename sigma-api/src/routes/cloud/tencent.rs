@@ -0,0 +1,679 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::errors::AppError;
+use crate::models::{CloudAccount, CloudSyncResult, IpEntry};
+use crate::routes::AppState;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const PAGE_SIZE: i64 = 100;
+
+/// Tencent Cloud product synced into `vps`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Product {
+    Cvm,
+    Lighthouse,
+}
+
+impl Product {
+    /// Config name, also the API service and endpoint host prefix.
+    fn name(self) -> &'static str {
+        match self {
+            Product::Cvm => "cvm",
+            Product::Lighthouse => "lighthouse",
+        }
+    }
+
+    fn version(self) -> &'static str {
+        match self {
+            Product::Cvm => "2017-03-12",
+            Product::Lighthouse => "2020-03-24",
+        }
+    }
+}
+
+/// Validate Tencent Cloud credentials by calling CVM DescribeRegions.
+pub async fn validate(config: &serde_json::Value) -> Result<(), AppError> {
+    let secret_id = config
+        .get("secret_id")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| AppError::BadRequest("Missing secret_id in config".into()))?;
+    let secret_key = config
+        .get("secret_key")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| AppError::BadRequest("Missing secret_key in config".into()))?;
+
+    let client = reqwest::Client::new();
+    let _resp = call_api(
+        &client,
+        secret_id,
+        secret_key,
+        Product::Cvm,
+        "ap-guangzhou",
+        "DescribeRegions",
+        &serde_json::json!({}),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Mask sensitive fields in Tencent Cloud config.
+pub fn mask_config(config: &serde_json::Value) -> serde_json::Value {
+    let secret_id = config
+        .get("secret_id")
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+    let regions = parse_regions(config);
+    let products: Vec<&str> = parse_products(config).into_iter().map(Product::name).collect();
+
+    serde_json::json!({
+        "secret_id": mask_key(secret_id),
+        "secret_key": "****",
+        "regions": regions,
+        "products": products,
+    })
+}
+
+/// Full sync: fetch CVM and Lighthouse instances across configured regions.
+pub async fn sync(state: &AppState, account: &CloudAccount) -> Result<CloudSyncResult, AppError> {
+    let secret_id = account
+        .config
+        .get("secret_id")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| AppError::Internal("Missing secret_id in config".into()))?;
+    let secret_key = account
+        .config
+        .get("secret_key")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| AppError::Internal("Missing secret_key in config".into()))?;
+    let regions = parse_regions(&account.config);
+    let products = parse_products(&account.config);
+
+    let provider_id = ensure_provider(state, "Tencent Cloud").await?;
+    let client = reqwest::Client::new();
+
+    let mut instances_found: i64 = 0;
+    let mut created: i64 = 0;
+    let mut updated: i64 = 0;
+    let merged: i64 = 0;
+    let mut seen_instance_ids: Vec<String> = Vec::new();
+
+    for product in &products {
+        for region in &regions {
+            let mut offset: i64 = 0;
+            loop {
+                let resp = call_api(
+                    &client,
+                    secret_id,
+                    secret_key,
+                    *product,
+                    region,
+                    "DescribeInstances",
+                    &serde_json::json!({ "Offset": offset, "Limit": PAGE_SIZE }),
+                )
+                .await?;
+
+                let instances = resp["InstanceSet"].as_array().cloned().unwrap_or_default();
+                if instances.is_empty() {
+                    break;
+                }
+
+                for inst in instances.iter().filter_map(|i| map_instance(*product, region, i)) {
+                    seen_instance_ids.push(inst.instance_id.clone());
+                    instances_found += 1;
+
+                    let ip_json = serde_json::to_value(&inst.ips).unwrap_or_default();
+
+                    // Upsert
+                    let existing_vps: Option<(Uuid, String, String)> = sqlx::query_as(
+                        "SELECT id, source, hostname FROM vps WHERE extra->>'cloud_instance_id' = $1",
+                    )
+                    .bind(&inst.instance_id)
+                    .fetch_optional(&state.db)
+                    .await?;
+
+                    if let Some((vps_id, _, _)) = existing_vps {
+                        sqlx::query(
+                            r#"UPDATE vps SET
+                                hostname = $2,
+                                ip_addresses = $3,
+                                status = $4,
+                                country = $5,
+                                city = $6,
+                                provider_id = $7,
+                                cloud_account_id = $8,
+                                cpu_cores = COALESCE($9, cpu_cores),
+                                ram_mb = COALESCE($10, ram_mb),
+                                disk_gb = COALESCE($11, disk_gb),
+                                purchase_date = COALESCE(purchase_date, $12),
+                                expire_date = COALESCE($13, expire_date),
+                                auto_renew = COALESCE($14, auto_renew),
+                                source = 'cloud-sync',
+                                extra = extra || $15::jsonb
+                            WHERE id = $1"#,
+                        )
+                        .bind(vps_id)
+                        .bind(&inst.hostname)
+                        .bind(&ip_json)
+                        .bind(inst.status)
+                        .bind(inst.country)
+                        .bind(inst.city)
+                        .bind(provider_id)
+                        .bind(account.id)
+                        .bind(inst.cpu_cores)
+                        .bind(inst.ram_mb)
+                        .bind(inst.disk_gb)
+                        .bind(inst.purchase_date)
+                        .bind(inst.expire_date)
+                        .bind(inst.auto_renew)
+                        .bind(&inst.extra)
+                        .execute(&state.db)
+                        .await?;
+                        updated += 1;
+                    } else {
+                        sqlx::query(
+                            r#"INSERT INTO vps (
+                                hostname, alias, provider_id,
+                                ip_addresses, ssh_port,
+                                country, city, dc_name,
+                                cpu_cores, ram_mb, disk_gb, bandwidth_tb,
+                                cost_monthly, currency,
+                                status, purchase_date, expire_date, auto_renew,
+                                purpose, vpn_protocol, tags,
+                                monitoring_enabled, node_exporter_port,
+                                extra, notes,
+                                source, cloud_account_id
+                            ) VALUES (
+                                $1, '', $2,
+                                $3, 22,
+                                $4, $5, $6,
+                                $7, $8, $9, NULL,
+                                NULL, 'CNY',
+                                $10, $11, $12, COALESCE($13, false),
+                                '', '', '{}',
+                                true, 9100,
+                                $14, '',
+                                'cloud-sync', $15
+                            )"#,
+                        )
+                        .bind(&inst.hostname)
+                        .bind(provider_id)
+                        .bind(&ip_json)
+                        .bind(inst.country)
+                        .bind(inst.city)
+                        .bind(&inst.zone)
+                        .bind(inst.cpu_cores)
+                        .bind(inst.ram_mb)
+                        .bind(inst.disk_gb)
+                        .bind(inst.status)
+                        .bind(inst.purchase_date)
+                        .bind(inst.expire_date)
+                        .bind(inst.auto_renew)
+                        .bind(&inst.extra)
+                        .bind(account.id)
+                        .execute(&state.db)
+                        .await?;
+                        created += 1;
+                    }
+                }
+
+                // Pagination
+                offset += instances.len() as i64;
+                if offset >= resp["TotalCount"].as_i64().unwrap_or(0) {
+                    break;
+                }
+            }
+        }
+    }
+
+    // Retire stale VPS
+    let retired = if !seen_instance_ids.is_empty() {
+        let result = sqlx::query(
+            r#"UPDATE vps SET status = 'retired', monitoring_enabled = false
+               WHERE cloud_account_id = $1
+                 AND source = 'cloud-sync'
+                 AND extra->>'cloud_instance_id' IS NOT NULL
+                 AND extra->>'cloud_instance_id' != ALL($2)
+                 AND status NOT IN ('retired', 'deleted')"#,
+        )
+        .bind(account.id)
+        .bind(&seen_instance_ids)
+        .execute(&state.db)
+        .await?;
+        result.rows_affected() as i64
+    } else {
+        0
+    };
+
+    Ok(CloudSyncResult {
+        instances_found,
+        created,
+        updated,
+        retired,
+        merged,
+    })
+}
+
+/// A CVM or Lighthouse instance mapped to VPS fields.
+#[derive(Debug)]
+struct Instance {
+    instance_id: String,
+    hostname: String,
+    ips: Vec<IpEntry>,
+    status: &'static str,
+    cpu_cores: Option<i16>,
+    ram_mb: Option<i32>,
+    disk_gb: Option<i32>,
+    country: &'static str,
+    city: &'static str,
+    zone: String,
+    purchase_date: Option<NaiveDate>,
+    /// Prepaid instances only
+    expire_date: Option<NaiveDate>,
+    /// None when the instance carries no renewal flag (pay-as-you-go)
+    auto_renew: Option<bool>,
+    extra: serde_json::Value,
+}
+
+/// Map a DescribeInstances entry. CVM and Lighthouse share most field names;
+/// they differ in where IPs and the zone live.
+fn map_instance(product: Product, region: &str, inst: &serde_json::Value) -> Option<Instance> {
+    let instance_id = inst["InstanceId"].as_str().filter(|s| !s.is_empty())?.to_string();
+    let hostname = inst["InstanceName"]
+        .as_str()
+        .filter(|s| !s.is_empty())
+        .unwrap_or(&instance_id)
+        .to_string();
+
+    let (public_key, private_key, zone) = match product {
+        Product::Cvm => ("PublicIpAddresses", "PrivateIpAddresses", &inst["Placement"]["Zone"]),
+        Product::Lighthouse => ("PublicAddresses", "PrivateAddresses", &inst["Zone"]),
+    };
+
+    // IPs
+    let mut ips: Vec<IpEntry> = Vec::new();
+    let mut push = |ip: &str, label: &str| {
+        if !ip.is_empty() && !ips.iter().any(|e| e.ip == ip) {
+            ips.push(IpEntry {
+                ip: ip.to_string(),
+                label: label.to_string(),
+            });
+        }
+    };
+    for ip in inst[public_key].as_array().into_iter().flatten().filter_map(|v| v.as_str()) {
+        push(ip, "overseas");
+    }
+    // CVM lists IPv6 as an array, Lighthouse as a single address
+    for ip in inst["IPv6Addresses"].as_array().into_iter().flatten().filter_map(|v| v.as_str()) {
+        push(ip, "overseas");
+    }
+    if let Some(ip) = inst["IPv6Address"].as_str() {
+        push(ip, "overseas");
+    }
+    for ip in inst[private_key].as_array().into_iter().flatten().filter_map(|v| v.as_str()) {
+        push(ip, "internal");
+    }
+
+    // Status mapping
+    let status = match inst["InstanceState"].as_str().unwrap_or("") {
+        "RUNNING" => "active",
+        "PENDING" | "STARTING" | "REBOOTING" => "provisioning",
+        "STOPPED" | "SHUTDOWN" | "FREEZING" | "LAUNCH_FAILED" => "suspended",
+        "STOPPING" | "TERMINATING" | "DELETING" => "retiring",
+        _ => "active",
+    };
+
+    let cpu_cores = inst["CPU"].as_i64().map(|v| v as i16);
+    // Memory is in GB
+    let ram_mb = inst["Memory"].as_i64().map(|gb| (gb * 1024) as i32);
+    let data_disks: i64 = inst["DataDisks"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|d| d["DiskSize"].as_i64())
+        .sum();
+    let disk_gb = inst["SystemDisk"]["DiskSize"].as_i64().map(|v| (v + data_disks) as i32);
+
+    let (country, city) = region_to_location(region);
+
+    let expire_date = parse_date(&inst["ExpiredTime"]);
+    let renew_flag = inst["RenewFlag"].as_str().unwrap_or("");
+    let auto_renew = (!renew_flag.is_empty()).then(|| renew_flag == "NOTIFY_AND_AUTO_RENEW");
+
+    let plan = match product {
+        Product::Cvm => inst["InstanceType"].as_str(),
+        Product::Lighthouse => inst["BundleId"].as_str(),
+    }
+    .unwrap_or("");
+
+    let extra = serde_json::json!({
+        "cloud_instance_id": instance_id,
+        "cloud_provider": "tencent",
+        "cloud_product": product.name(),
+        "cloud_region": region,
+        "zone_id": zone.as_str().unwrap_or(""),
+        "instance_type": plan,
+        "charge_type": inst["InstanceChargeType"].as_str().unwrap_or(""),
+        "renew_flag": renew_flag,
+    });
+
+    Some(Instance {
+        instance_id,
+        hostname,
+        ips,
+        status,
+        cpu_cores,
+        ram_mb,
+        disk_gb,
+        country,
+        city,
+        zone: zone.as_str().unwrap_or("").to_string(),
+        purchase_date: parse_date(&inst["CreatedTime"]),
+        expire_date,
+        auto_renew,
+        extra,
+    })
+}
+
+// ─── Tencent Cloud API (TC3-HMAC-SHA256) ────────────────
+
+async fn call_api(
+    client: &reqwest::Client,
+    secret_id: &str,
+    secret_key: &str,
+    product: Product,
+    region: &str,
+    action: &str,
+    payload: &serde_json::Value,
+) -> Result<serde_json::Value, AppError> {
+    let host = format!("{}.tencentcloudapi.com", product.name());
+    let body = payload.to_string();
+    let timestamp = Utc::now().timestamp();
+
+    let authorization = sign(
+        secret_id,
+        secret_key,
+        product.name(),
+        &host,
+        action,
+        &body,
+        timestamp,
+    );
+
+    let resp = client
+        .post(format!("https://{host}/"))
+        .header("Authorization", &authorization)
+        .header("Content-Type", "application/json; charset=utf-8")
+        .header("Host", &host)
+        .header("X-TC-Action", action)
+        .header("X-TC-Timestamp", timestamp.to_string())
+        .header("X-TC-Version", product.version())
+        .header("X-TC-Region", region)
+        .body(body)
+        .timeout(std::time::Duration::from_secs(30))
+        .send()
+        .await
+        .map_err(|e| AppError::Internal(format!("Tencent Cloud API request failed: {e}")))?;
+
+    let status = resp.status();
+    let body: serde_json::Value = resp
+        .json()
+        .await
+        .map_err(|e| AppError::Internal(format!("Tencent Cloud API response parse error: {e}")))?;
+
+    // Errors come back as HTTP 200 with Response.Error
+    if let Some(err) = body["Response"]["Error"].as_object() {
+        let code = err
+            .get("Code")
+            .and_then(|v| v.as_str())
+            .unwrap_or("Unknown");
+        let message = err
+            .get("Message")
+            .and_then(|v| v.as_str())
+            .unwrap_or("Unknown error");
+        return Err(AppError::BadRequest(format!(
+            "Tencent Cloud API error ({code}): {message}"
+        )));
+    }
+
+    if !status.is_success() {
+        return Err(AppError::Internal(format!(
+            "Tencent Cloud API HTTP error: {status}"
+        )));
+    }
+
+    Ok(body["Response"].clone())
+}
+
+/// Build the TC3-HMAC-SHA256 `Authorization` header for a JSON POST.
+fn sign(
+    secret_id: &str,
+    secret_key: &str,
+    service: &str,
+    host: &str,
+    action: &str,
+    body: &str,
+    timestamp: i64,
+) -> String {
+    let date = DateTime::from_timestamp(timestamp, 0)
+        .unwrap_or_default()
+        .format("%Y-%m-%d")
+        .to_string();
+
+    // Canonical request
+    let canonical_headers = format!(
+        "content-type:application/json; charset=utf-8\nhost:{}\nx-tc-action:{}\n",
+        host,
+        action.to_lowercase()
+    );
+    let signed_headers = "content-type;host;x-tc-action";
+    let canonical_request = format!(
+        "POST\n/\n\n{}\n{}\n{}",
+        canonical_headers,
+        signed_headers,
+        hex_sha256(body.as_bytes())
+    );
+
+    // Credential scope
+    let credential_scope = format!("{}/{}/tc3_request", date, service);
+
+    // String to sign
+    let string_to_sign = format!(
+        "TC3-HMAC-SHA256\n{}\n{}\n{}",
+        timestamp,
+        credential_scope,
+        hex_sha256(canonical_request.as_bytes())
+    );
+
+    // Derive signing key
+    let k_date = hmac_sha256(format!("TC3{secret_key}").as_bytes(), date.as_bytes());
+    let k_service = hmac_sha256(&k_date, service.as_bytes());
+    let k_signing = hmac_sha256(&k_service, b"tc3_request");
+
+    // Signature
+    let signature = hex::encode(hmac_sha256(&k_signing, string_to_sign.as_bytes()));
+
+    format!(
+        "TC3-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        secret_id, credential_scope, signed_headers, signature
+    )
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC key length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex_sha256(data: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    hex::encode(hasher.finalize())
+}
+
+// ─── Helpers ──────────────────────────────────────────────
+
+/// `2026-11-30T08:00:00Z` → 2026-11-30; None for absent or empty values.
+fn parse_date(v: &serde_json::Value) -> Option<NaiveDate> {
+    let s = v.as_str().filter(|s| !s.is_empty())?;
+    DateTime::parse_from_rfc3339(s)
+        .ok()
+        .map(|t| t.with_timezone(&Utc).date_naive())
+}
+
+fn parse_regions(config: &serde_json::Value) -> Vec<String> {
+    config
+        .get("regions")
+        .and_then(|v| v.as_array())
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                .collect()
+        })
+        .unwrap_or_else(|| vec!["ap-guangzhou".to_string()])
+}
+
+/// `products` in the config, e.g. `["lighthouse"]`; both when absent.
+fn parse_products(config: &serde_json::Value) -> Vec<Product> {
+    let products: Vec<Product> = config
+        .get("products")
+        .and_then(|v| v.as_array())
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|v| match v.as_str()? {
+                    "cvm" => Some(Product::Cvm),
+                    "lighthouse" => Some(Product::Lighthouse),
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    if products.is_empty() {
+        vec![Product::Cvm, Product::Lighthouse]
+    } else {
+        products
+    }
+}
+
+fn mask_key(key: &str) -> String {
+    if key.len() <= 8 {
+        "****".into()
+    } else {
+        format!("{}...{}", &key[..4], &key[key.len() - 4..])
+    }
+}
+
+/// Ensure a Provider record for Tencent Cloud exists, return its ID.
+async fn ensure_provider(state: &AppState, name: &str) -> Result<Uuid, AppError> {
+    let existing: Option<(Uuid,)> =
+        sqlx::query_as("SELECT id FROM providers WHERE LOWER(name) = LOWER($1) LIMIT 1")
+            .bind(name)
+            .fetch_optional(&state.db)
+            .await?;
+
+    if let Some((id,)) = existing {
+        Ok(id)
+    } else {
+        let (id,): (Uuid,) = sqlx::query_as(
+            "INSERT INTO providers (name, country, website, panel_url, api_supported, notes) VALUES ($1, 'CN', 'https://cloud.tencent.com', 'https://console.cloud.tencent.com', true, 'Auto-created by cloud sync') RETURNING id",
+        )
+        .bind(name)
+        .fetch_one(&state.db)
+        .await?;
+        Ok(id)
+    }
+}
+
+/// Map Tencent Cloud region to (ISO country code, city).
+fn region_to_location(region: &str) -> (&'static str, &'static str) {
+    match region {
+        "ap-guangzhou" => ("CN", "Guangzhou"),
+        "ap-shanghai" | "ap-shanghai-fsi" => ("CN", "Shanghai"),
+        "ap-beijing" | "ap-beijing-fsi" => ("CN", "Beijing"),
+        "ap-nanjing" => ("CN", "Nanjing"),
+        "ap-chengdu" => ("CN", "Chengdu"),
+        "ap-chongqing" => ("CN", "Chongqing"),
+        "ap-shenzhen-fsi" => ("CN", "Shenzhen"),
+        "ap-hongkong" => ("HK", "Hong Kong"),
+        "ap-taipei" => ("TW", "Taipei"),
+        "ap-singapore" => ("SG", "Singapore"),
+        "ap-bangkok" => ("TH", "Bangkok"),
+        "ap-jakarta" => ("ID", "Jakarta"),
+        "ap-seoul" => ("KR", "Seoul"),
+        "ap-tokyo" => ("JP", "Tokyo"),
+        "ap-mumbai" => ("IN", "Mumbai"),
+        "na-siliconvalley" => ("US", "Silicon Valley"),
+        "na-ashburn" => ("US", "Ashburn"),
+        "na-toronto" => ("CA", "Toronto"),
+        "sa-saopaulo" => ("BR", "São Paulo"),
+        "eu-frankfurt" => ("DE", "Frankfurt"),
+        "eu-moscow" => ("RU", "Moscow"),
+        _ => ("", ""),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_lighthouse_instance() {
+        let inst = serde_json::json!({
+            "InstanceId": "lhins-abc123",
+            "InstanceName": "hk-edge-1",
+            "InstanceState": "RUNNING",
+            "Zone": "ap-hongkong-2",
+            "BundleId": "bundle_starter_mc_med2_01",
+            "CPU": 2,
+            "Memory": 2,
+            "SystemDisk": {"DiskSize": 50},
+            "PublicAddresses": ["203.0.113.8"],
+            "PrivateAddresses": ["10.0.4.8"],
+            "InstanceChargeType": "PREPAID",
+            "RenewFlag": "NOTIFY_AND_AUTO_RENEW",
+            "CreatedTime": "2026-01-05T03:11:00Z",
+            "ExpiredTime": "2026-12-05T03:11:00Z",
+        });
+        let mapped = map_instance(Product::Lighthouse, "ap-hongkong", &inst).unwrap();
+        assert_eq!(mapped.status, "active");
+        assert_eq!((mapped.country, mapped.city), ("HK", "Hong Kong"));
+        assert_eq!((mapped.cpu_cores, mapped.ram_mb, mapped.disk_gb), (Some(2), Some(2048), Some(50)));
+        assert_eq!(mapped.ips.len(), 2);
+        assert_eq!(mapped.ips[1].label, "internal");
+        assert_eq!(mapped.expire_date, NaiveDate::from_ymd_opt(2026, 12, 5));
+        assert_eq!(mapped.auto_renew, Some(true));
+        assert_eq!(mapped.zone, "ap-hongkong-2");
+    }
+
+    #[test]
+    fn test_map_cvm_postpaid_instance() {
+        let inst = serde_json::json!({
+            "InstanceId": "ins-xyz789",
+            "InstanceName": "",
+            "InstanceState": "STOPPED",
+            "Placement": {"Zone": "ap-tokyo-1"},
+            "InstanceType": "S5.MEDIUM4",
+            "CPU": 2,
+            "Memory": 4,
+            "SystemDisk": {"DiskSize": 50},
+            "DataDisks": [{"DiskSize": 100}],
+            "PublicIpAddresses": ["198.51.100.4"],
+            "IPv6Addresses": ["2001:db8::4"],
+            "PrivateIpAddresses": ["172.16.0.4"],
+            "InstanceChargeType": "POSTPAID_BY_HOUR",
+            "RenewFlag": null,
+            "ExpiredTime": null,
+        });
+        let mapped = map_instance(Product::Cvm, "ap-tokyo", &inst).unwrap();
+        assert_eq!(mapped.hostname, "ins-xyz789");
+        assert_eq!(mapped.status, "suspended");
+        assert_eq!(mapped.disk_gb, Some(150));
+        assert_eq!(mapped.ips.len(), 3);
+        assert_eq!(mapped.expire_date, None);
+        assert_eq!(mapped.auto_renew, None);
+    }
+}
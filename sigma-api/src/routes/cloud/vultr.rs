@@ -0,0 +1,423 @@
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use crate::errors::AppError;
+use crate::models::{CloudAccount, CloudSyncResult, IpEntry};
+use crate::routes::AppState;
+
+const API_BASE: &str = "https://api.vultr.com/v2";
+const PER_PAGE: &str = "500";
+
+/// Validate Vultr credentials by calling GET /v2/account.
+pub async fn validate(
+    http_client: &reqwest::Client,
+    config: &serde_json::Value,
+) -> Result<(), AppError> {
+    let api_key = config
+        .get("api_key")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| AppError::BadRequest("Missing api_key in config".into()))?;
+
+    let resp = http_client
+        .get(format!("{API_BASE}/account"))
+        .bearer_auth(api_key)
+        .timeout(std::time::Duration::from_secs(15))
+        .send()
+        .await
+        .map_err(|e| AppError::BadRequest(format!("Vultr API request failed: {e}")))?;
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp.text().await.unwrap_or_default();
+        return Err(AppError::BadRequest(format!(
+            "Vultr auth failed ({status}): {body}"
+        )));
+    }
+
+    Ok(())
+}
+
+/// Mask sensitive fields in Vultr config.
+pub fn mask_config(config: &serde_json::Value) -> serde_json::Value {
+    let api_key = config
+        .get("api_key")
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+
+    serde_json::json!({
+        "api_key": mask_key(api_key),
+    })
+}
+
+/// Full sync: fetch all cloud compute instances from Vultr.
+pub async fn sync(state: &AppState, account: &CloudAccount) -> Result<CloudSyncResult, AppError> {
+    let api_key = account
+        .config
+        .get("api_key")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| AppError::Internal("Missing api_key in config".into()))?;
+
+    let provider_id = ensure_provider(state, "Vultr").await?;
+
+    // Plan id → monthly cost and bandwidth; instances only carry the plan id
+    let plans: HashMap<String, serde_json::Value> =
+        get_paginated(state, api_key, "plans", "plans")
+            .await?
+            .into_iter()
+            .filter_map(|p| Some((p["id"].as_str()?.to_string(), p)))
+            .collect();
+
+    let mut instances_found: i64 = 0;
+    let mut created: i64 = 0;
+    let mut updated: i64 = 0;
+    let merged: i64 = 0;
+    let mut seen_instance_ids: Vec<String> = Vec::new();
+
+    for instance in get_paginated(state, api_key, "instances", "instances").await? {
+        let instance_id = instance["id"].as_str().unwrap_or("").to_string();
+        if instance_id.is_empty() {
+            continue;
+        }
+        seen_instance_ids.push(instance_id.clone());
+        instances_found += 1;
+
+        let hostname = [&instance["label"], &instance["hostname"]]
+            .iter()
+            .filter_map(|v| v.as_str())
+            .find(|s| !s.is_empty())
+            .unwrap_or(&instance_id)
+            .to_string();
+
+        // Additional IPv4s are only listed by the per-instance endpoint
+        let ipv4s = get_paginated(state, api_key, &format!("instances/{instance_id}/ipv4"), "ipv4s")
+            .await?;
+        let ips = instance_ips(&instance, &ipv4s);
+        let ip_json = serde_json::to_value(&ips).unwrap_or_default();
+
+        // Status mapping: `status` is the subscription, `power_status` the VM
+        let status = match (
+            instance["status"].as_str().unwrap_or(""),
+            instance["power_status"].as_str().unwrap_or(""),
+        ) {
+            ("pending", _) => "provisioning",
+            ("suspended", _) | (_, "stopped") => "suspended",
+            _ => "active",
+        };
+
+        let vcpus = instance["vcpu_count"].as_i64().map(|v| v as i16);
+        let memory_mb = instance["ram"].as_i64().map(|v| v as i32);
+        let disk_gb = instance["disk"].as_i64().map(|v| v as i32);
+
+        let region = instance["region"].as_str().unwrap_or("");
+        let (country, city) = region_to_location(region);
+
+        let plan_id = instance["plan"].as_str().unwrap_or("");
+        let plan = plans.get(plan_id);
+        let cost_monthly = plan
+            .and_then(|p| p["monthly_cost"].as_f64())
+            .and_then(rust_decimal::Decimal::from_f64_retain);
+        // Plan bandwidth is in GB
+        let bandwidth_tb = plan
+            .and_then(|p| p["bandwidth"].as_f64())
+            .and_then(|gb| rust_decimal::Decimal::from_f64_retain(gb / 1000.0));
+
+        let extra = serde_json::json!({
+            "cloud_instance_id": instance_id,
+            "cloud_provider": "vultr",
+            "cloud_region": region,
+            "plan": plan_id,
+        });
+
+        // Upsert
+        let existing_vps: Option<(Uuid, String, String)> = sqlx::query_as(
+            "SELECT id, source, hostname FROM vps WHERE extra->>'cloud_instance_id' = $1",
+        )
+        .bind(&instance_id)
+        .fetch_optional(&state.db)
+        .await?;
+
+        if let Some((vps_id, _, _)) = existing_vps {
+            sqlx::query(
+                r#"UPDATE vps SET
+                    hostname = $2,
+                    ip_addresses = $3,
+                    status = $4,
+                    country = $5,
+                    city = $6,
+                    provider_id = $7,
+                    cloud_account_id = $8,
+                    cpu_cores = COALESCE($9, cpu_cores),
+                    ram_mb = COALESCE($10, ram_mb),
+                    disk_gb = COALESCE($11, disk_gb),
+                    bandwidth_tb = COALESCE($12, bandwidth_tb),
+                    cost_monthly = COALESCE($13, cost_monthly),
+                    currency = 'USD',
+                    source = 'cloud-sync',
+                    extra = extra || $14::jsonb
+                WHERE id = $1"#,
+            )
+            .bind(vps_id)
+            .bind(&hostname)
+            .bind(&ip_json)
+            .bind(status)
+            .bind(country)
+            .bind(city)
+            .bind(provider_id)
+            .bind(account.id)
+            .bind(vcpus)
+            .bind(memory_mb)
+            .bind(disk_gb)
+            .bind(bandwidth_tb)
+            .bind(cost_monthly)
+            .bind(&extra)
+            .execute(&state.db)
+            .await?;
+            updated += 1;
+        } else {
+            sqlx::query(
+                r#"INSERT INTO vps (
+                    hostname, alias, provider_id,
+                    ip_addresses, ssh_port,
+                    country, city, dc_name,
+                    cpu_cores, ram_mb, disk_gb, bandwidth_tb,
+                    cost_monthly, currency,
+                    status, purchase_date, expire_date,
+                    purpose, vpn_protocol, tags,
+                    monitoring_enabled, node_exporter_port,
+                    extra, notes,
+                    source, cloud_account_id
+                ) VALUES (
+                    $1, '', $2,
+                    $3, 22,
+                    $4, $5, '',
+                    $6, $7, $8, $9,
+                    $10, 'USD',
+                    $11, NULL, NULL,
+                    '', '', '{}',
+                    true, 9100,
+                    $12, '',
+                    'cloud-sync', $13
+                )"#,
+            )
+            .bind(&hostname)
+            .bind(provider_id)
+            .bind(&ip_json)
+            .bind(country)
+            .bind(city)
+            .bind(vcpus)
+            .bind(memory_mb)
+            .bind(disk_gb)
+            .bind(bandwidth_tb)
+            .bind(cost_monthly)
+            .bind(status)
+            .bind(&extra)
+            .bind(account.id)
+            .execute(&state.db)
+            .await?;
+            created += 1;
+        }
+    }
+
+    // Retire stale VPS: those linked to this account but not seen in sync
+    let retired = if !seen_instance_ids.is_empty() {
+        let result = sqlx::query(
+            r#"UPDATE vps SET status = 'retired', monitoring_enabled = false
+               WHERE cloud_account_id = $1
+                 AND source = 'cloud-sync'
+                 AND extra->>'cloud_instance_id' IS NOT NULL
+                 AND extra->>'cloud_instance_id' != ALL($2)
+                 AND status NOT IN ('retired', 'deleted')"#,
+        )
+        .bind(account.id)
+        .bind(&seen_instance_ids)
+        .execute(&state.db)
+        .await?;
+        result.rows_affected() as i64
+    } else {
+        0
+    };
+
+    Ok(CloudSyncResult {
+        instances_found,
+        created,
+        updated,
+        retired,
+        merged,
+    })
+}
+
+// ─── Helpers ──────────────────────────────────────────────
+
+/// GET a Vultr list endpoint, following `meta.links.next` cursors, and
+/// collect the array under `key`.
+async fn get_paginated(
+    state: &AppState,
+    api_key: &str,
+    path: &str,
+    key: &str,
+) -> Result<Vec<serde_json::Value>, AppError> {
+    let mut items = Vec::new();
+    let mut cursor = String::new();
+    loop {
+        let mut query = vec![("per_page", PER_PAGE.to_string())];
+        if !cursor.is_empty() {
+            query.push(("cursor", cursor.clone()));
+        }
+
+        let resp = state
+            .http_client
+            .get(format!("{API_BASE}/{path}"))
+            .bearer_auth(api_key)
+            .query(&query)
+            .timeout(std::time::Duration::from_secs(30))
+            .send()
+            .await
+            .map_err(|e| AppError::Internal(format!("Vultr API error: {e}")))?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            return Err(AppError::Internal(format!(
+                "Vultr list {key} failed ({status}): {body}"
+            )));
+        }
+
+        let body: serde_json::Value = resp
+            .json()
+            .await
+            .map_err(|e| AppError::Internal(format!("Vultr response parse error: {e}")))?;
+
+        items.extend(body[key].as_array().cloned().unwrap_or_default());
+
+        cursor = body["meta"]["links"]["next"].as_str().unwrap_or("").to_string();
+        if cursor.is_empty() {
+            break;
+        }
+    }
+    Ok(items)
+}
+
+/// IPs of an instance: main and additional IPv4s, main IPv6 and the VPC
+/// address (labelled `internal`). `ipv4s` is the /instances/{id}/ipv4 list.
+fn instance_ips(instance: &serde_json::Value, ipv4s: &[serde_json::Value]) -> Vec<IpEntry> {
+    let mut ips: Vec<IpEntry> = Vec::new();
+    let mut push = |ip: Option<&str>, label: &str| {
+        // Vultr reports unassigned addresses as "0.0.0.0" or ""
+        if let Some(ip) = ip.filter(|ip| !ip.is_empty() && *ip != "0.0.0.0") {
+            if !ips.iter().any(|e| e.ip == ip) {
+                ips.push(IpEntry {
+                    ip: ip.to_string(),
+                    label: label.to_string(),
+                });
+            }
+        }
+    };
+
+    push(instance["main_ip"].as_str(), "overseas");
+    for v4 in ipv4s {
+        push(v4["ip"].as_str(), "overseas");
+    }
+    push(instance["v6_main_ip"].as_str(), "overseas");
+    push(instance["internal_ip"].as_str(), "internal");
+    ips
+}
+
+fn mask_key(key: &str) -> String {
+    if key.len() <= 8 {
+        "****".into()
+    } else {
+        format!("{}...{}", &key[..4], &key[key.len() - 4..])
+    }
+}
+
+/// Ensure a Provider record for Vultr exists, return its ID.
+async fn ensure_provider(state: &AppState, name: &str) -> Result<Uuid, AppError> {
+    let existing: Option<(Uuid,)> =
+        sqlx::query_as("SELECT id FROM providers WHERE LOWER(name) = LOWER($1) LIMIT 1")
+            .bind(name)
+            .fetch_optional(&state.db)
+            .await?;
+
+    if let Some((id,)) = existing {
+        Ok(id)
+    } else {
+        let (id,): (Uuid,) = sqlx::query_as(
+            "INSERT INTO providers (name, country, website, panel_url, api_supported, notes) VALUES ($1, '', 'https://www.vultr.com', 'https://my.vultr.com', true, 'Auto-created by cloud sync') RETURNING id",
+        )
+        .bind(name)
+        .fetch_one(&state.db)
+        .await?;
+        Ok(id)
+    }
+}
+
+/// Map Vultr region id to (ISO country code, city).
+fn region_to_location(region: &str) -> (&'static str, &'static str) {
+    match region {
+        "ewr" => ("US", "New Jersey"),
+        "ord" => ("US", "Chicago"),
+        "dfw" => ("US", "Dallas"),
+        "sea" => ("US", "Seattle"),
+        "lax" => ("US", "Los Angeles"),
+        "atl" => ("US", "Atlanta"),
+        "sjc" => ("US", "Silicon Valley"),
+        "mia" => ("US", "Miami"),
+        "hnl" => ("US", "Honolulu"),
+        "yto" => ("CA", "Toronto"),
+        "mex" => ("MX", "Mexico City"),
+        "sao" => ("BR", "São Paulo"),
+        "scl" => ("CL", "Santiago"),
+        "ams" => ("NL", "Amsterdam"),
+        "lhr" => ("GB", "London"),
+        "man" => ("GB", "Manchester"),
+        "fra" => ("DE", "Frankfurt"),
+        "cdg" => ("FR", "Paris"),
+        "mad" => ("ES", "Madrid"),
+        "sto" => ("SE", "Stockholm"),
+        "waw" => ("PL", "Warsaw"),
+        "tlv" => ("IL", "Tel Aviv"),
+        "jnb" => ("ZA", "Johannesburg"),
+        "nrt" => ("JP", "Tokyo"),
+        "itm" | "osa" => ("JP", "Osaka"),
+        "icn" => ("KR", "Seoul"),
+        "sgp" => ("SG", "Singapore"),
+        "bom" => ("IN", "Mumbai"),
+        "del" => ("IN", "Delhi"),
+        "blr" => ("IN", "Bangalore"),
+        "syd" => ("AU", "Sydney"),
+        "mel" => ("AU", "Melbourne"),
+        _ => ("", ""),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instance_ips() {
+        let instance = serde_json::json!({
+            "main_ip": "203.0.113.10",
+            "v6_main_ip": "2001:db8::10",
+            "internal_ip": "10.1.96.3",
+        });
+        let ipv4s = vec![
+            serde_json::json!({"ip": "203.0.113.10", "type": "main_ip"}),
+            serde_json::json!({"ip": "203.0.113.11", "type": "secondary_ip"}),
+        ];
+        let ips: Vec<(String, String)> = instance_ips(&instance, &ipv4s)
+            .into_iter()
+            .map(|e| (e.ip, e.label))
+            .collect();
+        assert_eq!(
+            ips,
+            [
+                ("203.0.113.10".to_string(), "overseas".to_string()),
+                ("203.0.113.11".to_string(), "overseas".to_string()),
+                ("2001:db8::10".to_string(), "overseas".to_string()),
+                ("10.1.96.3".to_string(), "internal".to_string()),
+            ]
+        );
+    }
+}
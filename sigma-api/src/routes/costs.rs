@@ -5,10 +5,12 @@ use axum::{
 };
 use rust_decimal::Decimal;
 
+use crate::db::Db;
 use crate::errors::{AppError, ErrorResponse};
 use crate::models::{
-    ConvertedTotal, CostMonthlyQuery, CostMonthlyResponse, CostSummaryQuery, CostSummaryResponse,
-    CostSummaryRow, CurrencyBreakdown, MonthlyCostEntry, MonthlyCostRow,
+    ConvertedTotal, CostCompareQuery, CostCompareResponse, CostMonthlyQuery, CostMonthlyResponse,
+    CostSummaryQuery, CostSummaryResponse, CostSummaryRow, CurrencyBreakdown, MonthlyCostEntry,
+    MonthlyCostRow, PlanCostComparison, ProviderPlan,
 };
 use crate::routes::AppState;
 
@@ -16,6 +18,8 @@ pub fn router() -> Router<AppState> {
     Router::new()
         .route("/api/costs/summary", get(summary))
         .route("/api/costs/monthly", get(monthly))
+        .route("/api/costs/spend", get(spend))
+        .route("/api/costs/compare", get(compare))
 }
 
 #[utoipa::path(
@@ -198,8 +202,251 @@ pub async fn monthly(
     }))
 }
 
+/// Actual spend per month, from the renewal ledger (grouped by `paid_at`).
+#[utoipa::path(
+    get, path = "/api/costs/spend",
+    tag = "Costs",
+    params(CostMonthlyQuery),
+    responses(
+        (status = 200, body = CostMonthlyResponse),
+        (status = 400, body = ErrorResponse),
+        (status = 500, body = ErrorResponse),
+    )
+)]
+pub async fn spend(
+    State(state): State<AppState>,
+    Query(q): Query<CostMonthlyQuery>,
+) -> Result<Json<CostMonthlyResponse>, AppError> {
+    let months = q.months.unwrap_or(12).clamp(1, 120);
+
+    let mut where_parts: Vec<String> = vec![
+        "r.paid_at >= date_trunc('month', NOW()) - ($1 - 1) * INTERVAL '1 month'".to_string(),
+    ];
+    let mut bindings: Vec<QueryBinding> = Vec::new();
+
+    // $1 is months
+    bindings.push(QueryBinding::Int(months));
+
+    if let Some(ref provider_id) = q.provider_id {
+        bindings.push(QueryBinding::Uuid(*provider_id));
+        where_parts.push(format!("v.provider_id = ${}", bindings.len()));
+    }
+
+    if let Some(ref country) = q.country {
+        bindings.push(QueryBinding::Text(country.clone()));
+        where_parts.push(format!("v.country = ${}", bindings.len()));
+    }
+
+    let sql = format!(
+        r#"SELECT
+            date_trunc('month', r.paid_at)::date AS month,
+            r.currency,
+            COUNT(DISTINCT r.vps_id) AS vps_count,
+            COALESCE(SUM(r.amount), 0) AS total_cost
+        FROM vps_renewals r
+        JOIN vps v ON v.id = r.vps_id
+        WHERE {}
+        GROUP BY 1, r.currency
+        ORDER BY 1 DESC, r.currency"#,
+        where_parts.join(" AND ")
+    );
+
+    let mut query = sqlx::query_as::<_, SpendRow>(&sql);
+    for binding in &bindings {
+        match binding {
+            QueryBinding::Int(v) => query = query.bind(v),
+            QueryBinding::Text(v) => query = query.bind(v),
+            QueryBinding::Uuid(v) => query = query.bind(v),
+        }
+    }
+
+    let rows = query.fetch_all(&state.db).await?;
+
+    let mut month_map: std::collections::BTreeMap<chrono::NaiveDate, Vec<CurrencyBreakdown>> =
+        std::collections::BTreeMap::new();
+    for row in rows {
+        month_map.entry(row.month).or_default().push(CurrencyBreakdown {
+            currency: row.currency,
+            vps_count: row.vps_count,
+            total_cost: row.total_cost,
+        });
+    }
+
+    let mut months_result: Vec<MonthlyCostEntry> = Vec::new();
+    for (month, per_currency) in month_map.into_iter().rev() {
+        let converted_total = if let Some(ref target_currency) = q.convert_to {
+            Some(convert_totals(&state, &per_currency, target_currency).await?)
+        } else {
+            None
+        };
+        months_result.push(MonthlyCostEntry {
+            month,
+            per_currency,
+            converted_total,
+        });
+    }
+
+    Ok(Json(CostMonthlyResponse {
+        months: months_result,
+    }))
+}
+
+const COMPARE_SORTS: &[&str] = &["monthly", "per_core", "per_gb_ram", "per_gb_disk", "per_tb_bandwidth"];
+
+/// Compare provider plans by monthly price and by price per core, GB of RAM,
+/// GB of disk and TB of bandwidth.
+#[utoipa::path(
+    get, path = "/api/costs/compare",
+    tag = "Costs",
+    params(CostCompareQuery),
+    responses(
+        (status = 200, body = CostCompareResponse),
+        (status = 400, body = ErrorResponse),
+        (status = 500, body = ErrorResponse),
+    )
+)]
+pub async fn compare(
+    State(state): State<AppState>,
+    Query(q): Query<CostCompareQuery>,
+) -> Result<Json<CostCompareResponse>, AppError> {
+    let sort = q.sort.as_deref().unwrap_or("monthly");
+    if !COMPARE_SORTS.contains(&sort) {
+        return Err(AppError::BadRequest(format!(
+            "Invalid sort '{}' (allowed: {})",
+            sort,
+            COMPARE_SORTS.join(", ")
+        )));
+    }
+
+    let mut where_parts: Vec<String> = vec!["TRUE".to_string()];
+    let mut bindings: Vec<QueryBinding> = Vec::new();
+
+    if let Some(ref provider_id) = q.provider_id {
+        bindings.push(QueryBinding::Uuid(*provider_id));
+        where_parts.push(format!("pp.provider_id = ${}", bindings.len()));
+    }
+
+    if let Some(ref location) = q.location {
+        bindings.push(QueryBinding::Text(location.clone()));
+        where_parts.push(format!("${} = ANY(pp.locations)", bindings.len()));
+    }
+
+    let sql = format!(
+        r#"SELECT pp.*, p.name AS provider_name
+           FROM provider_plans pp
+           JOIN providers p ON p.id = pp.provider_id
+           WHERE {}
+           ORDER BY p.name, pp.name"#,
+        where_parts.join(" AND ")
+    );
+
+    let mut query = sqlx::query_as::<_, PlanRow>(&sql);
+    for binding in &bindings {
+        match binding {
+            QueryBinding::Text(v) => query = query.bind(v),
+            QueryBinding::Uuid(v) => query = query.bind(v),
+            QueryBinding::Int(v) => query = query.bind(v),
+        }
+    }
+
+    let rows = query.fetch_all(&state.db).await?;
+
+    let rates = match q.convert_to {
+        Some(ref target) => Some(load_rates(&state.db, target).await?),
+        None => None,
+    };
+
+    let mut plans = Vec::with_capacity(rows.len());
+    for row in rows {
+        let monthly = row.plan.price / Decimal::from(row.plan.billing_cycle_months);
+        let (currency, monthly) = match (&q.convert_to, &rates) {
+            (Some(target), Some(rates)) if &row.plan.currency != target => {
+                let rate = rates.get(&row.plan.currency).ok_or_else(|| {
+                    AppError::BadRequest(format!(
+                        "No exchange rate found for {} -> {}",
+                        row.plan.currency, target
+                    ))
+                })?;
+                (target.clone(), monthly * rate)
+            }
+            _ => (row.plan.currency.clone(), monthly),
+        };
+        plans.push(plan_comparison(row.plan, row.provider_name, currency, monthly));
+    }
+
+    // Plans missing the sorted-on spec go last
+    plans.sort_by(|a, b| {
+        let key = |p: &PlanCostComparison| match sort {
+            "per_core" => p.per_core,
+            "per_gb_ram" => p.per_gb_ram,
+            "per_gb_disk" => p.per_gb_disk,
+            "per_tb_bandwidth" => p.per_tb_bandwidth,
+            _ => Some(p.monthly),
+        };
+        match (key(a), key(b)) {
+            (Some(x), Some(y)) => x.cmp(&y),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        }
+    });
+
+    Ok(Json(CostCompareResponse { plans }))
+}
+
 // ─── Helpers ─────────────────────────────────────────────
 
+#[derive(sqlx::FromRow)]
+struct PlanRow {
+    #[sqlx(flatten)]
+    plan: ProviderPlan,
+    provider_name: String,
+}
+
+/// Unit prices for a plan whose monthly price (in `currency`) is `monthly`.
+fn plan_comparison(
+    plan: ProviderPlan,
+    provider_name: String,
+    currency: String,
+    monthly: Decimal,
+) -> PlanCostComparison {
+    let per = |units: Option<Decimal>| {
+        units
+            .filter(|u| *u > Decimal::ZERO)
+            .map(|u| (monthly / u).round_dp(4))
+    };
+    let per_core = per(plan.cpu_cores.map(Decimal::from));
+    let per_gb_ram = per(plan.ram_mb.map(|mb| Decimal::from(mb) / Decimal::from(1024)));
+    let per_gb_disk = per(plan.disk_gb.map(Decimal::from));
+    let per_tb_bandwidth = per(plan.bandwidth_tb);
+
+    PlanCostComparison {
+        plan_id: plan.id,
+        plan_name: plan.name,
+        provider_id: plan.provider_id,
+        provider_name,
+        cpu_cores: plan.cpu_cores,
+        ram_mb: plan.ram_mb,
+        disk_gb: plan.disk_gb,
+        bandwidth_tb: plan.bandwidth_tb,
+        locations: plan.locations,
+        currency,
+        monthly: monthly.round_dp(2),
+        per_core,
+        per_gb_ram,
+        per_gb_disk,
+        per_tb_bandwidth,
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct SpendRow {
+    month: chrono::NaiveDate,
+    currency: String,
+    vps_count: i64,
+    total_cost: Decimal,
+}
+
 enum QueryBinding {
     Text(String),
     Uuid(uuid::Uuid),
@@ -211,15 +458,7 @@ async fn convert_totals(
     per_currency: &[CurrencyBreakdown],
     target_currency: &str,
 ) -> Result<ConvertedTotal, AppError> {
-    let rates = sqlx::query_as::<_, (String, Decimal)>(
-        "SELECT from_currency, rate FROM exchange_rates WHERE to_currency = $1",
-    )
-    .bind(target_currency)
-    .fetch_all(&state.db)
-    .await?;
-
-    let rate_map: std::collections::HashMap<String, Decimal> =
-        rates.into_iter().collect();
+    let rate_map = load_rates(&state.db, target_currency).await?;
 
     let mut total = Decimal::ZERO;
     for entry in per_currency {
@@ -240,3 +479,54 @@ async fn convert_totals(
         amount: total,
     })
 }
+
+/// Exchange rates into `target_currency`, keyed by source currency.
+pub(crate) async fn load_rates(
+    db: &Db,
+    target_currency: &str,
+) -> Result<std::collections::HashMap<String, Decimal>, AppError> {
+    let rates = sqlx::query_as::<_, (String, Decimal)>(
+        "SELECT from_currency, rate FROM exchange_rates WHERE to_currency = $1",
+    )
+    .bind(target_currency)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rates.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(cpu: Option<i16>, ram_mb: Option<i32>, disk_gb: Option<i32>) -> ProviderPlan {
+        ProviderPlan {
+            id: uuid::Uuid::nil(),
+            provider_id: uuid::Uuid::nil(),
+            name: "s-2vcpu-4gb".into(),
+            cpu_cores: cpu,
+            ram_mb,
+            disk_gb,
+            bandwidth_tb: None,
+            price: Decimal::from(24),
+            currency: "USD".into(),
+            billing_cycle_months: 1,
+            locations: vec![],
+            stock_url: String::new(),
+            notes: String::new(),
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_plan_comparison_unit_prices() {
+        let p = plan(Some(2), Some(4096), Some(0));
+        let row = plan_comparison(p, "Acme".into(), "USD".into(), Decimal::from(24));
+        assert_eq!(row.monthly, Decimal::from(24));
+        assert_eq!(row.per_core, Some(Decimal::from(12)));
+        assert_eq!(row.per_gb_ram, Some(Decimal::from(6)));
+        assert_eq!(row.per_gb_disk, None);
+        assert_eq!(row.per_tb_bandwidth, None);
+    }
+}
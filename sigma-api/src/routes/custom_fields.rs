@@ -0,0 +1,272 @@
+use axum::{
+    extract::{Path, Query, State},
+    routing::get,
+    Extension, Json, Router,
+};
+use uuid::Uuid;
+
+use crate::auth::{require_role, CurrentUser};
+use crate::custom_fields::validate_definition;
+use crate::errors::{AppError, ErrorResponse};
+#[allow(unused_imports)]
+use crate::models::PaginatedCustomFieldResponse;
+use crate::models::{
+    CreateCustomField, CustomField, CustomFieldListQuery, PaginatedResponse, UpdateCustomField,
+};
+use crate::routes::audit_logs::log_audit;
+use crate::routes::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/api/custom-fields", get(list).post(create))
+        .route(
+            "/api/custom-fields/{id}",
+            get(get_one).put(update).delete(delete),
+        )
+}
+
+#[utoipa::path(
+    get, path = "/api/custom-fields",
+    tag = "Custom Fields",
+    params(CustomFieldListQuery),
+    responses(
+        (status = 200, body = PaginatedCustomFieldResponse),
+        (status = 500, body = ErrorResponse),
+    )
+)]
+pub async fn list(
+    State(state): State<AppState>,
+    Query(q): Query<CustomFieldListQuery>,
+) -> Result<Json<PaginatedResponse<CustomField>>, AppError> {
+    let per_page = q.per_page.clamp(1, 100);
+    let page = q.page.max(1);
+    let offset = (page - 1) * per_page;
+
+    let total: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM custom_fields")
+        .fetch_one(&state.db)
+        .await?;
+
+    let rows = sqlx::query_as::<_, CustomField>(
+        "SELECT * FROM custom_fields ORDER BY sort_order, name LIMIT $1 OFFSET $2",
+    )
+    .bind(per_page)
+    .bind(offset)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(PaginatedResponse {
+        data: rows,
+        total: total.0,
+        page,
+        per_page,
+    }))
+}
+
+#[utoipa::path(
+    get, path = "/api/custom-fields/{id}",
+    tag = "Custom Fields",
+    params(("id" = Uuid, Path, description = "Custom field ID")),
+    responses(
+        (status = 200, body = CustomField),
+        (status = 404, body = ErrorResponse),
+    )
+)]
+pub async fn get_one(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<CustomField>, AppError> {
+    let row = sqlx::query_as::<_, CustomField>("SELECT * FROM custom_fields WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    Ok(Json(row))
+}
+
+#[utoipa::path(
+    post, path = "/api/custom-fields",
+    tag = "Custom Fields",
+    request_body = CreateCustomField,
+    responses(
+        (status = 200, body = CustomField),
+        (status = 400, body = ErrorResponse),
+    )
+)]
+pub async fn create(
+    State(state): State<AppState>,
+    Extension(user): Extension<CurrentUser>,
+    Json(input): Json<CreateCustomField>,
+) -> Result<Json<CustomField>, AppError> {
+    require_role(&user, &["admin"])?;
+    let valid_name = input.name.starts_with(|c: char| c.is_ascii_lowercase())
+        && input.name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if !valid_name {
+        return Err(AppError::BadRequest(
+            "name must start with a lowercase letter and contain only a-z, 0-9 and '_'".into(),
+        ));
+    }
+    validate_definition(&input.field_type, &input.enum_values)?;
+
+    let row = sqlx::query_as::<_, CustomField>(
+        r#"INSERT INTO custom_fields
+            (name, label, field_type, enum_values, required, purposes, expose_label, sort_order)
+           VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+           RETURNING *"#,
+    )
+    .bind(&input.name)
+    .bind(&input.label)
+    .bind(&input.field_type)
+    .bind(&input.enum_values)
+    .bind(input.required)
+    .bind(&input.purposes)
+    .bind(input.expose_label)
+    .bind(input.sort_order)
+    .fetch_one(&state.db)
+    .await?;
+
+    log_audit(
+        &state.db,
+        &user,
+        "create",
+        "custom_field",
+        Some(&row.id.to_string()),
+        serde_json::json!({"name": row.name, "field_type": row.field_type}),
+    )
+    .await;
+
+    Ok(Json(row))
+}
+
+/// Update a custom field definition. The name is immutable because it keys
+/// the stored values; existing values are not re-validated.
+#[utoipa::path(
+    put, path = "/api/custom-fields/{id}",
+    tag = "Custom Fields",
+    params(("id" = Uuid, Path, description = "Custom field ID")),
+    request_body = UpdateCustomField,
+    responses(
+        (status = 200, body = CustomField),
+        (status = 400, body = ErrorResponse),
+        (status = 404, body = ErrorResponse),
+    )
+)]
+pub async fn update(
+    State(state): State<AppState>,
+    Extension(user): Extension<CurrentUser>,
+    Path(id): Path<Uuid>,
+    Json(input): Json<UpdateCustomField>,
+) -> Result<Json<CustomField>, AppError> {
+    require_role(&user, &["admin"])?;
+    let existing = sqlx::query_as::<_, CustomField>("SELECT * FROM custom_fields WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    let old = serde_json::to_value(&existing).unwrap_or_default();
+
+    let field_type = input.field_type.unwrap_or(existing.field_type);
+    let enum_values = input.enum_values.unwrap_or(existing.enum_values);
+    validate_definition(&field_type, &enum_values)?;
+
+    let row = sqlx::query_as::<_, CustomField>(
+        r#"UPDATE custom_fields SET
+            label = $2, field_type = $3, enum_values = $4, required = $5,
+            purposes = $6, expose_label = $7, sort_order = $8
+           WHERE id = $1
+           RETURNING *"#,
+    )
+    .bind(id)
+    .bind(input.label.unwrap_or(existing.label))
+    .bind(&field_type)
+    .bind(&enum_values)
+    .bind(input.required.unwrap_or(existing.required))
+    .bind(input.purposes.unwrap_or(existing.purposes))
+    .bind(input.expose_label.unwrap_or(existing.expose_label))
+    .bind(input.sort_order.unwrap_or(existing.sort_order))
+    .fetch_one(&state.db)
+    .await?;
+
+    let new_val = serde_json::to_value(&row).unwrap_or_default();
+    let mut changes = serde_json::Map::new();
+    let skip = ["id", "created_at", "updated_at"];
+    if let (serde_json::Value::Object(old_map), serde_json::Value::Object(new_map)) =
+        (&old, &new_val)
+    {
+        for (key, nv) in new_map {
+            if skip.contains(&key.as_str()) {
+                continue;
+            }
+            if let Some(ov) = old_map.get(key) {
+                if ov != nv {
+                    changes.insert(key.clone(), serde_json::json!({"from": ov, "to": nv}));
+                }
+            }
+        }
+    }
+
+    log_audit(
+        &state.db,
+        &user,
+        "update",
+        "custom_field",
+        Some(&id.to_string()),
+        serde_json::json!({"name": row.name, "changes": changes}),
+    )
+    .await;
+
+    Ok(Json(row))
+}
+
+#[utoipa::path(
+    delete, path = "/api/custom-fields/{id}",
+    tag = "Custom Fields",
+    params(("id" = Uuid, Path, description = "Custom field ID")),
+    responses(
+        (status = 200, description = "Custom field deleted"),
+        (status = 400, body = ErrorResponse),
+        (status = 404, body = ErrorResponse),
+    )
+)]
+pub async fn delete(
+    State(state): State<AppState>,
+    Extension(user): Extension<CurrentUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_role(&user, &["admin"])?;
+
+    let field = sqlx::query_as::<_, CustomField>("SELECT * FROM custom_fields WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    // Reject if any VPS still stores a value for this field
+    let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM vps WHERE extra->'custom' ? $1")
+        .bind(&field.name)
+        .fetch_one(&state.db)
+        .await?;
+
+    if count.0 > 0 {
+        return Err(AppError::BadRequest(format!(
+            "Cannot delete custom field '{}': {} VPS instance(s) still have a value",
+            field.name, count.0
+        )));
+    }
+
+    sqlx::query("DELETE FROM custom_fields WHERE id = $1")
+        .bind(id)
+        .execute(&state.db)
+        .await?;
+
+    log_audit(
+        &state.db,
+        &user,
+        "delete",
+        "custom_field",
+        Some(&id.to_string()),
+        serde_json::json!({"name": field.name}),
+    )
+    .await;
+
+    Ok(Json(serde_json::json!({ "deleted": true })))
+}
@@ -484,6 +484,44 @@ struct ZoneSyncResult {
     deleted: i64,
 }
 
+/// Point a DNS record at new content (used by IP rotation).
+pub async fn update_record_content(
+    state: &AppState,
+    account: &DnsAccount,
+    zone_cf_id: &str,
+    record_id: &str,
+    new_content: &str,
+) -> Result<(), AppError> {
+    let token = account
+        .config
+        .get("api_token")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| AppError::Internal("Missing api_token in account config".into()))?;
+
+    let resp = state
+        .http_client
+        .patch(format!(
+            "https://api.cloudflare.com/client/v4/zones/{zone_cf_id}/dns_records/{record_id}"
+        ))
+        .header("Authorization", format!("Bearer {token}"))
+        .json(&serde_json::json!({ "content": new_content }))
+        .send()
+        .await
+        .map_err(|e| AppError::Internal(format!("CF API error: {e}")))?;
+
+    let body = resp
+        .json::<CfResponse<serde_json::Value>>()
+        .await
+        .map_err(|e| AppError::Internal(format!("CF parse error: {e}")))?;
+
+    if !body.success {
+        return Err(AppError::BadGateway(format!(
+            "Cloudflare refused to update record {record_id}"
+        )));
+    }
+    Ok(())
+}
+
 /// Process a single zone: upsert zone, fetch records + cert + domain concurrently, upsert records.
 async fn sync_zone(
     db: &sqlx::PgPool,
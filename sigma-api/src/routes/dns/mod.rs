@@ -17,13 +17,25 @@ use crate::models::{
     CreateDnsAccount, DnsAccount, DnsAccountListQuery, DnsAccountResponse, DnsRecord,
     DnsRecordHistory, DnsRecordHistoryQuery, DnsRecordListQuery, DnsSyncResult, DnsZone,
     DnsZoneListQuery, PaginatedDnsAccountResponse, PaginatedDnsRecordHistoryResponse,
-    PaginatedDnsRecordResponse, PaginatedDnsZoneResponse, PaginatedResponse, UpdateDnsAccount,
+    ListResponse, PaginatedDnsRecordResponse, PaginatedDnsZoneResponse, PaginatedResponse,
+    UpdateDnsAccount,
 };
+use crate::pagination::{Keyed, Listing, SortColumn};
 use crate::routes::audit_logs::log_audit;
 use crate::routes::AppState;
 
 const VALID_PROVIDER_TYPES: &[&str] = &["cloudflare", "route53", "godaddy", "namecom"];
 
+/// Columns accepted by `sort=` on `GET /api/dns-records`.
+static DNS_RECORD_SORT_COLUMNS: &[SortColumn] = &[
+    SortColumn { name: "name", expr: "d.name", sql_type: "text" },
+    SortColumn { name: "zone_name", expr: "z.zone_name", sql_type: "text" },
+    SortColumn { name: "record_type", expr: "d.record_type", sql_type: "text" },
+    SortColumn { name: "synced_at", expr: "d.synced_at", sql_type: "timestamptz" },
+    SortColumn { name: "created_at", expr: "d.created_at", sql_type: "timestamptz" },
+    SortColumn { name: "updated_at", expr: "d.updated_at", sql_type: "timestamptz" },
+];
+
 pub fn router() -> Router<AppState> {
     Router::new()
         .route(
@@ -113,6 +125,52 @@ async fn sync_provider_zone(
     }
 }
 
+/// Point a record at new content on the DNS provider, then update the synced row.
+/// Only Cloudflare and Route 53 support writes.
+pub async fn repoint_record(
+    state: &AppState,
+    record_uuid: Uuid,
+    old_content: &str,
+    new_content: &str,
+) -> Result<(), AppError> {
+    let (account_id, zone_id, record_id): (Uuid, String, String) = sqlx::query_as(
+        r#"SELECT z.account_id, z.zone_id, d.record_id
+           FROM dns_records d JOIN dns_zones z ON z.id = d.zone_uuid
+           WHERE d.id = $1"#,
+    )
+    .bind(record_uuid)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or(AppError::NotFound)?;
+    let account = sqlx::query_as::<_, DnsAccount>("SELECT * FROM dns_accounts WHERE id = $1")
+        .bind(account_id)
+        .fetch_one(&state.db)
+        .await?;
+
+    match account.provider_type.as_str() {
+        "cloudflare" => {
+            cloudflare::update_record_content(state, &account, &zone_id, &record_id, new_content)
+                .await?
+        }
+        "route53" => {
+            route53::update_record_content(&account, &zone_id, &record_id, old_content, new_content)
+                .await?
+        }
+        other => {
+            return Err(AppError::BadRequest(format!(
+                "DNS provider {other} does not support record updates"
+            )))
+        }
+    }
+
+    sqlx::query("UPDATE dns_records SET content = $2 WHERE id = $1")
+        .bind(record_uuid)
+        .bind(new_content)
+        .execute(&state.db)
+        .await?;
+    Ok(())
+}
+
 /// Build a DnsAccountResponse from a DnsAccount by querying zone/record counts.
 async fn build_account_response(
     state: &AppState,
@@ -561,10 +619,15 @@ pub async fn list_zones(
 pub async fn list_dns_records(
     State(state): State<AppState>,
     Query(q): Query<DnsRecordListQuery>,
-) -> Result<Json<PaginatedResponse<DnsRecord>>, AppError> {
-    let per_page = q.per_page.clamp(1, 100);
-    let page = q.page.max(1);
-    let offset = (page - 1) * per_page;
+) -> Result<Json<ListResponse<DnsRecord>>, AppError> {
+    let listing = Listing::from_query(
+        q.page,
+        q.per_page,
+        q.cursor.as_deref(),
+        q.sort.as_deref(),
+        DNS_RECORD_SORT_COLUMNS,
+        "name",
+    )?;
 
     let mut where_clause = String::from(" WHERE 1=1");
     let mut param_idx = 0u32;
@@ -593,43 +656,41 @@ pub async fn list_dns_records(
         }
     }
 
-    let count_sql = format!(
-        "SELECT COUNT(*) FROM dns_records d JOIN dns_zones z ON z.id = d.zone_uuid{where_clause}"
-    );
-    let mut count_query = sqlx::query_as::<_, (i64,)>(&count_sql);
-    if let Some(ref v) = q.account_id {
-        count_query = count_query.bind(v);
-    }
-    if let Some(ref v) = q.zone_name {
-        count_query = count_query.bind(v);
-    }
-    if let Some(ref v) = q.record_type {
-        count_query = count_query.bind(v);
-    }
-    if let Some(ref v) = q.vps_id {
-        count_query = count_query.bind(v);
+    let mut total = 0;
+    if !listing.is_keyset() {
+        let count_sql = format!(
+            "SELECT COUNT(*) FROM dns_records d JOIN dns_zones z ON z.id = d.zone_uuid{where_clause}"
+        );
+        let mut count_query = sqlx::query_as::<_, (i64,)>(&count_sql);
+        if let Some(ref v) = q.account_id {
+            count_query = count_query.bind(v);
+        }
+        if let Some(ref v) = q.zone_name {
+            count_query = count_query.bind(v);
+        }
+        if let Some(ref v) = q.record_type {
+            count_query = count_query.bind(v);
+        }
+        if let Some(ref v) = q.vps_id {
+            count_query = count_query.bind(v);
+        }
+        total = count_query.fetch_one(&state.db).await?.0;
     }
-    let total = count_query.fetch_one(&state.db).await?.0;
-
-    param_idx += 1;
-    let limit_param = param_idx;
-    param_idx += 1;
-    let offset_param = param_idx;
 
     let data_sql = format!(
         r#"SELECT d.id, d.zone_uuid, d.record_id, d.record_type, d.name, d.content,
                   d.ttl, d.extra, d.vps_id, d.synced_at, d.created_at, d.updated_at,
                   z.zone_name, z.zone_id AS zone_id_ext,
-                  v.hostname AS vps_hostname, v.country AS vps_country
+                  v.hostname AS vps_hostname, v.country AS vps_country{}
            FROM dns_records d
            JOIN dns_zones z ON z.id = d.zone_uuid
            LEFT JOIN vps v ON v.id = d.vps_id
-           {where_clause}
-           ORDER BY z.zone_name, d.name
-           LIMIT ${limit_param} OFFSET ${offset_param}"#
+           {where_clause}{}"#,
+        listing.select("d.id"),
+        listing.tail("d.id", "z.zone_name, d.name", param_idx + 1)
     );
 
-    let mut query = sqlx::query_as::<_, DnsRecord>(&data_sql);
+    let mut query = sqlx::query_as::<_, Keyed<DnsRecord>>(&data_sql);
     if let Some(ref v) = q.account_id {
         query = query.bind(v);
     }
@@ -642,16 +703,11 @@ pub async fn list_dns_records(
     if let Some(ref v) = q.vps_id {
         query = query.bind(v);
     }
-    query = query.bind(per_page).bind(offset);
+    query = listing.bind(query);
 
     let rows = query.fetch_all(&state.db).await?;
 
-    Ok(Json(PaginatedResponse {
-        data: rows,
-        total,
-        page,
-        per_page,
-    }))
+    Ok(Json(listing.finish(rows, total)))
 }
 
 // ─── DNS Record History ────────────────────────────────
@@ -267,6 +267,81 @@ pub async fn sync_single_zone(
     })
 }
 
+/// Replace one value of a record set (used by IP rotation).
+/// `record_id` is the `name:type:index` key assigned during sync.
+pub async fn update_record_content(
+    account: &DnsAccount,
+    zone_r53_id: &str,
+    record_id: &str,
+    old_content: &str,
+    new_content: &str,
+) -> Result<(), AppError> {
+    use aws_sdk_route53::types::{
+        Change, ChangeAction, ChangeBatch, ResourceRecord, ResourceRecordSet, RrType,
+    };
+
+    let mut parts = record_id.rsplitn(3, ':');
+    let (_, record_type, name) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(i), Some(t), Some(n)) => (i, t, n),
+        _ => return Err(AppError::Internal(format!("Malformed Route 53 record id: {record_id}"))),
+    };
+    let fqdn = format!("{}.", name.trim_end_matches('.'));
+
+    let client = make_r53_client(&account.config).await?;
+    let resp = client
+        .list_resource_record_sets()
+        .hosted_zone_id(zone_r53_id)
+        .start_record_name(&fqdn)
+        .start_record_type(RrType::from(record_type))
+        .max_items(1)
+        .send()
+        .await
+        .map_err(|e| AppError::BadGateway(format!("Route 53 list records error: {e}")))?;
+
+    let rrset = resp
+        .resource_record_sets
+        .into_iter()
+        .find(|r| r.name == fqdn && r.r#type.as_str() == record_type)
+        .ok_or_else(|| AppError::BadGateway(format!("Route 53 record set {name} {record_type} not found")))?;
+    let values = rrset
+        .resource_records
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest(format!("Route 53 record {name} is an alias record")))?;
+
+    let records = values
+        .iter()
+        .map(|rr| {
+            let value = if rr.value == old_content { new_content } else { rr.value.as_str() };
+            ResourceRecord::builder().value(value).build()
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Internal(format!("Route 53 request build error: {e}")))?;
+
+    let build = || -> Result<ChangeBatch, aws_sdk_route53::error::BuildError> {
+        let set = ResourceRecordSet::builder()
+            .name(&fqdn)
+            .r#type(rrset.r#type.clone())
+            .set_ttl(rrset.ttl)
+            .set_resource_records(Some(records))
+            .build()?;
+        let change = Change::builder()
+            .action(ChangeAction::Upsert)
+            .resource_record_set(set)
+            .build()?;
+        ChangeBatch::builder().changes(change).build()
+    };
+    let batch = build().map_err(|e| AppError::Internal(format!("Route 53 request build error: {e}")))?;
+
+    client
+        .change_resource_record_sets()
+        .hosted_zone_id(zone_r53_id)
+        .change_batch(batch)
+        .send()
+        .await
+        .map_err(|e| AppError::BadGateway(format!("Route 53 update error: {e}")))?;
+    Ok(())
+}
+
 /// Full sync: fetch all hosted zones and record sets from Route 53.
 pub async fn sync(state: &AppState, account: &DnsAccount) -> Result<DnsSyncResult, AppError> {
     let client = make_r53_client(&account.config).await?;
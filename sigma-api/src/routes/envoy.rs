@@ -1,5 +1,6 @@
 use axum::{
     extract::{Path, Query, State},
+    http::HeaderMap,
     routing::get,
     Extension, Json, Router,
 };
@@ -7,6 +8,7 @@ use uuid::Uuid;
 
 use crate::auth::{require_role, CurrentUser};
 use crate::errors::AppError;
+use crate::etag::{check_if_match, tagged, Tagged};
 use std::collections::{HashMap, HashSet};
 
 use crate::models::{
@@ -375,21 +377,21 @@ pub async fn list_routes(
     tag = "Envoy",
     params(("id" = Uuid, Path, description = "Envoy Route ID")),
     responses(
-        (status = 200, body = EnvoyRoute),
+        (status = 200, body = EnvoyRoute, headers(("ETag" = String, description = "Row version for If-Match"))),
         (status = 404),
     )
 )]
 pub async fn get_route(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
-) -> Result<Json<EnvoyRoute>, AppError> {
+) -> Result<Tagged<EnvoyRoute>, AppError> {
     let row = sqlx::query_as::<_, EnvoyRoute>("SELECT * FROM envoy_routes WHERE id = $1")
         .bind(id)
         .fetch_optional(&state.db)
         .await?
         .ok_or(AppError::NotFound)?;
 
-    Ok(Json(row))
+    Ok(tagged(row.updated_at, row))
 }
 
 #[utoipa::path(
@@ -460,23 +462,27 @@ pub async fn create_route(
     params(("id" = Uuid, Path, description = "Envoy Route ID")),
     request_body = UpdateEnvoyRoute,
     responses(
-        (status = 200, body = EnvoyRoute),
+        (status = 200, body = EnvoyRoute, headers(("ETag" = String, description = "Row version for If-Match"))),
         (status = 404),
+        (status = 412, description = "If-Match does not match the current ETag"),
     )
 )]
 pub async fn update_route(
     State(state): State<AppState>,
     Extension(user): Extension<CurrentUser>,
     Path(id): Path<Uuid>,
+    headers: HeaderMap,
     Json(input): Json<UpdateEnvoyRoute>,
-) -> Result<Json<EnvoyRoute>, AppError> {
+) -> Result<Tagged<EnvoyRoute>, AppError> {
     require_role(&user, &["admin", "operator", "agent"])?;
 
-    let existing = sqlx::query_as::<_, EnvoyRoute>("SELECT * FROM envoy_routes WHERE id = $1")
+    let mut tx = state.db.begin().await?;
+    let existing = sqlx::query_as::<_, EnvoyRoute>("SELECT * FROM envoy_routes WHERE id = $1 FOR UPDATE")
         .bind(id)
-        .fetch_optional(&state.db)
+        .fetch_optional(&mut *tx)
         .await?
         .ok_or(AppError::NotFound)?;
+    check_if_match(&headers, existing.updated_at)?;
     let old = serde_json::to_value(&existing).unwrap_or_default();
 
     let row = sqlx::query_as::<_, EnvoyRoute>(
@@ -496,14 +502,15 @@ pub async fn update_route(
     .bind(input.connect_timeout_secs.unwrap_or(existing.connect_timeout_secs))
     .bind(input.proxy_protocol.unwrap_or(existing.proxy_protocol))
     .bind(input.status.unwrap_or(existing.status))
-    .fetch_one(&state.db)
+    .fetch_one(&mut *tx)
     .await?;
 
     // Bump config_version on parent node
     sqlx::query("UPDATE envoy_nodes SET config_version = config_version + 1, updated_at = now() WHERE id = $1")
         .bind(row.envoy_node_id)
-        .execute(&state.db)
+        .execute(&mut *tx)
         .await?;
+    tx.commit().await?;
 
     let new = serde_json::to_value(&row).unwrap_or_default();
     let mut changes = serde_json::Map::new();
@@ -534,7 +541,7 @@ pub async fn update_route(
     )
     .await;
 
-    Ok(Json(row))
+    Ok(tagged(row.updated_at, row))
 }
 
 #[utoipa::path(
@@ -545,32 +552,37 @@ pub async fn update_route(
     responses(
         (status = 200),
         (status = 404),
+        (status = 412, description = "If-Match does not match the current ETag"),
     )
 )]
 pub async fn delete_route(
     State(state): State<AppState>,
     Extension(user): Extension<CurrentUser>,
     Path(id): Path<Uuid>,
+    headers: HeaderMap,
 ) -> Result<Json<serde_json::Value>, AppError> {
     require_role(&user, &["admin", "operator", "agent"])?;
 
     // Fetch the route to get the parent node ID before deletion
-    let route = sqlx::query_as::<_, EnvoyRoute>("SELECT * FROM envoy_routes WHERE id = $1")
+    let mut tx = state.db.begin().await?;
+    let route = sqlx::query_as::<_, EnvoyRoute>("SELECT * FROM envoy_routes WHERE id = $1 FOR UPDATE")
         .bind(id)
-        .fetch_optional(&state.db)
+        .fetch_optional(&mut *tx)
         .await?
         .ok_or(AppError::NotFound)?;
+    check_if_match(&headers, route.updated_at)?;
 
     sqlx::query("DELETE FROM envoy_routes WHERE id = $1")
         .bind(id)
-        .execute(&state.db)
+        .execute(&mut *tx)
         .await?;
 
     // Bump config_version on parent node
     sqlx::query("UPDATE envoy_nodes SET config_version = config_version + 1, updated_at = now() WHERE id = $1")
         .bind(route.envoy_node_id)
-        .execute(&state.db)
+        .execute(&mut *tx)
         .await?;
+    tx.commit().await?;
 
     log_audit(
         &state.db,
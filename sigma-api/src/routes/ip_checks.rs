@@ -11,10 +11,17 @@ use crate::errors::{AppError, ErrorResponse};
 use crate::models::PaginatedIpCheckResponse;
 use crate::models::{
     CreateIpCheck, IpCheck, IpCheckListQuery, IpCheckSummary, IpCheckSummaryQuery,
-    PaginatedResponse, PurgeQuery,
+    ListResponse, PurgeQuery,
 };
+use crate::pagination::{Keyed, Listing, SortColumn};
 use crate::routes::AppState;
 
+/// Columns accepted by `sort=` on `GET /api/ip-checks`.
+static IP_CHECK_SORT_COLUMNS: &[SortColumn] = &[
+    SortColumn { name: "checked_at", expr: "checked_at", sql_type: "timestamptz" },
+    SortColumn { name: "latency_ms", expr: "COALESCE(latency_ms, 0)", sql_type: "integer" },
+];
+
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/api/ip-checks", get(list).post(create))
@@ -35,10 +42,15 @@ pub fn router() -> Router<AppState> {
 pub async fn list(
     State(state): State<AppState>,
     Query(q): Query<IpCheckListQuery>,
-) -> Result<Json<PaginatedResponse<IpCheck>>, AppError> {
-    let per_page = q.per_page.clamp(1, 100);
-    let page = q.page.max(1);
-    let offset = (page - 1) * per_page;
+) -> Result<Json<ListResponse<IpCheck>>, AppError> {
+    let listing = Listing::from_query(
+        q.page,
+        q.per_page,
+        q.cursor.as_deref(),
+        q.sort.as_deref(),
+        IP_CHECK_SORT_COLUMNS,
+        "-checked_at",
+    )?;
 
     let mut where_clause = String::from(" WHERE 1=1");
     let mut param_idx = 0u32;
@@ -64,47 +76,41 @@ pub async fn list(
         where_clause.push_str(&format!(" AND success = ${}", param_idx));
     }
 
-    // Count query
-    let count_sql = format!("SELECT COUNT(*) FROM ip_checks{}", where_clause);
-    let mut count_query = sqlx::query_as::<_, (i64,)>(&count_sql);
+    // Count query (page mode only)
+    let mut total = 0;
+    if !listing.is_keyset() {
+        let count_sql = format!("SELECT COUNT(*) FROM ip_checks{}", where_clause);
+        let mut count_query = sqlx::query_as::<_, (i64,)>(&count_sql);
 
-    if let Some(ref v) = q.vps_id { count_query = count_query.bind(v); }
-    if let Some(ref v) = q.ip { count_query = count_query.bind(v); }
-    if let Some(ref v) = q.source { count_query = count_query.bind(v); }
-    if let Some(ref v) = q.check_type { count_query = count_query.bind(v); }
-    if let Some(v) = q.success { count_query = count_query.bind(v); }
+        if let Some(ref v) = q.vps_id { count_query = count_query.bind(v); }
+        if let Some(ref v) = q.ip { count_query = count_query.bind(v); }
+        if let Some(ref v) = q.source { count_query = count_query.bind(v); }
+        if let Some(ref v) = q.check_type { count_query = count_query.bind(v); }
+        if let Some(v) = q.success { count_query = count_query.bind(v); }
 
-    let total = count_query.fetch_one(&state.db).await?.0;
+        total = count_query.fetch_one(&state.db).await?.0;
+    }
 
     // Data query
-    param_idx += 1;
-    let limit_param = param_idx;
-    param_idx += 1;
-    let offset_param = param_idx;
-
     let data_sql = format!(
-        "SELECT id, vps_id, host(ip) as ip, check_type, source, success, latency_ms, checked_at \
-         FROM ip_checks{} ORDER BY checked_at DESC LIMIT ${} OFFSET ${}",
-        where_clause, limit_param, offset_param
+        "SELECT id, vps_id, host(ip) as ip, check_type, source, success, latency_ms, checked_at{} \
+         FROM ip_checks{}{}",
+        listing.select("id"),
+        where_clause,
+        listing.tail("id", "checked_at DESC", param_idx + 1)
     );
-    let mut query = sqlx::query_as::<_, IpCheck>(&data_sql);
+    let mut query = sqlx::query_as::<_, Keyed<IpCheck>>(&data_sql);
 
     if let Some(ref v) = q.vps_id { query = query.bind(v); }
     if let Some(ref v) = q.ip { query = query.bind(v); }
     if let Some(ref v) = q.source { query = query.bind(v); }
     if let Some(ref v) = q.check_type { query = query.bind(v); }
     if let Some(v) = q.success { query = query.bind(v); }
-
-    query = query.bind(per_page).bind(offset);
+    query = listing.bind(query);
 
     let rows = query.fetch_all(&state.db).await?;
 
-    Ok(Json(PaginatedResponse {
-        data: rows,
-        total,
-        page,
-        per_page,
-    }))
+    Ok(Json(listing.finish(rows, total)))
 }
 
 #[utoipa::path(
@@ -0,0 +1,574 @@
+use axum::{
+    extract::{Path, Query, State},
+    routing::{get, post},
+    Extension, Json, Router,
+};
+use rust_decimal::Decimal;
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::auth::{require_role, CurrentUser};
+use crate::errors::{AppError, ErrorResponse};
+#[allow(unused_imports)]
+use crate::models::PaginatedPoolIpResponse;
+use crate::models::{
+    AssignPoolIp, CreatePoolIp, IpEntry, PaginatedResponse, PoolIp, PoolIpListQuery,
+    ReleasePoolIp, UpdatePoolIp, Vps,
+};
+use crate::routes::audit_logs::log_audit;
+use crate::routes::AppState;
+use crate::vps_lifecycle::{set_change_context, SOURCE_IP_POOL};
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/api/ip-pool", get(list).post(create))
+        .route(
+            "/api/ip-pool/{id}",
+            get(get_one).put(update).delete(delete),
+        )
+        .route("/api/ip-pool/{id}/assign", post(assign))
+        .route("/api/ip-pool/{id}/release", post(release))
+}
+
+#[utoipa::path(
+    get, path = "/api/ip-pool",
+    tag = "IP Pool",
+    params(PoolIpListQuery),
+    responses(
+        (status = 200, body = PaginatedPoolIpResponse),
+        (status = 500, body = ErrorResponse),
+    )
+)]
+pub async fn list(
+    State(state): State<AppState>,
+    Query(q): Query<PoolIpListQuery>,
+) -> Result<Json<PaginatedResponse<PoolIp>>, AppError> {
+    let per_page = q.per_page.clamp(1, 100);
+    let page = q.page.max(1);
+    let offset = (page - 1) * per_page;
+
+    let mut where_clause = String::from(" WHERE 1=1");
+    let mut param_idx = 0u32;
+
+    if q.provider_id.is_some() {
+        param_idx += 1;
+        where_clause.push_str(&format!(" AND provider_id = ${}", param_idx));
+    }
+    if q.status.is_some() {
+        param_idx += 1;
+        where_clause.push_str(&format!(" AND status = ${}", param_idx));
+    }
+    if q.floating.is_some() {
+        param_idx += 1;
+        where_clause.push_str(&format!(" AND floating = ${}", param_idx));
+    }
+    if q.vps_id.is_some() {
+        param_idx += 1;
+        where_clause.push_str(&format!(" AND vps_id = ${}", param_idx));
+    }
+
+    let count_sql = format!("SELECT COUNT(*) FROM ip_pool{}", where_clause);
+    let mut count_query = sqlx::query_as::<_, (i64,)>(&count_sql);
+    if let Some(ref v) = q.provider_id { count_query = count_query.bind(v); }
+    if let Some(ref v) = q.status { count_query = count_query.bind(v); }
+    if let Some(v) = q.floating { count_query = count_query.bind(v); }
+    if let Some(ref v) = q.vps_id { count_query = count_query.bind(v); }
+    let total = count_query.fetch_one(&state.db).await?.0;
+
+    let data_sql = format!(
+        "SELECT * FROM ip_pool{} ORDER BY try_inet(ip), ip LIMIT ${} OFFSET ${}",
+        where_clause,
+        param_idx + 1,
+        param_idx + 2
+    );
+    let mut query = sqlx::query_as::<_, PoolIp>(&data_sql);
+    if let Some(ref v) = q.provider_id { query = query.bind(v); }
+    if let Some(ref v) = q.status { query = query.bind(v); }
+    if let Some(v) = q.floating { query = query.bind(v); }
+    if let Some(ref v) = q.vps_id { query = query.bind(v); }
+    let rows = query.bind(per_page).bind(offset).fetch_all(&state.db).await?;
+
+    Ok(Json(PaginatedResponse {
+        data: rows,
+        total,
+        page,
+        per_page,
+    }))
+}
+
+#[utoipa::path(
+    get, path = "/api/ip-pool/{id}",
+    tag = "IP Pool",
+    params(("id" = Uuid, Path, description = "Pool IP ID")),
+    responses(
+        (status = 200, body = PoolIp),
+        (status = 404, body = ErrorResponse),
+    )
+)]
+pub async fn get_one(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<PoolIp>, AppError> {
+    let row = sqlx::query_as::<_, PoolIp>("SELECT * FROM ip_pool WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    Ok(Json(row))
+}
+
+/// Add an address to the pool. An address already listed on exactly one live VPS
+/// is recorded as assigned to it.
+#[utoipa::path(
+    post, path = "/api/ip-pool",
+    tag = "IP Pool",
+    request_body = CreatePoolIp,
+    responses(
+        (status = 200, body = PoolIp),
+        (status = 400, body = ErrorResponse),
+    )
+)]
+pub async fn create(
+    State(state): State<AppState>,
+    Extension(user): Extension<CurrentUser>,
+    Json(input): Json<CreatePoolIp>,
+) -> Result<Json<PoolIp>, AppError> {
+    require_role(&user, &["admin", "operator"])?;
+    let ip = parse_ip(&input.ip)?;
+    validate_status(&input.status)?;
+
+    let exists = sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM ip_pool WHERE ip = $1)")
+        .bind(&ip)
+        .fetch_one(&state.db)
+        .await?;
+    if exists {
+        return Err(AppError::BadRequest(format!("{} is already in the pool", ip)));
+    }
+
+    let holders = vps_holding(&state.db, &ip, None).await?;
+    let (status, vps_id) = match holders.as_slice() {
+        [holder] => ("assigned".to_string(), Some(*holder)),
+        _ => (input.status, None),
+    };
+
+    let row = sqlx::query_as::<_, PoolIp>(
+        r#"INSERT INTO ip_pool (
+               ip, provider_id, status, floating, vps_id,
+               label, purpose, cost_monthly, currency, notes
+           ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+           RETURNING *"#,
+    )
+    .bind(&ip)
+    .bind(input.provider_id)
+    .bind(&status)
+    .bind(input.floating)
+    .bind(vps_id)
+    .bind(&input.label)
+    .bind(&input.purpose)
+    .bind(input.cost_monthly.and_then(Decimal::from_f64_retain))
+    .bind(&input.currency)
+    .bind(&input.notes)
+    .fetch_one(&state.db)
+    .await?;
+
+    log_audit(
+        &state.db,
+        &user,
+        "create",
+        "ip_pool",
+        Some(&row.id.to_string()),
+        serde_json::json!({"ip": row.ip, "status": row.status, "floating": row.floating}),
+    )
+    .await;
+
+    Ok(Json(row))
+}
+
+#[utoipa::path(
+    put, path = "/api/ip-pool/{id}",
+    tag = "IP Pool",
+    params(("id" = Uuid, Path, description = "Pool IP ID")),
+    request_body = UpdatePoolIp,
+    responses(
+        (status = 200, body = PoolIp),
+        (status = 400, body = ErrorResponse),
+        (status = 404, body = ErrorResponse),
+    )
+)]
+pub async fn update(
+    State(state): State<AppState>,
+    Extension(user): Extension<CurrentUser>,
+    Path(id): Path<Uuid>,
+    Json(input): Json<UpdatePoolIp>,
+) -> Result<Json<PoolIp>, AppError> {
+    require_role(&user, &["admin", "operator"])?;
+    let existing = sqlx::query_as::<_, PoolIp>("SELECT * FROM ip_pool WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    let old = serde_json::to_value(&existing).unwrap_or_default();
+
+    if let Some(ref status) = input.status {
+        validate_status(status)?;
+        if existing.status == "assigned" {
+            return Err(AppError::BadRequest(
+                "address is assigned; release it to change its status".into(),
+            ));
+        }
+    }
+
+    let row = sqlx::query_as::<_, PoolIp>(
+        r#"UPDATE ip_pool SET
+               provider_id = $2, status = $3, floating = $4, label = $5,
+               purpose = $6, cost_monthly = $7, currency = $8, notes = $9
+           WHERE id = $1
+           RETURNING *"#,
+    )
+    .bind(id)
+    .bind(input.provider_id.unwrap_or(existing.provider_id))
+    .bind(input.status.unwrap_or(existing.status))
+    .bind(input.floating.unwrap_or(existing.floating))
+    .bind(input.label.unwrap_or(existing.label))
+    .bind(input.purpose.unwrap_or(existing.purpose))
+    .bind(input.cost_monthly.map(|v| v.and_then(Decimal::from_f64_retain)).unwrap_or(existing.cost_monthly))
+    .bind(input.currency.unwrap_or(existing.currency))
+    .bind(input.notes.unwrap_or(existing.notes))
+    .fetch_one(&state.db)
+    .await?;
+
+    let new_val = serde_json::to_value(&row).unwrap_or_default();
+    let mut changes = serde_json::Map::new();
+    let skip = ["id", "created_at", "updated_at"];
+    if let (serde_json::Value::Object(old_map), serde_json::Value::Object(new_map)) =
+        (&old, &new_val)
+    {
+        for (key, nv) in new_map {
+            if skip.contains(&key.as_str()) {
+                continue;
+            }
+            if let Some(ov) = old_map.get(key) {
+                if ov != nv {
+                    changes.insert(key.clone(), serde_json::json!({"from": ov, "to": nv}));
+                }
+            }
+        }
+    }
+
+    log_audit(
+        &state.db,
+        &user,
+        "update",
+        "ip_pool",
+        Some(&id.to_string()),
+        serde_json::json!({"ip": row.ip, "changes": changes}),
+    )
+    .await;
+
+    Ok(Json(row))
+}
+
+#[utoipa::path(
+    delete, path = "/api/ip-pool/{id}",
+    tag = "IP Pool",
+    params(("id" = Uuid, Path, description = "Pool IP ID")),
+    responses(
+        (status = 200, description = "Address removed from the pool"),
+        (status = 400, body = ErrorResponse, description = "Address is still assigned"),
+        (status = 404, body = ErrorResponse),
+    )
+)]
+pub async fn delete(
+    State(state): State<AppState>,
+    Extension(user): Extension<CurrentUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_role(&user, &["admin", "operator"])?;
+
+    let row = sqlx::query_as::<_, PoolIp>("SELECT * FROM ip_pool WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    if row.status == "assigned" {
+        return Err(AppError::BadRequest(format!(
+            "{} is assigned; release it before removing it from the pool",
+            row.ip
+        )));
+    }
+
+    sqlx::query("DELETE FROM ip_pool WHERE id = $1")
+        .bind(id)
+        .execute(&state.db)
+        .await?;
+
+    log_audit(
+        &state.db,
+        &user,
+        "delete",
+        "ip_pool",
+        Some(&id.to_string()),
+        serde_json::json!({"ip": row.ip}),
+    )
+    .await;
+
+    Ok(Json(serde_json::json!({ "deleted": true })))
+}
+
+/// Bind a pool address to a VPS by adding it to the VPS's `ip_addresses`.
+/// A floating address assigned elsewhere is moved: it is removed from the previous VPS
+/// in the same transaction. Both changes are recorded in `vps_ip_history` with source `ip_pool`.
+#[utoipa::path(
+    post, path = "/api/ip-pool/{id}/assign",
+    tag = "IP Pool",
+    params(("id" = Uuid, Path, description = "Pool IP ID")),
+    request_body = AssignPoolIp,
+    responses(
+        (status = 200, body = PoolIp),
+        (status = 400, body = ErrorResponse, description = "Address is bound elsewhere or VPS is deleted"),
+        (status = 404, body = ErrorResponse),
+    )
+)]
+pub async fn assign(
+    State(state): State<AppState>,
+    Extension(user): Extension<CurrentUser>,
+    Path(id): Path<Uuid>,
+    Json(input): Json<AssignPoolIp>,
+) -> Result<Json<PoolIp>, AppError> {
+    require_role(&user, &["admin", "operator"])?;
+
+    let mut tx = state.db.begin().await?;
+    let pool = sqlx::query_as::<_, PoolIp>("SELECT * FROM ip_pool WHERE id = $1 FOR UPDATE")
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    if pool.vps_id == Some(input.vps_id) {
+        return Err(AppError::BadRequest(format!("{} is already assigned to this VPS", pool.ip)));
+    }
+    if pool.vps_id.is_some() && !pool.floating {
+        return Err(AppError::BadRequest(format!(
+            "{} is assigned to another VPS; release it first",
+            pool.ip
+        )));
+    }
+    if !pool.floating {
+        let holders = vps_holding(&mut *tx, &pool.ip, Some(input.vps_id)).await?;
+        if let Some(holder) = holders.first() {
+            return Err(AppError::BadRequest(format!(
+                "{} is already in use by VPS {}",
+                pool.ip, holder
+            )));
+        }
+    }
+
+    let target = lock_vps(&mut tx, input.vps_id)
+        .await?
+        .ok_or_else(|| AppError::BadRequest(format!("unknown VPS {}", input.vps_id)))?;
+    if target.status == "deleted" {
+        return Err(AppError::BadRequest("cannot assign an address to a deleted VPS".into()));
+    }
+
+    set_change_context(&mut tx, SOURCE_IP_POOL, &user.email).await?;
+
+    let previous = pool.vps_id;
+    if let Some(prev_id) = previous {
+        if let Some(prev) = lock_vps(&mut tx, prev_id).await? {
+            let ips = without_ip(&prev.ip_addresses.0, &pool.ip);
+            set_ips(&mut tx, prev_id, &ips).await?;
+        }
+    }
+
+    let label = input.label.unwrap_or_else(|| pool.label.clone());
+    let ips = with_ip(&target.ip_addresses.0, &pool.ip, &label);
+    set_ips(&mut tx, input.vps_id, &ips).await?;
+
+    let row = sqlx::query_as::<_, PoolIp>(
+        "UPDATE ip_pool SET status = 'assigned', vps_id = $2 WHERE id = $1 RETURNING *",
+    )
+    .bind(id)
+    .bind(input.vps_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    log_audit(
+        &state.db,
+        &user,
+        "assign",
+        "ip_pool",
+        Some(&id.to_string()),
+        serde_json::json!({
+            "ip": row.ip,
+            "vps_id": input.vps_id,
+            "hostname": target.hostname,
+            "previous_vps_id": previous,
+        }),
+    )
+    .await;
+
+    Ok(Json(row))
+}
+
+/// Unbind a pool address: remove it from its VPS's `ip_addresses` and return it to
+/// available (or reserved). Recorded in `vps_ip_history` with source `ip_pool`.
+#[utoipa::path(
+    post, path = "/api/ip-pool/{id}/release",
+    tag = "IP Pool",
+    params(("id" = Uuid, Path, description = "Pool IP ID")),
+    request_body = ReleasePoolIp,
+    responses(
+        (status = 200, body = PoolIp),
+        (status = 400, body = ErrorResponse, description = "Address is not assigned"),
+        (status = 404, body = ErrorResponse),
+    )
+)]
+pub async fn release(
+    State(state): State<AppState>,
+    Extension(user): Extension<CurrentUser>,
+    Path(id): Path<Uuid>,
+    Json(input): Json<ReleasePoolIp>,
+) -> Result<Json<PoolIp>, AppError> {
+    require_role(&user, &["admin", "operator"])?;
+
+    let mut tx = state.db.begin().await?;
+    let pool = sqlx::query_as::<_, PoolIp>("SELECT * FROM ip_pool WHERE id = $1 FOR UPDATE")
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    if pool.status != "assigned" {
+        return Err(AppError::BadRequest(format!("{} is not assigned", pool.ip)));
+    }
+
+    set_change_context(&mut tx, SOURCE_IP_POOL, &user.email).await?;
+
+    // The VPS may have been hard-deleted (vps_id set to NULL) or edited by hand
+    if let Some(vps_id) = pool.vps_id {
+        if let Some(vps) = lock_vps(&mut tx, vps_id).await? {
+            let ips = without_ip(&vps.ip_addresses.0, &pool.ip);
+            if ips.len() != vps.ip_addresses.0.len() {
+                set_ips(&mut tx, vps_id, &ips).await?;
+            }
+        }
+    }
+
+    let status = if input.reserve { "reserved" } else { "available" };
+    let row = sqlx::query_as::<_, PoolIp>(
+        "UPDATE ip_pool SET status = $2, vps_id = NULL WHERE id = $1 RETURNING *",
+    )
+    .bind(id)
+    .bind(status)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    log_audit(
+        &state.db,
+        &user,
+        "release",
+        "ip_pool",
+        Some(&id.to_string()),
+        serde_json::json!({"ip": row.ip, "vps_id": pool.vps_id, "status": row.status}),
+    )
+    .await;
+
+    Ok(Json(row))
+}
+
+fn parse_ip(raw: &str) -> Result<String, AppError> {
+    raw.trim()
+        .parse::<std::net::IpAddr>()
+        .map(|ip| ip.to_string())
+        .map_err(|_| AppError::BadRequest(format!("Invalid IP address: '{}'", raw.trim())))
+}
+
+fn validate_status(status: &str) -> Result<(), AppError> {
+    match status {
+        "available" | "reserved" => Ok(()),
+        _ => Err(AppError::BadRequest(format!(
+            "status must be available or reserved, got '{}'",
+            status
+        ))),
+    }
+}
+
+/// Live VPS (other than `except`) whose `ip_addresses` list `ip`.
+async fn vps_holding<'e, E>(db: E, ip: &str, except: Option<Uuid>) -> Result<Vec<Uuid>, AppError>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let ids = sqlx::query_scalar::<_, Uuid>(
+        r#"SELECT id FROM vps
+           WHERE ip_addresses @> jsonb_build_array(jsonb_build_object('ip', $1::TEXT))
+             AND status NOT IN ('retired', 'deleted')
+             AND ($2::UUID IS NULL OR id != $2)
+           ORDER BY created_at"#,
+    )
+    .bind(ip)
+    .bind(except)
+    .fetch_all(db)
+    .await?;
+    Ok(ids)
+}
+
+async fn lock_vps(conn: &mut PgConnection, id: Uuid) -> Result<Option<Vps>, AppError> {
+    let row = sqlx::query_as::<_, Vps>("SELECT * FROM vps WHERE id = $1 FOR UPDATE")
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await?;
+    Ok(row)
+}
+
+async fn set_ips(conn: &mut PgConnection, id: Uuid, ips: &[IpEntry]) -> Result<(), AppError> {
+    sqlx::query("UPDATE vps SET ip_addresses = $2 WHERE id = $1")
+        .bind(id)
+        .bind(sqlx::types::Json(ips))
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+fn with_ip(entries: &[IpEntry], ip: &str, label: &str) -> Vec<IpEntry> {
+    let mut out = entries.to_vec();
+    if !out.iter().any(|e| e.ip.trim() == ip) {
+        out.push(IpEntry { ip: ip.to_string(), label: label.to_string() });
+    }
+    out
+}
+
+fn without_ip(entries: &[IpEntry], ip: &str) -> Vec<IpEntry> {
+    entries.iter().filter(|e| e.ip.trim() != ip).cloned().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(ip: &str, label: &str) -> IpEntry {
+        IpEntry { ip: ip.into(), label: label.into() }
+    }
+
+    #[test]
+    fn test_with_and_without_ip() {
+        let ips = vec![entry("203.0.113.1", "")];
+        let added = with_ip(&ips, "198.51.100.7", "anycast");
+        assert_eq!(added.len(), 2);
+        assert_eq!(added[1].label, "anycast");
+        assert_eq!(with_ip(&added, "198.51.100.7", "").len(), 2);
+
+        let removed = without_ip(&added, "198.51.100.7");
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].ip, "203.0.113.1");
+    }
+
+    #[test]
+    fn test_parse_ip_normalizes() {
+        assert_eq!(parse_ip(" 2001:DB8::1 ").unwrap(), "2001:db8::1");
+        assert!(parse_ip("300.1.1.1").is_err());
+        assert!(validate_status("assigned").is_err());
+    }
+}
@@ -0,0 +1,177 @@
+use axum::{
+    extract::{Query, State},
+    routing::get,
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+use crate::errors::{AppError, ErrorResponse};
+use crate::models::{
+    IpHolding, IpLookupDnsRecord, IpLookupQuery, IpLookupResponse, IpLookupVps, IpReachability,
+    Vps,
+};
+use crate::routes::AppState;
+use crate::vps_filter::is_ip_or_cidr;
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/api/lookup", get(lookup))
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct HistoryRow {
+    vps_id: Uuid,
+    action: String,
+    ip: String,
+    label: String,
+    created_at: DateTime<Utc>,
+}
+
+/// Reverse lookup of an IP address or CIDR.
+/// Returns every VPS that holds or once held a matching address (from IP history),
+/// the DNS A/AAAA records pointing at it, and the latest probe results per source.
+/// All searches go through GiST inet indexes on `try_inet(...)`.
+#[utoipa::path(
+    get, path = "/api/lookup",
+    tag = "Lookup",
+    params(IpLookupQuery),
+    responses(
+        (status = 200, body = IpLookupResponse),
+        (status = 400, body = ErrorResponse),
+    )
+)]
+pub async fn lookup(
+    State(state): State<AppState>,
+    Query(q): Query<IpLookupQuery>,
+) -> Result<Json<IpLookupResponse>, AppError> {
+    let query = q.ip.trim().to_string();
+    if !is_ip_or_cidr(&query) {
+        return Err(AppError::BadRequest(format!(
+            "'{}' is not a valid IP address or CIDR",
+            query
+        )));
+    }
+
+    let history = sqlx::query_as::<_, HistoryRow>(
+        r#"SELECT vps_id, action, ip, label, created_at
+           FROM vps_ip_history
+           WHERE try_inet(ip) <<= $1::INET
+           ORDER BY vps_id, ip, created_at, action"#,
+    )
+    .bind(&query)
+    .fetch_all(&state.db)
+    .await?;
+
+    let dns_records = sqlx::query_as::<_, IpLookupDnsRecord>(
+        r#"SELECT d.id, z.zone_name, d.record_type, d.name, d.content, d.vps_id
+           FROM dns_records d
+           JOIN dns_zones z ON z.id = d.zone_uuid
+           WHERE d.record_type IN ('A', 'AAAA')
+             AND try_inet(d.content) <<= $1::INET
+           ORDER BY d.name"#,
+    )
+    .bind(&query)
+    .fetch_all(&state.db)
+    .await?;
+
+    let reachability = sqlx::query_as::<_, IpReachability>(
+        r#"SELECT DISTINCT ON (vps_id, ip, source, check_type)
+                  vps_id, host(ip) AS ip, source, check_type, success, latency_ms, checked_at
+           FROM ip_checks
+           WHERE ip <<= $1::INET
+           ORDER BY vps_id, ip, source, check_type, checked_at DESC"#,
+    )
+    .bind(&query)
+    .fetch_all(&state.db)
+    .await?;
+
+    let mut vps_ids: Vec<Uuid> = history.iter().map(|h| h.vps_id).collect();
+    vps_ids.extend(dns_records.iter().filter_map(|d| d.vps_id));
+    vps_ids.extend(reachability.iter().map(|r| r.vps_id));
+    vps_ids.sort();
+    vps_ids.dedup();
+
+    let rows = sqlx::query_as::<_, Vps>("SELECT * FROM vps WHERE id = ANY($1) ORDER BY hostname")
+        .bind(&vps_ids)
+        .fetch_all(&state.db)
+        .await?;
+
+    let mut holdings = build_holdings(&history);
+    let mut checks: HashMap<Uuid, Vec<IpReachability>> = HashMap::new();
+    for r in reachability {
+        checks.entry(r.vps_id).or_default().push(r);
+    }
+
+    let vps = rows
+        .into_iter()
+        .map(|v| {
+            let current_ips: Vec<&str> = v.ip_addresses.0.iter().map(|e| e.ip.as_str()).collect();
+            let mut held = holdings.remove(&v.id).unwrap_or_default();
+            for h in &mut held {
+                h.current = h.until.is_none() && current_ips.contains(&h.ip.as_str());
+            }
+            let held_ips: Vec<&str> = held.iter().map(|h| h.ip.as_str()).collect();
+            let mut dns_names: Vec<String> = dns_records
+                .iter()
+                .filter(|d| d.vps_id == Some(v.id) || held_ips.contains(&d.content.as_str()))
+                .map(|d| d.name.clone())
+                .collect();
+            dns_names.sort();
+            dns_names.dedup();
+
+            IpLookupVps {
+                vps_id: v.id,
+                current: held.iter().any(|h| h.current),
+                holdings: held,
+                dns_names,
+                reachability: checks.remove(&v.id).unwrap_or_default(),
+                hostname: v.hostname,
+                alias: v.alias,
+                status: v.status,
+                country: v.country,
+                provider_id: v.provider_id,
+            }
+        })
+        .collect();
+
+    Ok(Json(IpLookupResponse {
+        query,
+        vps,
+        dns_records,
+    }))
+}
+
+/// Pair 'added' / 'removed' history rows into holding periods per VPS.
+/// Rows must be ordered by (vps_id, ip, created_at).
+fn build_holdings(history: &[HistoryRow]) -> BTreeMap<Uuid, Vec<IpHolding>> {
+    let mut out: BTreeMap<Uuid, Vec<IpHolding>> = BTreeMap::new();
+    for h in history {
+        let spans = out.entry(h.vps_id).or_default();
+        let open = spans
+            .iter_mut()
+            .rev()
+            .find(|s| s.ip == h.ip)
+            .filter(|s| s.until.is_none());
+        match (h.action.as_str(), open) {
+            ("added", Some(_)) => {}
+            ("added", None) => spans.push(IpHolding {
+                ip: h.ip.clone(),
+                label: h.label.clone(),
+                from: Some(h.created_at),
+                until: None,
+                current: false,
+            }),
+            ("removed", Some(span)) => span.until = Some(h.created_at),
+            ("removed", None) => spans.push(IpHolding {
+                ip: h.ip.clone(),
+                label: h.label.clone(),
+                from: None,
+                until: Some(h.created_at),
+                current: false,
+            }),
+            _ => {}
+        }
+    }
+    out
+}
@@ -6,13 +6,18 @@ pub mod auth_routes;
 pub mod cloud;
 pub mod dns;
 pub mod costs;
+pub mod custom_fields;
 pub mod envoy;
 pub mod exchange_rates;
 pub mod ip_checks;
+pub mod ip_pool;
 pub mod ip_labels;
+pub mod lookup;
 pub mod prometheus;
+pub mod provider_plans;
 pub mod providers;
 pub mod rate_limit;
+pub mod renewals;
 pub mod settings;
 pub mod stats;
 pub mod tickets;
@@ -1,38 +1,66 @@
-use axum::{extract::State, routing::get, Json, Router};
+use axum::{
+    extract::{Query, State},
+    routing::get,
+    Json, Router,
+};
 use std::collections::HashMap;
 
-use crate::errors::AppError;
-use crate::models::{IpEntry, PrometheusTarget};
+use crate::custom_fields::{exposed_labels, load_definitions};
+use crate::errors::{AppError, ErrorResponse};
+use crate::models::{IpEntry, IpGeo, PrometheusTarget, VpsFilterQuery};
 use crate::routes::AppState;
+use crate::vps_filter::{bind_filter, VpsFilter};
 
 pub fn router() -> Router<AppState> {
     Router::new().route("/api/prometheus/targets", get(targets))
 }
 
 /// Output Prometheus file_sd compatible JSON.
-/// Only includes VPS with monitoring_enabled = true and status in (active, provisioning).
+/// Only includes VPS with monitoring_enabled = true and status in (active, provisioning);
+/// the status restriction is dropped when the `q` filter expression selects on status itself.
+/// Custom fields marked `expose_label` are added as `custom_<name>` labels.
+/// GeoIP data of the scrape IP is added as `asn`, `as_org`, `geo_country` and `geo_city`.
 #[utoipa::path(
     get, path = "/api/prometheus/targets",
     tag = "Prometheus",
+    params(VpsFilterQuery),
     responses(
         (status = 200, body = Vec<PrometheusTarget>),
+        (status = 400, body = ErrorResponse),
     )
 )]
-pub async fn targets(State(state): State<AppState>) -> Result<Json<Vec<PrometheusTarget>>, AppError> {
-    let rows = sqlx::query_as::<_, VpsTargetRow>(
+pub async fn targets(
+    State(state): State<AppState>,
+    Query(q): Query<VpsFilterQuery>,
+) -> Result<Json<Vec<PrometheusTarget>>, AppError> {
+    let filter = VpsFilter::parse_opt(q.q.as_deref())?;
+    let mut where_clause = String::from(" WHERE v.monitoring_enabled = true");
+    if !filter.as_ref().is_some_and(|f| f.references("status")) {
+        where_clause.push_str(" AND v.status IN ('active', 'provisioning')");
+    }
+    let mut bindings = Vec::new();
+    if let Some(ref f) = filter {
+        let (sql, b) = f.to_sql("v", 1)?;
+        where_clause.push_str(&format!(" AND {}", sql));
+        bindings = b;
+    }
+
+    let sql = format!(
         r#"SELECT
             v.hostname, v.alias, v.ip_addresses, v.node_exporter_port,
             v.country, v.city, v.dc_name, v.status, v.purpose, v.vpn_protocol,
-            v.tags, v.expire_date,
+            v.tags, v.expire_date, v.extra, v.ip_geo, v.geo_mismatch,
             COALESCE(p.name, '') as provider_name
            FROM vps v
-           LEFT JOIN providers p ON p.id = v.provider_id
-           WHERE v.monitoring_enabled = true
-             AND v.status IN ('active', 'provisioning')
+           LEFT JOIN providers p ON p.id = v.provider_id{}
            ORDER BY v.hostname"#,
-    )
-    .fetch_all(&state.db)
-    .await?;
+        where_clause
+    );
+    let rows = bind_filter(sqlx::query_as::<_, VpsTargetRow>(&sql), &bindings)
+        .fetch_all(&state.db)
+        .await?;
+    let mut conn = state.db.acquire().await?;
+    let defs = load_definitions(&mut conn).await?;
 
     let targets: Vec<PrometheusTarget> = rows
         .into_iter()
@@ -70,6 +98,26 @@ pub async fn targets(State(state): State<AppState>) -> Result<Json<Vec<Prometheu
             if let Some(exp) = row.expire_date {
                 labels.insert("expire_date".into(), exp.to_string());
             }
+            if let Some(geo) = row.ip_geo.0.iter().find(|g| g.ip == first_ip.ip) {
+                if let Some(asn) = geo.asn {
+                    labels.insert("asn".into(), asn.to_string());
+                }
+                if let Some(ref org) = geo.as_org {
+                    labels.insert("as_org".into(), org.clone());
+                }
+                if let Some(ref country) = geo.country {
+                    labels.insert("geo_country".into(), country.clone());
+                }
+                if let Some(ref city) = geo.city {
+                    labels.insert("geo_city".into(), city.clone());
+                }
+            }
+            if row.geo_mismatch {
+                labels.insert("geo_mismatch".into(), "true".into());
+            }
+            for (name, value) in exposed_labels(&defs, &row.extra) {
+                labels.insert(format!("custom_{}", name), value);
+            }
 
             Some(PrometheusTarget {
                 targets: vec![target],
@@ -95,5 +143,8 @@ struct VpsTargetRow {
     vpn_protocol: String,
     tags: Vec<String>,
     expire_date: Option<chrono::NaiveDate>,
+    extra: serde_json::Value,
+    ip_geo: sqlx::types::Json<Vec<IpGeo>>,
+    geo_mismatch: bool,
     provider_name: String,
 }
@@ -0,0 +1,303 @@
+use axum::{
+    extract::{Path, Query, State},
+    routing::get,
+    Extension, Json, Router,
+};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::auth::{require_role, CurrentUser};
+use crate::errors::{AppError, ErrorResponse};
+#[allow(unused_imports)]
+use crate::models::PaginatedProviderPlanResponse;
+use crate::models::{
+    CreateProviderPlan, PaginatedResponse, ProviderPlan, ProviderPlanListQuery,
+    UpdateProviderPlan,
+};
+use crate::routes::audit_logs::log_audit;
+use crate::routes::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/api/provider-plans", get(list).post(create))
+        .route(
+            "/api/provider-plans/{id}",
+            get(get_one).put(update).delete(delete),
+        )
+}
+
+#[utoipa::path(
+    get, path = "/api/provider-plans",
+    tag = "Provider Plans",
+    params(ProviderPlanListQuery),
+    responses(
+        (status = 200, body = PaginatedProviderPlanResponse),
+        (status = 500, body = ErrorResponse),
+    )
+)]
+pub async fn list(
+    State(state): State<AppState>,
+    Query(q): Query<ProviderPlanListQuery>,
+) -> Result<Json<PaginatedResponse<ProviderPlan>>, AppError> {
+    let per_page = q.per_page.clamp(1, 100);
+    let page = q.page.max(1);
+    let offset = (page - 1) * per_page;
+
+    let mut where_clause = String::from(" WHERE 1=1");
+    let mut param_idx = 0u32;
+
+    if q.provider_id.is_some() {
+        param_idx += 1;
+        where_clause.push_str(&format!(" AND provider_id = ${}", param_idx));
+    }
+    if q.location.is_some() {
+        param_idx += 1;
+        where_clause.push_str(&format!(" AND ${} = ANY(locations)", param_idx));
+    }
+
+    let count_sql = format!("SELECT COUNT(*) FROM provider_plans{}", where_clause);
+    let mut count_query = sqlx::query_as::<_, (i64,)>(&count_sql);
+    if let Some(ref v) = q.provider_id { count_query = count_query.bind(v); }
+    if let Some(ref v) = q.location { count_query = count_query.bind(v); }
+    let total = count_query.fetch_one(&state.db).await?.0;
+
+    let data_sql = format!(
+        "SELECT * FROM provider_plans{} ORDER BY provider_id, price, name LIMIT ${} OFFSET ${}",
+        where_clause,
+        param_idx + 1,
+        param_idx + 2
+    );
+    let mut query = sqlx::query_as::<_, ProviderPlan>(&data_sql);
+    if let Some(ref v) = q.provider_id { query = query.bind(v); }
+    if let Some(ref v) = q.location { query = query.bind(v); }
+    let rows = query.bind(per_page).bind(offset).fetch_all(&state.db).await?;
+
+    Ok(Json(PaginatedResponse {
+        data: rows,
+        total,
+        page,
+        per_page,
+    }))
+}
+
+#[utoipa::path(
+    get, path = "/api/provider-plans/{id}",
+    tag = "Provider Plans",
+    params(("id" = Uuid, Path, description = "Provider plan ID")),
+    responses(
+        (status = 200, body = ProviderPlan),
+        (status = 404, body = ErrorResponse),
+    )
+)]
+pub async fn get_one(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ProviderPlan>, AppError> {
+    let row = sqlx::query_as::<_, ProviderPlan>("SELECT * FROM provider_plans WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    Ok(Json(row))
+}
+
+#[utoipa::path(
+    post, path = "/api/provider-plans",
+    tag = "Provider Plans",
+    request_body = CreateProviderPlan,
+    responses(
+        (status = 200, body = ProviderPlan),
+        (status = 400, body = ErrorResponse),
+    )
+)]
+pub async fn create(
+    State(state): State<AppState>,
+    Extension(user): Extension<CurrentUser>,
+    Json(input): Json<CreateProviderPlan>,
+) -> Result<Json<ProviderPlan>, AppError> {
+    require_role(&user, &["admin", "operator"])?;
+    validate_plan(&input.name, input.price, input.billing_cycle_months)?;
+
+    let provider_exists =
+        sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM providers WHERE id = $1)")
+            .bind(input.provider_id)
+            .fetch_one(&state.db)
+            .await?;
+    if !provider_exists {
+        return Err(AppError::BadRequest(format!("unknown provider {}", input.provider_id)));
+    }
+
+    let row = sqlx::query_as::<_, ProviderPlan>(
+        r#"INSERT INTO provider_plans (
+               provider_id, name, cpu_cores, ram_mb, disk_gb, bandwidth_tb,
+               price, currency, billing_cycle_months, locations, stock_url, notes
+           ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+           RETURNING *"#,
+    )
+    .bind(input.provider_id)
+    .bind(input.name.trim())
+    .bind(input.cpu_cores)
+    .bind(input.ram_mb)
+    .bind(input.disk_gb)
+    .bind(input.bandwidth_tb.and_then(Decimal::from_f64_retain))
+    .bind(Decimal::from_f64_retain(input.price))
+    .bind(&input.currency)
+    .bind(input.billing_cycle_months)
+    .bind(&input.locations)
+    .bind(&input.stock_url)
+    .bind(&input.notes)
+    .fetch_one(&state.db)
+    .await?;
+
+    log_audit(
+        &state.db,
+        &user,
+        "create",
+        "provider_plan",
+        Some(&row.id.to_string()),
+        serde_json::json!({"provider_id": row.provider_id, "name": row.name, "price": row.price}),
+    )
+    .await;
+
+    Ok(Json(row))
+}
+
+#[utoipa::path(
+    put, path = "/api/provider-plans/{id}",
+    tag = "Provider Plans",
+    params(("id" = Uuid, Path, description = "Provider plan ID")),
+    request_body = UpdateProviderPlan,
+    responses(
+        (status = 200, body = ProviderPlan),
+        (status = 400, body = ErrorResponse),
+        (status = 404, body = ErrorResponse),
+    )
+)]
+pub async fn update(
+    State(state): State<AppState>,
+    Extension(user): Extension<CurrentUser>,
+    Path(id): Path<Uuid>,
+    Json(input): Json<UpdateProviderPlan>,
+) -> Result<Json<ProviderPlan>, AppError> {
+    require_role(&user, &["admin", "operator"])?;
+    let existing = sqlx::query_as::<_, ProviderPlan>("SELECT * FROM provider_plans WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    let old = serde_json::to_value(&existing).unwrap_or_default();
+
+    let name = input.name.unwrap_or(existing.name);
+    let price = match input.price {
+        Some(p) => Decimal::from_f64_retain(p),
+        None => Some(existing.price),
+    };
+    let billing_cycle_months = input.billing_cycle_months.unwrap_or(existing.billing_cycle_months);
+    validate_plan(&name, input.price.unwrap_or_default(), billing_cycle_months)?;
+
+    let row = sqlx::query_as::<_, ProviderPlan>(
+        r#"UPDATE provider_plans SET
+               name = $2, cpu_cores = $3, ram_mb = $4, disk_gb = $5, bandwidth_tb = $6,
+               price = $7, currency = $8, billing_cycle_months = $9,
+               locations = $10, stock_url = $11, notes = $12
+           WHERE id = $1
+           RETURNING *"#,
+    )
+    .bind(id)
+    .bind(name.trim())
+    .bind(input.cpu_cores.unwrap_or(existing.cpu_cores))
+    .bind(input.ram_mb.unwrap_or(existing.ram_mb))
+    .bind(input.disk_gb.unwrap_or(existing.disk_gb))
+    .bind(input.bandwidth_tb.map(|v| v.and_then(Decimal::from_f64_retain)).unwrap_or(existing.bandwidth_tb))
+    .bind(price)
+    .bind(input.currency.unwrap_or(existing.currency))
+    .bind(billing_cycle_months)
+    .bind(input.locations.unwrap_or(existing.locations))
+    .bind(input.stock_url.unwrap_or(existing.stock_url))
+    .bind(input.notes.unwrap_or(existing.notes))
+    .fetch_one(&state.db)
+    .await?;
+
+    let new_val = serde_json::to_value(&row).unwrap_or_default();
+    let mut changes = serde_json::Map::new();
+    let skip = ["id", "created_at", "updated_at"];
+    if let (serde_json::Value::Object(old_map), serde_json::Value::Object(new_map)) =
+        (&old, &new_val)
+    {
+        for (key, nv) in new_map {
+            if skip.contains(&key.as_str()) {
+                continue;
+            }
+            if let Some(ov) = old_map.get(key) {
+                if ov != nv {
+                    changes.insert(key.clone(), serde_json::json!({"from": ov, "to": nv}));
+                }
+            }
+        }
+    }
+
+    log_audit(
+        &state.db,
+        &user,
+        "update",
+        "provider_plan",
+        Some(&id.to_string()),
+        serde_json::json!({"name": row.name, "changes": changes}),
+    )
+    .await;
+
+    Ok(Json(row))
+}
+
+#[utoipa::path(
+    delete, path = "/api/provider-plans/{id}",
+    tag = "Provider Plans",
+    params(("id" = Uuid, Path, description = "Provider plan ID")),
+    responses(
+        (status = 200, description = "Provider plan deleted; VPS created from it keep their specs"),
+        (status = 404, body = ErrorResponse),
+    )
+)]
+pub async fn delete(
+    State(state): State<AppState>,
+    Extension(user): Extension<CurrentUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_role(&user, &["admin", "operator"])?;
+
+    let plan = sqlx::query_as::<_, ProviderPlan>("SELECT * FROM provider_plans WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    sqlx::query("DELETE FROM provider_plans WHERE id = $1")
+        .bind(id)
+        .execute(&state.db)
+        .await?;
+
+    log_audit(
+        &state.db,
+        &user,
+        "delete",
+        "provider_plan",
+        Some(&id.to_string()),
+        serde_json::json!({"provider_id": plan.provider_id, "name": plan.name}),
+    )
+    .await;
+
+    Ok(Json(serde_json::json!({ "deleted": true })))
+}
+
+fn validate_plan(name: &str, price: f64, billing_cycle_months: i32) -> Result<(), AppError> {
+    if name.trim().is_empty() {
+        return Err(AppError::BadRequest("name is required".into()));
+    }
+    if price < 0.0 {
+        return Err(AppError::BadRequest("price must not be negative".into()));
+    }
+    if billing_cycle_months < 1 {
+        return Err(AppError::BadRequest("billing_cycle_months must be at least 1".into()));
+    }
+    Ok(())
+}
@@ -1,28 +1,35 @@
 use axum::{
     extract::{Path, Query, State},
-    http::header,
+    http::{header, HeaderMap},
     response::IntoResponse,
     routing::get,
     Extension, Json, Router,
 };
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
 use crate::auth::{require_role, CurrentUser};
 use crate::errors::{AppError, ErrorResponse};
+use crate::etag::{check_if_match, tagged, Tagged};
+use crate::import_plan::{diff_field, summarize, ImportMode};
 #[allow(unused_imports)]
 use crate::models::PaginatedProviderResponse;
 use crate::models::{
-    CreateProvider, ExportQuery, ImportRequest, ImportResult, PaginatedResponse, Provider,
-    ProviderCsvRow, ProviderListQuery, UpdateProvider,
+    CreateProvider, ExportQuery, ImportPlanRow, ImportRequest, ImportResult, PaginatedResponse,
+    Provider, ProviderCsvRow, ProviderListQuery, ProviderRanking, ProviderScorecard,
+    ScorecardQuery, UpdateProvider,
 };
 use crate::routes::audit_logs::log_audit;
 use crate::routes::AppState;
+use crate::scorecard;
 
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/api/providers", get(list).post(create))
         .route("/api/providers/export", get(export))
         .route("/api/providers/import", axum::routing::post(import))
+        .route("/api/providers/ranking", get(ranking))
+        .route("/api/providers/{id}/scorecard", get(scorecard))
         .route(
             "/api/providers/{id}",
             get(get_one).put(update).delete(delete),
@@ -71,20 +78,71 @@ pub async fn list(
     tag = "Providers",
     params(("id" = Uuid, Path, description = "Provider ID")),
     responses(
-        (status = 200, body = Provider),
+        (status = 200, body = Provider, headers(("ETag" = String, description = "Row version for If-Match"))),
         (status = 404, body = ErrorResponse),
     )
 )]
 pub async fn get_one(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
-) -> Result<Json<Provider>, AppError> {
+) -> Result<Tagged<Provider>, AppError> {
     let row = sqlx::query_as::<_, Provider>("SELECT * FROM providers WHERE id = $1")
         .bind(id)
         .fetch_optional(&state.db)
         .await?
         .ok_or(AppError::NotFound)?;
-    Ok(Json(row))
+    Ok(tagged(row.updated_at, row))
+}
+
+/// Start of the scorecard window and the target currency.
+fn scorecard_window(q: &ScorecardQuery) -> (chrono::DateTime<chrono::Utc>, String) {
+    let days = q.days.unwrap_or(365).clamp(1, 3650);
+    let since = chrono::Utc::now() - chrono::Duration::days(days as i64);
+    let currency = q.currency.clone().unwrap_or_else(|| "USD".to_string());
+    (since, currency)
+}
+
+#[utoipa::path(
+    get, path = "/api/providers/{id}/scorecard",
+    tag = "Providers",
+    params(("id" = Uuid, Path, description = "Provider ID"), ScorecardQuery),
+    responses(
+        (status = 200, body = ProviderScorecard),
+        (status = 404, body = ErrorResponse),
+    )
+)]
+pub async fn scorecard(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(q): Query<ScorecardQuery>,
+) -> Result<Json<ProviderScorecard>, AppError> {
+    let (since, currency) = scorecard_window(&q);
+    let card = scorecard::compute(&state.db, since, Some(id), &currency)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or(AppError::NotFound)?;
+    Ok(Json(card))
+}
+
+#[utoipa::path(
+    get, path = "/api/providers/ranking",
+    tag = "Providers",
+    params(ScorecardQuery),
+    responses(
+        (status = 200, body = ProviderRanking),
+        (status = 400, body = ErrorResponse),
+    )
+)]
+pub async fn ranking(
+    State(state): State<AppState>,
+    Query(q): Query<ScorecardQuery>,
+) -> Result<Json<ProviderRanking>, AppError> {
+    let (since, currency) = scorecard_window(&q);
+    let sort = q.sort.clone().unwrap_or_else(|| "agent_uptime_pct".to_string());
+    let mut providers = scorecard::compute(&state.db, since, None, &currency).await?;
+    scorecard::rank(&mut providers, &sort)?;
+    Ok(Json(ProviderRanking { since, sort, providers }))
 }
 
 #[utoipa::path(
@@ -129,22 +187,26 @@ pub async fn create(
     params(("id" = Uuid, Path, description = "Provider ID")),
     request_body = UpdateProvider,
     responses(
-        (status = 200, body = Provider),
+        (status = 200, body = Provider, headers(("ETag" = String, description = "Row version for If-Match"))),
         (status = 404, body = ErrorResponse),
+        (status = 412, body = ErrorResponse, description = "If-Match does not match the current ETag"),
     )
 )]
 pub async fn update(
     State(state): State<AppState>,
     Extension(user): Extension<CurrentUser>,
     Path(id): Path<Uuid>,
+    headers: HeaderMap,
     Json(input): Json<UpdateProvider>,
-) -> Result<Json<Provider>, AppError> {
+) -> Result<Tagged<Provider>, AppError> {
     require_role(&user, &["admin", "operator"])?;
-    let existing = sqlx::query_as::<_, Provider>("SELECT * FROM providers WHERE id = $1")
+    let mut tx = state.db.begin().await?;
+    let existing = sqlx::query_as::<_, Provider>("SELECT * FROM providers WHERE id = $1 FOR UPDATE")
         .bind(id)
-        .fetch_optional(&state.db)
+        .fetch_optional(&mut *tx)
         .await?
         .ok_or(AppError::NotFound)?;
+    check_if_match(&headers, existing.updated_at)?;
     let old = serde_json::to_value(&existing).unwrap_or_default();
 
     let row = sqlx::query_as::<_, Provider>(
@@ -162,8 +224,9 @@ pub async fn update(
     .bind(input.api_supported.unwrap_or(existing.api_supported))
     .bind(input.rating.unwrap_or(existing.rating))
     .bind(input.notes.unwrap_or(existing.notes))
-    .fetch_one(&state.db)
+    .fetch_one(&mut *tx)
     .await?;
+    tx.commit().await?;
 
     let new = serde_json::to_value(&row).unwrap_or_default();
     let mut changes = serde_json::Map::new();
@@ -182,7 +245,7 @@ pub async fn update(
     log_audit(&state.db, &user, "update", "provider", Some(&id.to_string()),
         serde_json::json!({"name": row.name, "changes": changes})).await;
 
-    Ok(Json(row))
+    Ok(tagged(row.updated_at, row))
 }
 
 #[utoipa::path(
@@ -192,22 +255,31 @@ pub async fn update(
     responses(
         (status = 200, description = "Provider deleted"),
         (status = 404, body = ErrorResponse),
+        (status = 412, body = ErrorResponse, description = "If-Match does not match the current ETag"),
     )
 )]
 pub async fn delete(
     State(state): State<AppState>,
     Extension(user): Extension<CurrentUser>,
     Path(id): Path<Uuid>,
+    headers: HeaderMap,
 ) -> Result<Json<serde_json::Value>, AppError> {
     require_role(&user, &["admin", "operator"])?;
-    let result = sqlx::query("DELETE FROM providers WHERE id = $1")
+    let mut tx = state.db.begin().await?;
+    let updated_at = sqlx::query_scalar::<_, chrono::DateTime<chrono::Utc>>(
+        "SELECT updated_at FROM providers WHERE id = $1 FOR UPDATE",
+    )
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::NotFound)?;
+    check_if_match(&headers, updated_at)?;
+
+    sqlx::query("DELETE FROM providers WHERE id = $1")
         .bind(id)
-        .execute(&state.db)
+        .execute(&mut *tx)
         .await?;
-
-    if result.rows_affected() == 0 {
-        return Err(AppError::NotFound);
-    }
+    tx.commit().await?;
 
     log_audit(&state.db, &user, "delete", "provider", Some(&id.to_string()),
         serde_json::json!({})).await;
@@ -300,6 +372,11 @@ pub async fn import(
     Json(input): Json<ImportRequest>,
 ) -> Result<Json<ImportResult>, AppError> {
     require_role(&user, &["admin", "operator"])?;
+    let mode = ImportMode::parse(&input.mode)?;
+    if let Some(key) = input.key.as_deref().filter(|k| *k != "name") {
+        return Err(AppError::BadRequest(format!("Invalid key '{key}' (allowed: name)")));
+    }
+
     let rows: Vec<ProviderCsvRow> = match input.format.as_str() {
         "csv" => {
             let mut rdr = csv::Reader::from_reader(input.data.as_bytes());
@@ -308,10 +385,8 @@ pub async fn import(
                 match result {
                     Ok(row) => parsed.push(row),
                     Err(e) => {
-                        return Ok(Json(ImportResult {
-                            imported: 0,
-                            errors: vec![format!("Row {}: parse error: {e}", i + 1)],
-                        }));
+                        let error = ImportPlanRow::error(i + 1, String::new(), format!("parse error: {e}"));
+                        return Ok(Json(summarize(mode, vec![error])));
                     }
                 }
             }
@@ -322,35 +397,136 @@ pub async fn import(
         _ => return Err(AppError::BadRequest("format must be 'csv' or 'json'".into())),
     };
 
-    let mut imported = 0usize;
-    let mut errors = Vec::new();
+    // Existing providers by lowercased name
+    let mut existing: HashMap<String, Vec<Provider>> = HashMap::new();
+    if mode.matches_existing() {
+        let providers = sqlx::query_as::<_, Provider>("SELECT * FROM providers")
+            .fetch_all(&state.db)
+            .await?;
+        for p in providers {
+            existing.entry(p.name.to_lowercase()).or_default().push(p);
+        }
+    }
 
-    for (i, row) in rows.iter().enumerate() {
+    // Plan every row; the index into `plan` travels with each write
+    let mut plan = Vec::new();
+    let mut writes = Vec::new();
+    let mut seen = HashSet::new();
+    for (i, row) in rows.into_iter().enumerate() {
         let row_num = i + 1;
-        if row.name.trim().is_empty() {
-            errors.push(format!("Row {row_num}: name is required"));
+        let key = row.name.trim().to_string();
+        if key.is_empty() {
+            plan.push(ImportPlanRow::error(row_num, key, "name is required".into()));
             continue;
         }
 
-        let result = sqlx::query(
+        let mut target = None;
+        if mode.matches_existing() {
+            if !seen.insert(key.to_lowercase()) {
+                let msg = format!("duplicate name '{key}' in import");
+                plan.push(ImportPlanRow::error(row_num, key, msg));
+                continue;
+            }
+            match existing.get(&key.to_lowercase()).map(Vec::as_slice) {
+                None | Some([]) => {}
+                Some([current]) => {
+                    let changes = provider_changes(current, &row);
+                    if changes.is_empty() {
+                        plan.push(ImportPlanRow::skip(row_num, key, current.id));
+                        continue;
+                    }
+                    plan.push(ImportPlanRow::update(row_num, key, current.id, changes));
+                    target = Some(current.id);
+                }
+                Some(matches) => {
+                    let msg = format!("name '{key}' matches {} existing providers", matches.len());
+                    plan.push(ImportPlanRow::error(row_num, key, msg));
+                    continue;
+                }
+            }
+        }
+        if target.is_none() {
+            plan.push(ImportPlanRow::create(row_num, key));
+        }
+        writes.push((plan.len() - 1, target, row));
+    }
+
+    // Apply
+    match mode {
+        ImportMode::DryRun => {}
+        ImportMode::Insert => {
+            for (idx, _, row) in writes {
+                if let Err(e) = write_provider(&state.db, None, &row).await {
+                    let (row_num, key) = (plan[idx].row, plan[idx].key.clone());
+                    plan[idx] = ImportPlanRow::error(row_num, key, e.to_string());
+                }
+            }
+        }
+        ImportMode::Upsert => {
+            let mut tx = state.db.begin().await?;
+            for (idx, target, row) in writes {
+                write_provider(&mut *tx, target, &row).await.map_err(|e| {
+                    AppError::BadRequest(format!("Row {}: {e}", plan[idx].row))
+                })?;
+            }
+            tx.commit().await?;
+        }
+    }
+
+    let result = summarize(mode, plan);
+    if mode != ImportMode::DryRun && result.imported + result.updated > 0 {
+        log_audit(&state.db, &user, "import", "provider", None,
+            serde_json::json!({
+                "mode": input.mode,
+                "imported": result.imported,
+                "updated": result.updated,
+                "errors": result.errors.len(),
+            })).await;
+    }
+
+    Ok(Json(result))
+}
+
+/// Field diff between an existing provider and an import row.
+fn provider_changes(current: &Provider, row: &ProviderCsvRow) -> serde_json::Map<String, serde_json::Value> {
+    let name = row.name.trim().to_string();
+    let mut changes = serde_json::Map::new();
+    diff_field(&mut changes, "name", &current.name, Some(&name));
+    diff_field(&mut changes, "country", &current.country, Some(&row.country));
+    diff_field(&mut changes, "website", &current.website, Some(&row.website));
+    diff_field(&mut changes, "panel_url", &current.panel_url, Some(&row.panel_url));
+    diff_field(&mut changes, "api_supported", &current.api_supported, Some(&row.api_supported));
+    diff_field(&mut changes, "rating", &current.rating, Some(&row.rating));
+    diff_field(&mut changes, "notes", &current.notes, Some(&row.notes));
+    changes
+}
+
+/// Insert an imported provider, or overwrite `target` with it.
+async fn write_provider<'e, E>(db: E, target: Option<Uuid>, row: &ProviderCsvRow) -> Result<(), sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let query = match target {
+        Some(id) => sqlx::query(
+            r#"UPDATE providers SET name = $2, country = $3, website = $4, panel_url = $5,
+                   api_supported = $6, rating = $7, notes = $8
+               WHERE id = $1"#,
+        )
+        .bind(id),
+        None => sqlx::query(
             r#"INSERT INTO providers (name, country, website, panel_url, api_supported, rating, notes)
                VALUES ($1, $2, $3, $4, $5, $6, $7)"#,
-        )
-        .bind(&row.name)
+        ),
+    };
+    query
+        .bind(row.name.trim())
         .bind(&row.country)
         .bind(&row.website)
         .bind(&row.panel_url)
         .bind(row.api_supported)
         .bind(row.rating)
         .bind(&row.notes)
-        .execute(&state.db)
-        .await;
-
-        match result {
-            Ok(_) => imported += 1,
-            Err(e) => errors.push(format!("Row {row_num}: {e}")),
-        }
-    }
-
-    Ok(Json(ImportResult { imported, errors }))
+        .execute(db)
+        .await?;
+    Ok(())
 }
@@ -0,0 +1,184 @@
+use axum::{
+    extract::{Path, Query, State},
+    routing::get,
+    Extension, Json, Router,
+};
+use chrono::{Months, NaiveDate};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::auth::{require_role, CurrentUser};
+use crate::errors::{AppError, ErrorResponse};
+#[allow(unused_imports)]
+use crate::models::PaginatedVpsRenewalResponse;
+use crate::models::{CreateVpsRenewal, PaginatedResponse, Vps, VpsRenewal, VpsRenewalQuery};
+use crate::routes::audit_logs::log_audit;
+use crate::routes::AppState;
+use crate::vps_lifecycle::{set_change_context, SOURCE_MANUAL};
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/api/vps/{id}/renewals", get(list).post(create))
+}
+
+#[utoipa::path(
+    get, path = "/api/vps/{id}/renewals",
+    tag = "VPS",
+    params(
+        ("id" = Uuid, Path, description = "VPS ID"),
+        VpsRenewalQuery,
+    ),
+    responses(
+        (status = 200, body = PaginatedVpsRenewalResponse),
+        (status = 404, body = ErrorResponse),
+    )
+)]
+pub async fn list(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(q): Query<VpsRenewalQuery>,
+) -> Result<Json<PaginatedResponse<VpsRenewal>>, AppError> {
+    let exists = sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM vps WHERE id = $1)")
+        .bind(id)
+        .fetch_one(&state.db)
+        .await?;
+    if !exists {
+        return Err(AppError::NotFound);
+    }
+
+    let per_page = q.per_page.clamp(1, 100);
+    let page = q.page.max(1);
+    let offset = (page - 1) * per_page;
+
+    let (total,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM vps_renewals WHERE vps_id = $1")
+        .bind(id)
+        .fetch_one(&state.db)
+        .await?;
+
+    let rows = sqlx::query_as::<_, VpsRenewal>(
+        "SELECT * FROM vps_renewals WHERE vps_id = $1 ORDER BY paid_at DESC LIMIT $2 OFFSET $3",
+    )
+    .bind(id)
+    .bind(per_page)
+    .bind(offset)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(PaginatedResponse {
+        data: rows,
+        total,
+        page,
+        per_page,
+    }))
+}
+
+/// Record a paid renewal and advance the VPS expire_date by the billing period.
+/// The period is added to the current expire_date, or to the payment date if none is set.
+#[utoipa::path(
+    post, path = "/api/vps/{id}/renewals",
+    tag = "VPS",
+    params(("id" = Uuid, Path, description = "VPS ID")),
+    request_body = CreateVpsRenewal,
+    responses(
+        (status = 200, body = VpsRenewal),
+        (status = 400, body = ErrorResponse),
+        (status = 404, body = ErrorResponse),
+    )
+)]
+pub async fn create(
+    State(state): State<AppState>,
+    Extension(user): Extension<CurrentUser>,
+    Path(id): Path<Uuid>,
+    Json(input): Json<CreateVpsRenewal>,
+) -> Result<Json<VpsRenewal>, AppError> {
+    require_role(&user, &["admin", "operator"])?;
+
+    if !(1..=120).contains(&input.period_months) {
+        return Err(AppError::BadRequest("period_months must be between 1 and 120".into()));
+    }
+    let amount = Decimal::from_f64_retain(input.amount)
+        .filter(|a| !a.is_sign_negative())
+        .ok_or_else(|| AppError::BadRequest("amount must be a non-negative number".into()))?;
+
+    let mut tx = state.db.begin().await?;
+    set_change_context(&mut tx, SOURCE_MANUAL, &user.email).await?;
+
+    let vps = sqlx::query_as::<_, Vps>("SELECT * FROM vps WHERE id = $1 FOR UPDATE")
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    if vps.status == "deleted" {
+        return Err(AppError::BadRequest("Cannot renew a deleted VPS".into()));
+    }
+
+    let paid_at = input.paid_at.unwrap_or_else(chrono::Utc::now);
+    let new_expire_date = advance_expire_date(
+        vps.expire_date.unwrap_or(paid_at.date_naive()),
+        input.period_months,
+    )?;
+    let currency = input.currency.unwrap_or_else(|| vps.currency.clone());
+
+    let renewal = sqlx::query_as::<_, VpsRenewal>(
+        r#"INSERT INTO vps_renewals (
+            vps_id, period_months, amount, currency, paid_at, invoice_ref,
+            previous_expire_date, new_expire_date, actor, notes
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        RETURNING *"#,
+    )
+    .bind(id)
+    .bind(input.period_months)
+    .bind(amount.round_dp(2))
+    .bind(&currency)
+    .bind(paid_at)
+    .bind(&input.invoice_ref)
+    .bind(vps.expire_date)
+    .bind(new_expire_date)
+    .bind(&user.email)
+    .bind(&input.notes)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    sqlx::query("UPDATE vps SET expire_date = $2, updated_at = now() WHERE id = $1")
+        .bind(id)
+        .bind(new_expire_date)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    log_audit(&state.db, &user, "renew", "vps", Some(&id.to_string()),
+        serde_json::json!({
+            "hostname": vps.hostname,
+            "period_months": renewal.period_months,
+            "amount": renewal.amount,
+            "currency": renewal.currency,
+            "expire_date": {"from": vps.expire_date, "to": new_expire_date},
+        })).await;
+
+    Ok(Json(renewal))
+}
+
+fn advance_expire_date(base: NaiveDate, period_months: i32) -> Result<NaiveDate, AppError> {
+    base.checked_add_months(Months::new(period_months as u32))
+        .ok_or_else(|| AppError::BadRequest("expire_date out of range".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(y: i32, m: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, day).unwrap()
+    }
+
+    #[test]
+    fn test_advance_monthly_and_yearly() {
+        assert_eq!(advance_expire_date(d(2026, 3, 15), 1).unwrap(), d(2026, 4, 15));
+        assert_eq!(advance_expire_date(d(2026, 3, 15), 12).unwrap(), d(2027, 3, 15));
+    }
+
+    #[test]
+    fn test_advance_clamps_to_month_end() {
+        assert_eq!(advance_expire_date(d(2026, 1, 31), 1).unwrap(), d(2026, 2, 28));
+    }
+}
@@ -1,11 +1,18 @@
-use axum::{extract::State, routing::get, Json, Router};
+use axum::{
+    extract::{Query, State},
+    routing::get,
+    Json, Router,
+};
 
 use crate::errors::AppError;
-use crate::models::{CountStat, DashboardStats, Vps};
+use crate::heartbeats::{provider_uptime, vps_uptime};
+use crate::models::{AgentUptimeReport, CountStat, DashboardStats, HeartbeatQuery, Vps};
 use crate::routes::AppState;
 
 pub fn router() -> Router<AppState> {
-    Router::new().route("/api/stats", get(dashboard))
+    Router::new()
+        .route("/api/stats", get(dashboard))
+        .route("/api/stats/agent-uptime", get(agent_uptime))
 }
 
 #[utoipa::path(
@@ -50,6 +57,18 @@ pub async fn dashboard(State(state): State<AppState>) -> Result<Json<DashboardSt
     .fetch_all(&state.db)
     .await?;
 
+    let by_agent_state = sqlx::query_as::<_, CountStat>(
+        "SELECT agent_state as label, COUNT(*) as count FROM vps WHERE status NOT IN ('retired', 'deleted') AND agent_state != '' GROUP BY agent_state ORDER BY count DESC",
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    let by_ip_status = sqlx::query_as::<_, CountStat>(
+        "SELECT ip_status as label, COUNT(*) as count FROM vps WHERE status NOT IN ('retired', 'deleted') AND ip_status != '' GROUP BY ip_status ORDER BY count DESC",
+    )
+    .fetch_all(&state.db)
+    .await?;
+
     let expiring_soon = sqlx::query_as::<_, Vps>(
         r#"SELECT * FROM vps
            WHERE expire_date IS NOT NULL
@@ -67,6 +86,31 @@ pub async fn dashboard(State(state): State<AppState>) -> Result<Json<DashboardSt
         by_country,
         by_provider,
         by_status,
+        by_agent_state,
+        by_ip_status,
         expiring_soon,
     }))
 }
+
+#[utoipa::path(
+    get, path = "/api/stats/agent-uptime",
+    tag = "Stats",
+    params(HeartbeatQuery),
+    responses(
+        (status = 200, body = AgentUptimeReport),
+    )
+)]
+pub async fn agent_uptime(
+    State(state): State<AppState>,
+    Query(q): Query<HeartbeatQuery>,
+) -> Result<Json<AgentUptimeReport>, AppError> {
+    let since = q.since.unwrap_or_else(|| chrono::Utc::now() - chrono::Duration::hours(24));
+    let vps = vps_uptime(&state.db, since, None).await?;
+    let providers = provider_uptime(&vps);
+
+    Ok(Json(AgentUptimeReport {
+        since,
+        vps,
+        providers,
+    }))
+}
@@ -1,5 +1,6 @@
 use axum::{
     extract::{Path, Query, State},
+    http::HeaderMap,
     routing::get,
     Extension, Json, Router,
 };
@@ -7,13 +8,24 @@ use uuid::Uuid;
 
 use crate::auth::{require_role, CurrentUser};
 use crate::errors::AppError;
+use crate::etag::{check_if_match, tagged, Tagged};
 use crate::models::{
-    CreateTicket, CreateTicketComment, PaginatedResponse, PaginatedTicketResponse, Ticket,
+    CreateTicket, CreateTicketComment, ListResponse, PaginatedTicketResponse, Ticket,
     TicketComment, TicketListQuery, UpdateTicket,
 };
+use crate::pagination::{Keyed, Listing, SortColumn};
 use crate::routes::audit_logs::log_audit;
 use crate::routes::AppState;
 
+/// Columns accepted by `sort=` on `GET /api/tickets`.
+static TICKET_SORT_COLUMNS: &[SortColumn] = &[
+    SortColumn { name: "created_at", expr: "created_at", sql_type: "timestamptz" },
+    SortColumn { name: "updated_at", expr: "updated_at", sql_type: "timestamptz" },
+    SortColumn { name: "priority", expr: "priority", sql_type: "text" },
+    SortColumn { name: "status", expr: "status", sql_type: "text" },
+    SortColumn { name: "title", expr: "title", sql_type: "text" },
+];
+
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/api/tickets", get(list).post(create))
@@ -36,10 +48,15 @@ pub fn router() -> Router<AppState> {
 pub async fn list(
     State(state): State<AppState>,
     Query(q): Query<TicketListQuery>,
-) -> Result<Json<PaginatedResponse<Ticket>>, AppError> {
-    let per_page = q.per_page.clamp(1, 100);
-    let page = q.page.max(1);
-    let offset = (page - 1) * per_page;
+) -> Result<Json<ListResponse<Ticket>>, AppError> {
+    let listing = Listing::from_query(
+        q.page,
+        q.per_page,
+        q.cursor.as_deref(),
+        q.sort.as_deref(),
+        TICKET_SORT_COLUMNS,
+        "-created_at",
+    )?;
 
     let mut where_clause = String::from(" WHERE 1=1");
     let mut param_idx = 0u32;
@@ -61,33 +78,33 @@ pub async fn list(
         where_clause.push_str(&format!(" AND vps_id = ${param_idx}"));
     }
 
-    // Count
-    let count_sql = format!("SELECT COUNT(*) FROM tickets{where_clause}");
-    let mut count_query = sqlx::query_as::<_, (i64,)>(&count_sql);
-    if let Some(ref v) = q.status {
-        count_query = count_query.bind(v);
-    }
-    if let Some(ref v) = q.priority {
-        count_query = count_query.bind(v);
-    }
-    if let Some(ref v) = q.assigned_to {
-        count_query = count_query.bind(v);
-    }
-    if let Some(ref v) = q.vps_id {
-        count_query = count_query.bind(v);
+    // Count (page mode only)
+    let mut total = 0;
+    if !listing.is_keyset() {
+        let count_sql = format!("SELECT COUNT(*) FROM tickets{where_clause}");
+        let mut count_query = sqlx::query_as::<_, (i64,)>(&count_sql);
+        if let Some(ref v) = q.status {
+            count_query = count_query.bind(v);
+        }
+        if let Some(ref v) = q.priority {
+            count_query = count_query.bind(v);
+        }
+        if let Some(ref v) = q.assigned_to {
+            count_query = count_query.bind(v);
+        }
+        if let Some(ref v) = q.vps_id {
+            count_query = count_query.bind(v);
+        }
+        total = count_query.fetch_one(&state.db).await?.0;
     }
-    let total = count_query.fetch_one(&state.db).await?.0;
 
     // Data
-    param_idx += 1;
-    let limit_param = param_idx;
-    param_idx += 1;
-    let offset_param = param_idx;
-
     let data_sql = format!(
-        "SELECT * FROM tickets{where_clause} ORDER BY created_at DESC LIMIT ${limit_param} OFFSET ${offset_param}"
+        "SELECT *{} FROM tickets{where_clause}{}",
+        listing.select("id"),
+        listing.tail("id", "created_at DESC", param_idx + 1)
     );
-    let mut query = sqlx::query_as::<_, Ticket>(&data_sql);
+    let mut query = sqlx::query_as::<_, Keyed<Ticket>>(&data_sql);
     if let Some(ref v) = q.status {
         query = query.bind(v);
     }
@@ -100,16 +117,11 @@ pub async fn list(
     if let Some(ref v) = q.vps_id {
         query = query.bind(v);
     }
-    query = query.bind(per_page).bind(offset);
+    query = listing.bind(query);
 
     let rows = query.fetch_all(&state.db).await?;
 
-    Ok(Json(PaginatedResponse {
-        data: rows,
-        total,
-        page,
-        per_page,
-    }))
+    Ok(Json(listing.finish(rows, total)))
 }
 
 #[utoipa::path(
@@ -162,21 +174,21 @@ pub async fn create(
     tag = "Tickets",
     params(("id" = Uuid, Path, description = "Ticket ID")),
     responses(
-        (status = 200, body = Ticket),
+        (status = 200, body = Ticket, headers(("ETag" = String, description = "Row version for If-Match"))),
         (status = 404),
     )
 )]
 pub async fn get_one(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
-) -> Result<Json<Ticket>, AppError> {
+) -> Result<Tagged<Ticket>, AppError> {
     let row = sqlx::query_as::<_, Ticket>("SELECT * FROM tickets WHERE id = $1")
         .bind(id)
         .fetch_optional(&state.db)
         .await?
         .ok_or(AppError::NotFound)?;
 
-    Ok(Json(row))
+    Ok(tagged(row.updated_at, row))
 }
 
 #[utoipa::path(
@@ -186,23 +198,27 @@ pub async fn get_one(
     params(("id" = Uuid, Path, description = "Ticket ID")),
     request_body = UpdateTicket,
     responses(
-        (status = 200, body = Ticket),
+        (status = 200, body = Ticket, headers(("ETag" = String, description = "Row version for If-Match"))),
         (status = 404),
+        (status = 412, description = "If-Match does not match the current ETag"),
     )
 )]
 pub async fn update(
     State(state): State<AppState>,
     Extension(user): Extension<CurrentUser>,
     Path(id): Path<Uuid>,
+    headers: HeaderMap,
     Json(input): Json<UpdateTicket>,
-) -> Result<Json<Ticket>, AppError> {
+) -> Result<Tagged<Ticket>, AppError> {
     require_role(&user, &["admin", "operator"])?;
 
-    let existing = sqlx::query_as::<_, Ticket>("SELECT * FROM tickets WHERE id = $1")
+    let mut tx = state.db.begin().await?;
+    let existing = sqlx::query_as::<_, Ticket>("SELECT * FROM tickets WHERE id = $1 FOR UPDATE")
         .bind(id)
-        .fetch_optional(&state.db)
+        .fetch_optional(&mut *tx)
         .await?
         .ok_or(AppError::NotFound)?;
+    check_if_match(&headers, existing.updated_at)?;
     let old = serde_json::to_value(&existing).unwrap_or_default();
 
     let row = sqlx::query_as::<_, Ticket>(
@@ -220,8 +236,9 @@ pub async fn update(
     .bind(input.vps_id.unwrap_or(existing.vps_id))
     .bind(input.provider_id.unwrap_or(existing.provider_id))
     .bind(input.assigned_to.unwrap_or(existing.assigned_to))
-    .fetch_one(&state.db)
+    .fetch_one(&mut *tx)
     .await?;
+    tx.commit().await?;
 
     let new = serde_json::to_value(&row).unwrap_or_default();
     let mut changes = serde_json::Map::new();
@@ -247,7 +264,7 @@ pub async fn update(
     )
     .await;
 
-    Ok(Json(row))
+    Ok(tagged(row.updated_at, row))
 }
 
 #[utoipa::path(
@@ -258,23 +275,32 @@ pub async fn update(
     responses(
         (status = 200),
         (status = 404),
+        (status = 412, description = "If-Match does not match the current ETag"),
     )
 )]
 pub async fn delete(
     State(state): State<AppState>,
     Extension(user): Extension<CurrentUser>,
     Path(id): Path<Uuid>,
+    headers: HeaderMap,
 ) -> Result<Json<serde_json::Value>, AppError> {
     require_role(&user, &["admin"])?;
 
-    let result = sqlx::query("DELETE FROM tickets WHERE id = $1")
+    let mut tx = state.db.begin().await?;
+    let updated_at = sqlx::query_scalar::<_, chrono::DateTime<chrono::Utc>>(
+        "SELECT updated_at FROM tickets WHERE id = $1 FOR UPDATE",
+    )
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::NotFound)?;
+    check_if_match(&headers, updated_at)?;
+
+    sqlx::query("DELETE FROM tickets WHERE id = $1")
         .bind(id)
-        .execute(&state.db)
+        .execute(&mut *tx)
         .await?;
-
-    if result.rows_affected() == 0 {
-        return Err(AppError::NotFound);
-    }
+    tx.commit().await?;
 
     log_audit(
         &state.db,
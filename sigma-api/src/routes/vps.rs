@@ -1,24 +1,39 @@
 use axum::{
     extract::{Path, Query, State},
-    http::header,
+    http::{header, HeaderMap},
     response::IntoResponse,
     routing::get,
     Extension, Json, Router,
 };
+use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
-use std::collections::HashMap;
+use sqlx::PgConnection;
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
 use crate::auth::{require_role, CurrentUser};
+use crate::custom_fields::{custom_values, load_definitions, validate_custom, value_to_string};
 use crate::errors::{AppError, ErrorResponse};
+use crate::etag::{check_if_match, tagged, Tagged};
+use crate::heartbeats::vps_uptime;
+use crate::import_plan::{diff_field, error_message, summarize, ImportMode};
 #[allow(unused_imports)]
-use crate::models::{PaginatedVpsResponse, PaginatedVpsIpHistoryResponse};
 use crate::models::{
-    CreateVps, ExportQuery, ImportRequest, ImportResult, IpEntry, PaginatedResponse, UpdateVps,
-    Vps, VpsCsvRow, VpsIpHistory, VpsIpHistoryQuery, VpsListQuery,
+    PaginatedVpsHistoryResponse, PaginatedVpsIpHistoryResponse, PaginatedVpsResponse,
+    PaginatedVpsStatusHistoryResponse,
 };
+use crate::models::{
+    CreateVps, CustomField, HeartbeatQuery, ImportPlanRow, ImportRequest, ImportResult, IpEntry, IpReputation, IpStatus, ListResponse,
+    PaginatedResponse, ProviderPlan, UpdateVps,
+    Vps, VpsBulkOperation, VpsBulkRequest, VpsBulkResponse, VpsBulkRowResult, VpsCsvRow,
+    VpsExportQuery, VpsHeartbeat, VpsHeartbeatSeries, VpsHistoryEvent, VpsHistoryQuery, VpsIpHistory, VpsIpHistoryQuery,
+    VpsIpReputation, VpsIpStatus, VpsListQuery, VpsStatusHistory, VpsStatusHistoryQuery,
+};
+use crate::pagination::{Keyed, Listing, SortColumn};
 use crate::routes::audit_logs::log_audit;
 use crate::routes::AppState;
+use crate::vps_filter::{bind_filter, VpsFilter};
+use crate::vps_lifecycle::{check_transition, set_change_context, SOURCE_IMPORT, SOURCE_MANUAL};
 
 const VPS_INSERT_SQL: &str = r#"INSERT INTO vps (
     hostname, alias, provider_id,
@@ -30,7 +45,8 @@ const VPS_INSERT_SQL: &str = r#"INSERT INTO vps (
     purpose, vpn_protocol, tags,
     monitoring_enabled, node_exporter_port,
     extra, notes,
-    source, cloud_account_id
+    source, cloud_account_id,
+    auto_renew, plan_id
 ) VALUES (
     $1, $2, $3,
     $4, $5,
@@ -41,18 +57,36 @@ const VPS_INSERT_SQL: &str = r#"INSERT INTO vps (
     $18, $19, $20,
     $21, $22,
     $23, $24,
-    'manual', NULL
+    'manual', NULL,
+    $25, $26
 ) RETURNING *"#;
 
+/// Columns accepted by `sort=` on `GET /api/vps`.
+static VPS_SORT_COLUMNS: &[SortColumn] = &[
+    SortColumn { name: "hostname", expr: "hostname", sql_type: "text" },
+    SortColumn { name: "country", expr: "country", sql_type: "text" },
+    SortColumn { name: "status", expr: "status", sql_type: "text" },
+    SortColumn { name: "expire_date", expr: "COALESCE(expire_date, 'infinity'::date)", sql_type: "date" },
+    SortColumn { name: "cost_monthly", expr: "COALESCE(cost_monthly, 0)", sql_type: "numeric" },
+    SortColumn { name: "created_at", expr: "created_at", sql_type: "timestamptz" },
+    SortColumn { name: "updated_at", expr: "updated_at", sql_type: "timestamptz" },
+];
+
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/api/vps", get(list).post(create))
         .route("/api/vps/export", get(export))
         .route("/api/vps/import", axum::routing::post(import))
+        .route("/api/vps/bulk", axum::routing::post(bulk))
         .route("/api/vps/{id}", get(get_one).put(update).delete(delete))
         .route("/api/vps/{id}/retire", axum::routing::post(retire))
         .route("/api/vps/{id}/restore", axum::routing::post(restore))
         .route("/api/vps/{id}/ip-history", get(ip_history))
+        .route("/api/vps/{id}/status-history", get(status_history))
+        .route("/api/vps/{id}/history", get(history))
+        .route("/api/vps/{id}/heartbeats", get(heartbeats))
+        .route("/api/vps/{id}/ip-status", get(ip_status))
+        .route("/api/vps/{id}/ip-reputation", get(ip_reputation))
         .route("/api/vps/{id}/allocate-ports", axum::routing::post(allocate_ports))
 }
 
@@ -68,10 +102,16 @@ pub fn router() -> Router<AppState> {
 pub async fn list(
     State(state): State<AppState>,
     Query(q): Query<VpsListQuery>,
-) -> Result<Json<PaginatedResponse<Vps>>, AppError> {
-    let per_page = q.per_page.clamp(1, 100);
-    let page = q.page.max(1);
-    let offset = (page - 1) * per_page;
+) -> Result<Json<ListResponse<Vps>>, AppError> {
+    let listing = Listing::from_query(
+        q.page,
+        q.per_page,
+        q.cursor.as_deref(),
+        q.sort.as_deref(),
+        VPS_SORT_COLUMNS,
+        "hostname",
+    )?;
+    let filter = VpsFilter::parse_opt(q.q.as_deref())?;
 
     let mut where_clause = String::from(" WHERE 1=1");
     let mut param_idx = 0u32;
@@ -79,7 +119,7 @@ pub async fn list(
     if q.status.is_some() {
         param_idx += 1;
         where_clause.push_str(&format!(" AND status = ${}", param_idx));
-    } else {
+    } else if !filter.as_ref().is_some_and(|f| f.references("status")) {
         where_clause.push_str(" AND status != 'deleted'");
     }
     if q.country.is_some() {
@@ -109,32 +149,50 @@ pub async fn list(
         param_idx += 1;
         where_clause.push_str(&format!(" AND source = ${}", param_idx));
     }
+    if q.agent_state.is_some() {
+        param_idx += 1;
+        where_clause.push_str(&format!(" AND agent_state = ${}", param_idx));
+    }
+    if q.ip_status.is_some() {
+        param_idx += 1;
+        where_clause.push_str(&format!(" AND ip_status = ${}", param_idx));
+    }
+    let mut filter_bindings = Vec::new();
+    if let Some(ref f) = filter {
+        let (sql, bindings) = f.to_sql("vps", param_idx as usize + 1)?;
+        param_idx += bindings.len() as u32;
+        where_clause.push_str(&format!(" AND {}", sql));
+        filter_bindings = bindings;
+    }
 
-    // Count query
-    let count_sql = format!("SELECT COUNT(*) FROM vps{}", where_clause);
-    let mut count_query = sqlx::query_as::<_, (i64,)>(&count_sql);
-
-    if let Some(ref v) = q.status { count_query = count_query.bind(v); }
-    if let Some(ref v) = q.country { count_query = count_query.bind(v); }
-    if let Some(ref v) = q.provider_id { count_query = count_query.bind(v); }
-    if let Some(ref v) = q.purpose { count_query = count_query.bind(v); }
-    if let Some(ref v) = q.tag { count_query = count_query.bind(v); }
-    if let Some(v) = q.expiring_within_days { count_query = count_query.bind(v); }
-    if let Some(ref v) = q.source { count_query = count_query.bind(v); }
-
-    let total = count_query.fetch_one(&state.db).await?.0;
+    // Count query (page mode only)
+    let mut total = 0;
+    if !listing.is_keyset() {
+        let count_sql = format!("SELECT COUNT(*) FROM vps{}", where_clause);
+        let mut count_query = sqlx::query_as::<_, (i64,)>(&count_sql);
+
+        if let Some(ref v) = q.status { count_query = count_query.bind(v); }
+        if let Some(ref v) = q.country { count_query = count_query.bind(v); }
+        if let Some(ref v) = q.provider_id { count_query = count_query.bind(v); }
+        if let Some(ref v) = q.purpose { count_query = count_query.bind(v); }
+        if let Some(ref v) = q.tag { count_query = count_query.bind(v); }
+        if let Some(v) = q.expiring_within_days { count_query = count_query.bind(v); }
+        if let Some(ref v) = q.source { count_query = count_query.bind(v); }
+        if let Some(ref v) = q.agent_state { count_query = count_query.bind(v); }
+        if let Some(ref v) = q.ip_status { count_query = count_query.bind(v); }
+        count_query = bind_filter(count_query, &filter_bindings);
+
+        total = count_query.fetch_one(&state.db).await?.0;
+    }
 
     // Data query with pagination
-    param_idx += 1;
-    let limit_param = param_idx;
-    param_idx += 1;
-    let offset_param = param_idx;
-
     let data_sql = format!(
-        "SELECT * FROM vps{} ORDER BY status, expire_date ASC NULLS LAST, hostname LIMIT ${} OFFSET ${}",
-        where_clause, limit_param, offset_param
+        "SELECT *{} FROM vps{}{}",
+        listing.select("id"),
+        where_clause,
+        listing.tail("id", "status, expire_date ASC NULLS LAST, hostname", param_idx + 1)
     );
-    let mut query = sqlx::query_as::<_, Vps>(&data_sql);
+    let mut query = sqlx::query_as::<_, Keyed<Vps>>(&data_sql);
 
     if let Some(ref v) = q.status { query = query.bind(v); }
     if let Some(ref v) = q.country { query = query.bind(v); }
@@ -143,17 +201,14 @@ pub async fn list(
     if let Some(ref v) = q.tag { query = query.bind(v); }
     if let Some(v) = q.expiring_within_days { query = query.bind(v); }
     if let Some(ref v) = q.source { query = query.bind(v); }
-
-    query = query.bind(per_page).bind(offset);
+    if let Some(ref v) = q.agent_state { query = query.bind(v); }
+    if let Some(ref v) = q.ip_status { query = query.bind(v); }
+    query = bind_filter(query, &filter_bindings);
+    query = listing.bind(query);
 
     let rows = query.fetch_all(&state.db).await?;
 
-    Ok(Json(PaginatedResponse {
-        data: rows,
-        total,
-        page,
-        per_page,
-    }))
+    Ok(Json(listing.finish(rows, total)))
 }
 
 #[utoipa::path(
@@ -161,20 +216,20 @@ pub async fn list(
     tag = "VPS",
     params(("id" = Uuid, Path, description = "VPS ID")),
     responses(
-        (status = 200, body = Vps),
+        (status = 200, body = Vps, headers(("ETag" = String, description = "Row version for If-Match"))),
         (status = 404, body = ErrorResponse),
     )
 )]
 pub async fn get_one(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
-) -> Result<Json<Vps>, AppError> {
+) -> Result<Tagged<Vps>, AppError> {
     let row = sqlx::query_as::<_, Vps>("SELECT * FROM vps WHERE id = $1")
         .bind(id)
         .fetch_optional(&state.db)
         .await?
         .ok_or(AppError::NotFound)?;
-    Ok(Json(row))
+    Ok(tagged(row.updated_at, row))
 }
 
 fn validate_ips(entries: &[IpEntry]) -> Result<(), AppError> {
@@ -203,41 +258,20 @@ fn validate_ips(entries: &[IpEntry]) -> Result<(), AppError> {
 pub async fn create(
     State(state): State<AppState>,
     Extension(user): Extension<CurrentUser>,
-    Json(input): Json<CreateVps>,
+    Json(mut input): Json<CreateVps>,
 ) -> Result<Json<Vps>, AppError> {
     require_role(&user, &["admin", "operator"])?;
     validate_ips(&input.ip_addresses)?;
 
-    let ip_json = serde_json::to_value(&input.ip_addresses)
-        .map_err(|e| AppError::BadRequest(format!("Invalid ip_addresses: {}", e)))?;
+    let mut tx = state.db.begin().await?;
+    set_change_context(&mut tx, SOURCE_MANUAL, &user.email).await?;
+    apply_plan(&mut tx, &mut input).await?;
+    let defs = load_definitions(&mut tx).await?;
+    validate_custom(&defs, &input.purpose, &input.extra)?;
 
-    let row = sqlx::query_as::<_, Vps>(VPS_INSERT_SQL)
-    .bind(&input.hostname)
-    .bind(&input.alias)
-    .bind(input.provider_id)
-    .bind(&ip_json)
-    .bind(input.ssh_port)
-    .bind(&input.country)
-    .bind(&input.city)
-    .bind(&input.dc_name)
-    .bind(input.cpu_cores)
-    .bind(input.ram_mb)
-    .bind(input.disk_gb)
-    .bind(input.bandwidth_tb.and_then(Decimal::from_f64_retain))
-    .bind(input.cost_monthly.and_then(Decimal::from_f64_retain))
-    .bind(&input.currency)
-    .bind(&input.status)
-    .bind(input.purchase_date)
-    .bind(input.expire_date)
-    .bind(&input.purpose)
-    .bind(&input.vpn_protocol)
-    .bind(&input.tags)
-    .bind(input.monitoring_enabled)
-    .bind(input.node_exporter_port)
-    .bind(&input.extra)
-    .bind(&input.notes)
-    .fetch_one(&state.db)
-    .await?;
+    let row = insert_vps(&mut tx, &input).await?;
+
+    tx.commit().await?;
 
     log_audit(&state.db, &user, "create", "vps", Some(&row.id.to_string()),
         serde_json::json!({"hostname": row.hostname, "country": row.country})).await;
@@ -251,22 +285,57 @@ pub async fn create(
     params(("id" = Uuid, Path, description = "VPS ID")),
     request_body = UpdateVps,
     responses(
-        (status = 200, body = Vps),
+        (status = 200, body = Vps, headers(("ETag" = String, description = "Row version for If-Match"))),
+        (status = 400, body = ErrorResponse, description = "Illegal status transition"),
         (status = 404, body = ErrorResponse),
+        (status = 412, body = ErrorResponse, description = "If-Match does not match the current ETag"),
     )
 )]
 pub async fn update(
     State(state): State<AppState>,
     Extension(user): Extension<CurrentUser>,
     Path(id): Path<Uuid>,
+    headers: HeaderMap,
     Json(input): Json<UpdateVps>,
-) -> Result<Json<Vps>, AppError> {
+) -> Result<Tagged<Vps>, AppError> {
     require_role(&user, &["admin", "operator"])?;
-    let existing = sqlx::query_as::<_, Vps>("SELECT * FROM vps WHERE id = $1")
+
+    let mut tx = state.db.begin().await?;
+    let existing = sqlx::query_as::<_, Vps>("SELECT * FROM vps WHERE id = $1 FOR UPDATE")
         .bind(id)
-        .fetch_optional(&state.db)
+        .fetch_optional(&mut *tx)
         .await?
         .ok_or(AppError::NotFound)?;
+    check_if_match(&headers, existing.updated_at)?;
+    set_change_context(&mut tx, SOURCE_MANUAL, &user.email).await?;
+    let (row, changes) = update_row(&mut tx, existing, input).await?;
+    tx.commit().await?;
+
+    log_audit(&state.db, &user, "update", "vps", Some(&id.to_string()),
+        serde_json::json!({"hostname": row.hostname, "changes": changes})).await;
+
+    Ok(tagged(row.updated_at, row))
+}
+
+/// Apply a partial update to an already-loaded VPS row.
+/// Returns the updated row and a diff of changed fields for the audit log.
+async fn update_row(
+    conn: &mut PgConnection,
+    existing: Vps,
+    input: UpdateVps,
+) -> Result<(Vps, serde_json::Map<String, serde_json::Value>), AppError> {
+    if let Some(ref status) = input.status {
+        check_transition(&existing.status, status)?;
+    }
+
+    // Only re-validate custom fields when the purpose or the values change, so
+    // unrelated edits are not blocked by definitions added later.
+    let purpose = input.purpose.as_ref().unwrap_or(&existing.purpose);
+    let extra = input.extra.as_ref().unwrap_or(&existing.extra);
+    if purpose != &existing.purpose || custom_values(extra) != custom_values(&existing.extra) {
+        let defs = load_definitions(conn).await?;
+        validate_custom(&defs, purpose, extra)?;
+    }
 
     let old = serde_json::to_value(&existing).unwrap_or_default();
 
@@ -290,11 +359,12 @@ pub async fn update(
             status = $16, purchase_date = $17, expire_date = $18,
             purpose = $19, vpn_protocol = $20, tags = $21,
             monitoring_enabled = $22, node_exporter_port = $23,
-            extra = $24, notes = $25
+            extra = $24, notes = $25,
+            auto_renew = $26
            WHERE id = $1
            RETURNING *"#,
     )
-    .bind(id)
+    .bind(existing.id)
     .bind(input.hostname.unwrap_or(existing.hostname))
     .bind(input.alias.unwrap_or(existing.alias))
     .bind(input.provider_id.or(existing.provider_id))
@@ -319,7 +389,8 @@ pub async fn update(
     .bind(input.node_exporter_port.unwrap_or(existing.node_exporter_port))
     .bind(input.extra.unwrap_or(existing.extra))
     .bind(input.notes.unwrap_or(existing.notes))
-    .fetch_one(&state.db)
+    .bind(input.auto_renew.unwrap_or(existing.auto_renew))
+    .fetch_one(&mut *conn)
     .await?;
 
     // Build a diff of changed fields for the audit log
@@ -337,10 +408,7 @@ pub async fn update(
         }
     }
 
-    log_audit(&state.db, &user, "update", "vps", Some(&id.to_string()),
-        serde_json::json!({"hostname": row.hostname, "changes": changes})).await;
-
-    Ok(Json(row))
+    Ok((row, changes))
 }
 
 #[utoipa::path(
@@ -349,23 +417,41 @@ pub async fn update(
     params(("id" = Uuid, Path, description = "VPS ID")),
     responses(
         (status = 200, description = "VPS deleted"),
+        (status = 400, body = ErrorResponse, description = "Illegal status transition"),
         (status = 404, body = ErrorResponse),
+        (status = 412, body = ErrorResponse, description = "If-Match does not match the current ETag"),
     )
 )]
 pub async fn delete(
     State(state): State<AppState>,
     Extension(user): Extension<CurrentUser>,
     Path(id): Path<Uuid>,
+    headers: HeaderMap,
 ) -> Result<Json<serde_json::Value>, AppError> {
     require_role(&user, &["admin", "operator"])?;
 
-    let row = sqlx::query_as::<_, Vps>(
-        "UPDATE vps SET status = 'deleted', updated_at = now() WHERE id = $1 AND status != 'deleted' RETURNING *",
+    let mut tx = state.db.begin().await?;
+    let (status, updated_at) = sqlx::query_as::<_, (String, chrono::DateTime<chrono::Utc>)>(
+        "SELECT status, updated_at FROM vps WHERE id = $1 FOR UPDATE",
     )
     .bind(id)
-    .fetch_optional(&state.db)
+    .fetch_optional(&mut *tx)
     .await?
     .ok_or(AppError::NotFound)?;
+    if status == "deleted" {
+        return Err(AppError::NotFound);
+    }
+    check_if_match(&headers, updated_at)?;
+    check_transition(&status, "deleted")?;
+    set_change_context(&mut tx, SOURCE_MANUAL, &user.email).await?;
+
+    let row = sqlx::query_as::<_, Vps>(
+        "UPDATE vps SET status = 'deleted', updated_at = now() WHERE id = $1 RETURNING *",
+    )
+    .bind(id)
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
 
     log_audit(&state.db, &user, "delete", "vps", Some(&id.to_string()),
         serde_json::json!({
@@ -378,6 +464,15 @@ pub async fn delete(
     Ok(Json(serde_json::json!({ "deleted": true })))
 }
 
+/// Lock a VPS row and return its status.
+async fn current_status(conn: &mut PgConnection, id: Uuid) -> Result<String, AppError> {
+    sqlx::query_scalar::<_, String>("SELECT status FROM vps WHERE id = $1 FOR UPDATE")
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or(AppError::NotFound)
+}
+
 /// Quick action: mark a VPS as retired
 #[utoipa::path(
     post, path = "/api/vps/{id}/retire",
@@ -385,6 +480,7 @@ pub async fn delete(
     params(("id" = Uuid, Path, description = "VPS ID")),
     responses(
         (status = 200, body = Vps, description = "VPS marked as retired"),
+        (status = 400, body = ErrorResponse, description = "Illegal status transition"),
         (status = 404, body = ErrorResponse),
     )
 )]
@@ -395,6 +491,11 @@ pub async fn retire(
 ) -> Result<Json<Vps>, AppError> {
     require_role(&user, &["admin", "operator"])?;
 
+    let mut tx = state.db.begin().await?;
+    let status = current_status(&mut tx, id).await?;
+    check_transition(&status, "retired")?;
+    set_change_context(&mut tx, SOURCE_MANUAL, &user.email).await?;
+
     let now = chrono::Utc::now().to_rfc3339();
     let row = sqlx::query_as::<_, Vps>(
         r#"UPDATE vps SET
@@ -405,9 +506,9 @@ pub async fn retire(
     )
     .bind(id)
     .bind(&now)
-    .fetch_optional(&state.db)
-    .await?
-    .ok_or(AppError::NotFound)?;
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
 
     log_audit(&state.db, &user, "retire", "vps", Some(&id.to_string()),
         serde_json::json!({"hostname": row.hostname})).await;
@@ -432,13 +533,16 @@ pub async fn restore(
 ) -> Result<Json<Vps>, AppError> {
     require_role(&user, &["admin", "operator"])?;
 
+    let mut tx = state.db.begin().await?;
+    set_change_context(&mut tx, SOURCE_MANUAL, &user.email).await?;
     let row = sqlx::query_as::<_, Vps>(
         "UPDATE vps SET status = 'retired', updated_at = now() WHERE id = $1 AND status = 'deleted' RETURNING *",
     )
     .bind(id)
-    .fetch_optional(&state.db)
+    .fetch_optional(&mut *tx)
     .await?
     .ok_or(AppError::NotFound)?;
+    tx.commit().await?;
 
     log_audit(&state.db, &user, "restore", "vps", Some(&id.to_string()),
         serde_json::json!({"hostname": row.hostname})).await;
@@ -446,6 +550,242 @@ pub async fn restore(
     Ok(Json(row))
 }
 
+// ─── Bulk Operations ─────────────────────────────────────
+
+/// Upper bound on the number of VPS a single bulk request may touch.
+const BULK_MAX_TARGETS: usize = 1000;
+
+/// Apply one operation to many VPS in a single transaction.
+/// Each row runs inside a savepoint so every row gets a result; if any row
+/// fails (or `dry_run` is set) the whole transaction is rolled back.
+#[utoipa::path(
+    post, path = "/api/vps/bulk",
+    tag = "VPS",
+    request_body = VpsBulkRequest,
+    responses(
+        (status = 200, body = VpsBulkResponse),
+        (status = 400, body = ErrorResponse),
+    )
+)]
+pub async fn bulk(
+    State(state): State<AppState>,
+    Extension(user): Extension<CurrentUser>,
+    Json(input): Json<VpsBulkRequest>,
+) -> Result<Json<VpsBulkResponse>, AppError> {
+    require_role(&user, &["admin", "operator"])?;
+    validate_bulk_operation(&input.operation)?;
+
+    if let VpsBulkOperation::SetProvider { provider_id } = input.operation {
+        let exists = sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM providers WHERE id = $1)")
+            .bind(provider_id)
+            .fetch_one(&state.db)
+            .await?;
+        if !exists {
+            return Err(AppError::BadRequest(format!("Provider {} not found", provider_id)));
+        }
+    }
+
+    let mut tx = state.db.begin().await?;
+    set_change_context(&mut tx, SOURCE_MANUAL, &user.email).await?;
+
+    let mut results = Vec::new();
+    let targets = match (&input.ids, input.filter.as_deref()) {
+        (Some(ids), None) => {
+            let mut ids = ids.clone();
+            ids.sort();
+            ids.dedup();
+            if ids.len() > BULK_MAX_TARGETS {
+                return Err(AppError::BadRequest(format!(
+                    "Bulk operations are limited to {} VPS", BULK_MAX_TARGETS
+                )));
+            }
+            let rows = sqlx::query_as::<_, Vps>(
+                "SELECT * FROM vps WHERE id = ANY($1) ORDER BY hostname FOR UPDATE",
+            )
+            .bind(&ids)
+            .fetch_all(&mut *tx)
+            .await?;
+            for id in ids.iter().filter(|id| !rows.iter().any(|r| r.id == **id)) {
+                results.push(VpsBulkRowResult {
+                    id: *id,
+                    hostname: None,
+                    success: false,
+                    error: Some("VPS not found".into()),
+                    changes: serde_json::json!({}),
+                });
+            }
+            rows
+        }
+        (None, Some(expr)) => {
+            let filter = VpsFilter::parse(expr)?;
+            let (sql, bindings) = filter.to_sql("vps", 1)?;
+            let mut where_clause = format!(" WHERE {}", sql);
+            if !filter.references("status") {
+                where_clause.push_str(" AND status != 'deleted'");
+            }
+            let data_sql = format!("SELECT * FROM vps{} ORDER BY hostname FOR UPDATE", where_clause);
+            let rows = bind_filter(sqlx::query_as::<_, Vps>(&data_sql), &bindings)
+                .fetch_all(&mut *tx)
+                .await?;
+            if rows.len() > BULK_MAX_TARGETS {
+                return Err(AppError::BadRequest(format!(
+                    "Filter matches {} VPS; bulk operations are limited to {}",
+                    rows.len(), BULK_MAX_TARGETS
+                )));
+            }
+            rows
+        }
+        _ => {
+            return Err(AppError::BadRequest(
+                "Exactly one of 'ids' or 'filter' must be provided".into(),
+            ));
+        }
+    };
+    let matched = targets.len() + results.len();
+
+    let mut audited = Vec::new();
+    for existing in targets {
+        let id = existing.id;
+        let hostname = existing.hostname.clone();
+
+        sqlx::query("SAVEPOINT bulk_row").execute(&mut *tx).await?;
+        match apply_bulk_operation(&mut tx, existing, &input.operation).await {
+            Ok((row, changes)) => {
+                sqlx::query("RELEASE SAVEPOINT bulk_row").execute(&mut *tx).await?;
+                let changes = serde_json::Value::Object(changes);
+                audited.push((id, row.hostname, changes.clone()));
+                results.push(VpsBulkRowResult {
+                    id,
+                    hostname: Some(hostname),
+                    success: true,
+                    error: None,
+                    changes,
+                });
+            }
+            Err(e) => {
+                sqlx::query("ROLLBACK TO SAVEPOINT bulk_row").execute(&mut *tx).await?;
+                results.push(VpsBulkRowResult {
+                    id,
+                    hostname: Some(hostname),
+                    success: false,
+                    error: Some(e.to_string()),
+                    changes: serde_json::json!({}),
+                });
+            }
+        }
+    }
+
+    let failed = results.iter().filter(|r| !r.success).count();
+    let applied = !input.dry_run && failed == 0;
+    if applied {
+        tx.commit().await?;
+        let action = bulk_audit_action(&input.operation);
+        for (id, hostname, changes) in audited {
+            log_audit(&state.db, &user, action, "vps", Some(&id.to_string()),
+                serde_json::json!({"hostname": hostname, "changes": changes, "bulk": true})).await;
+        }
+    } else {
+        tx.rollback().await?;
+    }
+
+    Ok(Json(VpsBulkResponse {
+        dry_run: input.dry_run,
+        applied,
+        matched,
+        succeeded: results.len() - failed,
+        failed,
+        results,
+    }))
+}
+
+fn validate_bulk_operation(op: &VpsBulkOperation) -> Result<(), AppError> {
+    match op {
+        VpsBulkOperation::SetFields { fields } => {
+            if fields.hostname.is_some() || fields.alias.is_some() || fields.ip_addresses.is_some() {
+                return Err(AppError::BadRequest(
+                    "hostname, alias and ip_addresses cannot be set in bulk".into(),
+                ));
+            }
+        }
+        VpsBulkOperation::AddTags { tags } | VpsBulkOperation::RemoveTags { tags } => {
+            if tags.iter().all(|t| t.trim().is_empty()) {
+                return Err(AppError::BadRequest("tags must not be empty".into()));
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn bulk_audit_action(op: &VpsBulkOperation) -> &'static str {
+    match op {
+        VpsBulkOperation::Retire => "retire",
+        VpsBulkOperation::Restore => "restore",
+        _ => "update",
+    }
+}
+
+/// Translate a bulk operation into a partial update for one row.
+async fn apply_bulk_operation(
+    conn: &mut PgConnection,
+    existing: Vps,
+    op: &VpsBulkOperation,
+) -> Result<(Vps, serde_json::Map<String, serde_json::Value>), AppError> {
+    let input = match op {
+        VpsBulkOperation::SetFields { fields } => fields.clone(),
+        VpsBulkOperation::AddTags { tags } => {
+            let mut merged = existing.tags.clone();
+            for tag in tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+                if !merged.iter().any(|t| t == tag) {
+                    merged.push(tag.to_string());
+                }
+            }
+            UpdateVps { tags: Some(merged), ..Default::default() }
+        }
+        VpsBulkOperation::RemoveTags { tags } => {
+            let remaining = existing
+                .tags
+                .iter()
+                .filter(|t| !tags.iter().any(|r| r.trim() == t.as_str()))
+                .cloned()
+                .collect();
+            UpdateVps { tags: Some(remaining), ..Default::default() }
+        }
+        VpsBulkOperation::Retire => {
+            if existing.status == "deleted" {
+                return Err(AppError::BadRequest("VPS is deleted; restore it first".into()));
+            }
+            let mut extra = match existing.extra.clone() {
+                serde_json::Value::Object(m) => m,
+                _ => serde_json::Map::new(),
+            };
+            extra.insert(
+                "retired_at".to_string(),
+                serde_json::Value::String(chrono::Utc::now().to_rfc3339()),
+            );
+            UpdateVps {
+                status: Some("retired".into()),
+                monitoring_enabled: Some(false),
+                extra: Some(serde_json::Value::Object(extra)),
+                ..Default::default()
+            }
+        }
+        VpsBulkOperation::Restore => {
+            if existing.status != "deleted" {
+                return Err(AppError::BadRequest("VPS is not deleted".into()));
+            }
+            UpdateVps { status: Some("retired".into()), ..Default::default() }
+        }
+        VpsBulkOperation::SetProvider { provider_id } => {
+            UpdateVps { provider_id: Some(*provider_id), ..Default::default() }
+        }
+        VpsBulkOperation::SetMonitoring { enabled } => {
+            UpdateVps { monitoring_enabled: Some(*enabled), ..Default::default() }
+        }
+    };
+    update_row(conn, existing, input).await
+}
+
 // ─── IP History ──────────────────────────────────────────
 
 #[utoipa::path(
@@ -520,6 +860,337 @@ pub async fn ip_history(
     }))
 }
 
+// ─── Status History ──────────────────────────────────────
+
+#[utoipa::path(
+    get, path = "/api/vps/{id}/status-history",
+    tag = "VPS",
+    params(
+        ("id" = Uuid, Path, description = "VPS ID"),
+        VpsStatusHistoryQuery,
+    ),
+    responses(
+        (status = 200, body = PaginatedVpsStatusHistoryResponse),
+        (status = 404, body = ErrorResponse),
+    )
+)]
+pub async fn status_history(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(q): Query<VpsStatusHistoryQuery>,
+) -> Result<Json<PaginatedResponse<VpsStatusHistory>>, AppError> {
+    let exists = sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM vps WHERE id = $1)")
+        .bind(id)
+        .fetch_one(&state.db)
+        .await?;
+    if !exists {
+        return Err(AppError::NotFound);
+    }
+
+    let per_page = q.per_page.clamp(1, 100);
+    let page = q.page.max(1);
+    let offset = (page - 1) * per_page;
+
+    let mut where_clause = String::from(" WHERE vps_id = $1");
+    let mut param_idx = 1u32;
+
+    if q.to_status.is_some() {
+        param_idx += 1;
+        where_clause.push_str(&format!(" AND to_status = ${}", param_idx));
+    }
+    if q.source.is_some() {
+        param_idx += 1;
+        where_clause.push_str(&format!(" AND source = ${}", param_idx));
+    }
+
+    let count_sql = format!("SELECT COUNT(*) FROM vps_status_history{}", where_clause);
+    let mut count_query = sqlx::query_as::<_, (i64,)>(&count_sql).bind(id);
+    if let Some(ref v) = q.to_status { count_query = count_query.bind(v); }
+    if let Some(ref v) = q.source { count_query = count_query.bind(v); }
+    let total = count_query.fetch_one(&state.db).await?.0;
+
+    param_idx += 1;
+    let limit_param = param_idx;
+    param_idx += 1;
+    let offset_param = param_idx;
+
+    // Durations are computed over the full history before filtering.
+    let data_sql = format!(
+        r#"SELECT * FROM (
+               SELECT h.*,
+                      EXTRACT(EPOCH FROM COALESCE(
+                          LEAD(created_at) OVER (ORDER BY created_at), now()
+                      ) - created_at)::BIGINT AS duration_secs
+               FROM vps_status_history h
+               WHERE vps_id = $1
+           ) h{} ORDER BY created_at DESC LIMIT ${} OFFSET ${}"#,
+        where_clause, limit_param, offset_param
+    );
+    let mut query = sqlx::query_as::<_, VpsStatusHistory>(&data_sql).bind(id);
+    if let Some(ref v) = q.to_status { query = query.bind(v); }
+    if let Some(ref v) = q.source { query = query.bind(v); }
+    query = query.bind(per_page).bind(offset);
+
+    let rows = query.fetch_all(&state.db).await?;
+
+    Ok(Json(PaginatedResponse {
+        data: rows,
+        total,
+        page,
+        per_page,
+    }))
+}
+
+// ─── Timeline ────────────────────────────────────────────
+
+const VPS_HISTORY_KINDS: &[&str] = &["field", "ip", "ticket", "audit"];
+
+#[utoipa::path(
+    get, path = "/api/vps/{id}/history",
+    tag = "VPS",
+    params(
+        ("id" = Uuid, Path, description = "VPS ID"),
+        VpsHistoryQuery,
+    ),
+    responses(
+        (status = 200, body = PaginatedVpsHistoryResponse),
+        (status = 400, body = ErrorResponse),
+        (status = 404, body = ErrorResponse),
+    )
+)]
+pub async fn history(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(q): Query<VpsHistoryQuery>,
+) -> Result<Json<PaginatedResponse<VpsHistoryEvent>>, AppError> {
+    let exists = sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM vps WHERE id = $1)")
+        .bind(id)
+        .fetch_one(&state.db)
+        .await?;
+    if !exists {
+        return Err(AppError::NotFound);
+    }
+    if let Some(ref kind) = q.kind {
+        if !VPS_HISTORY_KINDS.contains(&kind.as_str()) {
+            return Err(AppError::BadRequest(format!(
+                "Invalid kind '{}' (allowed: {})",
+                kind,
+                VPS_HISTORY_KINDS.join(", ")
+            )));
+        }
+    }
+
+    let per_page = q.per_page.clamp(1, 100);
+    let page = q.page.max(1);
+    let offset = (page - 1) * per_page;
+
+    // Field changes, IP changes, tickets and audit entries merged into one timeline
+    let timeline = r#"(
+            SELECT 'field' AS kind, id AS ref_id, field, old_value, new_value, source, actor,
+                   '{}'::jsonb AS details, created_at
+            FROM vps_change_history WHERE vps_id = $1
+            UNION ALL
+            SELECT 'ip', id, 'ip_addresses',
+                   CASE WHEN action = 'removed' THEN to_jsonb(ip) END,
+                   CASE WHEN action = 'added' THEN to_jsonb(ip) END,
+                   source, '',
+                   jsonb_build_object('action', action, 'ip', ip, 'label', label), created_at
+            FROM vps_ip_history WHERE vps_id = $1
+            UNION ALL
+            SELECT 'ticket', t.id, NULL, NULL, NULL, '', COALESCE(u.email, ''),
+                   jsonb_build_object('title', t.title, 'status', t.status, 'priority', t.priority),
+                   t.created_at
+            FROM tickets t LEFT JOIN users u ON u.id = t.created_by
+            WHERE t.vps_id = $1
+            UNION ALL
+            SELECT 'audit', id, NULL, NULL, NULL, '', user_email,
+                   jsonb_build_object('action', action, 'details', details), created_at
+            FROM audit_logs WHERE resource = 'vps' AND resource_id = $1::text
+        ) e"#;
+
+    let mut where_clause = String::from(" WHERE 1=1");
+    let mut param_idx = 1u32;
+
+    if q.kind.is_some() {
+        param_idx += 1;
+        where_clause.push_str(&format!(" AND kind = ${}", param_idx));
+    }
+    if q.field.is_some() {
+        param_idx += 1;
+        where_clause.push_str(&format!(" AND field = ${}", param_idx));
+    }
+
+    let count_sql = format!("SELECT COUNT(*) FROM {}{}", timeline, where_clause);
+    let mut count_query = sqlx::query_as::<_, (i64,)>(&count_sql).bind(id);
+    if let Some(ref v) = q.kind { count_query = count_query.bind(v); }
+    if let Some(ref v) = q.field { count_query = count_query.bind(v); }
+    let total = count_query.fetch_one(&state.db).await?.0;
+
+    param_idx += 1;
+    let limit_param = param_idx;
+    param_idx += 1;
+    let offset_param = param_idx;
+
+    let data_sql = format!(
+        "SELECT * FROM {}{} ORDER BY created_at DESC, ref_id LIMIT ${} OFFSET ${}",
+        timeline, where_clause, limit_param, offset_param
+    );
+    let mut query = sqlx::query_as::<_, VpsHistoryEvent>(&data_sql).bind(id);
+    if let Some(ref v) = q.kind { query = query.bind(v); }
+    if let Some(ref v) = q.field { query = query.bind(v); }
+    query = query.bind(per_page).bind(offset);
+
+    let rows = query.fetch_all(&state.db).await?;
+
+    Ok(Json(PaginatedResponse {
+        data: rows,
+        total,
+        page,
+        per_page,
+    }))
+}
+
+// ─── Heartbeats ──────────────────────────────────────────
+
+#[utoipa::path(
+    get, path = "/api/vps/{id}/heartbeats",
+    tag = "VPS",
+    params(
+        ("id" = Uuid, Path, description = "VPS ID"),
+        HeartbeatQuery,
+    ),
+    responses(
+        (status = 200, body = VpsHeartbeatSeries),
+        (status = 404, body = ErrorResponse),
+    )
+)]
+pub async fn heartbeats(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(q): Query<HeartbeatQuery>,
+) -> Result<Json<VpsHeartbeatSeries>, AppError> {
+    let exists = sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM vps WHERE id = $1)")
+        .bind(id)
+        .fetch_one(&state.db)
+        .await?;
+    if !exists {
+        return Err(AppError::NotFound);
+    }
+
+    let since = q.since.unwrap_or_else(|| chrono::Utc::now() - chrono::Duration::hours(24));
+
+    // Raw samples for the recent past, hourly rollups beyond the raw retention
+    let rows = sqlx::query_as::<_, VpsHeartbeat>(
+        r#"SELECT ts, resolution, samples, covered_secs, load_1, load_5, load_15,
+                  ram_mb, ram_used_mb, disk_gb, disk_used_gb, uptime_seconds
+           FROM vps_heartbeats WHERE vps_id = $1 AND ts >= $2
+           ORDER BY ts"#,
+    )
+    .bind(id)
+    .bind(since)
+    .fetch_all(&state.db)
+    .await?;
+
+    let uptime_pct = vps_uptime(&state.db, since, Some(id))
+        .await?
+        .first()
+        .map(|u| u.uptime_pct);
+
+    Ok(Json(VpsHeartbeatSeries {
+        vps_id: id,
+        since,
+        uptime_pct,
+        data: rows,
+    }))
+}
+
+// ─── IP Status ───────────────────────────────────────────
+
+#[utoipa::path(
+    get, path = "/api/vps/{id}/ip-status",
+    tag = "VPS",
+    params(("id" = Uuid, Path, description = "VPS ID")),
+    responses(
+        (status = 200, body = VpsIpStatus),
+        (status = 404, body = ErrorResponse),
+    )
+)]
+pub async fn ip_status(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<VpsIpStatus>, AppError> {
+    let ip_status = sqlx::query_scalar::<_, String>("SELECT ip_status FROM vps WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    let ips = sqlx::query_as::<_, IpStatus>(
+        "SELECT * FROM ip_status WHERE vps_id = $1 AND ended_at IS NULL ORDER BY ip",
+    )
+    .bind(id)
+    .fetch_all(&state.db)
+    .await?;
+
+    let transitions = sqlx::query_as::<_, IpStatus>(
+        "SELECT * FROM ip_status WHERE vps_id = $1 ORDER BY created_at DESC LIMIT 100",
+    )
+    .bind(id)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(VpsIpStatus {
+        vps_id: id,
+        ip_status,
+        ips,
+        transitions,
+    }))
+}
+
+// ─── IP Reputation ───────────────────────────────────────
+
+#[utoipa::path(
+    get, path = "/api/vps/{id}/ip-reputation",
+    tag = "VPS",
+    params(("id" = Uuid, Path, description = "VPS ID")),
+    responses(
+        (status = 200, body = VpsIpReputation),
+        (status = 404, body = ErrorResponse),
+    )
+)]
+pub async fn ip_reputation(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<VpsIpReputation>, AppError> {
+    let blocklists =
+        sqlx::query_scalar::<_, Vec<String>>("SELECT blocklists FROM vps WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&state.db)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+    let listings = sqlx::query_as::<_, IpReputation>(
+        "SELECT * FROM ip_reputation WHERE vps_id = $1 AND delisted_at IS NULL ORDER BY ip, list",
+    )
+    .bind(id)
+    .fetch_all(&state.db)
+    .await?;
+
+    let history = sqlx::query_as::<_, IpReputation>(
+        "SELECT * FROM ip_reputation WHERE vps_id = $1 ORDER BY listed_at DESC LIMIT 100",
+    )
+    .bind(id)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(VpsIpReputation {
+        vps_id: id,
+        blocklists,
+        listings,
+        history,
+    }))
+}
+
 // ─── Allocate Ports (proxy to agent) ─────────────────────
 
 #[derive(Debug, serde::Deserialize, utoipa::ToSchema)]
@@ -644,20 +1315,30 @@ struct ProviderNameRow {
 #[utoipa::path(
     get, path = "/api/vps/export",
     tag = "VPS",
-    params(ExportQuery),
+    params(VpsExportQuery),
     responses(
         (status = 200, description = "Export data as CSV or JSON"),
+        (status = 400, body = ErrorResponse),
     )
 )]
 pub async fn export(
     State(state): State<AppState>,
-    Query(q): Query<ExportQuery>,
+    Query(q): Query<VpsExportQuery>,
 ) -> Result<impl IntoResponse, AppError> {
-    let rows = sqlx::query_as::<_, Vps>(
-        "SELECT * FROM vps ORDER BY status, expire_date ASC NULLS LAST, hostname",
-    )
-    .fetch_all(&state.db)
-    .await?;
+    let (where_clause, bindings) = match VpsFilter::parse_opt(q.q.as_deref())? {
+        Some(f) => {
+            let (sql, bindings) = f.to_sql("vps", 1)?;
+            (format!(" WHERE {}", sql), bindings)
+        }
+        None => (String::new(), Vec::new()),
+    };
+    let sql = format!(
+        "SELECT * FROM vps{} ORDER BY status, expire_date ASC NULLS LAST, hostname",
+        where_clause
+    );
+    let rows = bind_filter(sqlx::query_as::<_, Vps>(&sql), &bindings)
+        .fetch_all(&state.db)
+        .await?;
 
     let providers = sqlx::query_as::<_, ProviderNameRow>("SELECT id, name FROM providers")
         .fetch_all(&state.db)
@@ -704,6 +1385,9 @@ pub async fn export(
             let data = wtr
                 .into_inner()
                 .map_err(|e| AppError::Internal(format!("CSV flush error: {e}")))?;
+            let mut conn = state.db.acquire().await?;
+            let defs = load_definitions(&mut conn).await?;
+            let data = append_custom_columns(data, &rows, &defs)?;
 
             Ok((
                 [
@@ -736,8 +1420,48 @@ pub async fn export(
     }
 }
 
+/// Append one `custom.<name>` column per custom field definition to an exported CSV.
+/// Records are in the same order as `rows`.
+fn append_custom_columns(data: Vec<u8>, rows: &[Vps], defs: &[CustomField]) -> Result<Vec<u8>, AppError> {
+    if defs.is_empty() || rows.is_empty() {
+        return Ok(data);
+    }
+    let csv_err = |e: csv::Error| AppError::Internal(format!("CSV write error: {e}"));
+
+    let mut rdr = csv::Reader::from_reader(data.as_slice());
+    let mut wtr = csv::Writer::from_writer(vec![]);
+
+    let mut headers = rdr.headers().map_err(csv_err)?.clone();
+    for def in defs {
+        headers.push_field(&format!("custom.{}", def.name));
+    }
+    wtr.write_record(&headers).map_err(csv_err)?;
+
+    for (record, vps) in rdr.records().zip(rows) {
+        let mut record = record.map_err(csv_err)?;
+        let values = custom_values(&vps.extra);
+        for def in defs {
+            let value = values
+                .and_then(|m| m.get(&def.name))
+                .map(value_to_string)
+                .unwrap_or_default();
+            record.push_field(&value);
+        }
+        wtr.write_record(&record).map_err(csv_err)?;
+    }
+
+    wtr.into_inner()
+        .map_err(|e| AppError::Internal(format!("CSV flush error: {e}")))
+}
+
 // ─── Import ──────────────────────────────────────────────
 
+/// One write planned by an upsert or insert import.
+enum VpsImportOp {
+    Create(CreateVps),
+    Update(Uuid, UpdateVps),
+}
+
 #[utoipa::path(
     post, path = "/api/vps/import",
     tag = "VPS",
@@ -753,6 +1477,16 @@ pub async fn import(
     Json(input): Json<ImportRequest>,
 ) -> Result<Json<ImportResult>, AppError> {
     require_role(&user, &["admin", "operator"])?;
+    let mode = ImportMode::parse(&input.mode)?;
+    let key_field = match input.key.as_deref().unwrap_or("hostname") {
+        k @ ("hostname" | "alias") => k,
+        other => {
+            return Err(AppError::BadRequest(format!(
+                "Invalid key '{other}' (allowed: hostname, alias)"
+            )))
+        }
+    };
+
     // Build provider name → id lookup (case-insensitive)
     let providers = sqlx::query_as::<_, ProviderNameRow>("SELECT id, name FROM providers")
         .fetch_all(&state.db)
@@ -761,130 +1495,346 @@ pub async fn import(
         .into_iter()
         .map(|p| (p.name.to_lowercase(), p.id))
         .collect();
+    let mut conn = state.db.acquire().await?;
+    let defs = load_definitions(&mut conn).await?;
+    drop(conn);
 
-    let mut imported = 0usize;
-    let mut errors = Vec::new();
-
-    match input.format.as_str() {
+    let rows: Vec<Result<CreateVps, String>> = match input.format.as_str() {
         "csv" => {
             let mut rdr = csv::Reader::from_reader(input.data.as_bytes());
-            for (i, result) in rdr.deserialize::<VpsCsvRow>().enumerate() {
-                let row_num = i + 1;
-                let row = match result {
-                    Ok(r) => r,
-                    Err(e) => {
-                        errors.push(format!("Row {row_num}: parse error: {e}"));
-                        continue;
-                    }
-                };
+            rdr.deserialize::<VpsCsvRow>()
+                .map(|result| match result {
+                    Ok(row) => csv_row_to_create(row, &provider_lookup).map_err(error_message),
+                    Err(e) => Err(format!("parse error: {e}")),
+                })
+                .collect()
+        }
+        "json" => serde_json::from_str::<Vec<CreateVps>>(&input.data)
+            .map_err(|e| AppError::BadRequest(format!("Invalid JSON: {e}")))?
+            .into_iter()
+            .map(Ok)
+            .collect(),
+        _ => return Err(AppError::BadRequest("format must be 'csv' or 'json'".into())),
+    };
 
-                if row.hostname.trim().is_empty() {
-                    errors.push(format!("Row {row_num}: hostname is required"));
-                    continue;
-                }
+    // Existing (non-deleted) rows sharing a natural key with the input
+    let mut existing: HashMap<String, Vec<Vps>> = HashMap::new();
+    if mode.matches_existing() {
+        let keys: Vec<String> = rows
+            .iter()
+            .flatten()
+            .map(|v| import_key(v, key_field).to_string())
+            .collect();
+        let sql = format!("SELECT * FROM vps WHERE status != 'deleted' AND {key_field} = ANY($1)");
+        let matches = sqlx::query_as::<_, Vps>(&sql)
+            .bind(&keys)
+            .fetch_all(&state.db)
+            .await?;
+        for vps in matches {
+            let key = match key_field {
+                "alias" => vps.alias.clone(),
+                _ => vps.hostname.clone(),
+            };
+            existing.entry(key).or_default().push(vps);
+        }
+    }
 
-                let provider_id = match provider_lookup.get(&row.provider_name.to_lowercase()) {
-                    Some(id) => *id,
-                    None => {
-                        errors.push(format!(
-                            "Row {row_num}: unknown provider '{}'",
-                            row.provider_name
-                        ));
-                        continue;
-                    }
-                };
+    // Plan every row
+    let mut plan = Vec::new();
+    let mut ops = Vec::new();
+    let mut seen = HashSet::new();
+    for (i, parsed) in rows.into_iter().enumerate() {
+        let row_num = i + 1;
+        let vps = match parsed.and_then(|v| validate_import_row(v).map_err(error_message)) {
+            Ok(v) => v,
+            Err(e) => {
+                plan.push(ImportPlanRow::error(row_num, String::new(), e));
+                continue;
+            }
+        };
+        let key = import_key(&vps, key_field).to_string();
 
-                match import_vps_csv_row(&state, &row, provider_id).await {
-                    Ok(_) => imported += 1,
-                    Err(e) => errors.push(format!("Row {row_num}: {e}")),
-                }
+        if mode.matches_existing() {
+            if key.is_empty() {
+                plan.push(ImportPlanRow::error(row_num, key, format!("{key_field} is required")));
+                continue;
             }
-        }
-        "json" => {
-            let vps_list: Vec<CreateVps> = serde_json::from_str(&input.data)
-                .map_err(|e| AppError::BadRequest(format!("Invalid JSON: {e}")))?;
-
-            for (i, vps) in vps_list.iter().enumerate() {
-                let row_num = i + 1;
-                if vps.hostname.trim().is_empty() {
-                    errors.push(format!("Row {row_num}: hostname is required"));
+            if !seen.insert(key.clone()) {
+                let msg = format!("duplicate {key_field} '{key}' in import");
+                plan.push(ImportPlanRow::error(row_num, key, msg));
+                continue;
+            }
+            match existing.get(&key).map(Vec::as_slice) {
+                None | Some([]) => {}
+                Some([current]) => {
+                    let update = import_update(vps);
+                    match plan_update(&defs, current, &update) {
+                        Ok(changes) if changes.is_empty() => {
+                            plan.push(ImportPlanRow::skip(row_num, key, current.id));
+                        }
+                        Ok(changes) => {
+                            plan.push(ImportPlanRow::update(row_num, key, current.id, changes));
+                            ops.push((plan.len() - 1, VpsImportOp::Update(current.id, update)));
+                        }
+                        Err(e) => plan.push(ImportPlanRow::error(row_num, key, error_message(e))),
+                    }
                     continue;
                 }
-
-                if let Err(e) = validate_ips(&vps.ip_addresses) {
-                    errors.push(format!("Row {row_num}: {e}"));
+                Some(matches) => {
+                    let msg = format!("{key_field} '{key}' matches {} existing VPS", matches.len());
+                    plan.push(ImportPlanRow::error(row_num, key, msg));
                     continue;
                 }
+            }
+        }
 
-                let ip_json = match serde_json::to_value(&vps.ip_addresses) {
-                    Ok(v) => v,
+        if let Err(e) = validate_custom(&defs, &vps.purpose, &vps.extra) {
+            plan.push(ImportPlanRow::error(row_num, key, error_message(e)));
+            continue;
+        }
+        plan.push(ImportPlanRow::create(row_num, key));
+        ops.push((plan.len() - 1, VpsImportOp::Create(vps)));
+    }
+
+    // Apply
+    match mode {
+        ImportMode::DryRun => {}
+        ImportMode::Insert => {
+            // Each row stands alone, as before upsert existed
+            for (idx, op) in ops {
+                let VpsImportOp::Create(vps) = op else { continue };
+                let mut tx = state.db.begin().await?;
+                set_change_context(&mut tx, SOURCE_IMPORT, &user.email).await?;
+                match insert_vps(&mut tx, &vps).await {
+                    Ok(_) => tx.commit().await?,
                     Err(e) => {
-                        errors.push(format!("Row {row_num}: invalid ip_addresses: {e}"));
-                        continue;
+                        let (row_num, key) = (plan[idx].row, plan[idx].key.clone());
+                        plan[idx] = ImportPlanRow::error(row_num, key, e.to_string());
                     }
-                };
-
-                let result = sqlx::query(VPS_INSERT_SQL)
-                    .bind(&vps.hostname)
-                    .bind(&vps.alias)
-                    .bind(vps.provider_id)
-                    .bind(&ip_json)
-                    .bind(vps.ssh_port)
-                    .bind(&vps.country)
-                    .bind(&vps.city)
-                    .bind(&vps.dc_name)
-                    .bind(vps.cpu_cores)
-                    .bind(vps.ram_mb)
-                    .bind(vps.disk_gb)
-                    .bind(vps.bandwidth_tb.and_then(Decimal::from_f64_retain))
-                    .bind(vps.cost_monthly.and_then(Decimal::from_f64_retain))
-                    .bind(&vps.currency)
-                    .bind(&vps.status)
-                    .bind(vps.purchase_date)
-                    .bind(vps.expire_date)
-                    .bind(&vps.purpose)
-                    .bind(&vps.vpn_protocol)
-                    .bind(&vps.tags)
-                    .bind(vps.monitoring_enabled)
-                    .bind(vps.node_exporter_port)
-                    .bind(&vps.extra)
-                    .bind(&vps.notes)
-                    .execute(&state.db)
-                    .await;
-
-                match result {
-                    Ok(_) => imported += 1,
-                    Err(e) => errors.push(format!("Row {row_num}: {e}")),
                 }
             }
         }
-        _ => return Err(AppError::BadRequest("format must be 'csv' or 'json'".into())),
+        ImportMode::Upsert => {
+            let mut tx = state.db.begin().await?;
+            set_change_context(&mut tx, SOURCE_IMPORT, &user.email).await?;
+            for (idx, op) in ops {
+                let row_num = plan[idx].row;
+                let row_err = |e: AppError| AppError::BadRequest(format!("Row {row_num}: {}", error_message(e)));
+                match op {
+                    VpsImportOp::Create(vps) => {
+                        insert_vps(&mut tx, &vps).await.map_err(|e| row_err(e.into()))?;
+                    }
+                    VpsImportOp::Update(id, update) => {
+                        let current = sqlx::query_as::<_, Vps>("SELECT * FROM vps WHERE id = $1 FOR UPDATE")
+                            .bind(id)
+                            .fetch_one(&mut *tx)
+                            .await?;
+                        let (_, changes) = update_row(&mut tx, current, update).await.map_err(row_err)?;
+                        plan[idx].changes = serde_json::Value::Object(changes);
+                    }
+                }
+            }
+            tx.commit().await?;
+        }
     }
 
-    if imported > 0 {
+    let result = summarize(mode, plan);
+    if mode != ImportMode::DryRun && result.imported + result.updated > 0 {
         log_audit(&state.db, &user, "import", "vps", None,
-            serde_json::json!({"imported": imported, "errors": errors.len()})).await;
+            serde_json::json!({
+                "mode": input.mode,
+                "imported": result.imported,
+                "updated": result.updated,
+                "errors": result.errors.len(),
+            })).await;
     }
 
-    Ok(Json(ImportResult { imported, errors }))
+    Ok(Json(result))
+}
+
+/// Natural key value of an import row.
+fn import_key<'a>(vps: &'a CreateVps, key_field: &str) -> &'a str {
+    match key_field {
+        "alias" => vps.alias.trim(),
+        _ => vps.hostname.trim(),
+    }
+}
+
+/// Checks that do not depend on an existing row.
+fn validate_import_row(vps: CreateVps) -> Result<CreateVps, AppError> {
+    if vps.hostname.trim().is_empty() {
+        return Err(AppError::BadRequest("hostname is required".into()));
+    }
+    validate_ips(&vps.ip_addresses)?;
+    Ok(vps)
+}
+
+/// Turn an import row into an update of a matched VPS. `auto_renew` and `source`
+/// are left alone, as are `provider_id` and `extra` when the row leaves them empty.
+fn import_update(vps: CreateVps) -> UpdateVps {
+    let extra_empty = vps.extra.is_null() || vps.extra.as_object().is_some_and(|m| m.is_empty());
+    UpdateVps {
+        hostname: Some(vps.hostname),
+        alias: Some(vps.alias),
+        provider_id: vps.provider_id,
+        ip_addresses: Some(vps.ip_addresses),
+        ssh_port: Some(vps.ssh_port),
+        country: Some(vps.country),
+        city: Some(vps.city),
+        dc_name: Some(vps.dc_name),
+        cpu_cores: Some(vps.cpu_cores),
+        ram_mb: Some(vps.ram_mb),
+        disk_gb: Some(vps.disk_gb),
+        bandwidth_tb: Some(vps.bandwidth_tb),
+        cost_monthly: Some(vps.cost_monthly),
+        currency: Some(vps.currency),
+        status: Some(vps.status),
+        purchase_date: Some(vps.purchase_date),
+        expire_date: Some(vps.expire_date),
+        auto_renew: None,
+        purpose: Some(vps.purpose),
+        vpn_protocol: Some(vps.vpn_protocol),
+        tags: Some(vps.tags),
+        monitoring_enabled: Some(vps.monitoring_enabled),
+        node_exporter_port: Some(vps.node_exporter_port),
+        extra: if extra_empty { None } else { Some(vps.extra) },
+        notes: Some(vps.notes),
+    }
+}
+
+/// Validate an update the way [`update_row`] will and preview its field diff
+/// without writing. An empty diff means the row is already up to date.
+fn plan_update(
+    defs: &[CustomField],
+    current: &Vps,
+    input: &UpdateVps,
+) -> Result<serde_json::Map<String, serde_json::Value>, AppError> {
+    if let Some(ref status) = input.status {
+        check_transition(&current.status, status)?;
+    }
+    let purpose = input.purpose.as_ref().unwrap_or(&current.purpose);
+    let extra = input.extra.as_ref().unwrap_or(&current.extra);
+    if purpose != &current.purpose || custom_values(extra) != custom_values(&current.extra) {
+        validate_custom(defs, purpose, extra)?;
+    }
+
+    // NUMERIC(10,2) columns: compare at the stored precision
+    let decimal = |v: &Option<Option<f64>>| {
+        v.map(|v| v.and_then(Decimal::from_f64_retain).map(|d| d.round_dp(2)))
+    };
+
+    let mut changes = serde_json::Map::new();
+    diff_field(&mut changes, "hostname", &current.hostname, input.hostname.as_ref());
+    diff_field(&mut changes, "alias", &current.alias, input.alias.as_ref());
+    diff_field(&mut changes, "provider_id", &current.provider_id, input.provider_id.map(Some).as_ref());
+    diff_field(&mut changes, "ip_addresses", &current.ip_addresses.0, input.ip_addresses.as_ref());
+    diff_field(&mut changes, "ssh_port", &current.ssh_port, input.ssh_port.as_ref());
+    diff_field(&mut changes, "country", &current.country, input.country.as_ref());
+    diff_field(&mut changes, "city", &current.city, input.city.as_ref());
+    diff_field(&mut changes, "dc_name", &current.dc_name, input.dc_name.as_ref());
+    diff_field(&mut changes, "cpu_cores", &current.cpu_cores, input.cpu_cores.as_ref());
+    diff_field(&mut changes, "ram_mb", &current.ram_mb, input.ram_mb.as_ref());
+    diff_field(&mut changes, "disk_gb", &current.disk_gb, input.disk_gb.as_ref());
+    diff_field(&mut changes, "bandwidth_tb", &current.bandwidth_tb, decimal(&input.bandwidth_tb).as_ref());
+    diff_field(&mut changes, "cost_monthly", &current.cost_monthly, decimal(&input.cost_monthly).as_ref());
+    diff_field(&mut changes, "currency", &current.currency, input.currency.as_ref());
+    diff_field(&mut changes, "status", &current.status, input.status.as_ref());
+    diff_field(&mut changes, "purchase_date", &current.purchase_date, input.purchase_date.as_ref());
+    diff_field(&mut changes, "expire_date", &current.expire_date, input.expire_date.as_ref());
+    diff_field(&mut changes, "auto_renew", &current.auto_renew, input.auto_renew.as_ref());
+    diff_field(&mut changes, "purpose", &current.purpose, input.purpose.as_ref());
+    diff_field(&mut changes, "vpn_protocol", &current.vpn_protocol, input.vpn_protocol.as_ref());
+    diff_field(&mut changes, "tags", &current.tags, input.tags.as_ref());
+    diff_field(&mut changes, "monitoring_enabled", &current.monitoring_enabled, input.monitoring_enabled.as_ref());
+    diff_field(&mut changes, "node_exporter_port", &current.node_exporter_port, input.node_exporter_port.as_ref());
+    diff_field(&mut changes, "extra", &current.extra, input.extra.as_ref());
+    diff_field(&mut changes, "notes", &current.notes, input.notes.as_ref());
+    Ok(changes)
 }
 
-async fn import_vps_csv_row(
-    state: &AppState,
-    row: &VpsCsvRow,
-    provider_id: Uuid,
-) -> Result<(), AppError> {
+/// Insert a new VPS with `VPS_INSERT_SQL`. Validation is the caller's job.
+async fn insert_vps(conn: &mut PgConnection, vps: &CreateVps) -> Result<Vps, sqlx::Error> {
+    let ip_json = serde_json::to_value(&vps.ip_addresses).unwrap_or_default();
+    sqlx::query_as::<_, Vps>(VPS_INSERT_SQL)
+        .bind(&vps.hostname)
+        .bind(&vps.alias)
+        .bind(vps.provider_id)
+        .bind(&ip_json)
+        .bind(vps.ssh_port)
+        .bind(&vps.country)
+        .bind(&vps.city)
+        .bind(&vps.dc_name)
+        .bind(vps.cpu_cores)
+        .bind(vps.ram_mb)
+        .bind(vps.disk_gb)
+        .bind(vps.bandwidth_tb.and_then(Decimal::from_f64_retain))
+        .bind(vps.cost_monthly.and_then(Decimal::from_f64_retain))
+        .bind(&vps.currency)
+        .bind(&vps.status)
+        .bind(vps.purchase_date)
+        .bind(vps.expire_date)
+        .bind(&vps.purpose)
+        .bind(&vps.vpn_protocol)
+        .bind(&vps.tags)
+        .bind(vps.monitoring_enabled)
+        .bind(vps.node_exporter_port)
+        .bind(&vps.extra)
+        .bind(&vps.notes)
+        .bind(vps.auto_renew)
+        .bind(vps.plan_id)
+        .fetch_one(&mut *conn)
+        .await
+}
+
+/// Fill provider, specs and cost left unset on `input` from its `plan_id`.
+/// The monthly cost is the plan price spread over its billing cycle.
+async fn apply_plan(conn: &mut PgConnection, input: &mut CreateVps) -> Result<(), AppError> {
+    let Some(plan_id) = input.plan_id else {
+        return Ok(());
+    };
+    let plan = sqlx::query_as::<_, ProviderPlan>("SELECT * FROM provider_plans WHERE id = $1")
+        .bind(plan_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| AppError::BadRequest(format!("unknown plan {}", plan_id)))?;
+
+    if input.provider_id.is_some_and(|p| p != plan.provider_id) {
+        return Err(AppError::BadRequest(format!(
+            "plan '{}' belongs to a different provider",
+            plan.name
+        )));
+    }
+    input.provider_id = Some(plan.provider_id);
+    input.cpu_cores = input.cpu_cores.or(plan.cpu_cores);
+    input.ram_mb = input.ram_mb.or(plan.ram_mb);
+    input.disk_gb = input.disk_gb.or(plan.disk_gb);
+    if input.bandwidth_tb.is_none() {
+        input.bandwidth_tb = plan.bandwidth_tb.and_then(|v| v.to_f64());
+    }
+    if input.cost_monthly.is_none() {
+        let monthly = plan.price / Decimal::from(plan.billing_cycle_months);
+        input.cost_monthly = monthly.round_dp(2).to_f64();
+        input.currency = plan.currency;
+    }
+    Ok(())
+}
+
+/// Parse the string-encoded CSV columns of an import row into a `CreateVps`.
+fn csv_row_to_create(
+    row: VpsCsvRow,
+    provider_lookup: &HashMap<String, Uuid>,
+) -> Result<CreateVps, AppError> {
+    let provider_id = *provider_lookup
+        .get(&row.provider_name.to_lowercase())
+        .ok_or_else(|| AppError::BadRequest(format!("unknown provider '{}'", row.provider_name)))?;
+
     // Parse ip_addresses from JSON string
-    let ip_entries: Vec<IpEntry> = if row.ip_addresses.trim().is_empty() {
+    let ip_addresses: Vec<IpEntry> = if row.ip_addresses.trim().is_empty() {
         vec![]
     } else {
         serde_json::from_str(&row.ip_addresses)
             .map_err(|e| AppError::BadRequest(format!("invalid ip_addresses JSON: {e}")))?
     };
-    validate_ips(&ip_entries)?;
-
-    let ip_json = serde_json::to_value(&ip_entries)
-        .map_err(|e| AppError::BadRequest(format!("ip_addresses serialize error: {e}")))?;
 
     // Parse tags from semicolon-separated
     let tags: Vec<String> = if row.tags.trim().is_empty() {
@@ -902,55 +1852,45 @@ async fn import_vps_csv_row(
     };
 
     // Parse dates
-    let purchase_date = if row.purchase_date.trim().is_empty() {
-        None
-    } else {
-        Some(
-            row.purchase_date
-                .trim()
-                .parse::<chrono::NaiveDate>()
-                .map_err(|e| AppError::BadRequest(format!("invalid purchase_date: {e}")))?,
-        )
-    };
-
-    let expire_date = if row.expire_date.trim().is_empty() {
-        None
-    } else {
-        Some(
-            row.expire_date
-                .trim()
-                .parse::<chrono::NaiveDate>()
-                .map_err(|e| AppError::BadRequest(format!("invalid expire_date: {e}")))?,
-        )
+    let parse_date = |value: &str, field: &str| -> Result<Option<chrono::NaiveDate>, AppError> {
+        if value.trim().is_empty() {
+            return Ok(None);
+        }
+        value
+            .trim()
+            .parse::<chrono::NaiveDate>()
+            .map(Some)
+            .map_err(|e| AppError::BadRequest(format!("invalid {field}: {e}")))
     };
-
-    sqlx::query(VPS_INSERT_SQL)
-        .bind(&row.hostname)
-        .bind(&row.alias)
-        .bind(provider_id)
-        .bind(&ip_json)
-        .bind(row.ssh_port)
-        .bind(&row.country)
-        .bind(&row.city)
-        .bind(&row.dc_name)
-        .bind(row.cpu_cores)
-        .bind(row.ram_mb)
-        .bind(row.disk_gb)
-        .bind(row.bandwidth_tb.and_then(Decimal::from_f64_retain))
-        .bind(row.cost_monthly.and_then(Decimal::from_f64_retain))
-        .bind(&row.currency)
-        .bind(&row.status)
-        .bind(purchase_date)
-        .bind(expire_date)
-        .bind(&row.purpose)
-        .bind(&row.vpn_protocol)
-        .bind(&tags)
-        .bind(row.monitoring_enabled)
-        .bind(row.node_exporter_port)
-        .bind(&extra)
-        .bind(&row.notes)
-        .execute(&state.db)
-        .await?;
-
-    Ok(())
+    let purchase_date = parse_date(&row.purchase_date, "purchase_date")?;
+    let expire_date = parse_date(&row.expire_date, "expire_date")?;
+
+    Ok(CreateVps {
+        hostname: row.hostname,
+        alias: row.alias,
+        provider_id: Some(provider_id),
+        plan_id: None,
+        ip_addresses,
+        ssh_port: row.ssh_port,
+        country: row.country,
+        city: row.city,
+        dc_name: row.dc_name,
+        cpu_cores: row.cpu_cores,
+        ram_mb: row.ram_mb,
+        disk_gb: row.disk_gb,
+        bandwidth_tb: row.bandwidth_tb,
+        cost_monthly: row.cost_monthly,
+        currency: row.currency,
+        status: row.status,
+        purchase_date,
+        expire_date,
+        auto_renew: false,
+        purpose: row.purpose,
+        vpn_protocol: row.vpn_protocol,
+        tags,
+        monitoring_enabled: row.monitoring_enabled,
+        node_exporter_port: row.node_exporter_port,
+        extra,
+        notes: row.notes,
+    })
 }
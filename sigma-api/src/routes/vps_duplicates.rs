@@ -48,6 +48,10 @@ pub async fn detect_duplicates(
             WHERE v.status NOT IN ('retired', 'deleted')
               AND e->>'label' != 'internal'
               AND e->>'ip' IS NOT NULL
+              -- Floating IPs managed by the pool legitimately move between hosts
+              AND NOT EXISTS (
+                  SELECT 1 FROM ip_pool p WHERE p.floating AND p.ip = e->>'ip'
+              )
         )
         SELECT
             a.vps_id AS vps_a_id,
@@ -0,0 +1,349 @@
+//! Provider scorecards computed from fleet history: VPS lifespan (status
+//! history), IP blocking (ip_checks, vps_ip_history), tickets, agent uptime
+//! (heartbeats) and cost per active VPS-month.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::db::Db;
+use crate::errors::AppError;
+use crate::heartbeats::{provider_uptime, vps_uptime};
+use crate::models::ProviderScorecard;
+use crate::routes::costs::load_rates;
+
+/// Ranking keys and whether a higher value is better.
+pub const SCORECARD_SORTS: &[(&str, bool)] = &[
+    ("agent_uptime_pct", true),
+    ("ip_block_rate", false),
+    ("median_lifespan_days", true),
+    ("tickets_per_vps", false),
+    ("median_resolution_hours", false),
+    ("cost_per_active_month", false),
+];
+
+/// Every non-deleted VPS with when it started and, if retired, when it retired.
+/// VPS retired before status history existed fall back to `updated_at`.
+const FLEET_CTE: &str = r#"WITH fleet AS (
+    SELECT v.id, v.provider_id, v.status, v.cost_monthly, v.currency,
+           COALESCE(v.purchase_date::timestamptz, v.created_at) AS started_at,
+           CASE WHEN v.status = 'retired' THEN COALESCE(
+               (SELECT MAX(h.created_at) FROM vps_status_history h
+                WHERE h.vps_id = v.id AND h.to_status = 'retired'),
+               v.updated_at)
+           END AS retired_at
+    FROM vps v
+    WHERE v.status != 'deleted' AND v.provider_id IS NOT NULL
+      AND ($2::UUID IS NULL OR v.provider_id = $2)
+)"#;
+
+#[derive(sqlx::FromRow)]
+struct ProviderRow {
+    id: Uuid,
+    name: String,
+    rating: Option<i16>,
+}
+
+#[derive(sqlx::FromRow)]
+struct FleetRow {
+    provider_id: Uuid,
+    vps_count: i64,
+    vps_active: i64,
+    retired_count: i64,
+    median_lifespan_days: Option<f64>,
+}
+
+#[derive(sqlx::FromRow)]
+struct CostRow {
+    provider_id: Uuid,
+    currency: String,
+    active_months: f64,
+    costed_months: Decimal,
+    cost: Decimal,
+}
+
+#[derive(sqlx::FromRow)]
+struct IpRow {
+    provider_id: Uuid,
+    ips_checked: i64,
+    ips_blocked: i64,
+}
+
+#[derive(sqlx::FromRow)]
+struct TicketRow {
+    provider_id: Uuid,
+    tickets_opened: i64,
+    tickets_resolved: i64,
+    median_resolution_hours: Option<f64>,
+}
+
+/// Scorecards for all providers, or for one when `provider_id` is given.
+pub async fn compute(
+    db: &Db,
+    since: DateTime<Utc>,
+    provider_id: Option<Uuid>,
+    currency: &str,
+) -> Result<Vec<ProviderScorecard>, AppError> {
+    let providers = sqlx::query_as::<_, ProviderRow>(
+        "SELECT id, name, rating FROM providers WHERE $1::UUID IS NULL OR id = $1 ORDER BY name",
+    )
+    .bind(provider_id)
+    .fetch_all(db)
+    .await?;
+
+    let fleet = sqlx::query_as::<_, FleetRow>(&format!(
+        r#"{FLEET_CTE}
+           SELECT provider_id,
+                  COUNT(*) FILTER (WHERE retired_at IS NULL OR retired_at >= $1) AS vps_count,
+                  COUNT(*) FILTER (WHERE status = 'active') AS vps_active,
+                  COUNT(*) FILTER (WHERE retired_at >= $1) AS retired_count,
+                  percentile_cont(0.5) WITHIN GROUP (
+                      ORDER BY EXTRACT(EPOCH FROM retired_at - started_at)::FLOAT8 / 86400
+                  ) FILTER (WHERE retired_at >= $1) AS median_lifespan_days
+           FROM fleet
+           GROUP BY provider_id"#
+    ))
+    .bind(since)
+    .bind(provider_id)
+    .fetch_all(db)
+    .await?;
+
+    // Months of service inside the window, per VPS, then summed per currency
+    let costs = sqlx::query_as::<_, CostRow>(&format!(
+        r#"{FLEET_CTE},
+           served AS (
+               SELECT provider_id, currency, cost_monthly,
+                      GREATEST(EXTRACT(EPOCH FROM COALESCE(retired_at, now()) - GREATEST(started_at, $1)), 0)
+                          / 2629800 AS months
+               FROM fleet
+               WHERE retired_at IS NULL OR retired_at >= $1
+           )
+           SELECT provider_id, currency,
+                  COALESCE(SUM(months), 0)::FLOAT8 AS active_months,
+                  COALESCE(SUM(months) FILTER (WHERE cost_monthly IS NOT NULL), 0)::NUMERIC AS costed_months,
+                  COALESCE(SUM(cost_monthly * months), 0)::NUMERIC AS cost
+           FROM served
+           GROUP BY provider_id, currency"#
+    ))
+    .bind(since)
+    .bind(provider_id)
+    .fetch_all(db)
+    .await?;
+
+    let ips = sqlx::query_as::<_, IpRow>(&format!(
+        r#"{FLEET_CTE}
+           SELECT f.provider_id,
+                  COUNT(*) AS ips_checked,
+                  COUNT(*) FILTER (WHERE s.ok_ratio < 0.5) AS ips_blocked
+           FROM (
+               SELECT vps_id, ip, AVG(CASE WHEN success THEN 1.0 ELSE 0.0 END) AS ok_ratio
+               FROM ip_checks WHERE checked_at >= $1
+               GROUP BY vps_id, ip
+           ) s
+           JOIN fleet f ON f.id = s.vps_id
+           GROUP BY f.provider_id"#
+    ))
+    .bind(since)
+    .bind(provider_id)
+    .fetch_all(db)
+    .await?;
+
+    let ip_changes = sqlx::query_as::<_, (Uuid, i64)>(&format!(
+        r#"{FLEET_CTE}
+           SELECT f.provider_id, COUNT(*)
+           FROM vps_ip_history h
+           JOIN fleet f ON f.id = h.vps_id
+           WHERE h.action = 'removed' AND h.created_at >= $1
+           GROUP BY f.provider_id"#
+    ))
+    .bind(since)
+    .bind(provider_id)
+    .fetch_all(db)
+    .await?;
+
+    let tickets = sqlx::query_as::<_, TicketRow>(
+        r#"SELECT COALESCE(t.provider_id, v.provider_id) AS provider_id,
+                  COUNT(*) AS tickets_opened,
+                  COUNT(t.resolved_at) AS tickets_resolved,
+                  percentile_cont(0.5) WITHIN GROUP (
+                      ORDER BY EXTRACT(EPOCH FROM t.resolved_at - t.created_at)::FLOAT8 / 3600
+                  ) FILTER (WHERE t.resolved_at IS NOT NULL) AS median_resolution_hours
+           FROM tickets t
+           LEFT JOIN vps v ON v.id = t.vps_id
+           WHERE t.created_at >= $1
+             AND COALESCE(t.provider_id, v.provider_id) IS NOT NULL
+             AND ($2::UUID IS NULL OR COALESCE(t.provider_id, v.provider_id) = $2)
+           GROUP BY 1"#,
+    )
+    .bind(since)
+    .bind(provider_id)
+    .fetch_all(db)
+    .await?;
+
+    let uptime = provider_uptime(&vps_uptime(db, since, None).await?);
+    let rates = load_rates(db, currency).await?;
+
+    let mut fleet: HashMap<Uuid, FleetRow> = fleet.into_iter().map(|r| (r.provider_id, r)).collect();
+    let mut ips: HashMap<Uuid, IpRow> = ips.into_iter().map(|r| (r.provider_id, r)).collect();
+    let ip_changes: HashMap<Uuid, i64> = ip_changes.into_iter().collect();
+    let mut tickets: HashMap<Uuid, TicketRow> =
+        tickets.into_iter().map(|r| (r.provider_id, r)).collect();
+    let uptime: HashMap<Uuid, f64> = uptime
+        .into_iter()
+        .filter_map(|u| u.provider_id.map(|id| (id, u.uptime_pct)))
+        .collect();
+
+    let mut cards = Vec::with_capacity(providers.len());
+    for p in providers {
+        let f = fleet.remove(&p.id);
+        let ip = ips.remove(&p.id);
+        let t = tickets.remove(&p.id);
+
+        // Cost per active month in the target currency
+        let mut active_months = 0.0;
+        let mut costed_months = Decimal::ZERO;
+        let mut cost = Decimal::ZERO;
+        let mut unconverted_currencies = Vec::new();
+        for c in costs.iter().filter(|c| c.provider_id == p.id) {
+            active_months += c.active_months;
+            if c.costed_months.is_zero() {
+                continue;
+            }
+            let rate = if c.currency == currency {
+                Some(Decimal::ONE)
+            } else {
+                rates.get(&c.currency).copied()
+            };
+            match rate {
+                Some(rate) => {
+                    costed_months += c.costed_months;
+                    cost += c.cost * rate;
+                }
+                None => unconverted_currencies.push(c.currency.clone()),
+            }
+        }
+        let cost_per_active_month =
+            (!costed_months.is_zero()).then(|| (cost / costed_months).round_dp(2));
+
+        let vps_count = f.as_ref().map_or(0, |f| f.vps_count);
+        let tickets_opened = t.as_ref().map_or(0, |t| t.tickets_opened);
+        let (ips_checked, ips_blocked) = ip.map_or((0, 0), |i| (i.ips_checked, i.ips_blocked));
+
+        cards.push(ProviderScorecard {
+            provider_id: p.id,
+            provider_name: p.name,
+            rating: p.rating,
+            since,
+            vps_count,
+            vps_active: f.as_ref().map_or(0, |f| f.vps_active),
+            retired_count: f.as_ref().map_or(0, |f| f.retired_count),
+            median_lifespan_days: f.and_then(|f| f.median_lifespan_days),
+            ips_checked,
+            ips_blocked,
+            ip_block_rate: (ips_checked > 0).then(|| ips_blocked as f64 / ips_checked as f64),
+            ip_changes: ip_changes.get(&p.id).copied().unwrap_or(0),
+            tickets_opened,
+            tickets_resolved: t.as_ref().map_or(0, |t| t.tickets_resolved),
+            tickets_per_vps: (vps_count > 0).then(|| tickets_opened as f64 / vps_count as f64),
+            median_resolution_hours: t.and_then(|t| t.median_resolution_hours),
+            agent_uptime_pct: uptime.get(&p.id).copied(),
+            active_months,
+            currency: currency.to_string(),
+            cost_per_active_month,
+            unconverted_currencies,
+        });
+    }
+
+    Ok(cards)
+}
+
+/// Order scorecards best first by `sort`; providers without that metric go last.
+pub fn rank(cards: &mut [ProviderScorecard], sort: &str) -> Result<(), AppError> {
+    let higher_is_better = SCORECARD_SORTS
+        .iter()
+        .find(|(name, _)| *name == sort)
+        .map(|(_, higher)| *higher)
+        .ok_or_else(|| {
+            let allowed: Vec<&str> = SCORECARD_SORTS.iter().map(|(name, _)| *name).collect();
+            AppError::BadRequest(format!(
+                "Invalid sort '{}' (allowed: {})",
+                sort,
+                allowed.join(", ")
+            ))
+        })?;
+
+    let key = |c: &ProviderScorecard| -> Option<f64> {
+        match sort {
+            "agent_uptime_pct" => c.agent_uptime_pct,
+            "ip_block_rate" => c.ip_block_rate,
+            "median_lifespan_days" => c.median_lifespan_days,
+            "tickets_per_vps" => c.tickets_per_vps,
+            "median_resolution_hours" => c.median_resolution_hours,
+            _ => c.cost_per_active_month.and_then(|d| d.to_f64()),
+        }
+    };
+
+    cards.sort_by(|a, b| match (key(a), key(b)) {
+        (Some(x), Some(y)) => {
+            let ord = x.partial_cmp(&y).unwrap_or(std::cmp::Ordering::Equal);
+            if higher_is_better { ord.reverse() } else { ord }
+        }
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => a.provider_name.cmp(&b.provider_name),
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(name: &str, uptime: Option<f64>, block_rate: Option<f64>) -> ProviderScorecard {
+        ProviderScorecard {
+            provider_id: Uuid::new_v4(),
+            provider_name: name.into(),
+            rating: None,
+            since: Utc::now(),
+            vps_count: 0,
+            vps_active: 0,
+            retired_count: 0,
+            median_lifespan_days: None,
+            ips_checked: 0,
+            ips_blocked: 0,
+            ip_block_rate: block_rate,
+            ip_changes: 0,
+            tickets_opened: 0,
+            tickets_resolved: 0,
+            tickets_per_vps: None,
+            median_resolution_hours: None,
+            agent_uptime_pct: uptime,
+            active_months: 0.0,
+            currency: "USD".into(),
+            cost_per_active_month: None,
+            unconverted_currencies: vec![],
+        }
+    }
+
+    #[test]
+    fn test_rank_direction_and_missing_last() {
+        let mut cards = vec![
+            card("a", Some(97.0), Some(0.1)),
+            card("b", None, Some(0.4)),
+            card("c", Some(99.5), None),
+        ];
+
+        rank(&mut cards, "agent_uptime_pct").unwrap();
+        let names: Vec<&str> = cards.iter().map(|c| c.provider_name.as_str()).collect();
+        assert_eq!(names, ["c", "a", "b"]);
+
+        rank(&mut cards, "ip_block_rate").unwrap();
+        let names: Vec<&str> = cards.iter().map(|c| c.provider_name.as_str()).collect();
+        assert_eq!(names, ["a", "b", "c"]);
+
+        assert!(rank(&mut cards, "rating").is_err());
+    }
+}
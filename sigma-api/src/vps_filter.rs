@@ -0,0 +1,782 @@
+//! Filter-expression language for selecting VPS rows.
+//!
+//! Expressions look like
+//! `country:(JP|HK) AND tag:cn2 AND NOT purpose:relay AND ip:103.21.0.0/16 AND expire<30d`
+//! and are compiled into a parameterized SQL fragment against the `vps` table,
+//! so the same saved expression selects the same hosts in the list, export,
+//! Ansible inventory and Prometheus target endpoints.
+//!
+//! Grammar:
+//!
+//! ```text
+//! expr  := and ( OR and )*
+//! and   := not ( [AND] not )*          -- juxtaposition is an implicit AND
+//! not   := NOT not | '(' expr ')' | term
+//! term  := field op value | field (':' | '=') '(' value ( '|' value )* ')'
+//! op    := ':' | '=' | '!=' | '<' | '<=' | '>' | '>='
+//! value := bare-word | "quoted string"
+//! ```
+//!
+//! Text values containing `*` match as case-insensitive wildcards.
+//! `custom.<name>` matches a typed custom field stored in `extra.custom`;
+//! ordered comparisons are numeric for numbers and textual otherwise, which
+//! also orders ISO dates correctly.
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use sqlx::postgres::{PgArguments, Postgres};
+use sqlx::query::QueryAs;
+use std::net::IpAddr;
+use std::str::FromStr;
+use uuid::Uuid;
+
+use crate::errors::AppError;
+
+/// A parsed filter expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VpsFilter {
+    expr: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Term(Term),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Term {
+    field: String,
+    op: Op,
+    values: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Op {
+    fn sql(self) -> &'static str {
+        match self {
+            Op::Eq => "=",
+            Op::Ne => "<>",
+            Op::Lt => "<",
+            Op::Le => "<=",
+            Op::Gt => ">",
+            Op::Ge => ">=",
+        }
+    }
+}
+
+/// A typed value bound to a placeholder in the compiled SQL.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterBinding {
+    Text(String),
+    Uuid(Uuid),
+    Int(i32),
+    Decimal(Decimal),
+    Date(NaiveDate),
+    Bool(bool),
+}
+
+/// Bind compiled filter values onto a query, in placeholder order.
+pub fn bind_filter<'q, O>(
+    mut query: QueryAs<'q, Postgres, O, PgArguments>,
+    bindings: &[FilterBinding],
+) -> QueryAs<'q, Postgres, O, PgArguments> {
+    for b in bindings {
+        query = match b {
+            FilterBinding::Text(v) => query.bind(v.clone()),
+            FilterBinding::Uuid(v) => query.bind(*v),
+            FilterBinding::Int(v) => query.bind(*v),
+            FilterBinding::Decimal(v) => query.bind(*v),
+            FilterBinding::Date(v) => query.bind(*v),
+            FilterBinding::Bool(v) => query.bind(*v),
+        };
+    }
+    query
+}
+
+/// Text columns that support equality and wildcard matching.
+const TEXT_FIELDS: &[(&str, &str)] = &[
+    ("hostname", "hostname"),
+    ("alias", "alias"),
+    ("country", "country"),
+    ("city", "city"),
+    ("dc", "dc_name"),
+    ("dc_name", "dc_name"),
+    ("status", "status"),
+    ("purpose", "purpose"),
+    ("vpn", "vpn_protocol"),
+    ("vpn_protocol", "vpn_protocol"),
+    ("source", "source"),
+    ("currency", "currency"),
+    ("agent", "agent_state"),
+    ("agent_state", "agent_state"),
+    ("ip_status", "ip_status"),
+];
+
+/// Integer columns that support comparisons.
+const INT_FIELDS: &[(&str, &str)] = &[
+    ("cpu", "cpu_cores"),
+    ("cpu_cores", "cpu_cores"),
+    ("ram", "ram_mb"),
+    ("ram_mb", "ram_mb"),
+    ("disk", "disk_gb"),
+    ("disk_gb", "disk_gb"),
+    ("ssh_port", "ssh_port"),
+];
+
+fn bad(msg: impl Into<String>) -> AppError {
+    AppError::BadRequest(format!("Invalid filter expression: {}", msg.into()))
+}
+
+impl VpsFilter {
+    /// Parse a filter expression. Returns `BadRequest` on syntax errors or unknown fields.
+    pub fn parse(input: &str) -> Result<Self, AppError> {
+        let mut p = Parser { src: input, pos: 0 };
+        p.skip_ws();
+        if p.eof() {
+            return Err(bad("expression is empty"));
+        }
+        let expr = p.parse_or()?;
+        p.skip_ws();
+        if !p.eof() {
+            return Err(bad(format!("unexpected '{}' at position {}", p.rest_preview(), p.pos)));
+        }
+        let filter = VpsFilter { expr };
+        // Validate field names and values eagerly so errors surface as 400s.
+        filter.to_sql("vps", 1)?;
+        Ok(filter)
+    }
+
+    /// Parse an optional expression, treating empty strings as "no filter".
+    pub fn parse_opt(input: Option<&str>) -> Result<Option<Self>, AppError> {
+        match input.map(str::trim) {
+            Some(s) if !s.is_empty() => Self::parse(s).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Whether the expression mentions the given field anywhere.
+    /// Callers use this to drop their default `status` restriction when the
+    /// expression selects on status explicitly.
+    pub fn references(&self, field: &str) -> bool {
+        fn walk(e: &Expr, field: &str) -> bool {
+            match e {
+                Expr::And(a, b) | Expr::Or(a, b) => walk(a, field) || walk(b, field),
+                Expr::Not(inner) => walk(inner, field),
+                Expr::Term(t) => t.field == field,
+            }
+        }
+        walk(&self.expr, field)
+    }
+
+    /// Compile into a SQL boolean expression. Columns are qualified with `table`
+    /// (a table name or alias) and placeholders are numbered from `first_param`.
+    pub fn to_sql(
+        &self,
+        table: &str,
+        first_param: usize,
+    ) -> Result<(String, Vec<FilterBinding>), AppError> {
+        let mut c = Compiler {
+            table,
+            first_param,
+            bindings: Vec::new(),
+        };
+        let sql = c.expr(&self.expr)?;
+        Ok((sql, c.bindings))
+    }
+}
+
+// ─── Parser ──────────────────────────────────────────────
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn eof(&self) -> bool {
+        self.pos >= self.src.len()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_ws(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+
+    fn rest_preview(&self) -> String {
+        self.src[self.pos..].chars().take(16).collect()
+    }
+
+    /// Consume `kw` if it appears next as a standalone word (case-insensitive).
+    fn eat_keyword(&mut self, kw: &str) -> bool {
+        let rest = &self.src[self.pos..];
+        if rest.len() < kw.len() || !rest[..kw.len()].eq_ignore_ascii_case(kw) {
+            return false;
+        }
+        match rest[kw.len()..].chars().next() {
+            None => {}
+            Some(c) if c.is_whitespace() || c == '(' => {}
+            _ => return false,
+        }
+        self.pos += kw.len();
+        true
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, AppError> {
+        let mut left = self.parse_and()?;
+        loop {
+            self.skip_ws();
+            if !self.eat_keyword("OR") {
+                return Ok(left);
+            }
+            let right = self.parse_and()?;
+            left = Expr::Or(Box::new(left), Box::new(right));
+        }
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, AppError> {
+        let mut left = self.parse_not()?;
+        loop {
+            self.skip_ws();
+            if self.eof() || self.peek() == Some(')') {
+                return Ok(left);
+            }
+            let save = self.pos;
+            if self.eat_keyword("OR") {
+                self.pos = save;
+                return Ok(left);
+            }
+            self.eat_keyword("AND");
+            let right = self.parse_not()?;
+            left = Expr::And(Box::new(left), Box::new(right));
+        }
+    }
+
+    fn parse_not(&mut self) -> Result<Expr, AppError> {
+        self.skip_ws();
+        if self.eat_keyword("NOT") {
+            let inner = self.parse_not()?;
+            return Ok(Expr::Not(Box::new(inner)));
+        }
+        if self.peek() == Some('(') {
+            self.bump();
+            let inner = self.parse_or()?;
+            self.skip_ws();
+            if self.bump() != Some(')') {
+                return Err(bad(format!("expected ')' at position {}", self.pos)));
+            }
+            return Ok(inner);
+        }
+        self.parse_term().map(Expr::Term)
+    }
+
+    fn parse_term(&mut self) -> Result<Term, AppError> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+        {
+            self.bump();
+        }
+        let field = self.src[start..self.pos].to_ascii_lowercase();
+        if field.is_empty() {
+            return Err(if self.eof() {
+                bad("unexpected end of expression")
+            } else {
+                bad(format!("expected a field name at position {}", start))
+            });
+        }
+
+        let op = match (self.bump(), self.peek()) {
+            (Some(':'), _) => Op::Eq,
+            (Some('='), _) => Op::Eq,
+            (Some('!'), Some('=')) => {
+                self.bump();
+                Op::Ne
+            }
+            (Some('<'), Some('=')) => {
+                self.bump();
+                Op::Le
+            }
+            (Some('>'), Some('=')) => {
+                self.bump();
+                Op::Ge
+            }
+            (Some('<'), _) => Op::Lt,
+            (Some('>'), _) => Op::Gt,
+            _ => {
+                return Err(bad(format!(
+                    "expected an operator after '{}' at position {}",
+                    field, self.pos
+                )));
+            }
+        };
+
+        let mut values = Vec::new();
+        if matches!(op, Op::Eq | Op::Ne) && self.peek() == Some('(') {
+            self.bump();
+            loop {
+                self.skip_ws();
+                values.push(self.parse_value(true)?);
+                self.skip_ws();
+                match self.bump() {
+                    Some('|') => continue,
+                    Some(')') => break,
+                    _ => return Err(bad(format!("expected '|' or ')' at position {}", self.pos))),
+                }
+            }
+        } else {
+            values.push(self.parse_value(false)?);
+        }
+
+        Ok(Term { field, op, values })
+    }
+
+    fn parse_value(&mut self, in_list: bool) -> Result<String, AppError> {
+        if self.peek() == Some('"') {
+            self.bump();
+            let mut out = String::new();
+            loop {
+                match self.bump() {
+                    Some('"') => return Ok(out),
+                    Some('\\') => match self.bump() {
+                        Some(c) => out.push(c),
+                        None => break,
+                    },
+                    Some(c) => out.push(c),
+                    None => break,
+                }
+            }
+            return Err(bad("unterminated quoted string"));
+        }
+
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() || c == ')' || c == '(' || (in_list && c == '|') {
+                break;
+            }
+            self.bump();
+        }
+        if self.pos == start {
+            return Err(bad(format!("expected a value at position {}", start)));
+        }
+        Ok(self.src[start..self.pos].to_string())
+    }
+}
+
+// ─── SQL compilation ─────────────────────────────────────
+
+struct Compiler<'a> {
+    table: &'a str,
+    first_param: usize,
+    bindings: Vec<FilterBinding>,
+}
+
+impl Compiler<'_> {
+    fn push(&mut self, b: FilterBinding) -> String {
+        self.bindings.push(b);
+        format!("${}", self.first_param + self.bindings.len() - 1)
+    }
+
+    fn col(&self, name: &str) -> String {
+        format!("{}.{}", self.table, name)
+    }
+
+    fn expr(&mut self, e: &Expr) -> Result<String, AppError> {
+        Ok(match e {
+            Expr::And(a, b) => format!("({} AND {})", self.expr(a)?, self.expr(b)?),
+            Expr::Or(a, b) => format!("({} OR {})", self.expr(a)?, self.expr(b)?),
+            // COALESCE so that NOT over a NULL comparison (e.g. missing expire_date) matches.
+            Expr::Not(inner) => format!("NOT COALESCE({}, false)", self.expr(inner)?),
+            Expr::Term(t) => self.term(t)?,
+        })
+    }
+
+    fn term(&mut self, t: &Term) -> Result<String, AppError> {
+        let field = t.field.as_str();
+
+        if let Some((_, column)) = TEXT_FIELDS.iter().find(|(f, _)| *f == field) {
+            let column = self.col(column);
+            return self.equality(t, |c, v| {
+                if v.contains('*') {
+                    let p = c.push(FilterBinding::Text(v.replace('*', "%")));
+                    Ok(format!("{} ILIKE {}", column, p))
+                } else {
+                    let p = c.push(FilterBinding::Text(v.to_string()));
+                    Ok(format!("{} = {}", column, p))
+                }
+            });
+        }
+
+        if let Some((_, column)) = INT_FIELDS.iter().find(|(f, _)| *f == field) {
+            let column = self.col(column);
+            return self.comparison(t, |c, op, v| {
+                let n: i32 = v
+                    .parse()
+                    .map_err(|_| bad(format!("'{}' expects an integer, got '{}'", field, v)))?;
+                let p = c.push(FilterBinding::Int(n));
+                Ok(format!("{} {} {}", column, op.sql(), p))
+            });
+        }
+
+        if let Some(name) = field.strip_prefix("custom.") {
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                return Err(bad(format!("invalid custom field name '{}'", name)));
+            }
+            let column = self.col("extra");
+            let key = self.push(FilterBinding::Text(name.to_string()));
+            let text = format!("{}->'custom'->>{}", column, key);
+            let json = format!("{}->'custom'->{}", column, key);
+            return self.comparison(t, |c, op, v| {
+                if matches!(op, Op::Eq | Op::Ne) && v.contains('*') {
+                    let p = c.push(FilterBinding::Text(v.replace('*', "%")));
+                    return Ok(format!("{} ILIKE {}", text, p));
+                }
+                if !matches!(op, Op::Eq | Op::Ne) {
+                    if let Ok(d) = Decimal::from_str(v) {
+                        let p = c.push(FilterBinding::Decimal(d));
+                        return Ok(format!(
+                            "(CASE WHEN jsonb_typeof({}) = 'number' THEN ({})::NUMERIC END) {} {}",
+                            json, text, op.sql(), p
+                        ));
+                    }
+                }
+                let p = c.push(FilterBinding::Text(v.to_string()));
+                Ok(format!("{} {} {}", text, op.sql(), p))
+            });
+        }
+
+        match field {
+            "tag" | "tags" | "blocklist" | "blocklists" => {
+                let column = self.col(if field.starts_with("tag") { "tags" } else { "blocklists" });
+                self.equality(t, |c, v| {
+                    if v.contains('*') {
+                        let p = c.push(FilterBinding::Text(v.replace('*', "%")));
+                        Ok(format!(
+                            "EXISTS (SELECT 1 FROM unnest({}) AS t(tag) WHERE t.tag ILIKE {})",
+                            column, p
+                        ))
+                    } else {
+                        let p = c.push(FilterBinding::Text(v.to_string()));
+                        Ok(format!("{} = ANY({})", p, column))
+                    }
+                })
+            }
+            "provider" => {
+                let column = self.col("provider_id");
+                self.equality(t, |c, v| {
+                    if let Ok(id) = Uuid::parse_str(v) {
+                        let p = c.push(FilterBinding::Uuid(id));
+                        Ok(format!("{} = {}", column, p))
+                    } else {
+                        let p = c.push(FilterBinding::Text(v.replace('*', "%")));
+                        Ok(format!(
+                            "{} IN (SELECT id FROM providers WHERE name ILIKE {})",
+                            column, p
+                        ))
+                    }
+                })
+            }
+            "provider_id" | "id" | "cloud_account_id" => {
+                let column = self.col(field);
+                self.equality(t, |c, v| {
+                    let id = Uuid::parse_str(v)
+                        .map_err(|_| bad(format!("'{}' expects a UUID, got '{}'", field, v)))?;
+                    let p = c.push(FilterBinding::Uuid(id));
+                    Ok(format!("{} = {}", column, p))
+                })
+            }
+            "ip" => {
+                let column = self.col("ip_addresses");
+                self.equality(t, |c, v| {
+                    if !is_ip_or_cidr(v) {
+                        return Err(bad(format!("'{}' is not a valid IP address or CIDR", v)));
+                    }
+                    let p = c.push(FilterBinding::Text(v.to_string()));
+                    Ok(format!(
+                        "EXISTS (SELECT 1 FROM jsonb_array_elements({}) AS e(entry) WHERE (e.entry->>'ip')::INET <<= {}::INET)",
+                        column, p
+                    ))
+                })
+            }
+            "label" | "ip_label" => {
+                let column = self.col("ip_addresses");
+                self.equality(t, |c, v| {
+                    let p = c.push(FilterBinding::Text(v.to_string()));
+                    Ok(format!(
+                        "EXISTS (SELECT 1 FROM jsonb_array_elements({}) AS e(entry) WHERE e.entry->>'label' = {})",
+                        column, p
+                    ))
+                })
+            }
+            "expire" | "expire_date" | "purchase" | "purchase_date" => {
+                let column = self.col(if field.starts_with("expire") {
+                    "expire_date"
+                } else {
+                    "purchase_date"
+                });
+                self.comparison(t, |c, op, v| {
+                    if v.eq_ignore_ascii_case("none") {
+                        return match op {
+                            Op::Eq => Ok(format!("{} IS NULL", column)),
+                            Op::Ne => Ok(format!("{} IS NOT NULL", column)),
+                            _ => Err(bad(format!("'{}' only supports ':' and '!=' with 'none'", field))),
+                        };
+                    }
+                    if let Some(days) = v.strip_suffix('d').and_then(|d| d.parse::<i32>().ok()) {
+                        let p = c.push(FilterBinding::Int(days));
+                        return Ok(format!("{} {} CURRENT_DATE + {}::INT", column, op.sql(), p));
+                    }
+                    let date = NaiveDate::parse_from_str(v, "%Y-%m-%d").map_err(|_| {
+                        bad(format!("'{}' expects a date (YYYY-MM-DD) or days (e.g. 30d), got '{}'", field, v))
+                    })?;
+                    let p = c.push(FilterBinding::Date(date));
+                    Ok(format!("{} {} {}", column, op.sql(), p))
+                })
+            }
+            "cost" | "cost_monthly" => {
+                let column = self.col("cost_monthly");
+                self.comparison(t, |c, op, v| {
+                    let d = Decimal::from_str(v)
+                        .map_err(|_| bad(format!("'{}' expects a number, got '{}'", field, v)))?;
+                    let p = c.push(FilterBinding::Decimal(d));
+                    Ok(format!("{} {} {}", column, op.sql(), p))
+                })
+            }
+            "monitoring" | "monitoring_enabled" | "auto_renew" | "geo_mismatch" => {
+                let column = self.col(match field {
+                    "auto_renew" => "auto_renew",
+                    "geo_mismatch" => "geo_mismatch",
+                    _ => "monitoring_enabled",
+                });
+                self.equality(t, |c, v| {
+                    let b = match v.to_ascii_lowercase().as_str() {
+                        "true" | "yes" | "on" | "1" => true,
+                        "false" | "no" | "off" | "0" => false,
+                        _ => return Err(bad(format!("'{}' expects true or false, got '{}'", field, v))),
+                    };
+                    let p = c.push(FilterBinding::Bool(b));
+                    Ok(format!("{} = {}", column, p))
+                })
+            }
+            _ => Err(bad(format!("unknown field '{}'", field))),
+        }
+    }
+
+    /// `field:v`, `field:(a|b)` and `field!=v` — values are OR'ed, `!=` negates.
+    fn equality<F>(&mut self, t: &Term, mut each: F) -> Result<String, AppError>
+    where
+        F: FnMut(&mut Self, &str) -> Result<String, AppError>,
+    {
+        if !matches!(t.op, Op::Eq | Op::Ne) {
+            return Err(bad(format!("'{}' only supports ':' and '!='", t.field)));
+        }
+        let parts = t
+            .values
+            .iter()
+            .map(|v| each(self, v))
+            .collect::<Result<Vec<_>, _>>()?;
+        let joined = if parts.len() == 1 {
+            parts.into_iter().next().unwrap_or_default()
+        } else {
+            format!("({})", parts.join(" OR "))
+        };
+        Ok(if t.op == Op::Ne {
+            format!("NOT COALESCE({}, false)", joined)
+        } else {
+            joined
+        })
+    }
+
+    /// Ordered comparisons; a value list is only allowed with ':' / '!='.
+    fn comparison<F>(&mut self, t: &Term, mut each: F) -> Result<String, AppError>
+    where
+        F: FnMut(&mut Self, Op, &str) -> Result<String, AppError>,
+    {
+        if t.values.len() > 1 {
+            let op = t.op;
+            let negate = op == Op::Ne;
+            let parts = t
+                .values
+                .iter()
+                .map(|v| each(self, Op::Eq, v))
+                .collect::<Result<Vec<_>, _>>()?;
+            let joined = format!("({})", parts.join(" OR "));
+            return Ok(if negate {
+                format!("NOT COALESCE({}, false)", joined)
+            } else {
+                joined
+            });
+        }
+        each(self, t.op, &t.values[0])
+    }
+}
+
+pub(crate) fn is_ip_or_cidr(v: &str) -> bool {
+    match v.split_once('/') {
+        Some((ip, prefix)) => {
+            let Ok(addr) = ip.parse::<IpAddr>() else {
+                return false;
+            };
+            let max = if addr.is_ipv4() { 32 } else { 128 };
+            prefix.parse::<u8>().is_ok_and(|p| p <= max)
+        }
+        None => v.parse::<IpAddr>().is_ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compile(input: &str) -> (String, Vec<FilterBinding>) {
+        VpsFilter::parse(input).unwrap().to_sql("v", 1).unwrap()
+    }
+
+    #[test]
+    fn test_full_example() {
+        let (sql, binds) =
+            compile("country:(JP|HK) AND tag:cn2 AND NOT purpose:relay AND ip:103.21.0.0/16 AND expire<30d");
+        assert_eq!(
+            sql,
+            "(((((v.country = $1 OR v.country = $2) AND $3 = ANY(v.tags)) AND NOT COALESCE(v.purpose = $4, false)) \
+             AND EXISTS (SELECT 1 FROM jsonb_array_elements(v.ip_addresses) AS e(entry) WHERE (e.entry->>'ip')::INET <<= $5::INET)) \
+             AND v.expire_date < CURRENT_DATE + $6::INT)"
+        );
+        assert_eq!(
+            binds,
+            vec![
+                FilterBinding::Text("JP".into()),
+                FilterBinding::Text("HK".into()),
+                FilterBinding::Text("cn2".into()),
+                FilterBinding::Text("relay".into()),
+                FilterBinding::Text("103.21.0.0/16".into()),
+                FilterBinding::Int(30),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_implicit_and_and_or_precedence() {
+        let (sql, _) = compile("status:active country:JP OR country:HK");
+        assert_eq!(
+            sql,
+            "((v.status = $1 AND v.country = $2) OR v.country = $3)"
+        );
+    }
+
+    #[test]
+    fn test_param_offset() {
+        let f = VpsFilter::parse("tag:cn2").unwrap();
+        let (sql, _) = f.to_sql("vps", 4).unwrap();
+        assert_eq!(sql, "$4 = ANY(vps.tags)");
+    }
+
+    #[test]
+    fn test_blocklist_field() {
+        let (sql, binds) = compile("blocklist:zen.spamhaus.org OR blocklist:*");
+        assert_eq!(
+            sql,
+            "($1 = ANY(v.blocklists) OR EXISTS (SELECT 1 FROM unnest(v.blocklists) AS t(tag) WHERE t.tag ILIKE $2))"
+        );
+        assert_eq!(binds.len(), 2);
+    }
+
+    #[test]
+    fn test_wildcard_and_quoted_values() {
+        let (sql, binds) = compile(r#"hostname:hk-* alias:"tokyo edge""#);
+        assert_eq!(sql, "(v.hostname ILIKE $1 AND v.alias = $2)");
+        assert_eq!(
+            binds,
+            vec![
+                FilterBinding::Text("hk-%".into()),
+                FilterBinding::Text("tokyo edge".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ipv6_value() {
+        let (_, binds) = compile("ip:2001:db8::/32");
+        assert_eq!(binds, vec![FilterBinding::Text("2001:db8::/32".into())]);
+    }
+
+    #[test]
+    fn test_typed_comparisons() {
+        let (sql, binds) = compile("cpu>=2 cost<10.5 expire>=2026-01-01 monitoring:false");
+        assert_eq!(
+            sql,
+            "(((v.cpu_cores >= $1 AND v.cost_monthly < $2) AND v.expire_date >= $3) AND v.monitoring_enabled = $4)"
+        );
+        assert_eq!(binds[0], FilterBinding::Int(2));
+        assert_eq!(binds[1], FilterBinding::Decimal(Decimal::from_str("10.5").unwrap()));
+        assert_eq!(
+            binds[2],
+            FilterBinding::Date(NaiveDate::from_ymd_opt(2026, 1, 1).unwrap())
+        );
+        assert_eq!(binds[3], FilterBinding::Bool(false));
+    }
+
+    #[test]
+    fn test_custom_fields() {
+        let (sql, binds) = compile("custom.tier:(gold|silver) custom.contract_id>=100");
+        assert_eq!(
+            sql,
+            "((v.extra->'custom'->>$1 = $2 OR v.extra->'custom'->>$1 = $3) AND \
+             (CASE WHEN jsonb_typeof(v.extra->'custom'->$4) = 'number' THEN (v.extra->'custom'->>$4)::NUMERIC END) >= $5)"
+        );
+        assert_eq!(binds[0], FilterBinding::Text("tier".into()));
+        assert_eq!(binds[4], FilterBinding::Decimal(Decimal::from(100)));
+        assert!(VpsFilter::parse("custom.:x").is_err());
+    }
+
+    #[test]
+    fn test_references() {
+        let f = VpsFilter::parse("NOT (status:retired OR tag:x)").unwrap();
+        assert!(f.references("status"));
+        assert!(!f.references("country"));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(VpsFilter::parse("").is_err());
+        assert!(VpsFilter::parse("bogus:1").is_err());
+        assert!(VpsFilter::parse("country:(JP|HK").is_err());
+        assert!(VpsFilter::parse("ip:not-an-ip").is_err());
+        assert!(VpsFilter::parse("ip:10.0.0.0/33").is_err());
+        assert!(VpsFilter::parse("country<JP").is_err());
+        assert!(VpsFilter::parse("cpu:many").is_err());
+        assert!(VpsFilter::parse("(tag:a").is_err());
+        assert!(VpsFilter::parse("tag:a)").is_err());
+        assert!(VpsFilter::parse("country").is_err());
+    }
+
+    #[test]
+    fn test_parse_opt_blank_is_none() {
+        assert!(VpsFilter::parse_opt(None).unwrap().is_none());
+        assert!(VpsFilter::parse_opt(Some("   ")).unwrap().is_none());
+        assert!(VpsFilter::parse_opt(Some("tag:a")).unwrap().is_some());
+    }
+}
@@ -0,0 +1,116 @@
+//! VPS lifecycle state machine.
+//!
+//! The main path is `provisioning → active → retiring → retired → deleted`;
+//! `suspended` hangs off `active`, and restore paths lead back from `deleted`
+//! to `retired` and from `retired` / `retiring` to `active`.
+//!
+//! Manual changes (PUT, retire, restore, delete, bulk) are checked against
+//! [`TRANSITIONS`]. Agent heartbeats and cloud sync report observed state and
+//! are not checked. Every change is recorded in `vps_status_history` by a
+//! trigger, which reads the source and actor set with [`set_change_context`].
+
+use sqlx::PgConnection;
+
+use crate::errors::AppError;
+
+/// Change sources recorded in the `source` column of the `vps_*_history` tables.
+pub const SOURCE_MANUAL: &str = "manual";
+pub const SOURCE_IMPORT: &str = "import";
+pub const SOURCE_IP_POOL: &str = "ip_pool";
+pub const SOURCE_CLOUD_ROTATE: &str = "cloud_rotate";
+pub const SOURCE_PANEL_SYNC: &str = "panel_sync";
+
+/// Allowed `from → to` status transitions. Staying in the same status is always allowed.
+pub const TRANSITIONS: &[(&str, &[&str])] = &[
+    ("provisioning", &["active", "retired", "deleted"]),
+    ("active", &["retiring", "retired", "suspended"]),
+    ("suspended", &["active", "retiring", "retired"]),
+    ("retiring", &["retired", "active"]),
+    ("retired", &["deleted", "active"]),
+    ("deleted", &["retired"]),
+];
+
+/// Targets reachable from `from`, or None if `from` is not a known status.
+pub fn allowed_targets(from: &str) -> Option<&'static [&'static str]> {
+    TRANSITIONS.iter().find(|(s, _)| *s == from).map(|(_, t)| *t)
+}
+
+/// Check a manual status change. Returns `BadRequest` naming the allowed targets.
+pub fn check_transition(from: &str, to: &str) -> Result<(), AppError> {
+    if allowed_targets(to).is_none() {
+        return Err(AppError::BadRequest(format!("Unknown status '{}'", to)));
+    }
+    if from == to {
+        return Ok(());
+    }
+    match allowed_targets(from) {
+        Some(targets) if targets.contains(&to) => Ok(()),
+        Some(targets) => Err(AppError::BadRequest(format!(
+            "Illegal status transition {} → {} (allowed: {})",
+            from,
+            to,
+            targets.join(", ")
+        ))),
+        // Rows predating the state machine may carry an unknown status; let them move anywhere.
+        None => Ok(()),
+    }
+}
+
+/// Set the transaction-local change source and actor read by the `vps` history triggers.
+/// Must run inside a transaction.
+pub async fn set_change_context(
+    conn: &mut PgConnection,
+    source: &str,
+    actor: &str,
+) -> Result<(), AppError> {
+    sqlx::query("SELECT set_config('app.change_source', $1, true), set_config('app.actor', $2, true)")
+        .bind(source)
+        .bind(actor)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forward_path() {
+        assert!(check_transition("provisioning", "active").is_ok());
+        assert!(check_transition("active", "retiring").is_ok());
+        assert!(check_transition("retiring", "retired").is_ok());
+        assert!(check_transition("retired", "deleted").is_ok());
+    }
+
+    #[test]
+    fn test_restore_paths() {
+        assert!(check_transition("deleted", "retired").is_ok());
+        assert!(check_transition("retired", "active").is_ok());
+        assert!(check_transition("retiring", "active").is_ok());
+        assert!(check_transition("suspended", "active").is_ok());
+    }
+
+    #[test]
+    fn test_illegal_transitions() {
+        assert!(check_transition("deleted", "provisioning").is_err());
+        assert!(check_transition("deleted", "active").is_err());
+        assert!(check_transition("active", "provisioning").is_err());
+        assert!(check_transition("active", "deleted").is_err());
+    }
+
+    #[test]
+    fn test_same_status_and_unknown() {
+        assert!(check_transition("active", "active").is_ok());
+        assert!(check_transition("active", "bogus").is_err());
+    }
+
+    #[test]
+    fn test_every_target_is_a_known_status() {
+        for (_, targets) in TRANSITIONS {
+            for t in *targets {
+                assert!(allowed_targets(t).is_some(), "unknown target {t}");
+            }
+        }
+    }
+}
@@ -70,6 +70,7 @@ pub async fn setup() -> (Router, PgPool) {
 
     let api_routes = Router::new()
         .merge(routes::providers::router())
+        .merge(routes::provider_plans::router())
         .merge(routes::vps::router())
         .merge(routes::ip_checks::router())
         .merge(routes::prometheus::router())
@@ -159,6 +160,45 @@ pub async fn request_with_token(
     (status, json)
 }
 
+/// Send a request with a JWT Bearer token and extra headers; returns (status, headers, body_json)
+#[allow(dead_code)]
+pub async fn request_with_headers(
+    router: &Router,
+    method: &str,
+    uri: &str,
+    token: &str,
+    headers: &[(&str, &str)],
+    body: Option<Value>,
+) -> (u16, axum::http::HeaderMap, Value) {
+    let body = match body {
+        Some(json) => Body::from(serde_json::to_string(&json).unwrap()),
+        None => Body::empty(),
+    };
+
+    let mut req = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("authorization", format!("Bearer {}", token))
+        .header("content-type", "application/json");
+    for (name, value) in headers {
+        req = req.header(*name, *value);
+    }
+    let req = req.body(body).unwrap();
+
+    let response = router.clone().oneshot(req).await.unwrap();
+    let status = response.status().as_u16();
+    let resp_headers = response.headers().clone();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+
+    let json: Value = if bytes.is_empty() {
+        Value::Null
+    } else {
+        serde_json::from_slice(&bytes).unwrap_or(Value::Null)
+    };
+
+    (status, resp_headers, json)
+}
+
 /// Send a request with X-Api-Key header
 #[allow(dead_code)]
 pub async fn request_with_api_key(
@@ -125,6 +125,114 @@ async fn test_delete_provider() {
     common::cleanup(&pool).await;
 }
 
+#[tokio::test]
+async fn test_provider_if_match() {
+    let (router, pool) = common::setup().await;
+    let token = common::login_admin(&router).await;
+
+    let body = json!({ "name": "Versioned", "country": "JP" });
+    let (status, create_json) =
+        common::request_with_token(&router, "POST", "/api/providers", &token, Some(body)).await;
+    assert_eq!(status, 200);
+    let uri = format!("/api/providers/{}", create_json["id"].as_str().unwrap());
+
+    // GET returns an ETag
+    let (status, headers, _) =
+        common::request_with_headers(&router, "GET", &uri, &token, &[], None).await;
+    assert_eq!(status, 200);
+    let etag = headers["etag"].to_str().unwrap().to_string();
+
+    // First writer with the current ETag succeeds
+    let (status, _, json) = common::request_with_headers(
+        &router,
+        "PUT",
+        &uri,
+        &token,
+        &[("if-match", &etag)],
+        Some(json!({ "notes": "first" })),
+    )
+    .await;
+    assert_eq!(status, 200);
+    assert_eq!(json["notes"], "first");
+
+    // Second writer with the stale ETag is rejected
+    let (status, _, _) = common::request_with_headers(
+        &router,
+        "PUT",
+        &uri,
+        &token,
+        &[("if-match", &etag)],
+        Some(json!({ "notes": "second" })),
+    )
+    .await;
+    assert_eq!(status, 412);
+
+    let (status, _, _) =
+        common::request_with_headers(&router, "DELETE", &uri, &token, &[("if-match", &etag)], None)
+            .await;
+    assert_eq!(status, 412);
+
+    common::cleanup(&pool).await;
+}
+
+#[tokio::test]
+async fn test_provider_import_dry_run_and_upsert() {
+    let (router, pool) = common::setup().await;
+    let token = common::login_admin(&router).await;
+
+    let body = json!({ "name": "Acme", "country": "US", "rating": 3 });
+    let (status, _) =
+        common::request_with_token(&router, "POST", "/api/providers", &token, Some(body)).await;
+    assert_eq!(status, 200);
+
+    let data = json!([
+        { "name": "Acme", "country": "DE", "rating": 3 },
+        { "name": "NewCo", "country": "JP" },
+        { "name": "" }
+    ])
+    .to_string();
+
+    // Dry run: plan only
+    let body = json!({ "format": "json", "data": data, "mode": "dry_run" });
+    let (status, json) =
+        common::request_with_token(&router, "POST", "/api/providers/import", &token, Some(body))
+            .await;
+    assert_eq!(status, 200);
+    assert_eq!(json["dry_run"], true);
+    assert_eq!(json["plan"][0]["action"], "update");
+    assert_eq!(json["plan"][0]["changes"]["country"], json!({"from": "US", "to": "DE"}));
+    assert_eq!(json["plan"][1]["action"], "create");
+    assert_eq!(json["plan"][2]["action"], "error");
+
+    let (_, list) =
+        common::request_with_token(&router, "GET", "/api/providers", &token, None).await;
+    assert_eq!(list["total"], 1);
+    assert_eq!(list["data"][0]["country"], "US");
+
+    // Upsert applies the same plan
+    let body = json!({ "format": "json", "data": data, "mode": "upsert" });
+    let (status, json) =
+        common::request_with_token(&router, "POST", "/api/providers/import", &token, Some(body))
+            .await;
+    assert_eq!(status, 200);
+    assert_eq!(json["imported"], 1);
+    assert_eq!(json["updated"], 1);
+    assert_eq!(json["errors"].as_array().unwrap().len(), 1);
+
+    let (_, list) =
+        common::request_with_token(&router, "GET", "/api/providers", &token, None).await;
+    assert_eq!(list["total"], 2);
+
+    // Running it again changes nothing
+    let body = json!({ "format": "json", "data": data, "mode": "upsert" });
+    let (_, json) =
+        common::request_with_token(&router, "POST", "/api/providers/import", &token, Some(body))
+            .await;
+    assert_eq!(json["skipped"], 2);
+
+    common::cleanup(&pool).await;
+}
+
 #[tokio::test]
 async fn test_readonly_cannot_create_provider() {
     let (router, pool) = common::setup().await;
@@ -167,3 +275,99 @@ async fn test_readonly_cannot_create_provider() {
 
     common::cleanup(&pool).await;
 }
+
+#[tokio::test]
+async fn test_create_vps_from_plan() {
+    let (router, pool) = common::setup().await;
+    let token = common::login_admin(&router).await;
+
+    let body = json!({ "name": "Acme", "country": "US" });
+    let (_, provider) =
+        common::request_with_token(&router, "POST", "/api/providers", &token, Some(body)).await;
+
+    let body = json!({
+        "provider_id": provider["id"],
+        "name": "vps-2g",
+        "cpu_cores": 2,
+        "ram_mb": 2048,
+        "disk_gb": 40,
+        "price": 120.0,
+        "currency": "EUR",
+        "billing_cycle_months": 12,
+        "locations": ["fra", "ams"]
+    });
+    let (status, plan) =
+        common::request_with_token(&router, "POST", "/api/provider-plans", &token, Some(body))
+            .await;
+    assert_eq!(status, 200);
+
+    let body = json!({ "hostname": "fra-01", "plan_id": plan["id"], "disk_gb": 80 });
+    let (status, vps) =
+        common::request_with_token(&router, "POST", "/api/vps", &token, Some(body)).await;
+    assert_eq!(status, 200);
+    assert_eq!(vps["provider_id"], provider["id"]);
+    assert_eq!(vps["plan_id"], plan["id"]);
+    assert_eq!(vps["cpu_cores"], 2);
+    assert_eq!(vps["disk_gb"], 80);
+    assert_eq!(vps["cost_monthly"], "10.00");
+    assert_eq!(vps["currency"], "EUR");
+
+    let (status, cmp) = common::request_with_token(
+        &router,
+        "GET",
+        "/api/costs/compare?location=fra&sort=per_core",
+        &token,
+        None,
+    )
+    .await;
+    assert_eq!(status, 200);
+    let per_core: f64 = cmp["plans"][0]["per_core"].as_str().unwrap().parse().unwrap();
+    assert_eq!(per_core, 5.0);
+
+    common::cleanup(&pool).await;
+}
+
+#[tokio::test]
+async fn test_provider_scorecard() {
+    let (router, pool) = common::setup().await;
+    let token = common::login_admin(&router).await;
+
+    let body = json!({ "name": "Acme", "country": "US" });
+    let (_, provider) =
+        common::request_with_token(&router, "POST", "/api/providers", &token, Some(body)).await;
+
+    let body = json!({ "hostname": "sc-01", "provider_id": provider["id"], "status": "active" });
+    let (status, _) =
+        common::request_with_token(&router, "POST", "/api/vps", &token, Some(body)).await;
+    assert_eq!(status, 200);
+
+    let uri = format!("/api/providers/{}/scorecard?days=30", provider["id"].as_str().unwrap());
+    let (status, card) = common::request_with_token(&router, "GET", &uri, &token, None).await;
+    assert_eq!(status, 200);
+    assert_eq!(card["vps_count"], 1);
+    assert_eq!(card["vps_active"], 1);
+    assert_eq!(card["tickets_opened"], 0);
+
+    let (status, ranking) = common::request_with_token(
+        &router,
+        "GET",
+        "/api/providers/ranking?sort=tickets_per_vps",
+        &token,
+        None,
+    )
+    .await;
+    assert_eq!(status, 200);
+    assert_eq!(ranking["providers"][0]["provider_id"], provider["id"]);
+
+    let (status, _) = common::request_with_token(
+        &router,
+        "GET",
+        "/api/providers/ranking?sort=rating",
+        &token,
+        None,
+    )
+    .await;
+    assert_eq!(status, 400);
+
+    common::cleanup(&pool).await;
+}
@@ -52,6 +52,71 @@ async fn test_list_tickets() {
     common::cleanup(&pool).await;
 }
 
+#[tokio::test]
+async fn test_list_tickets_cursor() {
+    let (router, pool) = common::setup().await;
+    let token = common::login_admin(&router).await;
+
+    for title in &["Ticket A", "Ticket B", "Ticket C"] {
+        let body = json!({ "title": title });
+        let (status, _) =
+            common::request_with_token(&router, "POST", "/api/tickets", &token, Some(body)).await;
+        assert_eq!(status, 200);
+    }
+
+    // First page in keyset mode
+    let (status, json) = common::request_with_token(
+        &router,
+        "GET",
+        "/api/tickets?sort=title&per_page=2&cursor=",
+        &token,
+        None,
+    )
+    .await;
+    assert_eq!(status, 200);
+    assert!(json.get("total").is_none());
+    assert_eq!(json["sort"], "title");
+    let data = json["data"].as_array().unwrap();
+    assert_eq!(data.len(), 2);
+    assert_eq!(data[0]["title"], "Ticket A");
+    assert_eq!(data[1]["title"], "Ticket B");
+    let cursor = json["next_cursor"].as_str().unwrap().to_string();
+
+    // Second (last) page
+    let (status, json) = common::request_with_token(
+        &router,
+        "GET",
+        &format!("/api/tickets?sort=title&per_page=2&cursor={cursor}"),
+        &token,
+        None,
+    )
+    .await;
+    assert_eq!(status, 200);
+    let data = json["data"].as_array().unwrap();
+    assert_eq!(data.len(), 1);
+    assert_eq!(data[0]["title"], "Ticket C");
+    assert!(json["next_cursor"].is_null());
+
+    // Cursor from a different sort is rejected
+    let (status, _) = common::request_with_token(
+        &router,
+        "GET",
+        &format!("/api/tickets?sort=-created_at&cursor={cursor}"),
+        &token,
+        None,
+    )
+    .await;
+    assert_eq!(status, 400);
+
+    // Unknown sort column is rejected
+    let (status, _) =
+        common::request_with_token(&router, "GET", "/api/tickets?sort=description", &token, None)
+            .await;
+    assert_eq!(status, 400);
+
+    common::cleanup(&pool).await;
+}
+
 #[tokio::test]
 async fn test_get_ticket() {
     let (router, pool) = common::setup().await;
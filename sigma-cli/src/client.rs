@@ -36,9 +36,28 @@ impl SigmaClient {
         }
     }
 
+    fn add_if_match(
+        builder: reqwest::RequestBuilder,
+        if_match: Option<&str>,
+    ) -> reqwest::RequestBuilder {
+        match if_match {
+            Some(etag) => builder.header("If-Match", etag),
+            None => builder,
+        }
+    }
+
     async fn handle_error(resp: Response) -> anyhow::Error {
         let status = resp.status();
         let body = resp.text().await.unwrap_or_default();
+        if status == StatusCode::PRECONDITION_FAILED {
+            let msg = serde_json::from_str::<serde_json::Value>(&body)
+                .ok()
+                .and_then(|v| v.get("error").and_then(|e| e.as_str()).map(String::from))
+                .unwrap_or(body);
+            return anyhow::anyhow!(
+                "Conflict: {msg}\nThe resource was changed by someone else. Run `get` again to see the current state and ETag, then retry."
+            );
+        }
         if let Ok(err) = serde_json::from_str::<serde_json::Value>(&body) {
             if let Some(msg) = err.get("error").and_then(|e| e.as_str()) {
                 return anyhow::anyhow!("API error ({}): {}", status, msg);
@@ -59,6 +78,25 @@ impl SigmaClient {
         resp.json::<T>().await.context("Failed to parse response")
     }
 
+    /// GET a resource together with its `ETag` header, for use with `If-Match`.
+    pub async fn get_tagged<T: DeserializeOwned>(&self, path: &str) -> Result<(T, Option<String>)> {
+        let resp = self
+            .add_auth(self.client.get(self.url(path)))
+            .send()
+            .await
+            .context("Failed to connect to API")?;
+        if !resp.status().is_success() {
+            return Err(Self::handle_error(resp).await);
+        }
+        let etag = resp
+            .headers()
+            .get("etag")
+            .and_then(|v| v.to_str().ok())
+            .map(String::from);
+        let body = resp.json::<T>().await.context("Failed to parse response")?;
+        Ok((body, etag))
+    }
+
     pub async fn get_text(&self, path: &str) -> Result<(String, Option<String>)> {
         let resp = self
             .add_auth(self.client.get(self.url(path)))
@@ -106,13 +144,16 @@ impl SigmaClient {
         resp.json::<T>().await.context("Failed to parse response")
     }
 
-    pub async fn put<B: Serialize, T: DeserializeOwned>(
+    /// PUT that fails with a conflict error if the resource no longer matches `if_match`.
+    pub async fn put_if_match<B: Serialize, T: DeserializeOwned>(
         &self,
         path: &str,
         body: &B,
+        if_match: Option<&str>,
     ) -> Result<T> {
+        let builder = Self::add_if_match(self.client.put(self.url(path)), if_match);
         let resp = self
-            .add_auth(self.client.put(self.url(path)))
+            .add_auth(builder)
             .json(body)
             .send()
             .await
@@ -124,8 +165,14 @@ impl SigmaClient {
     }
 
     pub async fn delete(&self, path: &str) -> Result<()> {
+        self.delete_if_match(path, None).await
+    }
+
+    /// DELETE that fails with a conflict error if the resource no longer matches `if_match`.
+    pub async fn delete_if_match(&self, path: &str, if_match: Option<&str>) -> Result<()> {
+        let builder = Self::add_if_match(self.client.delete(self.url(path)), if_match);
         let resp = self
-            .add_auth(self.client.delete(self.url(path)))
+            .add_auth(builder)
             .send()
             .await
             .context("Failed to connect to API")?;
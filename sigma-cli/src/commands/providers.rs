@@ -37,7 +37,7 @@ pub async fn list(client: &SigmaClient, page: i64, per_page: i64, json: bool) ->
 }
 
 pub async fn get(client: &SigmaClient, id: Uuid, json: bool) -> Result<()> {
-    let provider: Provider = client.get(&format!("/providers/{id}")).await?;
+    let (provider, etag): (Provider, _) = client.get_tagged(&format!("/providers/{id}")).await?;
 
     if json {
         return output::print_json(&provider);
@@ -56,6 +56,7 @@ pub async fn get(client: &SigmaClient, id: Uuid, json: bool) -> Result<()> {
             vec!["Notes".into(), provider.notes],
             vec!["Created".into(), provider.created_at.to_rfc3339()],
             vec!["Updated".into(), provider.updated_at.to_rfc3339()],
+            vec!["ETag".into(), etag.unwrap_or_else(|| "-".into())],
         ],
     );
     Ok(())
@@ -102,6 +103,7 @@ pub async fn update(
     api_supported: Option<bool>,
     rating: Option<i16>,
     notes: Option<String>,
+    if_match: Option<String>,
     json: bool,
 ) -> Result<()> {
     let body = UpdateProvider {
@@ -114,7 +116,9 @@ pub async fn update(
         notes,
     };
 
-    let provider: Provider = client.put(&format!("/providers/{id}"), &body).await?;
+    let provider: Provider = client
+        .put_if_match(&format!("/providers/{id}"), &body, if_match.as_deref())
+        .await?;
 
     if json {
         return output::print_json(&provider);
@@ -124,8 +128,10 @@ pub async fn update(
     Ok(())
 }
 
-pub async fn delete(client: &SigmaClient, id: Uuid) -> Result<()> {
-    client.delete(&format!("/providers/{id}")).await?;
+pub async fn delete(client: &SigmaClient, id: Uuid, if_match: Option<String>) -> Result<()> {
+    client
+        .delete_if_match(&format!("/providers/{id}"), if_match.as_deref())
+        .await?;
     println!("Deleted provider {id}");
     Ok(())
 }
@@ -147,21 +153,23 @@ pub async fn export(
     Ok(())
 }
 
-pub async fn import(client: &SigmaClient, file: &str, format: &str) -> Result<()> {
+pub async fn import(
+    client: &SigmaClient,
+    file: &str,
+    format: &str,
+    mode: &str,
+    key: Option<&str>,
+) -> Result<()> {
     let data = std::fs::read_to_string(file)?;
     let body = ImportRequest {
         format: format.to_string(),
         data,
+        mode: mode.to_string(),
+        key: key.map(String::from),
     };
 
     let result: ImportResult = client.post("/providers/import", &body).await?;
 
-    println!("Imported {} providers", result.imported);
-    if !result.errors.is_empty() {
-        println!("Errors:");
-        for err in &result.errors {
-            println!("  - {err}");
-        }
-    }
+    output::print_import_result(&result, "providers");
     Ok(())
 }
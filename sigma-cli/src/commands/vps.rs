@@ -76,7 +76,7 @@ pub async fn list(
 }
 
 pub async fn get(client: &SigmaClient, id: Uuid, json: bool) -> Result<()> {
-    let v: Vps = client.get(&format!("/vps/{id}")).await?;
+    let (v, etag): (Vps, _) = client.get_tagged(&format!("/vps/{id}")).await?;
 
     if json {
         return output::print_json(&v);
@@ -123,6 +123,7 @@ pub async fn get(client: &SigmaClient, id: Uuid, json: bool) -> Result<()> {
             vec!["Notes".into(), v.notes],
             vec!["Created".into(), v.created_at.to_rfc3339()],
             vec!["Updated".into(), v.updated_at.to_rfc3339()],
+            vec!["ETag".into(), etag.unwrap_or_else(|| "-".into())],
         ],
     );
     Ok(())
@@ -237,6 +238,7 @@ pub async fn update(
     monitoring_enabled: Option<bool>,
     node_exporter_port: Option<i32>,
     notes: Option<String>,
+    if_match: Option<String>,
     json: bool,
 ) -> Result<()> {
     let ip_addresses = ip.map(|ips| {
@@ -278,7 +280,9 @@ pub async fn update(
         notes,
     };
 
-    let vps: Vps = client.put(&format!("/vps/{id}"), &body).await?;
+    let vps: Vps = client
+        .put_if_match(&format!("/vps/{id}"), &body, if_match.as_deref())
+        .await?;
 
     if json {
         return output::print_json(&vps);
@@ -288,8 +292,10 @@ pub async fn update(
     Ok(())
 }
 
-pub async fn delete(client: &SigmaClient, id: Uuid) -> Result<()> {
-    client.delete(&format!("/vps/{id}")).await?;
+pub async fn delete(client: &SigmaClient, id: Uuid, if_match: Option<String>) -> Result<()> {
+    client
+        .delete_if_match(&format!("/vps/{id}"), if_match.as_deref())
+        .await?;
     println!("Deleted VPS {id}");
     Ok(())
 }
@@ -322,21 +328,23 @@ pub async fn export(
     Ok(())
 }
 
-pub async fn import(client: &SigmaClient, file: &str, format: &str) -> Result<()> {
+pub async fn import(
+    client: &SigmaClient,
+    file: &str,
+    format: &str,
+    mode: &str,
+    key: Option<&str>,
+) -> Result<()> {
     let data = std::fs::read_to_string(file)?;
     let body = ImportRequest {
         format: format.to_string(),
         data,
+        mode: mode.to_string(),
+        key: key.map(String::from),
     };
 
     let result: ImportResult = client.post("/vps/import", &body).await?;
 
-    println!("Imported {} VPS instances", result.imported);
-    if !result.errors.is_empty() {
-        println!("Errors:");
-        for err in &result.errors {
-            println!("  - {err}");
-        }
-    }
+    output::print_import_result(&result, "VPS instances");
     Ok(())
 }
@@ -102,9 +102,17 @@ enum ProviderCommands {
         rating: Option<i16>,
         #[arg(long)]
         notes: Option<String>,
+        /// Only apply if the resource still has this ETag (from `get`)
+        #[arg(long)]
+        if_match: Option<String>,
     },
     /// Delete a provider
-    Delete { id: Uuid },
+    Delete {
+        id: Uuid,
+        /// Only apply if the resource still has this ETag (from `get`)
+        #[arg(long)]
+        if_match: Option<String>,
+    },
     /// Export providers
     Export {
         #[arg(long, default_value = "json")]
@@ -119,6 +127,9 @@ enum ProviderCommands {
         file: String,
         #[arg(long, default_value = "json")]
         format: String,
+        /// insert, upsert (match on name) or dry_run (show the upsert plan)
+        #[arg(long, default_value = "insert")]
+        mode: String,
     },
 }
 
@@ -254,9 +265,17 @@ enum VpsCommands {
         node_exporter_port: Option<i32>,
         #[arg(long)]
         notes: Option<String>,
+        /// Only apply if the resource still has this ETag (from `get`)
+        #[arg(long)]
+        if_match: Option<String>,
     },
     /// Delete a VPS
-    Delete { id: Uuid },
+    Delete {
+        id: Uuid,
+        /// Only apply if the resource still has this ETag (from `get`)
+        #[arg(long)]
+        if_match: Option<String>,
+    },
     /// Retire a VPS (sets status=retired, disables monitoring)
     Retire { id: Uuid },
     /// Export VPS data
@@ -273,6 +292,12 @@ enum VpsCommands {
         file: String,
         #[arg(long, default_value = "json")]
         format: String,
+        /// insert, upsert or dry_run (show the upsert plan)
+        #[arg(long, default_value = "insert")]
+        mode: String,
+        /// Natural key for upsert / dry_run: hostname (default) or alias
+        #[arg(long)]
+        key: Option<String>,
     },
 }
 
@@ -399,6 +424,7 @@ async fn main() -> Result<()> {
                 api_supported,
                 rating,
                 notes,
+                if_match,
             } => {
                 commands::providers::update(
                     &client,
@@ -410,18 +436,19 @@ async fn main() -> Result<()> {
                     api_supported,
                     rating,
                     notes,
+                    if_match,
                     json,
                 )
                 .await
             }
-            ProviderCommands::Delete { id } => {
-                commands::providers::delete(&client, id).await
+            ProviderCommands::Delete { id, if_match } => {
+                commands::providers::delete(&client, id, if_match).await
             }
             ProviderCommands::Export { format, output } => {
                 commands::providers::export(&client, &format, output.as_deref()).await
             }
-            ProviderCommands::Import { file, format } => {
-                commands::providers::import(&client, &file, &format).await
+            ProviderCommands::Import { file, format, mode } => {
+                commands::providers::import(&client, &file, &format, &mode, None).await
             }
         },
         Commands::Vps { command } => match command {
@@ -531,6 +558,7 @@ async fn main() -> Result<()> {
                 monitoring_enabled,
                 node_exporter_port,
                 notes,
+                if_match,
             } => {
                 commands::vps::update(
                     &client,
@@ -558,12 +586,13 @@ async fn main() -> Result<()> {
                     monitoring_enabled,
                     node_exporter_port,
                     notes,
+                    if_match,
                     json,
                 )
                 .await
             }
-            VpsCommands::Delete { id } => {
-                commands::vps::delete(&client, id).await
+            VpsCommands::Delete { id, if_match } => {
+                commands::vps::delete(&client, id, if_match).await
             }
             VpsCommands::Retire { id } => {
                 commands::vps::retire(&client, id, json).await
@@ -571,8 +600,8 @@ async fn main() -> Result<()> {
             VpsCommands::Export { format, output } => {
                 commands::vps::export(&client, &format, output.as_deref()).await
             }
-            VpsCommands::Import { file, format } => {
-                commands::vps::import(&client, &file, &format).await
+            VpsCommands::Import { file, format, mode, key } => {
+                commands::vps::import(&client, &file, &format, &mode, key.as_deref()).await
             }
         },
         Commands::IpChecks { command } => match command {
@@ -276,10 +276,31 @@ pub struct PurgeResult {
 pub struct ImportRequest {
     pub format: String,
     pub data: String,
+    pub mode: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct ImportResult {
+    #[serde(default)]
+    pub dry_run: bool,
     pub imported: usize,
+    #[serde(default)]
+    pub updated: usize,
+    #[serde(default)]
+    pub skipped: usize,
     pub errors: Vec<String>,
+    #[serde(default)]
+    pub plan: Vec<ImportPlanRow>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportPlanRow {
+    pub row: usize,
+    pub action: String,
+    pub key: String,
+    #[serde(default)]
+    pub changes: serde_json::Value,
+    pub error: Option<String>,
 }
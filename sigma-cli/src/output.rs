@@ -15,6 +15,44 @@ pub fn print_json<T: serde::Serialize>(value: &T) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Print the result of a VPS or provider import; dry runs list the per-row plan.
+pub fn print_import_result(result: &crate::models::ImportResult, noun: &str) {
+    if result.dry_run {
+        let rows = result
+            .plan
+            .iter()
+            .map(|p| {
+                let detail = match (&p.error, p.changes.as_object()) {
+                    (Some(err), _) => err.clone(),
+                    (None, Some(changes)) => changes
+                        .iter()
+                        .map(|(field, c)| format!("{field}: {} → {}", c["from"], c["to"]))
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                    (None, None) => String::new(),
+                };
+                vec![p.row.to_string(), p.action.clone(), p.key.clone(), detail]
+            })
+            .collect();
+        print_table(&["Row", "Action", "Key", "Changes"], rows);
+        println!(
+            "Dry run: would create {}, update {}, skip {} {noun}",
+            result.imported, result.updated, result.skipped
+        );
+    } else {
+        println!(
+            "Imported {} {noun}, updated {}, unchanged {}",
+            result.imported, result.updated, result.skipped
+        );
+    }
+    if !result.errors.is_empty() {
+        println!("Errors:");
+        for err in &result.errors {
+            println!("  - {err}");
+        }
+    }
+}
+
 pub fn print_pagination(page: i64, per_page: i64, total: i64) {
     let total_pages = (total + per_page - 1) / per_page;
     println!("Page {} of {} ({} total)", page, total_pages.max(1), total);
@@ -559,17 +559,26 @@ export interface CloudAccountResponse {
   last_synced_at: string | null;
   created_at: string;
   updated_at: string;
+  sync_enabled: boolean;
+  sync_interval_secs: number | null;
+  last_sync_attempt_at: string | null;
+  last_sync_error: string | null;
+  last_sync_result: CloudSyncResult | null;
 }
 
 export interface CreateCloudAccount {
   name: string;
   provider_type: CloudProviderType;
   config: Record<string, unknown>;
+  sync_enabled?: boolean;
+  sync_interval_secs?: number | null;
 }
 
 export interface UpdateCloudAccount {
   name?: string;
   config?: Record<string, unknown>;
+  sync_enabled?: boolean;
+  sync_interval_secs?: number;
 }
 
 // ─── VPS IP History ──────────────────────────────────────